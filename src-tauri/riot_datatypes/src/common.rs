@@ -12,6 +12,7 @@ pub type ChampionId = i64;
 pub type Timestamp = i64;
 pub type SpellId = i64;
 
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Champion {
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// '/lol-clash/v1/tournament/{id}'
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClashTournament {
+    pub id: i64,
+    /// e.g. 'bilgewater'
+    #[serde(default)]
+    pub name_loc_key: String,
+    /// e.g. 'day_2'
+    #[serde(default)]
+    pub name_loc_key_secondary: String,
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// '/lol-lobby/v2/lobby'
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lobby {
+    pub game_config: LobbyGameConfig,
+    /// the party of the player (everyone in the lobby for custom games)
+    #[serde(default)]
+    pub members: Vec<LobbyMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyGameConfig {
+    #[serde(default)]
+    pub is_custom: bool,
+    #[serde(default)]
+    pub custom_lobby_name: String,
+    /// blue side
+    #[serde(default)]
+    pub custom_team100: Vec<LobbyMember>,
+    /// red side
+    #[serde(default)]
+    pub custom_team200: Vec<LobbyMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyMember {
+    #[serde(default)]
+    pub puuid: String,
+    /// empty for most accounts since the switch to Riot IDs
+    #[serde(default)]
+    pub summoner_name: String,
+    #[serde(default)]
+    pub is_bot: bool,
+}
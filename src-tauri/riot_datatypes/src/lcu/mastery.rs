@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ChampionId;
+
+/// Entry of '/lol-champion-mastery/v1/local-player/champion-mastery'
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionMastery {
+    pub champion_id: ChampionId,
+    pub champion_level: i64,
+    pub champion_points: i64,
+}
+
+/// '/lol-summoner/v1/current-summoner' (only the level)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummonerLevel {
+    pub summoner_level: i64,
+}
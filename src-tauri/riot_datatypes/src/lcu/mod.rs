@@ -1,5 +1,11 @@
+mod clash;
 mod game;
+mod lobby;
+mod mastery;
 mod session;
 
+pub use clash::*;
 pub use game::*;
+pub use lobby::*;
+pub use mastery::*;
 pub use session::*;
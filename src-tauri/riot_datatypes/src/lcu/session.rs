@@ -16,6 +16,7 @@ pub struct SessionEventData {
     pub phase: GamePhase,
 }
 
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum GamePhase {
     None,
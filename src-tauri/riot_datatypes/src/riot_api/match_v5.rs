@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ChampionId, GameId, ParticipantId, QueueId, SpellId, Timestamp};
+
+/// Response of `/lol/match/v5/matches/{matchId}` (only the fields LeagueRecord uses)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchDto {
+    pub info: InfoDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoDto {
+    pub game_id: GameId,
+    pub platform_id: String,
+    pub queue_id: QueueId,
+    pub game_version: String,
+    /// in seconds
+    pub game_duration: Timestamp,
+    pub participants: Vec<ParticipantDto>,
+    pub teams: Vec<TeamDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantDto {
+    pub participant_id: ParticipantId,
+    pub puuid: String,
+    #[serde(default)]
+    pub riot_id_game_name: String,
+    #[serde(default)]
+    pub riot_id_tagline: String,
+    pub team_id: i64,
+    pub champion_id: ChampionId,
+    pub champion_name: String,
+    pub summoner1_id: SpellId,
+    pub summoner2_id: SpellId,
+    #[serde(default)]
+    pub team_position: String,
+    #[serde(default)]
+    pub lane: String,
+    #[serde(default)]
+    pub role: String,
+    pub kills: i64,
+    pub deaths: i64,
+    pub assists: i64,
+    pub largest_multi_kill: i64,
+    pub neutral_minions_killed: i64,
+    #[serde(default)]
+    pub total_enemy_jungle_minions_killed: i64,
+    #[serde(default)]
+    pub total_ally_jungle_minions_killed: i64,
+    pub total_minions_killed: i64,
+    pub vision_score: f64,
+    pub vision_wards_bought_in_game: i64,
+    pub wards_placed: i64,
+    pub wards_killed: i64,
+    pub game_ended_in_early_surrender: bool,
+    pub game_ended_in_surrender: bool,
+    pub win: bool,
+    pub item0: i64,
+    pub item1: i64,
+    pub item2: i64,
+    pub item3: i64,
+    pub item4: i64,
+    pub item5: i64,
+    pub item6: i64,
+    pub gold_earned: i64,
+    pub perks: PerksDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerksDto {
+    /// primary style first, secondary style second
+    pub styles: Vec<PerkStyleDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerkStyleDto {
+    pub style: i64,
+    pub selections: Vec<PerkStyleSelectionDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerkStyleSelectionDto {
+    pub perk: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamDto {
+    pub team_id: i64,
+    pub win: bool,
+    pub bans: Vec<BanDto>,
+    pub objectives: ObjectivesDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BanDto {
+    pub champion_id: ChampionId,
+    pub pick_turn: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectivesDto {
+    pub baron: ObjectiveDto,
+    pub dragon: ObjectiveDto,
+    pub inhibitor: ObjectiveDto,
+    pub rift_herald: ObjectiveDto,
+    pub tower: ObjectiveDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectiveDto {
+    pub first: bool,
+    pub kills: i64,
+}
@@ -1 +1,3 @@
-// currently empty
+mod match_v5;
+
+pub use match_v5::*;
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{AppEvent, EventManager};
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BulkOperation {
+    Delete,
+    Favorite,
+    Move,
+    Export,
+    Migrate,
+}
+
+/// Sent after every recording of a bulk operation
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkProgress {
+    pub operation: BulkOperation,
+    pub video_id: String,
+    /// number of recordings that have been processed (including this one)
+    pub done: usize,
+    pub total: usize,
+    pub error: Option<String>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkSummary {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Apply `action` to every recording, report the progress of each one and send a single RecordingsChanged event at the
+/// end instead of one per recording.
+pub fn run_bulk(
+    app_handle: &AppHandle,
+    operation: BulkOperation,
+    video_ids: Vec<String>,
+    mut action: impl FnMut(&Path) -> Result<()>,
+) -> BulkSummary {
+    let total = video_ids.len();
+    let mut summary = BulkSummary::default();
+
+    for (index, video_id) in video_ids.into_iter().enumerate() {
+        let error = action(Path::new(&video_id)).err().map(|e| {
+            log::warn!("bulk {operation:?} of {video_id} failed: {e}");
+            e.to_string()
+        });
+
+        let payload = BulkProgress {
+            operation,
+            video_id: video_id.clone(),
+            done: index + 1,
+            total,
+            error: error.clone(),
+        };
+        if let Err(e) = app_handle.send_event(AppEvent::BulkProgress { payload }) {
+            log::warn!("failed to send bulk progress: {e}");
+        }
+
+        match error {
+            None => summary.succeeded.push(video_id),
+            Some(_) => summary.failed.push(video_id),
+        }
+    }
+
+    if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
+        log::error!("failed to send event: {e}");
+    }
+    summary
+}
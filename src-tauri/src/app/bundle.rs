@@ -0,0 +1,201 @@
+//! Recording bundles for moving recordings between PCs.
+//!
+//! A bundle contains the video (with all of its segments), the metadata (highlights included), the screenshot and a
+//! `bundle.json` manifest. It is either a .zip file or a plain folder.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::action;
+use crate::constants::CURRENT_VERSION;
+use crate::recorder::{migration, MetadataFile};
+
+const MANIFEST: &str = "bundle.json";
+const BUNDLE_VERSION: u32 = 1;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleFormat {
+    Zip,
+    Folder,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: u32,
+    app_version: String,
+    /// file name of the video
+    video: String,
+    /// file names of the video and every file that belongs to it
+    files: Vec<String>,
+}
+
+/// Package the recording into `destination` (a folder). Returns the path of the created bundle.
+pub fn export(video_path: &Path, destination: &Path, format: BundleFormat) -> Result<PathBuf> {
+    if !action::is_native_recording(video_path) {
+        bail!("only LeagueRecord recordings can be exported");
+    }
+    let stem = video_path.file_stem().context("invalid video path")?;
+    let video = file_name(video_path)?;
+
+    let files = action::recording_files(video_path)?;
+    let manifest = Manifest {
+        version: BUNDLE_VERSION,
+        app_version: CURRENT_VERSION.to_owned(),
+        video,
+        files: files.iter().map(|file| file_name(file)).collect::<Result<_>>()?,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+
+    fs::create_dir_all(destination)?;
+    match format {
+        BundleFormat::Folder => {
+            let bundle_path = destination.join(stem);
+            if bundle_path.exists() {
+                bail!("{} already exists", bundle_path.display());
+            }
+            fs::create_dir(&bundle_path)?;
+
+            for file in &files {
+                fs::copy(file, bundle_path.join(file_name(file)?))?;
+            }
+            fs::write(bundle_path.join(MANIFEST), manifest)?;
+            Ok(bundle_path)
+        }
+        BundleFormat::Zip => {
+            let bundle_path = destination.join(stem).with_extension("zip");
+            if bundle_path.exists() {
+                bail!("{} already exists", bundle_path.display());
+            }
+
+            let result = write_zip(&bundle_path, &files, &manifest);
+            if result.is_err() {
+                _ = fs::remove_file(&bundle_path);
+            }
+            result.map(|_| bundle_path)
+        }
+    }
+}
+
+fn write_zip(bundle_path: &Path, files: &[PathBuf], manifest: &[u8]) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(bundle_path)?);
+    // videos don't get any smaller by compressing them again
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+
+    for file in files {
+        zip.start_file(file_name(file)?, options)?;
+        io::copy(&mut File::open(file)?, &mut zip)?;
+    }
+    zip.start_file(MANIFEST, options)?;
+    io::Write::write_all(&mut zip, manifest)?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Validate the bundle at `bundle_path` (.zip file or folder) and copy the recording into `recordings_folder`.
+/// Returns the path of the imported video.
+pub fn import(bundle_path: &Path, recordings_folder: &Path) -> Result<PathBuf> {
+    let mut bundle = Bundle::open(bundle_path)?;
+
+    let manifest: Manifest = serde_json::from_slice(&bundle.read(MANIFEST)?).context("invalid bundle manifest")?;
+    if manifest.version > BUNDLE_VERSION {
+        bail!(
+            "bundle was created by a newer version of LeagueRecord ({})",
+            manifest.app_version
+        );
+    }
+
+    // the file names come from the bundle => don't let them point outside of the recordings folder
+    for file in &manifest.files {
+        if Path::new(file).file_name().and_then(|name| name.to_str()) != Some(file.as_str()) {
+            bail!("invalid file name in bundle: {file}");
+        }
+    }
+    if !manifest.video.ends_with(".mp4") || !manifest.files.contains(&manifest.video) {
+        bail!("bundle doesn't contain a video");
+    }
+
+    let video_path = recordings_folder.join(&manifest.video);
+    let metadata_name = file_name(&video_path.with_extension("json"))?;
+    if !manifest.files.contains(&metadata_name) {
+        bail!("bundle doesn't contain metadata");
+    }
+    // metadata of older versions gets migrated once the recording is in the library
+    let mut metadata = serde_json::from_slice(&bundle.read(&metadata_name)?).context("invalid metadata in bundle")?;
+    migration::migrate(&mut metadata)?;
+    serde_json::from_value::<MetadataFile>(metadata).context("invalid metadata in bundle")?;
+
+    if let Some(existing) = manifest.files.iter().find(|file| recordings_folder.join(file).exists()) {
+        bail!("{existing} already exists in the recordings folder");
+    }
+
+    for (index, file) in manifest.files.iter().enumerate() {
+        if let Err(e) = bundle.copy_to(file, &recordings_folder.join(file)) {
+            // don't leave a partial recording behind
+            for copied in &manifest.files[..=index] {
+                _ = fs::remove_file(recordings_folder.join(copied));
+            }
+            return Err(e.context(format!("failed to import {file}")));
+        }
+    }
+
+    Ok(video_path)
+}
+
+enum Bundle {
+    Folder(PathBuf),
+    Zip(ZipArchive<File>),
+}
+
+impl Bundle {
+    fn open(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            Ok(Self::Folder(path.to_path_buf()))
+        } else {
+            Ok(Self::Zip(ZipArchive::new(File::open(path)?).context("not a zip file")?))
+        }
+    }
+
+    fn read(&mut self, name: &str) -> Result<Vec<u8>> {
+        let mut content = Vec::new();
+        match self {
+            Bundle::Folder(folder) => {
+                File::open(folder.join(name))?.read_to_end(&mut content)?;
+            }
+            Bundle::Zip(archive) => {
+                archive.by_name(name)?.read_to_end(&mut content)?;
+            }
+        }
+        Ok(content)
+    }
+
+    fn copy_to(&mut self, name: &str, destination: &Path) -> Result<()> {
+        match self {
+            Bundle::Folder(folder) => {
+                fs::copy(folder.join(name), destination)?;
+            }
+            Bundle::Zip(archive) => {
+                io::copy(&mut archive.by_name(name)?, &mut File::create(destination)?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_owned)
+        .context("invalid filename")
+}
@@ -0,0 +1,162 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{bail, Context, Result};
+use tauri::{AppHandle, Manager};
+
+use crate::state::SettingsWrapper;
+use crate::static_data;
+
+/// folders in the app local data dir that hold downloaded images, item data and the static data catalogs
+pub const CACHE_DIRS: [&str; 3] = [IMG_CACHE_DIR, "items_cache", static_data::CACHE_DIR];
+const IMG_CACHE_DIR: &str = "img_cache";
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    last_access: SystemTime,
+}
+
+pub trait CacheManager {
+    fn get_cache_dirs(&self) -> Vec<PathBuf>;
+    fn get_cache_size(&self) -> u64;
+
+    /// path of an image in the image cache (doesn't check whether it was downloaded)
+    fn get_image_cache_path(&self, category: &str, filename: &str) -> Option<PathBuf>;
+
+    fn touch_cache_entry(&self, path: &Path);
+
+    fn prune_cache(&self);
+    fn clear_cache(&self) -> Result<()>;
+}
+
+impl CacheManager for AppHandle {
+    fn get_cache_dirs(&self) -> Vec<PathBuf> {
+        let Ok(app_dir) = self.path().app_local_data_dir() else { return vec![] };
+        CACHE_DIRS.iter().map(|dir| app_dir.join(dir)).collect()
+    }
+
+    fn get_cache_size(&self) -> u64 {
+        let mut entries = Vec::new();
+        for dir in self.get_cache_dirs() {
+            collect_cache_entries(&dir, &mut entries);
+        }
+        entries.iter().map(|entry| entry.size).sum()
+    }
+
+    fn get_image_cache_path(&self, category: &str, filename: &str) -> Option<PathBuf> {
+        let app_dir = self.path().app_local_data_dir().ok()?;
+        Some(app_dir.join(IMG_CACHE_DIR).join(category).join(filename))
+    }
+
+    fn touch_cache_entry(&self, path: &Path) {
+        // the last access time reported by the filesystem is unreliable on windows (NTFS only updates it lazily or not
+        // at all) => use the modified time as 'last access' instead
+        let result = File::options()
+            .append(true)
+            .open(path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if let Err(e) = result {
+            log::warn!("failed to update access time of cache entry {}: {e}", path.display());
+        }
+    }
+
+    fn prune_cache(&self) {
+        let Some(max_mb) = self.state::<SettingsWrapper>().max_cache_size_mb() else { return };
+        let max_size = max_mb.saturating_mul(1_000_000); // convert to bytes
+
+        let mut entries = Vec::new();
+        for dir in self.get_cache_dirs() {
+            collect_cache_entries(&dir, &mut entries);
+        }
+
+        let mut total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+        if total_size <= max_size {
+            return;
+        }
+
+        // least recently used entries first
+        entries.sort_by_key(|entry| entry.last_access);
+
+        let mut removed = 0;
+        for entry in entries {
+            if total_size <= max_size {
+                break;
+            }
+
+            match fs::remove_file(&entry.path) {
+                Ok(_) => {
+                    total_size -= entry.size;
+                    removed += 1;
+                }
+                Err(e) => log::warn!("failed to remove cache entry {}: {e}", entry.path.display()),
+            }
+        }
+
+        log::info!("pruned {removed} cache entries (cache size now {total_size} bytes, limit {max_size} bytes)");
+    }
+
+    fn clear_cache(&self) -> Result<()> {
+        for dir in self.get_cache_dirs() {
+            if dir.exists() {
+                fs::remove_dir_all(&dir)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Download an image into the image cache (or serve it from there if it was downloaded before).
+/// Shared by the frontend (via the `download_image` command) and the backend.
+pub async fn cache_image(app_handle: &AppHandle, url: &str, category: &str, filename: &str) -> Result<PathBuf> {
+    if category.contains("..") || filename.contains("..") {
+        bail!("invalid path parameters");
+    }
+
+    let file_path = app_handle
+        .get_image_cache_path(category, filename)
+        .context("no app data directory")?;
+
+    // serve from cache if the image was downloaded before
+    if file_path.is_file() {
+        app_handle.touch_cache_entry(&file_path);
+        return Ok(file_path);
+    }
+
+    if let Some(category_dir) = file_path.parent() {
+        fs::create_dir_all(category_dir)?;
+    }
+
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        bail!("request failed: {}", response.status());
+    }
+    let bytes = response.bytes().await?;
+    fs::write(&file_path, &bytes)?;
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || app_handle.prune_cache());
+
+    Ok(file_path)
+}
+
+fn collect_cache_entries(dir: &Path, entries: &mut Vec<CacheEntry>) {
+    let Ok(read_dir) = dir.read_dir() else { return };
+
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else { continue };
+
+        if metadata.is_dir() {
+            collect_cache_entries(&path, entries);
+        } else if metadata.is_file() {
+            let last_access = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push(CacheEntry {
+                path,
+                size: metadata.len(),
+                last_access,
+            });
+        }
+    }
+}
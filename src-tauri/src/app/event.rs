@@ -1,16 +1,41 @@
 use serde::{Deserialize, Serialize};
 
+use crate::app::BulkProgress;
+use crate::backup::BackupStatus;
+use crate::error::RecorderError;
+use crate::filewatcher::RecordingsDelta;
+use crate::jobs::Job;
+use crate::recorder::{RecordingDegraded, RecordingFailed, RecordingStats, SavedHighlight};
+use crate::state::{LogEntry, Settings, SettingsWarning};
+use crate::uploader::UploadProgress;
+
 #[allow(clippy::enum_variant_names)]
 #[cfg_attr(test, derive(specta::Type, tauri_specta::Event))]
 #[derive(Debug, Clone, strum_macros::IntoStaticStr, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AppEvent {
     RecordingsChanged { payload: () },
+    RecordingsDelta { payload: RecordingsDelta },
+    RecordingRenamed { payload: (String, String) },
     MetadataChanged { payload: Vec<String> },
     MarkerflagsChanged { payload: () },
     RecordingStarted,
+    RecordingDegraded { payload: RecordingDegraded },
+    RecordingFailed { payload: RecordingFailed },
+    RecordingStats { payload: RecordingStats },
     GameDetected,
     RecordingFinished { payload: (String, bool) },
+    UploadProgress { payload: UploadProgress },
+    BackupStatus { payload: BackupStatus },
+    HighlightSaved { payload: SavedHighlight },
+    HighlightRemoved { payload: SavedHighlight },
+    SelectRecording { payload: String },
+    BulkProgress { payload: BulkProgress },
+    JobProgress { payload: Job },
+    SettingsChanged { payload: Settings },
+    SettingsWarnings { payload: Vec<SettingsWarning> },
+    LogEmitted { payload: LogEntry },
+    Error { payload: RecorderError },
 }
 
 pub trait EventManager {
@@ -27,6 +52,14 @@ impl EventManager for tauri::AppHandle {
             RecordingsChanged { payload } => {
                 self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
             }
+            // recordings that got added, removed or renamed in the watched folders (see `filewatcher`)
+            RecordingsDelta { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // (old video_id, new video_id) of a recording that got renamed outside of LeagueRecord
+            RecordingRenamed { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
             MetadataChanged { payload } => {
                 self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
             }
@@ -34,10 +67,59 @@ impl EventManager for tauri::AppHandle {
                 self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
             }
             RecordingStarted => self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), ())?,
+            // the recording continues with a lower framerate because encoding couldn't keep up
+            RecordingDegraded { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // the output of the recorder died and couldn't be restarted, the recording only goes up to the failure
+            RecordingFailed { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // health of the running recording, sent every few seconds
+            RecordingStats { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
             GameDetected => self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), ())?,
             RecordingFinished { payload } => {
                 self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
             }
+            UploadProgress { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            BackupStatus { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            SelectRecording { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            BulkProgress { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            JobProgress { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // sent after every reload of the settings (settings window, text editor or any other change to the file)
+            SettingsChanged { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // everything in settings.json that got ignored or replaced with a default after it was edited
+            SettingsWarnings { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // every log message while 'debugLog' is enabled, for the live log in developer mode
+            LogEmitted { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // a failure the user can do something about (see `RecorderError`)
+            Error { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
+            // the ingame time and category of the saved or removed highlight are shown as confirmation in the overlay
+            HighlightSaved { payload } | HighlightRemoved { payload } => self.emit_to(
+                EventTarget::webview_window(AppWindow::Overlay),
+                (&event).into(),
+                payload,
+            )?,
         };
 
         Ok(())
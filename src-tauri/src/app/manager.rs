@@ -7,12 +7,19 @@ use log::LevelFilter;
 use semver::Version;
 use tauri::{async_runtime, AppHandle, Manager};
 use tauri_plugin_dialog::DialogExt;
-use tauri_plugin_log::{Target, TargetKind};
-
-use super::{RecordingManager, SystemTrayManager};
-use crate::constants::{APP_NAME, CURRENT_VERSION};
-use crate::state::{SettingsFile, SettingsWrapper};
-use crate::{filewatcher, recorder::LeagueRecorder};
+use tauri_plugin_log::{fern, Target, TargetKind};
+
+use super::{AppEvent, CacheManager, EventManager, RecordingManager, SystemTrayManager};
+use crate::backup::{self, Backup};
+use crate::constants::{APP_NAME, CURRENT_VERSION, EXIT_SUCCESS};
+use crate::crash_reporter;
+use crate::filewatcher;
+use crate::jobs::{self, Jobs};
+use crate::privacy;
+use crate::recorder::{LeagueRecorder, PrewarmedRecorder};
+use crate::state::{LogBuffer, LogEntry, SettingsFile, SettingsWrapper, Shutdown};
+use crate::static_data;
+use crate::updater;
 
 pub trait AppManager {
     const SETTINGS_FILE: &'static str;
@@ -23,6 +30,7 @@ pub trait AppManager {
 
     fn add_log_plugin(&self) -> Result<()>;
     fn remove_log_plugin(&self);
+    fn capture_log(&self, record: &log::Record);
 
     fn check_app_updated(&self);
     fn get_last_version(&self) -> Option<Version>;
@@ -32,6 +40,9 @@ pub trait AppManager {
     fn sync_autostart(&self);
 
     fn update_hotkeys(&self);
+
+    /// Stop the recorder (finalizes a running recording) and exit the app
+    fn shutdown(&self);
 }
 
 impl AppManager for AppHandle {
@@ -52,17 +63,24 @@ impl AppManager for AppHandle {
         log::info!("debug_log: {}", if debug_log { "enabled" } else { "disabled" });
         log::info!("Settings: {}", settings.inner());
 
+        // opt-in, writes a minidump and the recent log messages if the app crashes
+        crash_reporter::init(self);
+
         // create system tray-icon
         self.init_tray_menu();
 
         self.check_app_updated();
 
+        // offer new releases in the tray menu
+        updater::watch(self);
+
         // make sure the system autostart setting for the app matches what is set in the settings
         self.sync_autostart();
 
         // Initialize Raw Input Listener (Background Thread)
         // This replaces the old windows-key-listener global hook to avoid Vanguard freezes.
         crate::state::RawInputListener::start(self.app_handle().clone());
+        crate::state::GamepadListener::start(self.app_handle().clone());
 
         self.update_hotkeys();
 
@@ -70,10 +88,54 @@ impl AppManager for AppHandle {
         let recordings_path = settings.get_recordings_path();
         log::info!("recordings folder: {recordings_path:?}");
         filewatcher::replace(self, &recordings_path);
+        filewatcher::watch_settings(self, self.state::<SettingsFile>().get());
+
+        static_data::init(self);
+        privacy::init(self);
 
         // start checking for LoL games to record
+        self.manage(PrewarmedRecorder::default());
         self.manage(LeagueRecorder::new(self.clone()));
 
+        // let external tools start/stop recordings and save highlights
+        #[cfg(target_os = "windows")]
+        crate::control::listen(self);
+
+        // stop the recording before the PC goes to sleep and reconnect to the League client after it woke up
+        #[cfg(target_os = "windows")]
+        crate::power::listen(self);
+
+        // retry the uploads of backups that didn't finish before the app was closed
+        self.manage(Backup::new(self)?);
+        backup::resume(self);
+
+        // post-processing of finished recordings, continues the jobs that didn't run before the app was closed
+        self.manage(Jobs::new(self)?);
+        jobs::resume(self);
+
+        #[cfg(feature = "telemetry")]
+        {
+            self.manage(crate::telemetry::Telemetry::new(self)?);
+            let app_handle = self.clone();
+            async_runtime::spawn(async move {
+                if let Err(e) = crate::telemetry::transmit(&app_handle).await {
+                    log::warn!("{e}");
+                }
+            });
+        }
+
+        // the recordings folder could have been changed while the app was closed
+        async_runtime::spawn_blocking({
+            let app_handle = self.clone();
+            move || app_handle.reindex_library()
+        });
+
+        // repair recordings that were still running when the app or PC crashed
+        async_runtime::spawn_blocking({
+            let app_handle = self.clone();
+            move || app_handle.recover_interrupted_recordings()
+        });
+
         // cleanup recordings if they are too old or the total size of the recordings gets too big
         // this only happens if 'maxRecordingAge' or 'maxRecordingsSize' is configured in the settings
         async_runtime::spawn_blocking({
@@ -81,6 +143,12 @@ impl AppManager for AppHandle {
             move || app_handle.cleanup_recordings()
         });
 
+        // remove the least recently used images if the image cache grew bigger than 'maxCacheSizeMb'
+        async_runtime::spawn_blocking({
+            let app_handle = self.clone();
+            move || app_handle.prune_cache()
+        });
+
         Ok(())
     }
 
@@ -104,6 +172,10 @@ impl AppManager for AppHandle {
             .targets([
                 Target::new(TargetKind::LogDir { file_name }),
                 Target::new(TargetKind::Stdout),
+                Target::new(TargetKind::Dispatch(fern::Dispatch::new().chain(fern::Output::call({
+                    let app_handle = self.clone();
+                    move |record| app_handle.capture_log(record)
+                })))),
             ])
             .level(LevelFilter::Info)
             .format(|out, msg, record| {
@@ -125,6 +197,17 @@ impl AppManager for AppHandle {
         self.remove_plugin("log");
     }
 
+    fn capture_log(&self, record: &log::Record) {
+        let entry = LogEntry {
+            level: record.level().into(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        };
+        self.state::<LogBuffer>().push(entry.clone());
+        // don't log the error, that would trigger another event
+        _ = self.send_event(AppEvent::LogEmitted { payload: entry });
+    }
+
     fn check_app_updated(&self) {
         // self.handle_update(Version::new(1, 0, 0)); // testing
         if let Some(version) = self.get_last_version() {
@@ -209,4 +292,20 @@ impl AppManager for AppHandle {
         // but current RawInput implementation reads settings on-the-fly.
         log::info!("Hotkeys managed by RawInputListener");
     }
+
+    fn shutdown(&self) {
+        // closing the main window and 'Quit' in the tray menu can both trigger the shutdown
+        let shutdown = self.state::<Shutdown>();
+        if shutdown.get() {
+            return;
+        }
+        shutdown.set();
+
+        let app_handle = self.clone();
+        async_runtime::spawn(async move {
+            log::info!("shutting down");
+            app_handle.state::<LeagueRecorder>().stop().await;
+            app_handle.exit(EXIT_SUCCESS);
+        });
+    }
 }
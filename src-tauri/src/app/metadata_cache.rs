@@ -0,0 +1,105 @@
+//! In-memory cache of the parsed metadata files.
+//!
+//! The recordings list reads the metadata of every recording, parsing (and migrating) the JSON again every time is
+//! most of the work for big libraries. An entry is only used as long as the file still has the same modification time
+//! and size, so changes by other programs (or other LeagueRecord windows) are picked up.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::recorder::MetadataFile;
+
+// the least recently used entries get dropped above this, a metadata file is a few hundred KB at most
+const MAX_ENTRIES: usize = 2000;
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+struct Cache {
+    entries: HashMap<PathBuf, Entry>,
+    // incremented on every access, `Entry::last_used` of the least recently used entry is the smallest
+    clock: u64,
+}
+
+struct Entry {
+    version: FileVersion,
+    metadata_file: MetadataFile,
+    last_used: u64,
+}
+
+#[derive(PartialEq)]
+struct FileVersion {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileVersion {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// The cached metadata of `metadata_path` if the file didn't change since it got cached
+pub fn get(metadata_path: &Path) -> Option<MetadataFile> {
+    let version = FileVersion::of(metadata_path);
+
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.as_mut()?;
+    cache.clock += 1;
+    let clock = cache.clock;
+
+    match cache.entries.get_mut(metadata_path) {
+        Some(entry) if version.as_ref() == Some(&entry.version) => {
+            entry.last_used = clock;
+            Some(entry.metadata_file.clone())
+        }
+        Some(_) => {
+            cache.entries.remove(metadata_path);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Cache the content of `metadata_path` that was just read or written
+pub fn insert(metadata_path: &Path, metadata_file: &MetadataFile) {
+    let Some(version) = FileVersion::of(metadata_path) else { return };
+
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.get_or_insert_with(|| Cache {
+        entries: HashMap::new(),
+        clock: 0,
+    });
+    cache.clock += 1;
+
+    if cache.entries.len() >= MAX_ENTRIES && !cache.entries.contains_key(metadata_path) {
+        let least_recently_used = cache
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(path, _)| path.clone());
+        if let Some(path) = least_recently_used {
+            cache.entries.remove(&path);
+        }
+    }
+
+    let entry = Entry {
+        version,
+        metadata_file: metadata_file.clone(),
+        last_used: cache.clock,
+    };
+    cache.entries.insert(metadata_path.to_path_buf(), entry);
+}
+
+/// Forget the metadata of a file that got deleted or moved
+pub fn remove(metadata_path: &Path) {
+    if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+        cache.entries.remove(metadata_path);
+    }
+}
@@ -1,38 +1,48 @@
+mod bulk;
+pub mod bundle;
+mod cache;
 mod event;
 mod manager;
+mod metadata_cache;
 mod recordings;
+pub mod support;
 mod system_tray;
+mod tray_icon;
+pub mod video_info;
 mod window;
 
+pub use bulk::{run_bulk, BulkOperation, BulkProgress, BulkSummary};
+pub use cache::{cache_image, CacheManager};
 pub use event::{AppEvent, EventManager};
 pub use manager::AppManager;
-pub use recordings::{action, RecordingManager};
+pub use recordings::{action, CleanupPreview, ClipMetadata, LibraryReport, RecordingManager};
 pub use system_tray::SystemTrayManager;
+pub use video_info::VideoInfo;
 pub use window::{AppWindow, WindowManager};
 
 pub fn process_app_event(app_handle: &tauri::AppHandle, event: tauri::RunEvent) {
-    use crate::state::Shutdown;
+    use manager::AppManager;
     use tauri::{Manager, RunEvent, WindowEvent};
     use window::WindowManager;
 
     match event {
         RunEvent::WindowEvent {
+            label,
             event: WindowEvent::CloseRequested { .. },
             ..
-        } => {
+        } if label == <&str>::from(AppWindow::Main) => {
             // triggered on window close (X Button)
-            // Set shutdown flag to true so the app exits completely
-            app_handle.state::<Shutdown>().set();
-
+            // the app exits completely once the recorder is stopped
             if let Some(window) = app_handle.get_webview_window(AppWindow::Main.into()) {
                 app_handle.save_window_state(&window);
                 let _ = window.close();
             }
+            app_handle.shutdown();
         }
-        RunEvent::ExitRequested { api, .. } => {
-            // triggered when no windows remain
+        RunEvent::ExitRequested { code, api, .. } => {
+            // triggered when no windows remain (no exit code) or by `AppManager::shutdown()`
             // prevent complete shutdown of program so that just the tray icon stays
-            if !app_handle.state::<Shutdown>().get() {
+            if code.is_none() {
                 api.prevent_exit();
             }
         }
@@ -2,17 +2,68 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
-use crate::state::{CurrentlyRecording, SettingsWrapper};
+use super::{AppEvent, EventManager};
+use crate::recorder::MetadataFile;
+use crate::state::{CurrentlyRecording, RecordingIndex, SettingsWrapper};
 use crate::util;
 
+/// Result of comparing the videos in the recordings folders with the files that belong to them
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryReport {
+    pub recordings: usize,
+    /// recordings whose metadata referenced files that don't exist anymore (fixed)
+    pub repaired: Vec<PathBuf>,
+    /// metadata files that can't be read
+    pub corrupt: Vec<PathBuf>,
+    /// metadata files and screenshots without a video (e.g. the video was moved while the app was closed)
+    pub orphans: Vec<PathBuf>,
+}
+
+/// Files the retention rules would delete
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupPreview {
+    pub recordings: Vec<PathBuf>,
+    pub clips: Vec<PathBuf>,
+    /// total size in bytes of everything that would be deleted
+    pub freed_bytes: u64,
+}
+
+/// Sidecar file of a clip (`<clip>.clip.json`)
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipMetadata {
+    #[serde(default)]
+    pub youtube_url: Option<String>,
+    /// stable ID of the recording the clip was cut from
+    #[serde(default)]
+    pub source_id: Option<String>,
+}
+
 pub trait RecordingManager {
     fn get_recordings(&self) -> Vec<PathBuf>;
+    fn get_external_recordings(&self) -> Vec<PathBuf>;
 
+    fn reindex_library(&self) -> LibraryReport;
+    fn rebuild_recording_index(&self);
+    /// Path of the recording a video_id (path of the video or stable ID of the recording) refers to
+    fn resolve_video_id(&self, video_id: &str) -> PathBuf;
+    fn recover_interrupted_recordings(&self);
+
+    /// Delete everything the retention rules don't keep (see [`RecordingManager::preview_cleanup`])
     fn cleanup_recordings(&self);
-    fn cleanup_recordings_by_size(&self);
-    fn cleanup_recordings_by_age(&self);
+    /// Recordings and clips that [`RecordingManager::cleanup_recordings`] would delete with the current settings
+    fn preview_cleanup(&self) -> CleanupPreview;
+    fn expired_recordings(&self) -> Vec<PathBuf>;
+    fn recordings_over_size_limit(&self, expired: &[PathBuf]) -> Vec<PathBuf>;
+    fn clips_to_cleanup(&self) -> Vec<PathBuf>;
 }
 
 impl RecordingManager for AppHandle {
@@ -25,18 +76,18 @@ impl RecordingManager for AppHandle {
         let paths_to_scan = vec![settings.get_recordings_path(), settings.get_clips_path()];
 
         for dir_path in paths_to_scan {
-            if let Ok(read_dir) = dir_path.read_dir() {
-                for entry in read_dir.flatten() {
-                    let path = entry.path();
-
-                    if !path.is_file() || Some(&path) == currently_recording.as_ref() {
-                        continue;
-                    }
+            for path in action::library_files(&dir_path) {
+                if Some(&path) == currently_recording.as_ref() {
+                    continue;
+                }
+                // segments are part of the recording they continue
+                if action::is_segment(&path) {
+                    continue;
+                }
 
-                    if let Some(ext) = path.extension() {
-                        if ext == "mp4" {
-                            recordings.push(path);
-                        }
+                if let Some(ext) = path.extension() {
+                    if ext == "mp4" {
+                        recordings.push(path);
                     }
                 }
             }
@@ -49,108 +100,322 @@ impl RecordingManager for AppHandle {
         recordings
     }
 
+    fn get_external_recordings(&self) -> Vec<PathBuf> {
+        let mut recordings: Vec<_> = self
+            .state::<SettingsWrapper>()
+            .external_folders()
+            .iter()
+            .flat_map(|folder| action::library_files(folder))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "mp4") && !action::is_segment(path))
+            .collect();
+
+        recordings.sort();
+        recordings.dedup();
+        recordings
+    }
+
+    fn reindex_library(&self) -> LibraryReport {
+        let recordings = self.get_recordings();
+        let mut report = LibraryReport {
+            recordings: recordings.len(),
+            ..Default::default()
+        };
+
+        let settings = self.state::<SettingsWrapper>();
+        for recording in recordings {
+            if !action::is_native_recording(&recording) {
+                continue;
+            }
+            // metadata files of older versions are only upgraded in memory when they are read
+            if let Err(e) = action::migrate_metadata(&recording, &settings) {
+                log::warn!("failed to migrate metadata of {}: {e}", recording.display());
+            }
+            match action::reconcile_recording(&recording) {
+                Ok(true) => report.repaired.push(recording),
+                Ok(false) => {}
+                Err(e) => {
+                    log::warn!("failed to read metadata of {}: {e}", recording.display());
+                    report.corrupt.push(recording.with_extension("json"));
+                }
+            }
+        }
+
+        let currently_recording = self.state::<CurrentlyRecording>().get();
+        for dir_path in [settings.get_recordings_path(), settings.get_clips_path()] {
+            for path in action::library_files(&dir_path) {
+                let Some(video_path) = action::sidecar_video_path(&path) else { continue };
+                if !video_path.is_file() && Some(&video_path) != currently_recording.as_ref() {
+                    report.orphans.push(path);
+                }
+            }
+        }
+        report.orphans.sort();
+        report.orphans.dedup();
+
+        self.rebuild_recording_index();
+
+        log::info!(
+            "reindexed {} recordings: {} repaired, {} corrupt, {} orphaned files",
+            report.recordings,
+            report.repaired.len(),
+            report.corrupt.len(),
+            report.orphans.len()
+        );
+        report
+    }
+
+    fn rebuild_recording_index(&self) {
+        let index = self
+            .get_recordings()
+            .into_iter()
+            .chain(self.get_external_recordings())
+            .filter_map(|recording| {
+                let metadata_file = action::get_recording_metadata(&recording, false).ok()?;
+                let id = metadata_file.id().to_owned();
+                Some((recording, id))
+            })
+            .collect();
+        self.state::<RecordingIndex>().replace(index);
+    }
+
+    fn resolve_video_id(&self, video_id: &str) -> PathBuf {
+        let index = self.state::<RecordingIndex>();
+        if let Some(video_path) = index.path_of(video_id).filter(|path| path.is_file()) {
+            return video_path;
+        }
+
+        let video_path = PathBuf::from(video_id);
+        if video_path.is_file() || uuid::Uuid::parse_str(video_id).is_err() {
+            return video_path;
+        }
+        // the recording got renamed since the index was built
+        self.rebuild_recording_index();
+        index.path_of(video_id).unwrap_or(video_path)
+    }
+
+    fn recover_interrupted_recordings(&self) {
+        let interrupted: Vec<_> = self
+            .get_recordings()
+            .into_iter()
+            .filter(|recording| action::is_interrupted_recording(recording))
+            .collect();
+        if interrupted.is_empty() {
+            return;
+        }
+
+        let ffmpeg = self
+            .state::<SettingsWrapper>()
+            .ffmpeg_path()
+            .unwrap_or_else(|| "ffmpeg".to_string());
+        for recording in &interrupted {
+            log::info!("recovering interrupted recording {}", recording.display());
+            if let Err(e) = action::recover_recording(recording, &ffmpeg) {
+                log::error!("failed to recover {}: {e}", recording.display());
+            }
+        }
+
+        // the game data of the interrupted recordings was never collected
+        for recording in &interrupted {
+            if let Err(e) = action::get_recording_metadata(recording, true) {
+                log::warn!("failed to repair metadata of {}: {e}", recording.display());
+            }
+        }
+
+        if let Err(e) = self.send_event(AppEvent::RecordingsChanged { payload: () }) {
+            log::error!("failed to emit 'recordings_changed' event: {e}");
+        }
+    }
+
     fn cleanup_recordings(&self) {
-        self.cleanup_recordings_by_age();
-        self.cleanup_recordings_by_size();
+        let preview = self.preview_cleanup();
+        for recording in preview.recordings {
+            if let Err(e) = action::delete_recording(recording) {
+                log::error!("failed to delete recording due to retention rules: {e}");
+            }
+        }
+        for clip in preview.clips {
+            if let Err(e) = action::delete_clip(&clip) {
+                log::error!("failed to delete clip due to retention rules: {e}");
+            }
+        }
+    }
+
+    fn preview_cleanup(&self) -> CleanupPreview {
+        let mut recordings = self.expired_recordings();
+        recordings.extend(self.recordings_over_size_limit(&recordings));
+        let clips = self.clips_to_cleanup();
+
+        let freed_bytes = recordings
+            .iter()
+            .chain(&clips)
+            .map(|path| action::recording_size(path))
+            .sum();
+        CleanupPreview { recordings, clips, freed_bytes }
+    }
+
+    fn expired_recordings(&self) -> Vec<PathBuf> {
+        let settings = self.state::<SettingsWrapper>();
+        let max_recording_age = settings.max_recording_age();
+        let retention = settings.retention();
+        let now = SystemTime::now();
+
+        // only manage recordings created by LeagueRecord, foreign videos in the same folder are left alone
+        self.get_recordings()
+            .into_iter()
+            .filter(|recording| action::is_native_recording(recording))
+            .filter(|recording| {
+                // in case reading the metadata or checking the age fails default to not deleting the file
+                let Ok(metadata_file) = action::get_recording_metadata(recording, false) else { return false };
+                if metadata_file.is_favorite() || metadata_file.is_protected() {
+                    return false;
+                }
+
+                let is_ranked = matches!(&metadata_file, MetadataFile::Metadata(metadata) if metadata.queue.is_ranked);
+                let max_age_days = if is_ranked {
+                    retention.ranked_max_age_days
+                } else {
+                    retention.other_max_age_days
+                };
+                max_age_days
+                    .or(max_recording_age)
+                    .is_some_and(|days| too_old(recording, days, now).unwrap_or(false))
+            })
+            .collect()
     }
 
-    fn cleanup_recordings_by_size(&self) {
+    fn recordings_over_size_limit(&self, expired: &[PathBuf]) -> Vec<PathBuf> {
         use std::cmp::Ordering;
 
-        let Some(max_gb) = self.state::<SettingsWrapper>().max_recordings_size() else { return };
+        let Some(max_gb) = self.state::<SettingsWrapper>().max_recordings_size() else { return vec![] };
         let max_size = max_gb * 1_000_000_000; // convert to bytes
 
-        let mut recordings = self.get_recordings();
+        // only manage recordings created by LeagueRecord, foreign videos in the same folder are left alone
+        let mut recordings: Vec<_> = self
+            .get_recordings()
+            .into_iter()
+            .filter(|recording| action::is_native_recording(recording) && !expired.contains(recording))
+            .collect();
         recordings.sort_by(|a, b| util::compare_time(a, b).unwrap_or(Ordering::Equal));
 
         let mut total_size = 0;
 
         // add size from video thats currently being recorded to the total (in case there is one)
         // so the total size of all videos stays below the threshhold set in settings
-        if let Some(currently_recording_metadata) = self
-            .state::<CurrentlyRecording>()
-            .get()
-            .and_then(|pb| pb.metadata().ok())
-        {
-            total_size += currently_recording_metadata.len();
+        if let Some(currently_recording) = self.state::<CurrentlyRecording>().get() {
+            total_size += action::recording_size(&currently_recording);
         }
 
-        // split recordings into 'favorites' and 'others' by json metadata 'favorite' value
-        // in case reading the metadata fails put the recording into favorites so it doesn't get deleted
-        let (favorites, others): (Vec<_>, Vec<_>) = recordings.into_iter().partition(|recording| {
+        // split recordings into 'kept' (favorite or protected) and 'others' by their json metadata
+        // in case reading the metadata fails keep the recording so it doesn't get deleted
+        let (kept, others): (Vec<_>, Vec<_>) = recordings.into_iter().partition(|recording| {
             action::get_recording_metadata(recording, false)
-                .map(|metadata_file| metadata_file.is_favorite())
+                .map(|metadata_file| metadata_file.is_favorite() || metadata_file.is_protected())
                 .unwrap_or(true)
         });
 
-        // get sum of sizes of recordings marked as favorites
-        for recording in favorites {
-            match recording.metadata() {
-                Ok(metadata) => total_size += metadata.len(),
-                Err(e) => log::warn!(
-                    "failed to get size of recording (favorite) {}: {e}",
-                    recording.display(),
-                ),
-            }
-        }
+        // get sum of sizes of recordings that are kept
+        total_size += kept
+            .iter()
+            .map(|recording| action::recording_size(recording))
+            .sum::<u64>();
 
-        for recording in others {
-            match recording.metadata() {
-                Ok(metadata) => total_size += metadata.len(),
-                Err(e) => log::warn!("failed to get size of recording {}: {e}", recording.display(),),
-            }
-
-            if total_size > max_size {
-                if let Err(e) = action::delete_recording(recording) {
-                    log::error!("failed to delete file due to size limit: {e}");
-                }
-            }
-        }
+        others
+            .into_iter()
+            .filter(|recording| {
+                total_size += action::recording_size(recording);
+                total_size > max_size
+            })
+            .collect()
     }
 
-    fn cleanup_recordings_by_age(&self) {
-        fn too_old(file: &Path, max_age: Duration, now: SystemTime) -> Result<bool> {
-            let creation_time = file.metadata()?.created()?;
-            let time_passed = now.duration_since(creation_time)?;
-            Ok(time_passed > max_age)
-        }
+    fn clips_to_cleanup(&self) -> Vec<PathBuf> {
+        use std::cmp::Ordering;
 
-        fn is_favorite(file: &Path) -> Result<bool> {
-            action::get_recording_metadata(file, false).map(|metadata_file| metadata_file.is_favorite())
+        let settings = self.state::<SettingsWrapper>();
+        let retention = settings.retention();
+        if retention.clips_max_age_days.is_none() && retention.clips_max_size_gb.is_none() {
+            return vec![];
         }
+        let max_size = retention.clips_max_size_gb.map(|gb| gb.saturating_mul(1_000_000_000));
+
+        let Ok(read_dir) = settings.get_clips_path().read_dir() else { return vec![] };
+        let mut clips: Vec<_> = read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "mp4"))
+            .filter(|path| !action::is_native_recording(path))
+            .collect();
+        clips.sort_by(|a, b| util::compare_time(a, b).unwrap_or(Ordering::Equal));
 
-        let Some(max_days) = self.state::<SettingsWrapper>().max_recording_age() else { return };
-        let max_age = Duration::from_secs(max_days * 24 * 60 * 60);
+        // newest clips first so the oldest ones get deleted once the budget is used up
         let now = SystemTime::now();
-        for recording in self.get_recordings() {
-            // in case checking 'too_old(...)' or 'is_favorite(...)' fails default to not deleting the file
-            if too_old(&recording, max_age, now).unwrap_or(false) && !is_favorite(&recording).unwrap_or(true) {
-                if let Err(e) = action::delete_recording(recording) {
-                    log::error!("failed to delete file due to age limit: {e}");
+        let mut total_size = 0;
+        clips
+            .into_iter()
+            .filter(|clip| {
+                if retention
+                    .clips_max_age_days
+                    .is_some_and(|days| too_old(clip, days, now).unwrap_or(false))
+                {
+                    return true;
                 }
-            }
-        }
+                total_size += action::recording_size(clip);
+                max_size.is_some_and(|max_size| total_size > max_size)
+            })
+            .collect()
     }
 }
 
+fn too_old(file: &Path, max_age_days: u64, now: SystemTime) -> Result<bool> {
+    let max_age = Duration::from_secs(max_age_days.saturating_mul(24 * 60 * 60));
+    let creation_time = file.metadata()?.created()?;
+    let time_passed = now.duration_since(creation_time)?;
+    Ok(time_passed > max_age)
+}
+
 pub mod action {
+    use std::ffi::OsStr;
     use std::fs::{self, File};
-    use std::io::{BufReader, BufWriter};
+    use std::io::{self, BufReader, BufWriter};
+    #[cfg(target_os = "windows")]
+    use std::os::windows::process::CommandExt;
     use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use std::time::UNIX_EPOCH;
 
     use anyhow::{bail, Context, Result};
-    use tauri::async_runtime;
+    use shaco::rest::LcuRestClient;
+    use tauri::{async_runtime, AppHandle, Manager};
 
+    use crate::app::{cache_image, metadata_cache, CacheManager};
+    use crate::error::RecorderError;
+    use crate::privacy;
+    use crate::recorder::migration::{self, METADATA_SCHEMA_VERSION};
     use crate::recorder::MetadataFile;
-    use crate::recorder::{self, Deferred, NoData};
+    use crate::recorder::{self, Deferred, GameMetadata, HighlightCategory, Markers, NoData, StopReason};
+    use crate::state::{DeletedRecording, DeletedRecordings, ExportPreset, SettingsWrapper};
+    use crate::util;
+
+    use super::ClipMetadata;
+
+    const CHAMPION_SPLASH_CATEGORY: &str = "champion_splash";
+    // files stored next to a recording with the same name as the video
+    const SIDECAR_SUFFIXES: [&str; 4] = [".sb.json", ".clip.json", ".json", ".png"];
+    // subfolder of the recordings folder for recordings that were too short (see 'keepDiscardedRecordings')
+    const DISCARDED_FOLDER: &str = "discarded";
+    // segments of a split recording are named '<recording>.part<n>.mp4' (n >= 2)
+    const SEGMENT_INFIX: &str = ".part";
+
+    // tokens in 'filenameFormat' that only get substituted once the game is over (see `apply_filename_tokens`)
+    const FILENAME_TOKENS: [&str; 5] = ["{champion}", "{queue}", "{result}", "{kda}", "{lp}"];
+    // seconds of the recording before and after a highlight that end up in the highlight reel
+    const REEL_LEAD_TIME: f64 = 10.0;
+    const REEL_TAIL_TIME: f64 = 5.0;
 
     pub fn rename_recording(recording_path: PathBuf, new_name: String) -> Result<bool> {
         let mut new_recording_path = recording_path.clone();
         new_recording_path.set_file_name(PathBuf::from(new_name).file_name().context("invalid new filename")?);
 
-        let mut metadata_path = recording_path.clone();
-        metadata_path.set_extension("json");
-
         let mut new_metadata_path = new_recording_path.clone();
         new_metadata_path.set_extension("json");
 
@@ -158,51 +423,463 @@ pub mod action {
             return Ok(false);
         }
 
-        fs::rename(&recording_path, &new_recording_path)?;
-        fs::rename(&metadata_path, &new_metadata_path)?;
+        // move the files that belong to the video first so the filewatcher doesn't try to follow the rename, they are
+        // moved back if the video can't be renamed (e.g. because it is open in the player)
+        let result = rename_sidecars(&recording_path, &new_recording_path)
+            .and_then(|_| Ok(fs::rename(&recording_path, &new_recording_path)?));
+        if let Err(e) = result {
+            if let Err(rollback_error) = rename_sidecars(&new_recording_path, &recording_path) {
+                log::error!(
+                    "failed to move the files of {} back: {rollback_error}",
+                    recording_path.display()
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(true)
+    }
+
+    /// Move the metadata, screenshot and segments of a recording that got renamed outside of LeagueRecord (e.g. in the
+    /// Explorer) to the new name of the video. Returns whether there was anything to move.
+    pub fn follow_rename(recording_path: &Path, new_recording_path: &Path) -> Result<bool> {
+        if is_segment(recording_path)
+            || !recording_path.with_extension("json").is_file()
+            || new_recording_path.with_extension("json").exists()
+        {
+            return Ok(false);
+        }
 
+        rename_sidecars(recording_path, new_recording_path)?;
         Ok(true)
     }
 
+    fn rename_sidecars(recording_path: &Path, new_recording_path: &Path) -> Result<()> {
+        let stem = recording_path
+            .file_stem()
+            .context("invalid recording filename")?
+            .to_string_lossy();
+        let new_stem = new_recording_path
+            .file_stem()
+            .context("invalid recording filename")?
+            .to_string_lossy();
+
+        for suffix in SIDECAR_SUFFIXES {
+            let sidecar = recording_path.with_file_name(format!("{stem}{suffix}"));
+            if sidecar.is_file() {
+                fs::rename(
+                    &sidecar,
+                    new_recording_path.with_file_name(format!("{new_stem}{suffix}")),
+                )?;
+            }
+        }
+
+        for (index, segment) in segment_paths(recording_path).into_iter().enumerate() {
+            fs::rename(segment, segment_path(new_recording_path, index + 2))?;
+        }
+
+        // the metadata references the segments by their file name
+        if let Ok(mut metadata_file) = get_recording_metadata(new_recording_path, false) {
+            if let Some(segments) = metadata_file.segments_mut().filter(|segments| !segments.is_empty()) {
+                for (index, segment) in segments.iter_mut().enumerate() {
+                    let path = segment_path(new_recording_path, index + 2);
+                    segment.file_name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                }
+                save_recording_metadata(&new_recording_path.with_extension("json"), &metadata_file)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Substitute the tokens of 'filenameFormat' that depend on the outcome of the game (e.g. `{champion}`) in the
+    /// name of the recording once its metadata is complete.
+    /// Returns the new path of the recording (`video_path` if there were no tokens to substitute).
+    ///
+    /// With `safe_titles` `{champion}` becomes the game id so the name doesn't give away the account.
+    pub fn apply_filename_tokens(video_path: &Path, metadata: &GameMetadata, safe_titles: bool) -> Result<PathBuf> {
+        let stem = video_path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .context("invalid filename")?;
+        if !has_filename_tokens(video_path) {
+            return Ok(video_path.to_path_buf());
+        }
+
+        let mut new_stem = stem.to_owned();
+        for token in FILENAME_TOKENS {
+            let value = filename_token_value(token, metadata, safe_titles);
+            let value = value.replace(['<', '>', ':', '"', '/', '\\', '|', '?', '*'], "-");
+            new_stem = new_stem.replace(token, value.trim());
+        }
+
+        // another recording can end up with the same name (e.g. same champion and result within the same minute)
+        for attempt in 1..100 {
+            let new_name = match attempt {
+                1 => format!("{new_stem}.mp4"),
+                n => format!("{new_stem} ({n}).mp4"),
+            };
+            if rename_recording(video_path.to_path_buf(), new_name.clone())? {
+                return Ok(video_path.with_file_name(new_name));
+            }
+        }
+        bail!("no free filename for {new_stem}")
+    }
+
+    /// The name of the recording still contains tokens of 'filenameFormat' (see `apply_filename_tokens`)
+    pub fn has_filename_tokens(video_path: &Path) -> bool {
+        video_path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .is_some_and(|stem| FILENAME_TOKENS.iter().any(|token| stem.contains(token)))
+    }
+
+    fn filename_token_value(token: &str, metadata: &GameMetadata, safe_titles: bool) -> String {
+        let stats = &metadata.stats;
+        match token {
+            "{champion}" if safe_titles => metadata.match_id.game_id.to_string(),
+            "{champion}" => metadata.champion_name.clone(),
+            "{queue}" => metadata.queue.name.clone(),
+            "{result}" if stats.game_ended_in_early_surrender => "Remake".into(),
+            "{result}" if stats.win => "Win".into(),
+            "{result}" => "Loss".into(),
+            "{kda}" => format!("{}-{}-{}", stats.kills, stats.deaths, stats.assists),
+            "{lp}" => metadata.lp_diff.map(|lp| format!("{lp:+}LP")).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
     pub fn delete_recording(recording: PathBuf) -> Result<()> {
         fs::remove_file(&recording)?;
 
+        for segment in segment_paths(&recording) {
+            fs::remove_file(segment)?;
+        }
+
+        let screenshot_path = recording.with_extension("png");
+        if screenshot_path.is_file() {
+            fs::remove_file(screenshot_path)?;
+        }
+
+        let clip_metadata_path = recording.with_extension("clip.json");
+        if clip_metadata_path.is_file() {
+            fs::remove_file(clip_metadata_path)?;
+        }
+
         let mut metadata_file = recording;
         metadata_file.set_extension("json");
-        fs::remove_file(metadata_file)?;
+        fs::remove_file(&metadata_file)?;
+        metadata_cache::remove(&metadata_file);
 
         Ok(())
     }
 
-    pub fn get_recording_metadata(video_path: &Path, fetch: bool) -> Result<MetadataFile> {
-        let video_path = video_path.to_owned();
-        if !video_path.is_file() {
+    pub fn delete_clip(clip: &Path) -> Result<()> {
+        fs::remove_file(clip)?;
+
+        let clip_metadata_path = clip.with_extension("clip.json");
+        if clip_metadata_path.is_file() {
+            fs::remove_file(clip_metadata_path)?;
+        }
+        Ok(())
+    }
+
+    /// Size in bytes of the video including all of its segments
+    pub fn recording_size(video_path: &Path) -> u64 {
+        std::iter::once(video_path.to_path_buf())
+            .chain(segment_paths(video_path))
+            .filter_map(|file| file.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Delete the recording permanently or move it to the recycle bin (see 'useRecycleBin') so it can be restored with
+    /// `undo_delete`.
+    pub fn remove_recording(app_handle: &AppHandle, recording: &Path) -> Result<()> {
+        let settings = app_handle.state::<SettingsWrapper>();
+        if settings.is_external(recording) {
+            bail!("recordings in external folders are read-only");
+        }
+        if !settings.use_recycle_bin() {
+            return delete_recording(recording.to_path_buf());
+        }
+
+        let files = trash_recording(recording)?;
+        app_handle.state::<DeletedRecordings>().push(DeletedRecording {
+            video_path: recording.to_path_buf(),
+            files,
+        });
+        Ok(())
+    }
+
+    /// Move the recording and every file that belongs to it to the recycle bin. Returns the moved files.
+    pub fn trash_recording(recording: &Path) -> Result<Vec<PathBuf>> {
+        let files = recording_files(recording)?;
+        trash::delete_all(&files)?;
+        Ok(files)
+    }
+
+    /// The video, its segments and all sidecar files that exist
+    pub fn recording_files(recording: &Path) -> Result<Vec<PathBuf>> {
+        let stem = recording
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .context("invalid recording filename")?;
+        Ok(std::iter::once(recording.to_path_buf())
+            .chain(segment_paths(recording))
+            .chain(
+                SIDECAR_SUFFIXES
+                    .iter()
+                    .map(|suffix| recording.with_file_name(format!("{stem}{suffix}"))),
+            )
+            .filter(|file| file.is_file())
+            .collect())
+    }
+
+    /// Move the recording and every file that belongs to it into `folder`. Returns the new path of the video.
+    pub fn move_recording(recording: &Path, folder: &Path) -> Result<PathBuf> {
+        let files = recording_files(recording)?;
+        if files.is_empty() {
             bail!("no such video");
         }
+        fs::create_dir_all(folder)?;
 
-        let mut metadata_path = video_path;
-        metadata_path.set_extension("json");
+        // check everything first so a name conflict doesn't leave a partially moved recording behind
+        let mut moves = Vec::with_capacity(files.len());
+        for file in files {
+            let destination = folder.join(file.file_name().context("invalid filename")?);
+            if destination.exists() {
+                bail!("{} already exists", destination.display());
+            }
+            moves.push((file, destination));
+        }
 
-        let filedata = if metadata_path.exists() && fs::metadata(&metadata_path)?.is_file() {
-            let reader = BufReader::new(File::open(&metadata_path)?);
-            serde_json::from_reader::<_, MetadataFile>(reader)?
-        } else {
-            let metadata_file = MetadataFile::NoData(NoData { favorite: false });
-            save_recording_metadata(&metadata_path, &metadata_file)?;
-            metadata_file
+        for (file, destination) in &moves {
+            // renaming fails if the folder is on a different drive
+            if fs::rename(file, destination).is_err() {
+                fs::copy(file, destination)?;
+                fs::remove_file(file)?;
+            }
+        }
+
+        Ok(folder.join(recording.file_name().context("invalid filename")?))
+    }
+
+    /// Restore files that were moved to the recycle bin by [`trash_recording`].
+    pub fn restore_recording(files: &[PathBuf]) -> Result<()> {
+        let mut items: Vec<_> = trash::os_limited::list()?
+            .into_iter()
+            .filter(|item| files.contains(&item.original_path()))
+            .collect();
+        if items.is_empty() {
+            bail!("the recording is not in the recycle bin anymore");
+        }
+
+        // a file with the same name could have been deleted before => only restore the most recent one
+        items.sort_by_key(|item| std::cmp::Reverse(item.time_deleted));
+        let mut restored = Vec::new();
+        items.retain(|item| {
+            let original_path = item.original_path();
+            let first = !restored.contains(&original_path);
+            restored.push(original_path);
+            first
+        });
+
+        trash::os_limited::restore_all(items)?;
+        Ok(())
+    }
+
+    /// Remove a recording that is too short to be useful (remake, failed launch, ...).
+    /// With `keep` the video and its sidecar files are moved into the 'discarded' subfolder instead of being deleted.
+    pub fn discard_recording(recording: &Path, keep: bool) -> Result<()> {
+        if !keep {
+            return delete_recording(recording.to_path_buf());
+        }
+
+        let discarded_folder = recording
+            .parent()
+            .context("recording without parent folder")?
+            .join(DISCARDED_FOLDER);
+        move_recording(recording, &discarded_folder)?;
+        Ok(())
+    }
+
+    /// Path of the `index`-th file of a split recording (the recording itself is the first).
+    pub fn segment_path(recording: &Path, index: usize) -> PathBuf {
+        let stem = recording.file_stem().unwrap_or_default().to_string_lossy();
+        recording.with_file_name(format!("{stem}{SEGMENT_INFIX}{index}.mp4"))
+    }
+
+    /// Whether `path` is a segment that continues another recording
+    pub fn is_segment(path: &Path) -> bool {
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else { return false };
+        stem.rsplit_once(SEGMENT_INFIX).is_some_and(|(recording, index)| {
+            !index.is_empty()
+                && index.bytes().all(|b| b.is_ascii_digit())
+                && path.with_file_name(format!("{recording}.mp4")).is_file()
+        })
+    }
+
+    /// All existing segments that continue `recording` in order
+    pub fn segment_paths(recording: &Path) -> Vec<PathBuf> {
+        (2..)
+            .map(|index| segment_path(recording, index))
+            .take_while(|segment| segment.is_file())
+            .collect()
+    }
+
+    /// A recording whose recorder never got stopped properly because the app or PC crashed: the stop info never got
+    /// written to the metadata and at least one of its video files isn't finalized.
+    pub fn is_interrupted_recording(video_path: &Path) -> bool {
+        let Ok(MetadataFile::Deferred(deferred)) = get_recording_metadata(video_path, false) else { return false };
+        deferred.stop_reason.is_none()
+            && std::iter::once(video_path.to_path_buf())
+                .chain(segment_paths(video_path))
+                .any(|file| !util::is_mp4_finalized(&file).unwrap_or(true))
+    }
+
+    /// Remux the unfinished video files of an interrupted recording to rebuild their index and mark it as interrupted.
+    /// The recording gets marked even if remuxing fails so a video that can't be repaired isn't retried on every start.
+    pub fn recover_recording(video_path: &Path, ffmpeg: &str) -> Result<()> {
+        let mut result = Ok(());
+        for file in std::iter::once(video_path.to_path_buf()).chain(segment_paths(video_path)) {
+            if !util::is_mp4_finalized(&file).unwrap_or(true) {
+                if let Err(e) = remux(&file, ffmpeg) {
+                    result = Err(e);
+                }
+            }
+        }
+
+        let MetadataFile::Deferred(mut deferred) = get_recording_metadata(video_path, false)? else {
+            bail!("recording is not deferred");
         };
+        deferred.stop_reason = Some(StopReason::Interrupted);
+        save_recording_metadata(video_path, &MetadataFile::Deferred(deferred))?;
+
+        result
+    }
+
+    /// Copy the streams of the video into a new container and replace the original with it.
+    pub fn remux(video_path: &Path, ffmpeg: &str) -> Result<()> {
+        let remuxed_path = video_path.with_extension("remux.tmp");
+
+        let mut command = ffmpeg_command(ffmpeg);
+
+        let status = command
+            .arg("-y")
+            .arg("-i")
+            .arg(video_path)
+            .arg("-map")
+            .arg("0")
+            .arg("-c")
+            .arg("copy")
+            .arg("-f")
+            .arg("mp4")
+            .arg(&remuxed_path)
+            .status()
+            .map_err(ffmpeg_error)?;
+        if !status.success() {
+            _ = fs::remove_file(&remuxed_path);
+            bail!("ffmpeg exited with {status}");
+        }
+
+        fs::rename(&remuxed_path, video_path)?;
+        Ok(())
+    }
+
+    /// Every recording LeagueRecord creates gets a .json metadata file next to it as soon as the recording starts.
+    /// Videos without one were written by some other program (e.g. another capture tool using the same folder), which
+    /// can also write a .json file with the same name.
+    pub fn is_native_recording(video_path: &Path) -> bool {
+        let metadata_path = video_path.with_extension("json");
+        if metadata_cache::get(&metadata_path).is_some() {
+            return true;
+        }
+
+        let Ok(json) = read_json(&metadata_path) else { return false };
+        if !migration::is_metadata_file(&json) {
+            return false;
+        }
+        // parsed from the same JSON and cached so reading the metadata afterwards doesn't read the file again
+        if let Err(e) = metadata_from_json(&metadata_path, json) {
+            log::warn!("failed to parse metadata {}: {e}", metadata_path.display());
+        }
+        true
+    }
+
+    /// Adopt a foreign video into the library by creating an empty metadata file for it.
+    pub fn import_recording(video_path: &Path) -> Result<MetadataFile> {
+        if !video_path.is_file() {
+            bail!("no such video");
+        }
+        if is_native_recording(video_path) {
+            bail!("video is already part of the library");
+        }
+        if video_path.with_extension("json").exists() {
+            bail!("the .json file next to the video belongs to another program");
+        }
+
+        let metadata_file = MetadataFile::NoData(NoData {
+            id: recorder::new_recording_id(),
+            favorite: false,
+            protected: false,
+            schema_version: METADATA_SCHEMA_VERSION,
+        });
+        save_recording_metadata(video_path, &metadata_file)?;
+        Ok(metadata_file)
+    }
+
+    pub fn get_recording_metadata(video_path: &Path, fetch: bool) -> Result<MetadataFile> {
+        if !video_path.is_file() {
+            bail!("no such video");
+        }
+        if !is_native_recording(video_path) {
+            bail!("not a LeagueRecord recording (no metadata file)");
+        }
+
+        let metadata_path = video_path.with_extension("json");
+        let filedata = read_metadata_file(&metadata_path)?;
 
         match filedata {
             MetadataFile::Deferred(Deferred {
+                id,
                 match_id,
+                account,
+                lobby,
+                clash,
+                party,
                 ingame_time_rec_start_offset,
+                raw_ingame_time_rec_start_offset,
                 favorite,
+                protected,
                 highlights,
+                scoreboard,
+                stop_reason,
+                video_duration,
+                segments,
+                recording_stats,
+                ..
             }) if fetch => {
-                let mut metadata =
-                    async_runtime::block_on(recorder::process_data(ingame_time_rec_start_offset, match_id, vec![]))?;
+                let mut metadata = async_runtime::block_on(recorder::process_data(
+                    ingame_time_rec_start_offset,
+                    match_id,
+                    vec![],
+                    vec![],
+                ))?;
+                metadata.id = id;
+                metadata.account = account;
+                metadata.lobby = lobby;
+                metadata.clash = clash;
+                metadata.mark_premades(&party);
                 metadata.favorite = favorite;
+                metadata.protected = protected;
                 metadata.highlights = highlights;
+                metadata.raw_ingame_time_rec_start_offset = raw_ingame_time_rec_start_offset;
+                metadata.scoreboard = scoreboard;
+                metadata.stop_reason = stop_reason;
+                metadata.video_duration = video_duration;
+                metadata.segments = segments;
+                metadata.recording_stats = recording_stats;
                 let metadata_file = MetadataFile::Metadata(metadata);
                 if let Err(e) = save_recording_metadata(&metadata_path, &metadata_file) {
                     log::error!("failed to save re-processed game metadata: {e}");
@@ -213,11 +890,461 @@ pub mod action {
         }
     }
 
+    /// Backfill the metadata of a deferred recording from the Riot API (Match-V5).
+    /// Only recordings that know their match (Deferred) can be backfilled. See [`recorder::process_riot_api_data`] for
+    /// when the League client has to be running.
+    pub async fn import_riot_metadata(video_path: &Path, api_key: &str) -> Result<MetadataFile> {
+        let metadata_path = video_path.with_extension("json");
+        let metadata_file = read_metadata_file(&metadata_path)?;
+        let MetadataFile::Deferred(Deferred {
+            id,
+            match_id,
+            account,
+            lobby,
+            clash,
+            party,
+            ingame_time_rec_start_offset,
+            raw_ingame_time_rec_start_offset,
+            favorite,
+            protected,
+            highlights,
+            scoreboard,
+            stop_reason,
+            video_duration,
+            segments,
+            recording_stats,
+            ..
+        }) = metadata_file
+        else {
+            bail!("recording has no deferred match to backfill");
+        };
+
+        let mut metadata =
+            recorder::process_riot_api_data(ingame_time_rec_start_offset, match_id, account.as_ref(), api_key).await?;
+        metadata.id = id;
+        metadata.account = account;
+        metadata.lobby = lobby;
+        metadata.clash = clash;
+        metadata.mark_premades(&party);
+        metadata.favorite = favorite;
+        metadata.protected = protected;
+        metadata.highlights = highlights;
+        metadata.raw_ingame_time_rec_start_offset = raw_ingame_time_rec_start_offset;
+        metadata.scoreboard = scoreboard;
+        metadata.stop_reason = stop_reason;
+        metadata.video_duration = video_duration;
+        metadata.segments = segments;
+        metadata.recording_stats = recording_stats;
+
+        let metadata_file = MetadataFile::Metadata(metadata);
+        save_recording_metadata(&metadata_path, &metadata_file)?;
+        Ok(metadata_file)
+    }
+
+    /// Download the official replay of the recorded game and store its path in the metadata file.
+    pub async fn download_replay(video_path: &Path) -> Result<PathBuf> {
+        let MetadataFile::Metadata(metadata) = get_recording_metadata(video_path, false)? else {
+            bail!("recording has no game data");
+        };
+
+        let lcu_rest_client = LcuRestClient::new()?;
+        let replay_path = recorder::download_replay(&lcu_rest_client, &metadata.match_id).await?;
+
+        // the download can take minutes, read the metadata again to keep changes made in the meantime (e.g. favorite)
+        let MetadataFile::Metadata(mut metadata) = get_recording_metadata(video_path, false)? else {
+            bail!("recording has no game data");
+        };
+        metadata.replay_path = Some(replay_path.clone());
+        save_recording_metadata(video_path, &MetadataFile::Metadata(metadata))?;
+        Ok(replay_path)
+    }
+
+    /// Open the official replay of the recorded game close to `video_time` (in seconds) of the recording.
+    /// Downloads the replay first if that hasn't happened yet.
+    pub async fn open_replay_at(video_path: &Path, video_time: f64) -> Result<()> {
+        let MetadataFile::Metadata(metadata) = get_recording_metadata(video_path, false)? else {
+            bail!("recording has no game data");
+        };
+
+        let lcu_rest_client = LcuRestClient::new()?;
+        if !metadata.replay_path.as_ref().is_some_and(|path| path.is_file()) {
+            download_replay(video_path).await?;
+        }
+
+        let game_time = recorder::game_time_at(&metadata, video_time);
+        recorder::watch_replay_at(&lcu_rest_client, &metadata.match_id, game_time).await
+    }
+
+    /// Download the splash art of the recorded champion into the image cache. It is the thumbnail of the recording until
+    /// a frame of the video is available (see [`thumbnail`]).
+    pub async fn cache_champion_splash(app_handle: &AppHandle, metadata: &GameMetadata) -> Result<PathBuf> {
+        let champion_id = metadata
+            .champion_id()
+            .context("recorded player not found in participants")?;
+        let url = format!("https://cdn.communitydragon.org/latest/champion/{champion_id}/splash-art/centered");
+        cache_image(
+            app_handle,
+            &url,
+            CHAMPION_SPLASH_CATEGORY,
+            &format!("{champion_id}.jpg"),
+        )
+        .await
+    }
+
+    /// Thumbnail of a recording: the post-game screenshot if there is one, otherwise the cached champion splash art.
+    pub fn thumbnail(app_handle: &AppHandle, video_path: &Path, metadata: Option<&MetadataFile>) -> Option<PathBuf> {
+        let screenshot_path = video_path.with_extension("png");
+        if screenshot_path.is_file() {
+            return Some(screenshot_path);
+        }
+
+        let Some(MetadataFile::Metadata(metadata)) = metadata else { return None };
+        let champion_id = metadata.champion_id()?;
+        app_handle
+            .get_image_cache_path(CHAMPION_SPLASH_CATEGORY, &format!("{champion_id}.jpg"))
+            .filter(|path| path.is_file())
+    }
+
+    /// Cut `duration` seconds starting at `start` out of the video without re-encoding.
+    /// Fails if `output_path` already exists unless `overwrite` is set.
+    pub fn extract_clip(
+        video_path: &Path,
+        output_path: &Path,
+        start: f64,
+        duration: f64,
+        overwrite: bool,
+        ffmpeg: &str,
+    ) -> Result<()> {
+        let mut command = ffmpeg_command(ffmpeg);
+
+        let status = command
+            .arg(if overwrite { "-y" } else { "-n" })
+            .arg("-ss")
+            .arg(format!("{start:.3}"))
+            .arg("-i")
+            .arg(video_path)
+            .arg("-t")
+            .arg(format!("{duration:.3}"))
+            .arg("-c")
+            .arg("copy")
+            .arg(output_path)
+            .status()
+            .map_err(ffmpeg_error)?;
+        if !status.success() {
+            bail!("ffmpeg exited with {status}");
+        }
+        Ok(())
+    }
+
+    /// Join the parts around the highlights of the recording into `<recording>_highlights.mp4` in `folder` without
+    /// re-encoding. Highlights that are close together share one part. Bookmarks aren't part of the reel.
+    pub fn create_highlight_reel(video_path: &Path, folder: &Path, ffmpeg: &str) -> Result<PathBuf> {
+        let metadata_file = get_recording_metadata(video_path, false)?;
+        let mut highlights: Vec<_> = Markers::new(&metadata_file)
+            .map(|markers| markers.highlights)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|highlight| highlight.category == HighlightCategory::Highlight)
+            .map(|highlight| highlight.position)
+            .collect();
+        if highlights.is_empty() {
+            bail!("recording has no highlights");
+        }
+        highlights.sort_by(|a, b| a.video_time.total_cmp(&b.video_time));
+
+        let mut parts: Vec<(f64, f64)> = Vec::new();
+        for highlight in highlights {
+            let start = (highlight.video_time - REEL_LEAD_TIME).max(0.0);
+            let end = highlight.video_time + REEL_TAIL_TIME;
+            match parts.last_mut() {
+                Some((_, last_end)) if start <= *last_end => *last_end = last_end.max(end),
+                _ => parts.push((start, end)),
+            }
+        }
+
+        let stem = video_path.file_stem().context("invalid video path")?.to_string_lossy();
+        let output_path = folder.join(format!("{stem}_highlights.mp4"));
+        if output_path.exists() {
+            bail!("{} already exists", output_path.display());
+        }
+        fs::create_dir_all(folder)?;
+
+        let temp_dir = std::env::temp_dir();
+        let part_paths: Vec<_> = (0..parts.len())
+            .map(|index| temp_dir.join(format!("{stem}_reel{index}.mp4")))
+            .collect();
+        let list_path = temp_dir.join(format!("{stem}_reel.txt"));
+
+        let mut result = Ok(());
+        for ((start, end), part_path) in parts.iter().zip(&part_paths) {
+            // left over parts of an earlier reel that failed
+            result = extract_clip(video_path, part_path, *start, end - start, true, ffmpeg);
+            if result.is_err() {
+                break;
+            }
+        }
+        if result.is_ok() {
+            result = concat_videos(&part_paths, &list_path, &output_path, ffmpeg);
+        }
+
+        for part_path in &part_paths {
+            _ = fs::remove_file(part_path);
+        }
+        _ = fs::remove_file(&list_path);
+        result.map(|_| output_path)
+    }
+
+    /// Join the videos with the concat demuxer of ffmpeg (which needs a list of the files in `list_path`)
+    fn concat_videos(videos: &[PathBuf], list_path: &Path, output_path: &Path, ffmpeg: &str) -> Result<()> {
+        let list: String = videos
+            .iter()
+            .map(|video| format!("file '{}'\n", video.display().to_string().replace('\'', r"'\''")))
+            .collect();
+        fs::write(list_path, list)?;
+
+        let mut command = ffmpeg_command(ffmpeg);
+
+        let status = command
+            .arg("-y")
+            .arg("-f")
+            .arg("concat")
+            .arg("-safe")
+            .arg("0")
+            .arg("-i")
+            .arg(list_path)
+            .arg("-c")
+            .arg("copy")
+            .arg(output_path)
+            .status()
+            .map_err(ffmpeg_error)?;
+        if !status.success() {
+            _ = fs::remove_file(output_path);
+            bail!("ffmpeg exited with {status}");
+        }
+        Ok(())
+    }
+
+    /// Export a copy of the video to the destination of `preset`. Only re-encodes if the preset changes the resolution or
+    /// bitrate, otherwise the streams are copied into the new container.
+    pub fn export_video(video_path: &Path, preset: &ExportPreset, ffmpeg: &str) -> Result<PathBuf> {
+        let file_stem = video_path.file_stem().context("invalid video path")?;
+        let output_path = preset.destination.join(file_stem).with_extension(&preset.format);
+        if output_path.exists() {
+            bail!("{} already exists", output_path.display());
+        }
+        fs::create_dir_all(&preset.destination)?;
+
+        let mut command = ffmpeg_command(ffmpeg);
+
+        command.arg("-i").arg(video_path);
+        if preset.resolution.is_none() && preset.bitrate.is_none() {
+            command.arg("-c").arg("copy");
+        } else {
+            if let Some(height) = preset.resolution {
+                // -2 keeps the aspect ratio with an even width which most encoders require
+                command.arg("-vf").arg(format!("scale=-2:{height}"));
+            }
+            if let Some(bitrate) = preset.bitrate {
+                command.arg("-b:v").arg(format!("{bitrate}k"));
+            }
+        }
+
+        let status = command.arg(&output_path).status().map_err(ffmpeg_error)?;
+        if !status.success() {
+            bail!("ffmpeg exited with {status}");
+        }
+
+        Ok(output_path)
+    }
+
+    /// Extract the last frame of the recording (usually the victory/defeat screen) as a .png next to the video.
+    pub fn create_postgame_screenshot(video_path: &Path, ffmpeg: &str) -> Result<PathBuf> {
+        if !video_path.is_file() {
+            bail!("no such video");
+        }
+        let screenshot_path = video_path.with_extension("png");
+
+        let mut command = ffmpeg_command(ffmpeg);
+
+        // seek to shortly before the end and keep overwriting the image with every decoded frame => last frame remains
+        let status = command
+            .arg("-y")
+            .arg("-sseof")
+            .arg("-1")
+            .arg("-i")
+            .arg(video_path)
+            .arg("-update")
+            .arg("1")
+            .arg(&screenshot_path)
+            .status()
+            .map_err(ffmpeg_error)?;
+        if !status.success() {
+            bail!("ffmpeg exited with {status}");
+        }
+
+        Ok(screenshot_path)
+    }
+
+    /// All files in `folder` and its subfolders (see 'organizeBy') except for discarded recordings
+    pub fn library_files(folder: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut folders = vec![folder.to_path_buf()];
+        while let Some(folder) = folders.pop() {
+            let Ok(read_dir) = folder.read_dir() else { continue };
+            for entry in read_dir.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    if path.file_name() != Some(OsStr::new(DISCARDED_FOLDER)) {
+                        folders.push(path);
+                    }
+                } else if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+        files
+    }
+
+    /// The video a sidecar file (metadata, screenshot, ...) belongs to. None if `path` isn't a sidecar file.
+    pub fn sidecar_video_path(path: &Path) -> Option<PathBuf> {
+        let file_name = path.file_name()?.to_str()?;
+        let stem = SIDECAR_SUFFIXES
+            .iter()
+            .find_map(|suffix| file_name.strip_suffix(suffix))?;
+        Some(path.with_file_name(format!("{stem}.mp4")))
+    }
+
+    /// Fix references to files that don't exist anymore in the metadata of a recording.
+    /// Returns whether the metadata had to be changed.
+    pub fn reconcile_recording(video_path: &Path) -> Result<bool> {
+        let MetadataFile::Metadata(mut metadata) = get_recording_metadata(video_path, false)? else {
+            return Ok(false);
+        };
+
+        let stale_replay = metadata.replay_path.as_ref().is_some_and(|path| !path.is_file());
+        if !stale_replay {
+            return Ok(false);
+        }
+
+        metadata.replay_path = None;
+        save_recording_metadata(video_path, &MetadataFile::Metadata(metadata))?;
+        Ok(true)
+    }
+
+    pub fn get_clip_metadata(clip_path: &Path) -> Result<ClipMetadata> {
+        let metadata_path = clip_path.with_extension("clip.json");
+        if !metadata_path.is_file() {
+            return Ok(ClipMetadata::default());
+        }
+        let reader = BufReader::new(File::open(metadata_path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save_clip_metadata(clip_path: &Path, clip_metadata: &ClipMetadata) -> Result<()> {
+        let writer = BufWriter::new(File::create(clip_path.with_extension("clip.json"))?);
+        Ok(serde_json::to_writer_pretty(writer, clip_metadata)?)
+    }
+
+    /// Save the metadata file of the recording upgraded to the current schema. Returns whether it had to be changed.
+    /// Reading metadata only upgrades it in memory, this is the only place that writes the upgraded file.
+    pub fn migrate_metadata(video_path: &Path, settings: &SettingsWrapper) -> Result<bool> {
+        if settings.is_external(video_path) {
+            bail!("the metadata of external recordings is never changed");
+        }
+
+        // the cached metadata is always upgraded, only the file tells whether it is outdated
+        let metadata_path = video_path.with_extension("json");
+        let (metadata_file, migrated) = metadata_from_json(&metadata_path, read_json(&metadata_path)?)?;
+        if migrated {
+            save_recording_metadata(&metadata_path, &metadata_file)?;
+            log::info!(
+                "migrated {} to metadata schema {METADATA_SCHEMA_VERSION}",
+                metadata_path.display()
+            );
+        }
+        Ok(migrated)
+    }
+
+    /// Read a metadata file and upgrade it to the current schema if it was written by an older version.
+    /// The file itself is left as it is (see [`migrate_metadata`]).
+    fn read_metadata_file(metadata_path: &Path) -> Result<MetadataFile> {
+        // cached metadata is already migrated
+        if let Some(metadata_file) = metadata_cache::get(metadata_path) {
+            return Ok(metadata_file);
+        }
+
+        metadata_from_json(metadata_path, read_json(metadata_path)?).map(|(metadata_file, _)| metadata_file)
+    }
+
+    fn read_json(path: &Path) -> Result<serde_json::Value> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Upgrade and parse the content of the metadata file `metadata_path`. Returns whether it had to be upgraded.
+    fn metadata_from_json(metadata_path: &Path, mut json: serde_json::Value) -> Result<(MetadataFile, bool)> {
+        // the migration to schema 2 gives the recording a random ID
+        let without_id = migration::schema_version(&json) < 2;
+
+        let migrated = migration::migrate(&mut json)?;
+        let mut metadata_file = serde_json::from_value::<MetadataFile>(json)?;
+        privacy::reveal(&mut metadata_file);
+        // a new random ID on every read would break everything that refers to the recording by its ID, this one stays
+        // the same until the file gets migrated (and then gets saved)
+        if without_id {
+            metadata_file.set_id(fallback_recording_id(metadata_path));
+        }
+        metadata_cache::insert(metadata_path, &metadata_file);
+        Ok((metadata_file, migrated))
+    }
+
+    /// Stable ID of a recording from before recordings had IDs (from the path and creation time of its metadata file)
+    fn fallback_recording_id(metadata_path: &Path) -> String {
+        let mut seed = metadata_path.as_os_str().as_encoded_bytes().to_vec();
+        let created = fs::metadata(metadata_path).and_then(|metadata| metadata.created());
+        if let Ok(since_epoch) = created.map(|created| created.duration_since(UNIX_EPOCH).unwrap_or_default()) {
+            seed.extend_from_slice(&since_epoch.as_nanos().to_le_bytes());
+        }
+        recorder::derived_recording_id(&seed)
+    }
+
     pub fn save_recording_metadata(path: &Path, metadata_file: &MetadataFile) -> Result<()> {
         let mut path = path.to_owned();
         path.set_extension("json");
 
-        let writer = BufWriter::new(File::create(path)?);
-        Ok(serde_json::to_writer_pretty(writer, &metadata_file)?)
+        // only the aliases of the other players get written to the file (see 'privacyMode')
+        let redacted;
+        let stored = if privacy::is_enabled() {
+            redacted = privacy::redact(metadata_file);
+            &redacted
+        } else {
+            metadata_file
+        };
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(&mut writer, stored)?;
+        writer.into_inner().map_err(|e| e.into_error())?;
+
+        // the file is closed => the cache entry has the final modification time
+        metadata_cache::insert(&path, metadata_file);
+        Ok(())
+    }
+
+    /// ffmpeg without a console window and with below normal priority so post-processing doesn't make the game stutter
+    fn ffmpeg_command(ffmpeg: &str) -> Command {
+        let mut command = Command::new(ffmpeg);
+        #[cfg(target_os = "windows")]
+        {
+            use windows_sys::Win32::System::Threading::{BELOW_NORMAL_PRIORITY_CLASS, CREATE_NO_WINDOW};
+            command.creation_flags(CREATE_NO_WINDOW | BELOW_NORMAL_PRIORITY_CLASS);
+        }
+        command
+    }
+
+    fn ffmpeg_error(error: io::Error) -> anyhow::Error {
+        match error.kind() {
+            io::ErrorKind::NotFound => RecorderError::FfmpegMissing.into(),
+            _ => anyhow::Error::new(error).context("failed to execute ffmpeg"),
+        }
     }
 }
@@ -1,19 +1,36 @@
-use tauri::menu::{Menu, MenuBuilder, MenuEvent, MenuItemBuilder};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::menu::{Menu, MenuBuilder, MenuEvent, MenuItemBuilder, SubmenuBuilder};
 use tauri::tray::{MouseButton, TrayIcon, TrayIconBuilder, TrayIconEvent};
 use tauri::{async_runtime, AppHandle, Manager, Wry};
 
-use super::{AppWindow, WindowManager};
-use crate::constants::{self, menu_item, EXIT_SUCCESS};
-use crate::recorder::LeagueRecorder;
-use crate::state::{SettingsWrapper, Shutdown, TrayState};
+use super::{action, tray_icon, AppManager, AppWindow, RecordingManager, WindowManager};
+use crate::constants::{self, menu_item};
+use crate::recorder::{LeagueRecorder, MetadataFile};
+use crate::state::{SettingsWrapper, TrayState, TrayStatus};
+use crate::updater;
+use crate::util::{self, compare_time};
+
+// number of games in the 'Recent games' submenu
+const RECENT_GAMES: usize = 5;
+const ICON_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
 
 pub trait SystemTrayManager {
     fn init_tray_menu(&self);
 
-    #[allow(dead_code)]
     fn set_tray_menu_update_available(&self, update_button: bool);
 
     fn set_tray_menu_recording(&self, recording: bool);
+
+    fn set_tray_status(&self, status: TrayStatus);
+
+    /// Render the icon for the current status (and elapsed recording time)
+    fn update_tray_icon(&self);
+
+    /// Rebuild the tray menu so the recent games are up to date
+    fn refresh_tray_menu(&self);
 }
 
 fn handle_system_tray_event(tray_icon: &TrayIcon, event: TrayIconEvent) {
@@ -27,24 +44,27 @@ fn handle_system_tray_menu_event(app_handle: &AppHandle, event: MenuEvent) {
     match event.id().as_ref() {
         menu_item::SETTINGS => SettingsWrapper::let_user_edit_settings(app_handle),
         menu_item::OPEN => app_handle.open_window(AppWindow::Main),
+        menu_item::START_RECORDING => app_handle.state::<LeagueRecorder>().manual_start(),
+        menu_item::STOP_RECORDING => app_handle.state::<LeagueRecorder>().manual_stop(),
+        menu_item::OPEN_LAST => {
+            if let Some(recording) = recent_games(app_handle).into_iter().next() {
+                app_handle.open_recording(&recording);
+            }
+        }
+        id if id.starts_with(menu_item::RECENT_PREFIX) => {
+            let recording = PathBuf::from(&id[menu_item::RECENT_PREFIX.len()..]);
+            app_handle.open_recording(&recording);
+        }
         menu_item::QUIT => {
             app_handle
                 .webview_windows()
                 .into_values()
                 .for_each(|window| _ = window.close());
 
-            async_runtime::spawn({
-                let app_handle = app_handle.clone();
-                async move {
-                    log::info!("Shutting down via tray");
-                    app_handle.state::<LeagueRecorder>().stop().await;
-
-                    app_handle.state::<Shutdown>().set();
-                    app_handle.exit(EXIT_SUCCESS);
-                }
-            });
+            log::info!("Shutting down via tray");
+            app_handle.shutdown();
         }
-        // menu_item::UPDATE => app_handle.update(),
+        menu_item::UPDATE => updater::confirm_install(app_handle),
         _ => {}
     }
 }
@@ -61,6 +81,23 @@ impl SystemTrayManager for AppHandle {
             .show_menu_on_left_click(false)
             .build(self)
             .unwrap();
+
+        // the icon shows the elapsed minutes while recording
+        let app_handle = self.clone();
+        async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(ICON_UPDATE_INTERVAL);
+            let mut shown_minutes = None;
+            loop {
+                interval.tick().await;
+
+                let tray_state = app_handle.state::<TrayState>();
+                let minutes = (tray_state.status() == TrayStatus::Recording).then(|| tray_state.recording_minutes());
+                if minutes.is_some() && minutes != shown_minutes {
+                    app_handle.update_tray_icon();
+                }
+                shown_minutes = minutes;
+            }
+        });
     }
 
     fn set_tray_menu_update_available(&self, update_available: bool) {
@@ -79,33 +116,83 @@ impl SystemTrayManager for AppHandle {
         let tray = self.tray_by_id(constants::TRAY_ID).unwrap();
         tray.set_menu(Some(create_tray_menu(self))).unwrap();
 
-        if recording {
-            let width = 32;
-            let height = 32;
-            let mut rgba = Vec::with_capacity((width * height * 4) as usize);
-            let center = 15.5f32;
-            let radius_sq = 15.0f32 * 15.0f32;
-
-            for y in 0..height {
-                for x in 0..width {
-                    let dx = x as f32 - center;
-                    let dy = y as f32 - center;
-                    if dx * dx + dy * dy <= radius_sq {
-                        rgba.extend_from_slice(&[255, 0, 0, 255]);
-                    } else {
-                        rgba.extend_from_slice(&[0, 0, 0, 0]);
-                    }
-                }
-            }
-            let icon = tauri::image::Image::new(&rgba, width, height);
-            if let Err(e) = tray.set_icon(Some(icon)) {
-                log::error!("failed to set recording icon: {e}");
-            }
-        } else if let Some(icon) = self.default_window_icon() {
-            if let Err(e) = tray.set_icon(Some(icon.clone())) {
-                log::error!("failed to reset tray icon: {e}");
-            }
+        self.update_tray_icon();
+    }
+
+    fn set_tray_status(&self, status: TrayStatus) {
+        self.state::<TrayState>().set_status(status);
+        self.update_tray_icon();
+    }
+
+    fn update_tray_icon(&self) {
+        let Some(tray) = self.tray_by_id(constants::TRAY_ID) else { return };
+        let tray_state = self.state::<TrayState>();
+
+        let icon = match tray_state.status() {
+            TrayStatus::Idle => self.default_window_icon().cloned(),
+            status => Some(tray_icon::render(status, tray_state.recording_minutes())),
+        };
+        if let Err(e) = tray.set_icon(icon) {
+            log::error!("failed to set tray icon: {e}");
+        }
+    }
+
+    fn refresh_tray_menu(&self) {
+        let Some(tray) = self.tray_by_id(constants::TRAY_ID) else { return };
+        if let Err(e) = tray.set_menu(Some(create_tray_menu(self))) {
+            log::error!("failed to refresh tray menu: {e}");
+        }
+    }
+}
+
+/// The most recent recordings of games (without clips and foreign videos), newest first
+fn recent_games(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let settings = app_handle.state::<SettingsWrapper>();
+    let recordings_path = settings.get_recordings_path();
+    let clips_path = settings.get_clips_path();
+    let is_clip = |path: &Path| clips_path != recordings_path && path.starts_with(&clips_path);
+    let mut recordings = app_handle
+        .get_recordings()
+        .into_iter()
+        .filter(|path| path.starts_with(&recordings_path) && !is_clip(path) && action::is_native_recording(path))
+        .collect::<Vec<_>>();
+    recordings.sort_by(|a, b| compare_time(a, b).unwrap_or(Ordering::Equal));
+    recordings.truncate(RECENT_GAMES);
+    recordings
+}
+
+/// 'Champion - Result (name of the video)', with 'safeTitles' the game id and the date instead of any names
+fn recent_game_label(recording: &Path, safe_titles: bool) -> String {
+    // recordings from before 'safeTitles' got enabled can still have the champion in their name
+    let name = if safe_titles {
+        util::recorded_at(recording)
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .map(|date| date.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    } else {
+        recording
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    match action::get_recording_metadata(recording, false) {
+        Ok(MetadataFile::Metadata(metadata)) => {
+            let result = if metadata.stats.game_ended_in_early_surrender {
+                "Remake"
+            } else if metadata.stats.win {
+                "Victory"
+            } else {
+                "Defeat"
+            };
+            let title = if safe_titles {
+                format!("Game {}", metadata.match_id.game_id)
+            } else {
+                metadata.champion_name
+            };
+            format!("{title} - {result} ({name})")
         }
+        _ if safe_titles => format!("Recording ({name})"),
+        _ => name,
     }
 }
 
@@ -130,23 +217,52 @@ fn create_tray_menu(app_handle: &AppHandle) -> Menu<Wry> {
         .id(menu_item::UPDATE)
         .build(app_handle)
         .unwrap();
+    let start_recording = MenuItemBuilder::new("Start recording now")
+        .id(menu_item::START_RECORDING)
+        .enabled(!recording)
+        .build(app_handle)
+        .unwrap();
+    let stop_recording = MenuItemBuilder::new("Stop recording")
+        .id(menu_item::STOP_RECORDING)
+        .enabled(recording)
+        .build(app_handle)
+        .unwrap();
+
+    let recent = recent_games(app_handle);
+    let safe_titles = app_handle.state::<SettingsWrapper>().safe_titles();
+    let open_last = MenuItemBuilder::new("Open last recording")
+        .id(menu_item::OPEN_LAST)
+        .enabled(!recent.is_empty())
+        .build(app_handle)
+        .unwrap();
+    let recent_submenu = recent
+        .iter()
+        .fold(
+            SubmenuBuilder::with_id(app_handle, menu_item::RECENT, "Recent games"),
+            |submenu, recording| {
+                let id = format!("{}{}", menu_item::RECENT_PREFIX, recording.display());
+                submenu.text(id, recent_game_label(recording, safe_titles))
+            },
+        )
+        .enabled(!recent.is_empty())
+        .build()
+        .unwrap();
 
+    let tray_menu = MenuBuilder::new(app_handle)
+        .check(menu_item::RECORDING, "Recording")
+        .item(&start_recording)
+        .item(&stop_recording)
+        .separator()
+        .item(&open_last)
+        .item(&recent_submenu)
+        .separator()
+        .item(&settings)
+        .item(&open)
+        .item(&quit);
     let tray_menu = if update_available {
-        MenuBuilder::new(app_handle)
-            .check(menu_item::RECORDING, "Recording")
-            .separator()
-            .item(&settings)
-            .item(&open)
-            .item(&quit)
-            .separator()
-            .item(&update)
+        tray_menu.separator().item(&update)
     } else {
-        MenuBuilder::new(app_handle)
-            .check(menu_item::RECORDING, "Recording")
-            .separator()
-            .item(&settings)
-            .item(&open)
-            .item(&quit)
+        tray_menu
     }
     .build()
     .unwrap();
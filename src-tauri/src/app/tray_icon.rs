@@ -0,0 +1,94 @@
+use tauri::image::Image;
+
+use crate::state::TrayStatus;
+
+const SIZE: u32 = 32;
+const CENTER: f32 = 15.5;
+const RADIUS: f32 = 15.0;
+// width of the ring while waiting for the game to start
+const RING_WIDTH: f32 = 4.0;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const YELLOW: [u8; 4] = [255, 200, 0, 255];
+const BLUE: [u8; 4] = [30, 144, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+// 3x5 pixel digits, each row is 3 bits (most significant bit = left pixel)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const DIGIT_SCALE: u32 = 3;
+const DIGIT_GAP: u32 = 2;
+
+/// Tray icon for a status other than `Idle` (which uses the app icon).
+/// While recording the icon shows the elapsed minutes (up to 99).
+pub fn render(status: TrayStatus, elapsed_minutes: u64) -> Image<'static> {
+    let mut rgba = vec![0; (SIZE * SIZE * 4) as usize];
+
+    let (color, filled) = match status {
+        TrayStatus::Idle | TrayStatus::Recording => (RED, true),
+        TrayStatus::WaitingForGame => (YELLOW, false),
+        TrayStatus::ProcessingMetadata => (BLUE, true),
+    };
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let dx = x as f32 - CENTER;
+            let dy = y as f32 - CENTER;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance <= RADIUS && (filled || distance >= RADIUS - RING_WIDTH) {
+                set_pixel(&mut rgba, x, y, color);
+            }
+        }
+    }
+
+    if status == TrayStatus::Recording {
+        draw_number(&mut rgba, elapsed_minutes.min(99) as u32);
+    }
+
+    Image::new_owned(rgba, SIZE, SIZE)
+}
+
+fn draw_number(rgba: &mut [u8], number: u32) {
+    let digits: Vec<usize> = if number >= 10 {
+        vec![(number / 10) as usize, (number % 10) as usize]
+    } else {
+        vec![number as usize]
+    };
+
+    let digit_width = 3 * DIGIT_SCALE;
+    let width = digits.len() as u32 * digit_width + (digits.len() as u32 - 1) * DIGIT_GAP;
+    let left = (SIZE - width) / 2;
+    let top = (SIZE - 5 * DIGIT_SCALE) / 2;
+
+    for (i, &digit) in digits.iter().enumerate() {
+        let digit_left = left + i as u32 * (digit_width + DIGIT_GAP);
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for sy in 0..DIGIT_SCALE {
+                    for sx in 0..DIGIT_SCALE {
+                        let x = digit_left + column * DIGIT_SCALE + sx;
+                        let y = top + row as u32 * DIGIT_SCALE + sy;
+                        set_pixel(rgba, x, y, WHITE);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn set_pixel(rgba: &mut [u8], x: u32, y: u32, color: [u8; 4]) {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    rgba[offset..offset + 4].copy_from_slice(&color);
+}
@@ -0,0 +1,185 @@
+//! Duration, resolution, codec and bitrate of a video, read from the index (moov box) of the MP4 file.
+//!
+//! Only the headers are read, the sample tables (most of the moov box) get skipped, so this is cheap enough to do for
+//! every recording in the list. The UI can show the length of a video without loading it.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoInfo {
+    /// in seconds
+    pub duration: f64,
+    pub width: u32,
+    pub height: u32,
+    /// e.g. 'h264', 'hevc' or 'av1' (the sample entry type for other codecs)
+    pub codec: String,
+    /// average bitrate of the whole file (video and audio) in kbit/s
+    pub bitrate_kbps: u32,
+}
+
+struct Mp4Box {
+    kind: [u8; 4],
+    /// position of the content after the header
+    start: u64,
+    end: u64,
+}
+
+/// Read the `VideoInfo` of an MP4 file. Fails for files without an index (e.g. recordings that are still running).
+pub fn probe(path: &Path) -> Result<VideoInfo> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let top_level = children(&mut file, 0, file_len)?;
+    let moov = find(&top_level, b"moov").context("no moov box")?;
+    let moov_children = children(&mut file, moov.start, moov.end)?;
+
+    let mvhd = find(&moov_children, b"mvhd").context("no mvhd box")?;
+    let mut duration = read_duration(&mut file, mvhd)?;
+    // fragmented files only have the total duration in the movie extends header
+    if duration == 0.0 {
+        if let Some(mvex) = find(&moov_children, b"mvex") {
+            if let Some(mehd) = find(&children(&mut file, mvex.start, mvex.end)?, b"mehd") {
+                duration = read_fragment_duration(&mut file, mehd, mvhd)?;
+            }
+        }
+    }
+
+    for trak in moov_children.iter().filter(|child| &child.kind == b"trak") {
+        let trak_children = children(&mut file, trak.start, trak.end)?;
+        let Some(mdia) = find(&trak_children, b"mdia") else { continue };
+        let mdia_children = children(&mut file, mdia.start, mdia.end)?;
+        let Some(hdlr) = find(&mdia_children, b"hdlr") else { continue };
+        // version/flags and pre_defined come before the handler type
+        if &read_at::<4>(&mut file, hdlr.start + 8)? != b"vide" {
+            continue;
+        }
+
+        let tkhd = find(&trak_children, b"tkhd").context("no tkhd box")?;
+        let (width, height) = read_dimensions(&mut file, tkhd)?;
+        let codec = read_codec(&mut file, &mdia_children)?;
+        let bitrate_kbps = match duration > 0.0 {
+            true => (file_len as f64 * 8.0 / duration / 1000.0).round() as u32,
+            false => 0,
+        };
+        return Ok(VideoInfo {
+            duration,
+            width,
+            height,
+            codec,
+            bitrate_kbps,
+        });
+    }
+    bail!("no video track")
+}
+
+/// The boxes between `start` and `end` (only their headers are read)
+fn children(file: &mut File, start: u64, end: u64) -> Result<Vec<Mp4Box>> {
+    let mut boxes = Vec::new();
+    let mut position = start;
+    while position + 8 <= end {
+        let header = read_at::<8>(file, position)?;
+        let kind = [header[4], header[5], header[6], header[7]];
+        let (size, header_len) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+            // box extends to the end of its parent
+            0 => (end - position, 8),
+            // 64 bit size follows the box type
+            1 => (u64::from_be_bytes(read_at::<8>(file, position + 8)?), 16),
+            size => (size as u64, 8),
+        };
+        if size < header_len || position + size > end {
+            bail!("truncated {} box", String::from_utf8_lossy(&kind));
+        }
+
+        boxes.push(Mp4Box {
+            kind,
+            start: position + header_len,
+            end: position + size,
+        });
+        position += size;
+    }
+    Ok(boxes)
+}
+
+fn find<'a>(boxes: &'a [Mp4Box], kind: &[u8; 4]) -> Option<&'a Mp4Box> {
+    boxes.iter().find(|child| &child.kind == kind)
+}
+
+fn read_at<const N: usize>(file: &mut File, position: u64) -> Result<[u8; N]> {
+    let mut buffer = [0; N];
+    file.seek(SeekFrom::Start(position))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Time scale (units per second) of the movie header
+fn read_timescale(file: &mut File, mvhd: &Mp4Box) -> Result<u32> {
+    let version = read_at::<1>(file, mvhd.start)?[0];
+    // after version/flags and the creation and modification time
+    let offset = if version == 1 { 20 } else { 12 };
+    Ok(u32::from_be_bytes(read_at(file, mvhd.start + offset)?))
+}
+
+fn read_duration(file: &mut File, mvhd: &Mp4Box) -> Result<f64> {
+    let version = read_at::<1>(file, mvhd.start)?[0];
+    let timescale = read_timescale(file, mvhd)?;
+    let duration = match version {
+        1 => u64::from_be_bytes(read_at(file, mvhd.start + 24)?),
+        _ => u32::from_be_bytes(read_at(file, mvhd.start + 16)?) as u64,
+    };
+    Ok(seconds(duration, timescale))
+}
+
+fn read_fragment_duration(file: &mut File, mehd: &Mp4Box, mvhd: &Mp4Box) -> Result<f64> {
+    let version = read_at::<1>(file, mehd.start)?[0];
+    let duration = match version {
+        1 => u64::from_be_bytes(read_at(file, mehd.start + 4)?),
+        _ => u32::from_be_bytes(read_at(file, mehd.start + 4)?) as u64,
+    };
+    Ok(seconds(duration, read_timescale(file, mvhd)?))
+}
+
+fn seconds(duration: u64, timescale: u32) -> f64 {
+    match timescale {
+        0 => 0.0,
+        timescale => duration as f64 / timescale as f64,
+    }
+}
+
+/// Width and height of the track header (16.16 fixed point numbers)
+fn read_dimensions(file: &mut File, tkhd: &Mp4Box) -> Result<(u32, u32)> {
+    let version = read_at::<1>(file, tkhd.start)?[0];
+    // version/flags, times, track id and duration, then reserved fields, layer, group, volume and the matrix
+    let offset = if version == 1 { 88 } else { 76 };
+    let dimensions = read_at::<8>(file, tkhd.start + offset)?;
+    let width = u32::from_be_bytes([dimensions[0], dimensions[1], dimensions[2], dimensions[3]]) >> 16;
+    let height = u32::from_be_bytes([dimensions[4], dimensions[5], dimensions[6], dimensions[7]]) >> 16;
+    Ok((width, height))
+}
+
+/// Codec of the first sample description (mdia > minf > stbl > stsd)
+fn read_codec(file: &mut File, mdia_children: &[Mp4Box]) -> Result<String> {
+    let minf = find(mdia_children, b"minf").context("no minf box")?;
+    let stbl = find(&children(file, minf.start, minf.end)?, b"stbl")
+        .map(|stbl| (stbl.start, stbl.end))
+        .context("no stbl box")?;
+    let stsd = find(&children(file, stbl.0, stbl.1)?, b"stsd")
+        .map(|stsd| stsd.start)
+        .context("no stsd box")?;
+
+    // version/flags, entry count and the size of the first entry come before its type
+    let entry_type = read_at::<4>(file, stsd + 12)?;
+    let codec = match &entry_type {
+        b"avc1" | b"avc3" => "h264".into(),
+        b"hvc1" | b"hev1" => "hevc".into(),
+        b"av01" => "av1".into(),
+        other => String::from_utf8_lossy(other).trim().to_owned(),
+    };
+    Ok(codec)
+}
@@ -1,11 +1,20 @@
+use std::path::Path;
+
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow};
 
+use super::{AppEvent, EventManager};
+
 use crate::constants::APP_NAME;
-use crate::state::WindowState;
+use crate::state::{OverlayPosition, OverlayState, SettingsWrapper, WindowState};
+
+// logical pixels
+const OVERLAY_SIZE: (f64, f64) = (200.0, 56.0);
+const OVERLAY_MARGIN: f64 = 16.0;
 
 #[derive(Copy, Clone, strum_macros::IntoStaticStr)]
 pub enum AppWindow {
     Main,
+    Overlay,
 }
 
 impl From<AppWindow> for String {
@@ -18,7 +27,15 @@ impl From<AppWindow> for String {
 pub trait WindowManager {
     fn open_window(&self, window: AppWindow);
 
+    /// Open the main window with `recording` selected
+    fn open_recording(&self, recording: &Path);
+
     fn save_window_state(&self, window: &WebviewWindow);
+
+    /// Show the ingame overlay for a recording that just started (if enabled in the settings)
+    fn show_overlay(&self);
+
+    fn close_overlay(&self);
 }
 
 impl WindowManager for AppHandle {
@@ -52,6 +69,60 @@ impl WindowManager for AppHandle {
         }
     }
 
+    fn open_recording(&self, recording: &Path) {
+        let Some(video_id) = recording.to_str().map(str::to_owned) else { return };
+
+        // a window that is still loading picks up the recording with 'take_pending_recording'
+        self.state::<WindowState>().set_pending_recording(video_id.clone());
+        self.open_window(AppWindow::Main);
+
+        if let Err(e) = self.send_event(AppEvent::SelectRecording { payload: video_id }) {
+            log::error!("failed to emit 'select_recording' event: {e}");
+        }
+    }
+
+    fn show_overlay(&self) {
+        let Some(overlay) = self.state::<SettingsWrapper>().overlay() else { return };
+
+        self.state::<OverlayState>()
+            .set_recording_since(Some(chrono::Utc::now().timestamp_millis()));
+
+        let label: &'static str = AppWindow::Overlay.into();
+        if self.get_webview_window(label).is_some() {
+            return;
+        }
+
+        let (x, y) = overlay_position(self, overlay.position);
+        let window_builder = WebviewWindow::builder(self, label, WebviewUrl::App("overlay.html".into()))
+            .title(APP_NAME)
+            .inner_size(OVERLAY_SIZE.0, OVERLAY_SIZE.1)
+            .position(x, y)
+            .transparent(true)
+            .decorations(false)
+            .shadow(false)
+            .resizable(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            // never take the focus away from the game
+            .focused(false);
+
+        match window_builder.build() {
+            // let clicks go through to the game
+            Ok(window) => _ = window.set_ignore_cursor_events(true),
+            Err(e) => log::error!("error creating overlay window: {e}"),
+        }
+    }
+
+    fn close_overlay(&self) {
+        self.state::<OverlayState>().set_recording_since(None);
+
+        if let Some(window) = self.get_webview_window(AppWindow::Overlay.into()) {
+            if let Err(e) = window.destroy() {
+                log::error!("error closing overlay window: {e}");
+            }
+        }
+    }
+
     fn save_window_state(&self, window: &WebviewWindow) {
         let scale_factor = match window.scale_factor() {
             Ok(scale_factor) => scale_factor,
@@ -72,3 +143,24 @@ impl WindowManager for AppHandle {
         }
     }
 }
+
+/// Logical position of the overlay window in the configured corner of the primary monitor
+fn overlay_position(app_handle: &AppHandle, position: OverlayPosition) -> (f64, f64) {
+    let Ok(Some(monitor)) = app_handle.primary_monitor() else {
+        return (OVERLAY_MARGIN, OVERLAY_MARGIN);
+    };
+
+    let origin = monitor.position().to_logical::<f64>(monitor.scale_factor());
+    let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+    let left = origin.x + OVERLAY_MARGIN;
+    let top = origin.y + OVERLAY_MARGIN;
+    let right = origin.x + size.width - OVERLAY_SIZE.0 - OVERLAY_MARGIN;
+    let bottom = origin.y + size.height - OVERLAY_SIZE.1 - OVERLAY_MARGIN;
+
+    match position {
+        OverlayPosition::TopLeft => (left, top),
+        OverlayPosition::TopRight => (right, top),
+        OverlayPosition::BottomLeft => (left, bottom),
+        OverlayPosition::BottomRight => (right, bottom),
+    }
+}
@@ -0,0 +1,325 @@
+mod storage;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Manager};
+
+use crate::app::{action, AppEvent, EventManager, RecordingManager};
+use crate::jobs::{self, JobKind};
+use crate::state::{BackupSettings, SettingsWrapper};
+use storage::Storage;
+
+const QUEUE_FILE: &str = "backup_queue.json";
+// give up on a recording after this many failed uploads
+const MAX_ATTEMPTS: u32 = 5;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BackupState {
+    Queued,
+    Uploading,
+    Done,
+    Failed(String),
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupStatus {
+    pub video_id: String,
+    pub state: BackupState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingBackup {
+    video_path: PathBuf,
+    /// stable ID of the recording to find it again if it got renamed before the upload
+    #[serde(default)]
+    recording_id: Option<String>,
+    attempts: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadedBackup {
+    /// names of the uploaded files in the storage
+    files: Vec<String>,
+    /// unix timestamp in seconds
+    uploaded_at: i64,
+}
+
+/// Recordings waiting for their backup and the backups that have been uploaded (for the retention).
+/// Persisted in the app config folder so failed uploads get retried after a restart.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupQueue {
+    pending: Vec<PendingBackup>,
+    uploaded: Vec<UploadedBackup>,
+}
+
+pub struct Backup {
+    queue: Mutex<BackupQueue>,
+    queue_file: PathBuf,
+    running: AtomicBool,
+}
+
+impl Backup {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let queue_file = app_handle.path().app_config_dir()?.join(QUEUE_FILE);
+        let queue = fs::read(&queue_file)
+            .ok()
+            .and_then(|queue| serde_json::from_slice(&queue).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            queue: Mutex::new(queue),
+            queue_file,
+            running: AtomicBool::new(false),
+        })
+    }
+
+    fn save(&self, queue: &BackupQueue) {
+        let result = serde_json::to_vec_pretty(queue)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&self.queue_file, json));
+        if let Err(e) = result {
+            log::warn!("failed to save backup queue: {e}");
+        }
+    }
+}
+
+/// Queue a finished recording for backup (if backups are configured). The upload runs as a background job.
+pub fn enqueue(app_handle: &AppHandle, video_path: &Path) {
+    let Some(settings) = app_handle.state::<SettingsWrapper>().backup() else { return };
+    if settings.only_favorites && !is_favorite(video_path) {
+        return;
+    }
+
+    jobs::enqueue(app_handle, JobKind::Backup, video_path);
+}
+
+/// Add the recording to the backup queue and upload everything in the queue (see [`JobKind::Backup`]).
+pub async fn run(app_handle: &AppHandle, video_path: &Path) -> Result<()> {
+    app_handle
+        .state::<SettingsWrapper>()
+        .backup()
+        .context("backups are disabled")?;
+
+    let recording_id = action::get_recording_metadata(video_path, false)
+        .ok()
+        .map(|metadata| metadata.id().to_owned());
+
+    let backup = app_handle.state::<Backup>();
+    {
+        let mut queue = backup.queue.lock().unwrap();
+        if !queue.pending.iter().any(|pending| pending.video_path == video_path) {
+            queue.pending.push(PendingBackup {
+                video_path: video_path.to_path_buf(),
+                recording_id,
+                attempts: 0,
+            });
+            backup.save(&queue);
+        }
+    }
+    send_status(app_handle, video_path, BackupState::Queued);
+
+    upload(app_handle).await;
+    Ok(())
+}
+
+/// Upload everything in the queue in the background (e.g. retry the uploads that failed before the app was closed).
+pub fn resume(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    async_runtime::spawn(async move { upload(&app_handle).await });
+}
+
+/// Upload everything in the queue and apply the retention. Does nothing if the backup is already running.
+async fn upload(app_handle: &AppHandle) {
+    let Some(settings) = app_handle.state::<SettingsWrapper>().backup() else { return };
+    if app_handle.state::<Backup>().running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    match Storage::new(settings.target.clone()) {
+        Ok(storage) => {
+            apply_retention(app_handle, &storage, &settings).await;
+            upload_pending(app_handle, &storage, &settings).await;
+        }
+        Err(e) => log::error!("invalid backup target: {e}"),
+    }
+    app_handle.state::<Backup>().running.store(false, Ordering::SeqCst);
+}
+
+async fn upload_pending(app_handle: &AppHandle, storage: &Storage, settings: &BackupSettings) {
+    let concurrency = settings.concurrency.max(1);
+    // the bandwidth limit is for all uploads together
+    let bytes_per_sec = settings
+        .bandwidth_limit_kbps
+        .map(|kbps| kbps.saturating_mul(1000) / concurrency as u64);
+
+    // recordings that get queued while uploading are picked up by the next loop
+    loop {
+        relocate_renamed(app_handle);
+        let pending = app_handle.state::<Backup>().queue.lock().unwrap().pending.clone();
+        if pending.is_empty() {
+            break;
+        }
+
+        let results = futures_util::stream::iter(pending)
+            .map(|pending| async move {
+                send_status(app_handle, &pending.video_path, BackupState::Uploading);
+                let result = upload_recording(storage, &pending, bytes_per_sec).await;
+                (pending, result)
+            })
+            .buffer_unordered(concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        // failed uploads are retried on the next start instead of hammering the storage now
+        let failed = finish_uploads(app_handle, results);
+        if failed {
+            break;
+        }
+    }
+}
+
+/// Update the paths of queued recordings that got renamed since they were queued
+fn relocate_renamed(app_handle: &AppHandle) {
+    let backup = app_handle.state::<Backup>();
+    let mut queue = backup.queue.lock().unwrap();
+
+    let mut changed = false;
+    for pending in queue.pending.iter_mut().filter(|pending| !pending.video_path.is_file()) {
+        let Some(recording_id) = &pending.recording_id else { continue };
+        let video_path = app_handle.resolve_video_id(recording_id);
+        if video_path.is_file() {
+            pending.video_path = video_path;
+            changed = true;
+        }
+    }
+    if changed {
+        backup.save(&queue);
+    }
+}
+
+/// Update the queue with the results of the uploads. Returns whether any upload failed.
+fn finish_uploads(app_handle: &AppHandle, results: Vec<(PendingBackup, Result<Vec<String>>)>) -> bool {
+    let backup = app_handle.state::<Backup>();
+    let mut queue = backup.queue.lock().unwrap();
+
+    let mut failed = false;
+    for (pending, result) in results {
+        queue.pending.retain(|p| p.video_path != pending.video_path);
+        match result {
+            Ok(files) => {
+                send_status(app_handle, &pending.video_path, BackupState::Done);
+                queue.uploaded.push(UploadedBackup {
+                    files,
+                    uploaded_at: chrono::Utc::now().timestamp(),
+                });
+            }
+            Err(e) => {
+                log::warn!("backup of {} failed: {e}", pending.video_path.display());
+                send_status(app_handle, &pending.video_path, BackupState::Failed(e.to_string()));
+
+                let attempts = pending.attempts + 1;
+                if attempts < MAX_ATTEMPTS && pending.video_path.is_file() {
+                    queue.pending.push(PendingBackup { attempts, ..pending });
+                }
+                failed = true;
+            }
+        }
+    }
+
+    backup.save(&queue);
+    failed
+}
+
+/// Upload the video and its metadata file. Returns the names of the uploaded files.
+///
+/// The names are prefixed with the stable ID of the recording, recordings with the same file name (e.g. from
+/// different folders or renamed ones) would overwrite each other otherwise.
+async fn upload_recording(
+    storage: &Storage,
+    pending: &PendingBackup,
+    bytes_per_sec: Option<u64>,
+) -> Result<Vec<String>> {
+    let video_path = &pending.video_path;
+    let recording_id = pending.recording_id.clone().or_else(|| {
+        action::get_recording_metadata(video_path, false)
+            .ok()
+            .map(|metadata| metadata.id().to_owned())
+    });
+
+    let mut files = Vec::new();
+    for path in [video_path.to_path_buf(), video_path.with_extension("json")] {
+        if !path.is_file() {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(OsStr::to_str) else { continue };
+        let name = match &recording_id {
+            Some(recording_id) => format!("{recording_id}_{name}"),
+            None => name.to_owned(),
+        };
+        storage.upload(&name, &path, bytes_per_sec).await?;
+        files.push(name);
+    }
+    Ok(files)
+}
+
+async fn apply_retention(app_handle: &AppHandle, storage: &Storage, settings: &BackupSettings) {
+    let Some(retention_days) = settings.retention_days else { return };
+    let retention_secs = i64::try_from(retention_days.saturating_mul(24 * 60 * 60)).unwrap_or(i64::MAX);
+    let cutoff = chrono::Utc::now().timestamp().saturating_sub(retention_secs);
+
+    let expired = {
+        let backup = app_handle.state::<Backup>();
+        let queue = backup.queue.lock().unwrap();
+        queue
+            .uploaded
+            .iter()
+            .filter(|uploaded| uploaded.uploaded_at < cutoff)
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    for uploaded in expired {
+        let mut deleted = true;
+        for file in &uploaded.files {
+            if let Err(e) = storage.delete(file).await {
+                log::warn!("failed to delete expired backup: {e}");
+                deleted = false;
+            }
+        }
+
+        if deleted {
+            let backup = app_handle.state::<Backup>();
+            let mut queue = backup.queue.lock().unwrap();
+            queue.uploaded.retain(|u| u.files != uploaded.files);
+            backup.save(&queue);
+        }
+    }
+}
+
+fn is_favorite(video_path: &Path) -> bool {
+    action::get_recording_metadata(video_path, false).is_ok_and(|metadata| metadata.is_favorite())
+}
+
+fn send_status(app_handle: &AppHandle, video_path: &Path, state: BackupState) {
+    let Some(video_id) = video_path.to_str() else { return };
+    let payload = BackupStatus {
+        video_id: video_id.to_owned(),
+        state,
+    };
+    if let Err(e) = app_handle.send_event(AppEvent::BackupStatus { payload }) {
+        log::warn!("failed to send backup status: {e}");
+    }
+}
@@ -0,0 +1,219 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use reqwest::header::{AUTHORIZATION, CONTENT_LENGTH};
+use reqwest::{Body, Client, Method, RequestBuilder, Url};
+use sha2::{Digest, Sha256};
+use tokio::time::sleep;
+
+use crate::state::BackupTarget;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+// S3 doesn't require the body hash when using https
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Remote storage the backups get mirrored to
+pub enum Storage {
+    S3 {
+        client: Client,
+        endpoint: Url,
+        region: String,
+        bucket: String,
+        access_key: String,
+        secret_key: String,
+        prefix: String,
+    },
+    WebDav {
+        client: Client,
+        url: Url,
+        username: Option<String>,
+        password: Option<String>,
+    },
+}
+
+impl Storage {
+    pub fn new(target: BackupTarget) -> Result<Self> {
+        let client = Client::new();
+        let storage = match target {
+            BackupTarget::S3 {
+                endpoint,
+                region,
+                bucket,
+                access_key,
+                secret_key,
+                prefix,
+            } => Storage::S3 {
+                client,
+                endpoint: Url::parse(&endpoint).context("invalid S3 endpoint")?,
+                region,
+                bucket,
+                access_key,
+                secret_key,
+                prefix,
+            },
+            BackupTarget::WebDav { url, username, password } => {
+                // without the trailing slash joining a file name would replace the last path segment
+                let url = if url.ends_with('/') { url } else { format!("{url}/") };
+                Storage::WebDav {
+                    client,
+                    url: Url::parse(&url).context("invalid WebDAV url")?,
+                    username,
+                    password,
+                }
+            }
+        };
+        Ok(storage)
+    }
+
+    /// Upload the file at `path` as `name`. `bytes_per_sec` limits the upload speed.
+    pub async fn upload(&self, name: &str, path: &Path, bytes_per_sec: Option<u64>) -> Result<()> {
+        let size = path.metadata()?.len();
+        let body = throttled_body(File::open(path)?, bytes_per_sec);
+
+        let response = self
+            .request(Method::PUT, name)?
+            // the length has to be set explicitly, otherwise a streamed body is sent chunked which S3 doesn't accept
+            .header(CONTENT_LENGTH, size)
+            .body(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("uploading {name} failed: {}", response.status());
+        }
+        Ok(())
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let response = self.request(Method::DELETE, name)?.send().await?;
+        if !response.status().is_success() {
+            bail!("deleting {name} failed: {}", response.status());
+        }
+        Ok(())
+    }
+
+    fn request(&self, method: Method, name: &str) -> Result<RequestBuilder> {
+        match self {
+            Storage::S3 {
+                client,
+                endpoint,
+                region,
+                bucket,
+                access_key,
+                secret_key,
+                prefix,
+            } => {
+                let path = format!("/{bucket}/{}", uri_encode(&format!("{prefix}{name}")));
+                let url = endpoint.join(&path)?;
+                let host = match (url.host_str(), url.port()) {
+                    (Some(host), Some(port)) => format!("{host}:{port}"),
+                    (Some(host), None) => host.to_owned(),
+                    _ => bail!("S3 endpoint without host"),
+                };
+
+                let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+                let authorization = sign_v4(&method, &host, &path, &amz_date, region, access_key, secret_key);
+                Ok(client
+                    .request(method, url)
+                    .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+                    .header("x-amz-date", amz_date)
+                    .header(AUTHORIZATION, authorization))
+            }
+            Storage::WebDav {
+                client,
+                url,
+                username,
+                password,
+            } => {
+                let request = client.request(method, url.join(name)?);
+                Ok(match username {
+                    Some(username) => request.basic_auth(username, password.as_ref()),
+                    None => request,
+                })
+            }
+        }
+    }
+}
+
+fn throttled_body(file: File, bytes_per_sec: Option<u64>) -> Body {
+    let stream = futures_util::stream::unfold(
+        (file, Instant::now(), 0u64, false),
+        move |(mut file, start, sent, failed)| async move {
+            if failed {
+                return None;
+            }
+
+            let mut buffer = vec![0; READ_CHUNK_SIZE];
+            let read = match file.read(&mut buffer) {
+                Ok(0) => return None,
+                Ok(read) => read,
+                Err(e) => return Some((Err(e), (file, start, sent, true))),
+            };
+            buffer.truncate(read);
+            let sent = sent + read as u64;
+
+            // sleep until the average speed since the start is back under the limit
+            if let Some(bytes_per_sec) = bytes_per_sec.filter(|&limit| limit > 0) {
+                let target = Duration::from_secs_f64(sent as f64 / bytes_per_sec as f64);
+                if let Some(ahead) = target.checked_sub(start.elapsed()) {
+                    sleep(ahead).await;
+                }
+            }
+
+            Some((Ok(buffer), (file, start, sent, false)))
+        },
+    );
+    Body::wrap_stream(stream)
+}
+
+// https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html
+fn sign_v4(
+    method: &Method,
+    host: &str,
+    path: &str,
+    amz_date: &str,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+) -> String {
+    let date = &amz_date[..8];
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{UNSIGNED_PAYLOAD}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{UNSIGNED_PAYLOAD}"
+    );
+
+    let scope = format!("{date}/{region}/s3/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request))
+    );
+
+    let key = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, b"s3");
+    let key = hmac_sha256(&key, b"aws4_request");
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    format!("AWS4-HMAC-SHA256 Credential={access_key}/{scope}, SignedHeaders={signed_headers}, Signature={signature}")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take a key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+// percent-encode everything except unreserved characters and '/'
+fn uri_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
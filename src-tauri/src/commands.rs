@@ -1,14 +1,42 @@
-use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs::metadata;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 
-use tauri::{AppHandle, State};
+use libobs_recorder::settings::{AudioSource, Resolution, StdResolution};
+use riot_datatypes::{ChampionId, GameId};
+use tauri::{AppHandle, Manager, State};
 
-use crate::app::{action, RecordingManager};
-use crate::recorder::MetadataFile;
-use crate::state::{MarkerFlags, SettingsFile, SettingsWrapper};
-use crate::util::compare_time;
+use crate::app::bundle::{self, BundleFormat};
+use crate::app::support;
+use crate::app::{
+    action, cache_image, run_bulk, AppEvent, BulkOperation, BulkSummary, CacheManager, CleanupPreview, ClipMetadata,
+    EventManager, LibraryReport, RecordingManager, VideoInfo,
+};
+use crate::backup;
+use crate::jobs::{self, Job, JobKind, Jobs};
+use crate::onboarding::{self, EncoderBenchmark, FirstTimeReport};
+use crate::privacy;
+use crate::recorder::{
+    metadata_trace, Account, Comment, GameMetadata, HighlightCategory, HighlightTask, KillPosition, LeagueRecorder,
+    Markers, MetadataFile, PipelineTimings, ReviewStats, StopReason, TimelineDocument, TimelineFormat,
+};
+use crate::state::{
+    self, CurrentlyRecording, DeletedRecordings, EncoderSettings, Hotkey, LastPipelineTimings, LogBuffer, LogEntry,
+    LogLevel, MarkerFlags, OverlayState, RawInputListener, RecorderHistory, RecorderState, RecorderStatus,
+    RecordingIndex, SettingsFile, SettingsWarning, SettingsWrapper, WindowState,
+};
+use crate::static_data::{self, Catalog};
+use crate::telemetry::{self, TelemetryEvent, TelemetryReport};
+use crate::updater::{self, UpdateReport};
+use crate::uploader::{self, Privacy, YoutubeClient, YoutubeLogin};
+use crate::util;
+
+// bitrate of the audio track of the recordings
+const AUDIO_BITRATE_KBPS: f64 = 160.0;
+const HOTKEY_CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_LOG_LIMIT: usize = 200;
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
@@ -45,28 +73,687 @@ pub fn get_recordings_size(app_handle: AppHandle) -> f32 {
     size as f32 / 1_000_000_000.0 // in Gigabyte
 }
 
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    // all sizes in Gigabyte
+    recordings_size: f32,
+    /// part of `recordings_size` that is protected from the retention rules
+    protected_size: f32,
+    cache_size: f32,
+}
+
+/// Rough estimate of the size in MB of a recording that is `minutes` long with the `encoder` settings and the current
+/// resolution and framerate. Recordings without a fixed 'outputResolution' are estimated at 1080p.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn estimate_file_size(encoder: EncoderSettings, minutes: f64, state: State<SettingsWrapper>) -> f64 {
+    let resolution = Resolution::from(state.get_output_resolution().unwrap_or(StdResolution::_1920x1080p));
+    let framerate = state.get_framerate();
+    let fps = framerate.num() as f64 / framerate.den().max(1) as f64;
+
+    let mut bitrate_kbps = encoder
+        .validated()
+        .estimated_bitrate_kbps(resolution.width(), resolution.height(), fps);
+    if !matches!(state.get_audio_source(), AudioSource::NONE) {
+        bitrate_kbps += AUDIO_BITRATE_KBPS;
+    }
+    bitrate_kbps * 1000.0 / 8.0 * minutes.max(0.0) * 60.0 / 1_000_000.0
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_storage_report(app_handle: AppHandle) -> StorageReport {
+    StorageReport {
+        recordings_size: get_recordings_size(app_handle.clone()),
+        protected_size: get_protected_size(&app_handle),
+        cache_size: app_handle.get_cache_size() as f32 / 1_000_000_000.0,
+    }
+}
+
+fn get_protected_size(app_handle: &AppHandle) -> f32 {
+    let size: u64 = app_handle
+        .get_recordings()
+        .into_iter()
+        .filter(|recording| {
+            action::get_recording_metadata(recording, false).is_ok_and(|metadata_file| metadata_file.is_protected())
+        })
+        .map(|recording| action::recording_size(&recording))
+        .sum();
+    size as f32 / 1_000_000_000.0 // in Gigabyte
+}
+
+/// Check everything a recording depends on (recorder, encoder, disk space, FFmpeg, autostart and the League client)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn run_first_time_checks(app_handle: AppHandle) -> FirstTimeReport {
+    onboarding::run_first_time_checks(&app_handle).await
+}
+
+/// Record a few seconds with every available encoder and report how well each keeps up with the current settings
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn run_encoder_benchmark(app_handle: AppHandle) -> Result<Vec<EncoderBenchmark>, String> {
+    onboarding::run_encoder_benchmark(&app_handle)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Look up the newest version of each release channel and offer the update of the selected channel in the tray menu
+///
+/// Also works with 'checkForUpdates' disabled.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn check_for_updates(app_handle: AppHandle) -> Result<UpdateReport, String> {
+    updater::check(&app_handle).await.map_err(|e| e.to_string())
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_last_pipeline_timings(last_pipeline_timings: State<LastPipelineTimings>) -> Option<PipelineTimings> {
+    last_pipeline_timings.get()
+}
+
+/// The last log messages (oldest first) with at least the severity of `level` (default: all), at most `limit`
+/// (default: 200). Only filled while 'debugLog' is enabled.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_recent_logs(level: Option<LogLevel>, limit: Option<usize>, log_buffer: State<LogBuffer>) -> Vec<LogEntry> {
+    log_buffer.recent(level.unwrap_or(LogLevel::Trace), limit.unwrap_or(DEFAULT_LOG_LIMIT))
+}
+
+/// What the recorder is doing right now, if the League client is connected and the last error that stopped a recording
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_recorder_state(
+    status: State<RecorderStatus>,
+    history: State<RecorderHistory>,
+    currently_recording: State<CurrentlyRecording>,
+) -> RecorderState {
+    let (listener, elapsed) = status.listener();
+    RecorderState {
+        listener,
+        elapsed_secs: elapsed.as_secs_f64(),
+        recording: currently_recording.get(),
+        lcu_connected: status.lcu_connected(),
+        last_error: history.last_error(),
+    }
+}
+
+/// Start recording the running game, same as the 'startRecordingHotkey'
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn start_recording(league_recorder: State<LeagueRecorder>) {
+    league_recorder.manual_start();
+}
+
+/// Stop the current recording, same as the 'stopRecordingHotkey'
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn stop_recording(league_recorder: State<LeagueRecorder>) {
+    league_recorder.manual_stop();
+}
+
+/// Mark the current ingame time of the recording (a highlight if `category` isn't set)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn add_highlight(
+    category: Option<HighlightCategory>,
+    currently_recording: State<CurrentlyRecording>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    if currently_recording.get().is_none() {
+        return Err(String::from("nothing is being recorded"));
+    }
+    HighlightTask::save(&app_handle, category.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayStatus {
+    /// unix timestamp in ms, `None` if nothing is being recorded
+    recording_since: Option<i64>,
+    opacity: f64,
+}
+
+/// Recording that was opened from outside of the window (e.g. tray menu) before the window finished loading
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn take_pending_recording(window_state: State<WindowState>) -> Option<String> {
+    window_state.take_pending_recording()
+}
+
+/// Fetched by the overlay window when it gets opened
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_overlay_status(overlay_state: State<OverlayState>, state: State<SettingsWrapper>) -> OverlayStatus {
+    OverlayStatus {
+        recording_since: overlay_state.recording_since(),
+        opacity: state.overlay().map_or(1.0, |overlay| overlay.opacity.clamp(0.0, 1.0)),
+    }
+}
+
 #[cfg_attr(test, derive(specta::Type))]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Recording {
     video_id: String,
+    // stable ID of the recording that survives renames (None for videos without metadata)
+    id: Option<String>,
     metadata: Option<MetadataFile>,
+    // video in one of the recordings folders that wasn't created by LeagueRecord (can be imported)
+    foreign: bool,
+    // video in one of the external folders (read-only)
+    external: bool,
+    /// unix timestamp in seconds
+    recorded_at: Option<i64>,
+    stop_reason: Option<StopReason>,
+    thumbnail: Option<PathBuf>,
+    collection: Option<Collection>,
+    /// e.g. '14.10' (None until the game data is available)
+    patch: Option<String>,
+    /// duration, resolution and codec of the video (only of the first part of split recordings)
+    video: Option<VideoInfo>,
+}
+
+/// Recordings that belong together, e.g. the games of a Clash tournament day
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    /// same for every recording of the collection
+    id: String,
+    name: String,
+    /// position of the recording in the collection (1 for the first game), None for recordings of older versions
+    round: Option<u32>,
 }
 
+/// Part of the recordings list, see `get_recordings_page`
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingsPage {
+    recordings: Vec<Recording>,
+    /// number of recordings that match the filters
+    total: u32,
+}
+
+/// All recordings (newest first). With `account` (puuid) only the recordings of that Riot account are listed, with
+/// `patch` (e.g. '14.10') only the recordings of games on that patch.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_recordings_list(account: Option<String>, patch: Option<String>, app_handle: AppHandle) -> Vec<Recording> {
+    // reading the metadata files is most of the work, files that didn't change since the last time are cached
+    let paths = recording_paths(&app_handle);
+    let mut ret: Vec<Recording> = util::parallel_map(&paths, |(path, external)| {
+        recording(&app_handle, path.clone(), *external)
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let index = ret
+        .iter()
+        .filter_map(|recording| Some((PathBuf::from(&recording.video_id), recording.id.clone()?)))
+        .collect::<HashMap<_, _>>();
+    // the metadata of every recording was read anyway
+    app_handle.state::<RecordingIndex>().replace(index);
+
+    ret.retain(|recording| matches_filters(recording.metadata.as_ref(), account.as_deref(), patch.as_deref()));
+
+    // sort by time recorded (index 0 is newest)
+    ret.sort_by(|a, b| b.recorded_at.cmp(&a.recorded_at));
+    ret
+}
+
+/// `limit` recordings of `get_recordings_list` starting at `offset` so big libraries don't have to be sent at once
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn get_recordings_list(app_handle: AppHandle) -> Vec<Recording> {
-    let mut recordings = app_handle.get_recordings();
-    // sort by time created (index 0 is newest)
-    recordings.sort_by(|a, b| compare_time(a, b).unwrap_or(Ordering::Equal));
-    let mut ret = Vec::new();
-    for path in recordings {
-        if let Some(video_id) = path.to_str().map(|s| s.to_string()) {
-            let metadata = action::get_recording_metadata(&path, true).ok();
-            ret.push(Recording { video_id, metadata });
+pub fn get_recordings_page(
+    account: Option<String>,
+    patch: Option<String>,
+    offset: u32,
+    limit: u32,
+    app_handle: AppHandle,
+) -> RecordingsPage {
+    // the filters and the order only need the metadata (cached) and the time of every recording, the thumbnails and
+    // video infos are only read for the recordings on the page
+    let filtered = account.is_some() || patch.is_some();
+    let mut paths: Vec<_> = util::parallel_map(&recording_paths(&app_handle), |(path, external)| {
+        if filtered {
+            let metadata = action::get_recording_metadata(path, !*external).ok();
+            if !matches_filters(metadata.as_ref(), account.as_deref(), patch.as_deref()) {
+                return None;
+            }
+        }
+        let foreign = !action::is_native_recording(path);
+        Some((path.clone(), *external, recorded_at(path, foreign)))
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    // same order as `get_recordings_list` (index 0 is newest)
+    paths.sort_by(|(_, _, a), (_, _, b)| b.cmp(a));
+
+    let total = paths.len() as u32;
+    let page: Vec<_> = paths.into_iter().skip(offset as usize).take(limit as usize).collect();
+    let recordings: Vec<Recording> = util::parallel_map(&page, |(path, external, _)| {
+        recording(&app_handle, path.clone(), *external)
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    RecordingsPage { recordings, total }
+}
+
+/// Every video in the recordings folder and the external folders, `true` for the external ones
+fn recording_paths(app_handle: &AppHandle) -> Vec<(PathBuf, bool)> {
+    let recordings = app_handle.get_recordings().into_iter().map(|path| (path, false));
+    let external_recordings = app_handle
+        .get_external_recordings()
+        .into_iter()
+        .map(|path| (path, true));
+    recordings.chain(external_recordings).collect()
+}
+
+/// Whether a recording is of the Riot account with the puuid `account` and of a game on `patch` (e.g. '14.10')
+fn matches_filters(metadata: Option<&MetadataFile>, account: Option<&str>, patch: Option<&str>) -> bool {
+    if let Some(puuid) = account {
+        let account = metadata.and_then(MetadataFile::account);
+        if !account.is_some_and(|account| account.puuid == puuid) {
+            return false;
         }
     }
-    ret
+    if let Some(patch) = patch {
+        let recording_patch = match metadata {
+            Some(MetadataFile::Metadata(metadata)) => metadata.patch(),
+            _ => None,
+        };
+        if recording_patch.as_deref() != Some(patch) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Foreign videos have no metadata, the time in their name (e.g. set by another capture tool) is better than the time
+/// the file was created (e.g. copied)
+fn recorded_at(path: &Path, foreign: bool) -> Option<i64> {
+    if foreign {
+        util::recorded_at(path)
+    } else {
+        util::created_at(path)
+    }
+}
+
+/// The recordings of a `RecordingsDelta` event (added or renamed), videos that don't exist (anymore) are skipped
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_recordings_by_id(video_ids: Vec<String>, app_handle: AppHandle) -> Vec<Recording> {
+    let settings = app_handle.state::<SettingsWrapper>();
+    video_ids
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let external = settings.is_external(&path);
+            recording(&app_handle, path, external)
+        })
+        .collect()
+}
+
+fn recording(app_handle: &AppHandle, path: PathBuf, external: bool) -> Option<Recording> {
+    let video_id = path.to_str()?.to_owned();
+    let foreign = !action::is_native_recording(&path);
+    // don't write the fetched metadata into external folders
+    let metadata = action::get_recording_metadata(&path, !external).ok();
+    let id = metadata.as_ref().map(|metadata| metadata.id().to_owned());
+    let stop_reason = metadata.as_ref().and_then(MetadataFile::stop_reason).cloned();
+    let patch = match &metadata {
+        Some(MetadataFile::Metadata(metadata)) => metadata.patch(),
+        _ => None,
+    };
+    let thumbnail = action::thumbnail(app_handle, &path, metadata.as_ref());
+    let video = app_handle.state::<RecordingIndex>().video_info(&path);
+    let recorded_at = recorded_at(&path, foreign);
+    let collection = collection(metadata.as_ref(), recorded_at);
+    Some(Recording {
+        video_id,
+        id,
+        metadata,
+        foreign,
+        external,
+        recorded_at,
+        stop_reason,
+        thumbnail,
+        collection,
+        patch,
+        video,
+    })
+}
+
+/// Clash games are grouped by tournament day, the round was stored when the game was recorded
+fn collection(metadata: Option<&MetadataFile>, recorded_at: Option<i64>) -> Option<Collection> {
+    let clash = metadata?.clash()?;
+    let id = match clash.tournament_id {
+        Some(tournament_id) => format!("clash_{tournament_id}"),
+        // without the tournament the games of the same day belong together
+        None => {
+            let date = recorded_at
+                .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            format!("clash_{date}")
+        }
+    };
+    let name = format!("{} {}", clash.name, clash.day).trim().to_owned();
+    Some(Collection { id, name, round: clash.round })
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummary {
+    account: Account,
+    recordings: u32,
+    /// sum of the LP gained and lost in the recorded ranked games
+    lp_diff: i32,
+}
+
+/// Riot accounts that have recordings in the library (recordings from before the account was stored aren't counted)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_accounts(app_handle: AppHandle) -> Vec<AccountSummary> {
+    let mut accounts: Vec<AccountSummary> = Vec::new();
+    for recording in app_handle.get_recordings() {
+        let Ok(metadata_file) = action::get_recording_metadata(&recording, false) else { continue };
+        let Some(account) = metadata_file.account() else { continue };
+        let lp_diff = match &metadata_file {
+            MetadataFile::Metadata(metadata) => metadata.lp_diff.unwrap_or(0),
+            _ => 0,
+        };
+
+        match accounts
+            .iter_mut()
+            .find(|summary| summary.account.puuid == account.puuid)
+        {
+            Some(summary) => {
+                summary.recordings += 1;
+                summary.lp_diff += lp_diff;
+            }
+            None => accounts.push(AccountSummary {
+                account: account.clone(),
+                recordings: 1,
+                lp_diff,
+            }),
+        }
+    }
+    accounts
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PremadeSummary {
+    /// 'name#tag'
+    riot_id: String,
+    games: u32,
+    wins: u32,
+}
+
+/// Players the recorded player queued with and how the games together went (most games first).
+/// With `account` (puuid) only the recordings of that Riot account are counted, with `patch` only the games on that
+/// patch.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_premade_stats(account: Option<String>, patch: Option<String>, app_handle: AppHandle) -> Vec<PremadeSummary> {
+    let mut premades: Vec<PremadeSummary> = Vec::new();
+    for metadata in game_metadata(&app_handle, account.as_deref(), patch.as_deref()) {
+        let premade_names = metadata
+            .participants
+            .iter()
+            .filter(|participant| participant.premade && participant.participant_id != metadata.participant_id)
+            .map(|participant| &participant.summoner_name);
+        for riot_id in premade_names {
+            let win = u32::from(metadata.stats.win);
+            match premades.iter_mut().find(|summary| &summary.riot_id == riot_id) {
+                Some(summary) => {
+                    summary.games += 1;
+                    summary.wins += win;
+                }
+                None => premades.push(PremadeSummary {
+                    riot_id: riot_id.clone(),
+                    games: 1,
+                    wins: win,
+                }),
+            }
+        }
+    }
+    premades.sort_by(|a, b| b.games.cmp(&a.games));
+    premades
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchSummary {
+    /// e.g. '14.10'
+    patch: String,
+    games: u32,
+    wins: u32,
+    patch_notes_url: String,
+}
+
+/// Games and wins per patch (newest patch first) to compare the performance before and after a patch.
+/// With `account` (puuid) only the recordings of that Riot account are counted.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_patch_stats(account: Option<String>, app_handle: AppHandle) -> Vec<PatchSummary> {
+    let mut patches: Vec<PatchSummary> = Vec::new();
+    for metadata in game_metadata(&app_handle, account.as_deref(), None) {
+        let Some(patch) = metadata.patch() else { continue };
+        let win = u32::from(metadata.stats.win);
+        match patches.iter_mut().find(|summary| summary.patch == patch) {
+            Some(summary) => {
+                summary.games += 1;
+                summary.wins += win;
+            }
+            None => patches.push(PatchSummary {
+                patch_notes_url: patch_notes_url(&patch),
+                patch,
+                games: 1,
+                wins: win,
+            }),
+        }
+    }
+    patches.sort_by_key(|summary| std::cmp::Reverse(patch_number(&summary.patch)));
+    patches
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillHeatmap {
+    /// number of games the positions are from
+    games: u32,
+    positions: Vec<KillPosition>,
+}
+
+/// Where the player killed, died and assisted across all recorded games for a minimap heatmap.
+/// Only games on `champion_id` and in `role` (lane or role of the match history, e.g. 'TOP' or 'SUPPORT') are counted
+/// if they are set, with `account` (puuid) only the recordings of that Riot account.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_kill_heatmap(
+    account: Option<String>,
+    champion_id: Option<ChampionId>,
+    role: Option<String>,
+    app_handle: AppHandle,
+) -> KillHeatmap {
+    let mut heatmap = KillHeatmap {
+        games: 0,
+        positions: Vec::new(),
+    };
+    for metadata in game_metadata(&app_handle, account.as_deref(), None) {
+        let Some(player) = metadata
+            .participants
+            .iter()
+            .find(|participant| participant.participant_id == metadata.participant_id)
+        else {
+            continue;
+        };
+        if champion_id.is_some_and(|champion_id| player.champion_id != champion_id) {
+            continue;
+        }
+        if let Some(role) = &role {
+            if !player.lane.eq_ignore_ascii_case(role) && !player.role.eq_ignore_ascii_case(role) {
+                continue;
+            }
+        }
+
+        heatmap.games += 1;
+        heatmap.positions.extend(metadata.kill_positions);
+    }
+    heatmap
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchupSummary {
+    champion_id: ChampionId,
+    opponent_champion_id: ChampionId,
+    /// see `Matchup::lane`
+    lane: String,
+    games: u32,
+    wins: u32,
+}
+
+/// Games and wins per matchup (champion of the player vs. their lane opponent in a lane), most games first.
+/// `champion_id` and `opponent_champion_id` only count the matchups with these champions, with `account` (puuid) only
+/// the recordings of that Riot account are counted, with `patch` only the games on that patch.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_matchup_stats(
+    account: Option<String>,
+    patch: Option<String>,
+    champion_id: Option<ChampionId>,
+    opponent_champion_id: Option<ChampionId>,
+    app_handle: AppHandle,
+) -> Vec<MatchupSummary> {
+    let mut matchups: Vec<MatchupSummary> = Vec::new();
+    for metadata in game_metadata(&app_handle, account.as_deref(), patch.as_deref()) {
+        let Some(matchup) = metadata.matchup else { continue };
+        if champion_id.is_some_and(|champion_id| matchup.champion_id != champion_id)
+            || opponent_champion_id.is_some_and(|opponent| matchup.opponent_champion_id != opponent)
+        {
+            continue;
+        }
+
+        let win = u32::from(metadata.stats.win);
+        match matchups.iter_mut().find(|summary| {
+            summary.champion_id == matchup.champion_id
+                && summary.opponent_champion_id == matchup.opponent_champion_id
+                && summary.lane == matchup.lane
+        }) {
+            Some(summary) => {
+                summary.games += 1;
+                summary.wins += win;
+            }
+            None => matchups.push(MatchupSummary {
+                champion_id: matchup.champion_id,
+                opponent_champion_id: matchup.opponent_champion_id,
+                lane: matchup.lane,
+                games: 1,
+                wins: win,
+            }),
+        }
+    }
+    matchups.sort_by(|a, b| b.games.cmp(&a.games));
+    matchups
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewTrendEntry {
+    game_id: GameId,
+    champion_id: Option<ChampionId>,
+    win: bool,
+    review_stats: ReviewStats,
+}
+
+/// CS, gold and XP differences @10/@15 and first blood participation of every recorded game (oldest first) to show how
+/// they develop over time.
+/// With `account` (puuid) only the recordings of that Riot account are counted, with `champion_id` only the games on
+/// that champion.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_review_trend(
+    account: Option<String>,
+    champion_id: Option<ChampionId>,
+    app_handle: AppHandle,
+) -> Vec<ReviewTrendEntry> {
+    let mut trend: Vec<_> = game_metadata(&app_handle, account.as_deref(), None)
+        .into_iter()
+        .filter(|metadata| champion_id.is_none() || metadata.champion_id() == champion_id)
+        .map(|metadata| ReviewTrendEntry {
+            game_id: metadata.match_id.game_id,
+            champion_id: metadata.champion_id(),
+            win: metadata.stats.win,
+            review_stats: metadata.review_stats,
+        })
+        .collect();
+    // game IDs increase over time
+    trend.sort_by_key(|entry| entry.game_id);
+    trend
+}
+
+/// Metadata of every recording with game data, optionally only of `account` (puuid) and `patch`
+fn game_metadata(app_handle: &AppHandle, account: Option<&str>, patch: Option<&str>) -> Vec<GameMetadata> {
+    app_handle
+        .get_recordings()
+        .into_iter()
+        .filter_map(|recording| match action::get_recording_metadata(&recording, false) {
+            Ok(MetadataFile::Metadata(metadata)) => Some(metadata),
+            _ => None,
+        })
+        .filter(|metadata| {
+            account.is_none() || metadata.account.as_ref().map(|account| account.puuid.as_str()) == account
+        })
+        .filter(|metadata| patch.is_none() || metadata.patch().as_deref() == patch)
+        .collect()
+}
+
+/// '14.10' => (14, 10) for sorting
+fn patch_number(patch: &str) -> (u32, u32) {
+    let (major, minor) = patch.split_once('.').unwrap_or((patch, "0"));
+    (major.parse().unwrap_or(0), minor.parse().unwrap_or(0))
+}
+
+fn patch_notes_url(patch: &str) -> String {
+    let (major, minor) = patch_number(patch);
+    // the patches are named after the year since 2025 (game version 15.x is patch 25.x)
+    let major = if major >= 15 { major + 10 } else { major };
+    format!("https://www.leagueoflegends.com/en-us/news/game-updates/patch-{major}-{minor}-notes/")
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn reindex_library(app_handle: AppHandle) -> LibraryReport {
+    let report = tauri::async_runtime::spawn_blocking({
+        let app_handle = app_handle.clone();
+        move || app_handle.reindex_library()
+    })
+    .await
+    .unwrap_or_default();
+
+    if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
+        log::error!("failed to send event: {e}");
+    }
+    report
 }
 
 #[cfg_attr(test, specta::specta)]
@@ -83,21 +770,48 @@ pub fn open_recordings_folder(state: State<SettingsWrapper>) {
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn rename_video(video_id: String, new_video_id: String, _state: State<SettingsWrapper>) -> bool {
-    let recording = PathBuf::from(video_id);
-    action::rename_recording(recording, new_video_id).unwrap_or_else(|e| {
-        log::error!("failed to rename video: {e}");
-        false
-    })
+pub fn rename_video(video_id: String, new_video_id: String, app_handle: AppHandle) -> bool {
+    let recording = app_handle.resolve_video_id(&video_id);
+    if app_handle.state::<SettingsWrapper>().is_external(&recording) {
+        log::error!("failed to rename video: recordings in external folders are read-only");
+        return false;
+    }
+    let Some(new_file_name) = Path::new(&new_video_id).file_name() else { return false };
+    let new_recording = recording.with_file_name(new_file_name);
+
+    match action::rename_recording(recording.clone(), new_video_id) {
+        Ok(renamed) => {
+            if renamed {
+                app_handle.state::<RecordingIndex>().rename(&recording, new_recording);
+            }
+            renamed
+        }
+        Err(e) => {
+            log::error!("failed to rename video: {e}");
+            false
+        }
+    }
+}
+
+/// Recordings and clips the retention rules would delete, so aggressive rules can be checked before enabling them
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn preview_cleanup(app_handle: AppHandle) -> CleanupPreview {
+    tauri::async_runtime::spawn_blocking(move || app_handle.preview_cleanup())
+        .await
+        .unwrap_or_default()
 }
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn delete_video(video_id: String, _state: State<SettingsWrapper>) -> bool {
-    let recording = PathBuf::from(video_id);
+pub fn delete_video(video_id: String, app_handle: AppHandle) -> bool {
+    let recording = app_handle.resolve_video_id(&video_id);
 
-    match action::delete_recording(recording) {
-        Ok(_) => true,
+    match action::remove_recording(&app_handle, &recording) {
+        Ok(_) => {
+            app_handle.state::<RecordingIndex>().remove(&recording);
+            true
+        }
         Err(e) => {
             log::error!("failed to delete video: {e}");
             false
@@ -105,26 +819,340 @@ pub fn delete_video(video_id: String, _state: State<SettingsWrapper>) -> bool {
     }
 }
 
+/// Restore the recording that was most recently moved to the recycle bin in this session.
+/// Returns the video_id of the restored recording.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn undo_delete(deleted_recordings: State<DeletedRecordings>) -> Option<String> {
+    let deleted_recording = deleted_recordings.pop()?;
+    match action::restore_recording(&deleted_recording.files) {
+        Ok(()) => deleted_recording.video_path.to_str().map(str::to_owned),
+        Err(e) => {
+            log::error!("failed to restore video: {e}");
+            None
+        }
+    }
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn bulk_delete(video_ids: Vec<String>, app_handle: AppHandle) -> BulkSummary {
+    tauri::async_runtime::spawn_blocking(move || {
+        run_bulk(&app_handle, BulkOperation::Delete, video_ids, |recording| {
+            action::remove_recording(&app_handle, recording)
+        })
+    })
+    .await
+    .unwrap_or_default()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn bulk_favorite(video_ids: Vec<String>, favorite: bool, app_handle: AppHandle) -> BulkSummary {
+    tauri::async_runtime::spawn_blocking(move || {
+        run_bulk(&app_handle, BulkOperation::Favorite, video_ids, |recording| {
+            if app_handle.state::<SettingsWrapper>().is_external(recording) {
+                anyhow::bail!("recordings in external folders are read-only");
+            }
+            let mut metadata = action::get_recording_metadata(recording, false)?;
+            metadata.set_favorite(favorite);
+            action::save_recording_metadata(recording, &metadata)?;
+
+            // with 'onlyFavorites' a recording gets backed up once it becomes a favorite
+            if favorite {
+                backup::enqueue(&app_handle, recording);
+            }
+            Ok(())
+        })
+    })
+    .await
+    .unwrap_or_default()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn bulk_move_to_folder(video_ids: Vec<String>, folder: PathBuf, app_handle: AppHandle) -> BulkSummary {
+    tauri::async_runtime::spawn_blocking(move || {
+        run_bulk(&app_handle, BulkOperation::Move, video_ids, |recording| {
+            if app_handle.state::<SettingsWrapper>().is_external(recording) {
+                anyhow::bail!("recordings in external folders are read-only");
+            }
+            action::move_recording(recording, &folder).map(|_| ())
+        })
+    })
+    .await
+    .unwrap_or_default()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn bulk_export(video_ids: Vec<String>, preset: String, app_handle: AppHandle) -> Result<BulkSummary, String> {
+    let state = app_handle.state::<SettingsWrapper>();
+    let preset = state
+        .get_export_preset(&preset)
+        .ok_or_else(|| format!("no export preset named '{preset}'"))?;
+    let ffmpeg = state.ffmpeg_path().unwrap_or_else(|| "ffmpeg".to_string());
+
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        run_bulk(&app_handle, BulkOperation::Export, video_ids, |recording| {
+            action::export_video(recording, &preset, &ffmpeg).map(|_| ())
+        })
+    })
+    .await
+    .unwrap_or_default();
+    Ok(summary)
+}
+
+/// Upgrade the metadata files of all recordings to the current schema right away instead of on the next start
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn migrate_all_metadata(app_handle: AppHandle) -> BulkSummary {
+    tauri::async_runtime::spawn_blocking(move || {
+        let video_ids = app_handle
+            .get_recordings()
+            .into_iter()
+            .filter(|recording| action::is_native_recording(recording))
+            .filter_map(|recording| recording.to_str().map(str::to_owned))
+            .collect();
+        let settings = app_handle.state::<SettingsWrapper>();
+        run_bulk(&app_handle, BulkOperation::Migrate, video_ids, |recording| {
+            action::migrate_metadata(recording, &settings).map(|_| ())
+        })
+    })
+    .await
+    .unwrap_or_default()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_jobs(jobs: State<Jobs>) -> Vec<Job> {
+    jobs.get()
+}
+
+/// Queue a post-processing job for the recording. Returns the ID of the job.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn enqueue_job(video_id: String, kind: JobKind, app_handle: AppHandle) -> Option<u32> {
+    let path = app_handle.resolve_video_id(&video_id);
+    jobs::enqueue(&app_handle, kind, &path)
+}
+
+/// Jobs can only be cancelled before they start
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn cancel_job(id: u32, jobs: State<Jobs>, app_handle: AppHandle) -> bool {
+    jobs.cancel(&app_handle, id)
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_metadata(video_id: String, state: State<SettingsWrapper>) -> Option<MetadataFile> {
+    let path = PathBuf::from(video_id);
+    action::get_recording_metadata(&path, !state.is_external(&path)).ok()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_markers(video_id: String) -> Option<Markers> {
+    let path = PathBuf::from(video_id);
+    let metadata_file = action::get_recording_metadata(&path, false).ok()?;
+    Markers::new(&metadata_file)
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_timeline(video_id: String, format: TimelineFormat) -> Result<String, String> {
+    let path = PathBuf::from(video_id);
+    let mut metadata_file = action::get_recording_metadata(&path, false).map_err(|e| e.to_string())?;
+    // exports get shared like the metadata file
+    if privacy::is_enabled() {
+        metadata_file = privacy::redact(&metadata_file);
+    }
+    let MetadataFile::Metadata(metadata) = metadata_file else {
+        return Err("recording has no game data".into());
+    };
+    TimelineDocument::new(&metadata)
+        .to_format(format)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn import_recording(video_id: String, state: State<SettingsWrapper>) -> Option<MetadataFile> {
+    let path = PathBuf::from(video_id);
+    if state.is_external(&path) {
+        log::error!("failed to import video: recordings in external folders are read-only");
+        return None;
+    }
+    action::import_recording(&path)
+        .map_err(|e| log::error!("failed to import video: {e}"))
+        .ok()
+}
+
+/// Package the recording with its metadata and screenshot into a .zip file or folder in `destination`
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn export_recording(video_id: String, destination: PathBuf, format: BundleFormat) -> Result<PathBuf, String> {
+    let video_path = PathBuf::from(video_id);
+    tauri::async_runtime::spawn_blocking(move || bundle::export(&video_path, &destination, format))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| {
+            log::error!("failed to export recording: {e}");
+            e.to_string()
+        })
+}
+
+/// Collect logs, settings and the recent recorder history for a bug report into a .zip file in `destination`.
+/// Credentials are removed from everything in the bundle.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn generate_support_bundle(destination: PathBuf, app_handle: AppHandle) -> Result<PathBuf, String> {
+    tauri::async_runtime::spawn_blocking(move || support::generate(&app_handle, &destination))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| {
+            log::error!("failed to generate support bundle: {e}");
+            e.to_string()
+        })
+}
+
+/// Copy the trace of how the metadata of a game got processed into `destination` (a folder). Traces are only written
+/// in developer mode.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_metadata_trace(game_id: GameId, destination: PathBuf, app_handle: AppHandle) -> Result<PathBuf, String> {
+    metadata_trace::export(&app_handle, game_id, &destination).map_err(|e| e.to_string())
+}
+
+/// Add a recording exported with `export_recording` to the recordings folder. Returns the video_id of the recording.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn import_recording_bundle(bundle_path: PathBuf, app_handle: AppHandle) -> Result<String, String> {
+    let recordings_folder = app_handle.state::<SettingsWrapper>().get_recordings_path();
+    let video_path = tauri::async_runtime::spawn_blocking(move || bundle::import(&bundle_path, &recordings_folder))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| {
+            log::error!("failed to import recording bundle: {e}");
+            e.to_string()
+        })?;
+
+    if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
+        log::error!("failed to send event: {e}");
+    }
+    Ok(video_path.to_string_lossy().to_string())
+}
+
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn get_metadata(video_id: String, _state: State<SettingsWrapper>) -> Option<MetadataFile> {
+pub async fn import_riot_metadata(
+    video_id: String,
+    settings: State<'_, SettingsWrapper>,
+) -> Result<MetadataFile, String> {
+    let Some(api_key) = settings.riot_api_key() else {
+        log::warn!("can't import metadata from the Riot API without an API key");
+        return Err("no Riot API key set ('riotApiKey' setting)".into());
+    };
+
     let path = PathBuf::from(video_id);
-    action::get_recording_metadata(&path, true).ok()
+    action::import_riot_metadata(&path, &api_key).await.map_err(|e| {
+        log::error!("failed to import metadata from the Riot API: {e}");
+        e.to_string()
+    })
 }
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn toggle_favorite(video_id: String, _state: State<SettingsWrapper>) -> Option<bool> {
+pub async fn download_replay(video_id: String) -> Result<PathBuf, String> {
     let path = PathBuf::from(video_id);
+    action::download_replay(&path).await.map_err(|e| {
+        log::error!("failed to download replay: {e}");
+        e.to_string()
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn open_replay_at(video_id: String, timestamp: f64) -> Result<(), String> {
+    let path = PathBuf::from(video_id);
+    action::open_replay_at(&path, timestamp).await.map_err(|e| {
+        log::error!("failed to open replay: {e}");
+        e.to_string()
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn get_postgame_screenshot(video_id: String, state: State<'_, SettingsWrapper>) -> Option<PathBuf> {
+    let video_path = PathBuf::from(video_id);
+    let screenshot_path = video_path.with_extension("png");
+    if screenshot_path.is_file() {
+        return Some(screenshot_path);
+    }
+
+    // recordings from before the screenshot was captured automatically get their screenshot on demand
+    let ffmpeg = state.ffmpeg_path().unwrap_or_else(|| "ffmpeg".to_string());
+    tauri::async_runtime::spawn_blocking(move || action::create_postgame_screenshot(&video_path, &ffmpeg))
+        .await
+        .ok()?
+        .map_err(|e| log::warn!("failed to create post-game screenshot: {e}"))
+        .ok()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn toggle_favorite(video_id: String, app_handle: AppHandle) -> Option<bool> {
+    let path = app_handle.resolve_video_id(&video_id);
+    if app_handle.state::<SettingsWrapper>().is_external(&path) {
+        return None;
+    }
 
     let mut metadata = action::get_recording_metadata(&path, true).ok()?;
     let favorite = !metadata.is_favorite();
     metadata.set_favorite(favorite);
     action::save_recording_metadata(&path, &metadata).ok()?;
 
+    // with 'onlyFavorites' a recording gets backed up once it becomes a favorite
+    if favorite {
+        backup::enqueue(&app_handle, &path);
+    }
+
     Some(favorite)
 }
 
+/// Protected recordings are never deleted by the retention rules ('maxRecordingAge', 'maxRecordingsSize', ...)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn set_protected(video_id: String, protected: bool, app_handle: AppHandle) -> Option<bool> {
+    let path = app_handle.resolve_video_id(&video_id);
+    if app_handle.state::<SettingsWrapper>().is_external(&path) {
+        return None;
+    }
+
+    let mut metadata = action::get_recording_metadata(&path, false).ok()?;
+    metadata.set_protected(protected);
+    action::save_recording_metadata(&path, &metadata).ok()?;
+    Some(protected)
+}
+
+/// Replace the comments of the recording (only recordings with game data can have comments)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn set_comments(video_id: String, comments: Vec<Comment>, app_handle: AppHandle) -> Result<(), String> {
+    let path = app_handle.resolve_video_id(&video_id);
+    if app_handle.state::<SettingsWrapper>().is_external(&path) {
+        return Err("recordings in external folders are read-only".into());
+    }
+
+    let mut metadata_file = action::get_recording_metadata(&path, false).map_err(|e| e.to_string())?;
+    let MetadataFile::Metadata(metadata) = &mut metadata_file else {
+        return Err("recording has no game data".into());
+    };
+    metadata.comments = comments;
+    action::save_recording_metadata(&path, &metadata_file).map_err(|e| e.to_string())
+}
+
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
 pub fn confirm_delete(settings: State<SettingsWrapper>) -> bool {
@@ -158,6 +1186,64 @@ pub async fn save_settings(
     Ok(())
 }
 
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_settings(path: PathBuf, settings: State<SettingsWrapper>) -> Result<(), String> {
+    settings.export_to(&path).map_err(|e| e.to_string())
+}
+
+/// Replace the settings with an exported settings file (the current settings.json gets backed up first).
+/// Returns everything in the imported file that got ignored or replaced with a default.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn import_settings(
+    path: PathBuf,
+    settings: State<'_, SettingsWrapper>,
+    settings_file: State<'_, SettingsFile>,
+    app_handle: AppHandle,
+) -> Result<Vec<SettingsWarning>, String> {
+    settings
+        .import_from(&path, settings_file.get(), &app_handle)
+        .map_err(|e| e.to_string())
+}
+
+/// Reset all settings to their defaults (the current settings.json gets backed up first)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn reset_settings(
+    settings: State<'_, SettingsWrapper>,
+    settings_file: State<'_, SettingsFile>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    settings
+        .reset(settings_file.get(), &app_handle)
+        .map_err(|e| e.to_string())
+}
+
+/// Check the content of a settings.json for unknown keys and values that would be ignored or replaced with a default
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn validate_settings(settings_json: String, app_handle: AppHandle) -> Result<Vec<SettingsWarning>, String> {
+    state::validate_settings(&app_handle, &settings_json).map_err(|e| e.to_string())
+}
+
+/// Check a hotkey like "ctrl + shift + h" and return it in its canonical form ("Ctrl+Shift+H")
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn validate_hotkey(hotkey: String) -> Result<String, String> {
+    hotkey.parse::<Hotkey>().map(|hotkey| hotkey.to_string())
+}
+
+/// Wait for the user to press a key (combination) and return it in its canonical form.
+/// Returns None if nothing got pressed within 5 seconds.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn begin_hotkey_capture() -> Option<String> {
+    RawInputListener::capture(HOTKEY_CAPTURE_TIMEOUT)
+        .await
+        .map(|hotkey| hotkey.to_string())
+}
+
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
 pub async fn pick_recordings_folder(app_handle: AppHandle) -> Option<PathBuf> {
@@ -189,9 +1275,11 @@ pub async fn create_clip(
     start: f64,
     end: f64,
     state: State<'_, SettingsWrapper>,
+    app_handle: AppHandle,
 ) -> Result<String, String> {
     let recordings_path = state.get_clips_path();
-    let video_path = state.get_recordings_path().join(&video_id);
+    let video_path = state.get_recordings_path().join(app_handle.resolve_video_id(&video_id));
+    let video_name = video_path.file_stem().unwrap_or_default().to_string_lossy();
 
     // Ensure clips directory exists
     if !recordings_path.exists() {
@@ -200,8 +1288,11 @@ pub async fn create_clip(
 
     // Output filename
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let output_filename = format!("{}_clip_{}.mp4", video_id.replace(".mp4", ""), timestamp);
+    let output_filename = format!("{video_name}_clip_{timestamp}.mp4");
     let output_path = recordings_path.join(&output_filename);
+    if output_path.exists() {
+        return Err(format!("{output_filename} already exists"));
+    }
 
     let duration = end - start;
     if duration <= 0.0 {
@@ -209,30 +1300,20 @@ pub async fn create_clip(
     }
 
     let ffmpeg_cmd = state.ffmpeg_path().unwrap_or_else(|| "ffmpeg".to_string());
-    let mut command = Command::new(ffmpeg_cmd);
+    action::extract_clip(&video_path, &output_path, start, duration, false, &ffmpeg_cmd).map_err(|e| e.to_string())?;
 
-    #[cfg(target_os = "windows")]
-    use std::os::windows::process::CommandExt;
-    #[cfg(target_os = "windows")]
-    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-    let status = command
-        .arg("-ss")
-        .arg(format!("{:.3}", start))
-        .arg("-i")
-        .arg(&video_path)
-        .arg("-t")
-        .arg(format!("{:.3}", duration))
-        .arg("-c")
-        .arg("copy")
-        .arg(&output_path)
-        .status();
-
-    match status {
-        Ok(s) if s.success() => Ok(output_filename),
-        Ok(_) => Err("FFmpeg exited with non-zero code.".into()),
-        Err(e) => Err(format!("Failed to execute ffmpeg: {}. Is FFmpeg installed?", e)),
+    // the clip keeps referencing its recording even if the recording gets renamed
+    let source_id = action::get_recording_metadata(&video_path, false).map(|metadata| metadata.id().to_owned());
+    let clip_metadata = ClipMetadata {
+        source_id: source_id.ok(),
+        ..Default::default()
+    };
+    if let Err(e) = action::save_clip_metadata(&output_path, &clip_metadata) {
+        log::warn!("failed to save clip metadata: {e}");
     }
+
+    telemetry::record(&app_handle, TelemetryEvent::ClipCreated);
+    Ok(output_filename)
 }
 
 #[cfg_attr(test, specta::specta)]
@@ -248,22 +1329,79 @@ pub async fn pick_ffmpeg_path(app_handle: AppHandle) -> Option<String> {
         .flatten()
 }
 
+/// Preview of the anonymized usage statistics that would be sent if the user opted in to telemetry.
+/// `None` if the app was built without telemetry.
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_pending_telemetry(app_handle: AppHandle) -> Option<TelemetryReport> {
+    telemetry::pending_report(&app_handle)
+}
+
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
 pub async fn clear_cache(app_handle: AppHandle) -> Result<(), String> {
-    use tauri::Manager;
-    // In Tauri v2, we use app_handle.path().app_local_data_dir()
-    let app_dir = app_handle.path().app_local_data_dir().map_err(|e| e.to_string())?;
+    app_handle
+        .clear_cache()
+        .map_err(|e| format!("Failed to clear cache: {e}"))
+}
 
-    let cache_dirs = ["img_cache", "items_cache"];
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn export_with_preset(
+    video_id: String,
+    preset: String,
+    state: State<'_, SettingsWrapper>,
+) -> Result<PathBuf, String> {
+    let video_path = PathBuf::from(video_id);
+    let preset = state
+        .get_export_preset(&preset)
+        .ok_or_else(|| format!("no export preset named '{preset}'"))?;
+    let ffmpeg = state.ffmpeg_path().unwrap_or_else(|| "ffmpeg".to_string());
 
-    for dir in cache_dirs {
-        let path = app_dir.join(dir);
-        if path.exists() {
-            std::fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete {}: {}", dir, e))?;
-        }
-    }
-    Ok(())
+    tauri::async_runtime::spawn_blocking(move || action::export_video(&video_path, &preset, &ffmpeg))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| {
+            log::error!("failed to export video: {e}");
+            e.to_string()
+        })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn start_youtube_login(app_handle: AppHandle) -> Result<YoutubeLogin, String> {
+    let client = YoutubeClient::new(&app_handle).map_err(|e| e.to_string())?;
+    client.request_login().await.map_err(|e| {
+        log::error!("failed to start YouTube login: {e}");
+        e.to_string()
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn finish_youtube_login(login: YoutubeLogin, app_handle: AppHandle) -> Result<(), String> {
+    let client = YoutubeClient::new(&app_handle).map_err(|e| e.to_string())?;
+    client.finish_login(&login).await.map_err(|e| {
+        log::error!("YouTube login failed: {e}");
+        e.to_string()
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn upload_clip_youtube(
+    clip_id: String,
+    title: String,
+    privacy: Privacy,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let clip_path = app_handle.state::<SettingsWrapper>().get_clips_path().join(&clip_id);
+    uploader::upload_clip(&app_handle, &clip_path, &title, privacy)
+        .await
+        .map_err(|e| {
+            log::error!("failed to upload clip to YouTube: {e}");
+            e.to_string()
+        })
 }
 
 #[cfg_attr(test, specta::specta)]
@@ -274,40 +1412,20 @@ pub async fn download_image(
     filename: String,
     app_handle: AppHandle,
 ) -> Result<String, String> {
-    use std::io::Write;
-    use tauri::Manager;
-
-    // Validate category/filename to be safe?
-    // Basic check: don't allow ".."
-    if category.contains("..") || filename.contains("..") {
-        return Err("Invalid path parameters".to_string());
-    }
-
-    let app_dir = app_handle.path().app_local_data_dir().map_err(|e| e.to_string())?;
-    let img_cache = app_dir.join("img_cache");
-    let category_dir = img_cache.join(&category);
-
-    if !img_cache.exists() {
-        std::fs::create_dir(&img_cache).map_err(|e| e.to_string())?;
-    }
-    if !category_dir.exists() {
-        std::fs::create_dir(&category_dir).map_err(|e| e.to_string())?;
-    }
-
-    let file_path = category_dir.join(&filename);
-
-    // Download
-    let response = reqwest::get(&url).await.map_err(|e| e.to_string())?;
-    if !response.status().is_success() {
-        return Err(format!("Request failed: {}", response.status()));
-    }
-
-    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
-
-    let mut file = std::fs::File::create(&file_path).map_err(|e| e.to_string())?;
-    file.write_all(&bytes).map_err(|e| e.to_string())?;
+    cache_image(&app_handle, &url, &category, &filename)
+        .await
+        .map(|file_path| file_path.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
+}
 
-    Ok(file_path.to_string_lossy().to_string())
+/// Names of the champions, items and runes of the patch of `game_version` (e.g. '14.10.584.1234')
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn get_static_data(game_version: String) -> Result<Catalog, String> {
+    static_data::catalog(&game_version)
+        .await
+        .map(|catalog| Catalog::clone(&catalog))
+        .map_err(|e| e.to_string())
 }
 
 #[cfg_attr(test, specta::specta)]
@@ -12,4 +12,10 @@ pub mod menu_item {
     pub const OPEN: &str = "open";
     pub const QUIT: &str = "quit";
     pub const UPDATE: &str = "update";
+    pub const START_RECORDING: &str = "start_recording";
+    pub const STOP_RECORDING: &str = "stop_recording";
+    pub const OPEN_LAST: &str = "open_last";
+    pub const RECENT: &str = "recent";
+    // followed by the path of the recording
+    pub const RECENT_PREFIX: &str = "recent:";
 }
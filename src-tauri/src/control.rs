@@ -0,0 +1,150 @@
+//! Control channel for external tools (Stream Deck, macro software, scripts, ...).
+//!
+//! Commands can either be written line by line to the named pipe `\\.\pipe\LeagueRecord` or be passed to a second
+//! instance of the app with `--command <start|stop|highlight|bookmark|open>` (or just `--stop`, ...) which forwards them
+//! to the running instance.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::app::{AppWindow, WindowManager};
+use crate::recorder::{HighlightCategory, LeagueRecorder};
+
+const COMMAND_ARG: &str = "--command";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    Start,
+    Stop,
+    Highlight,
+    Bookmark,
+    /// show the main window
+    Open,
+}
+
+impl FromStr for ControlCommand {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "start" => Ok(Self::Start),
+            "stop" => Ok(Self::Stop),
+            "highlight" => Ok(Self::Highlight),
+            "bookmark" => Ok(Self::Bookmark),
+            "open" => Ok(Self::Open),
+            other => Err(anyhow!("unknown command '{other}'")),
+        }
+    }
+}
+
+impl ControlCommand {
+    /// Parse the command of `--command <command>` or `--<command>` from the command line arguments of an app instance
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let Some(position) = args.iter().position(|arg| arg == COMMAND_ARG) else {
+            // other arguments (e.g. '--debug') aren't commands
+            return args
+                .iter()
+                .filter_map(|arg| arg.strip_prefix("--"))
+                .find_map(|command| command.parse().ok());
+        };
+
+        match args.get(position + 1)?.parse() {
+            Ok(command) => Some(command),
+            Err(e) => {
+                log::warn!("invalid {COMMAND_ARG} argument: {e}");
+                None
+            }
+        }
+    }
+
+    /// Do the same thing as the corresponding hotkey
+    pub fn execute(self, app_handle: &AppHandle) {
+        log::info!("control command: {self:?}");
+        match self {
+            ControlCommand::Start => app_handle.state::<LeagueRecorder>().manual_start(),
+            ControlCommand::Stop => app_handle.state::<LeagueRecorder>().manual_stop(),
+            ControlCommand::Highlight | ControlCommand::Bookmark => {
+                let category = if self == ControlCommand::Bookmark {
+                    HighlightCategory::Bookmark
+                } else {
+                    HighlightCategory::Highlight
+                };
+                if let Err(e) = app_handle.emit("shortcut-event", category) {
+                    log::error!("failed to emit highlight event: {e}");
+                }
+            }
+            ControlCommand::Open => app_handle.open_window(AppWindow::Main),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub use pipe::listen;
+
+#[cfg(target_os = "windows")]
+mod pipe {
+    use tauri::{async_runtime, AppHandle};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+
+    use super::ControlCommand;
+
+    const PIPE_NAME: &str = r"\\.\pipe\LeagueRecord";
+
+    /// Listen for commands on the named pipe. Every line is one command that gets answered with 'ok' or an error.
+    pub fn listen(app_handle: &AppHandle) {
+        let app_handle = app_handle.clone();
+        async_runtime::spawn(async move {
+            let mut server = match ServerOptions::new().first_pipe_instance(true).create(PIPE_NAME) {
+                Ok(server) => server,
+                Err(e) => {
+                    log::error!("failed to create control pipe: {e}");
+                    return;
+                }
+            };
+
+            loop {
+                if let Err(e) = server.connect().await {
+                    log::warn!("control pipe connection failed: {e}");
+                    continue;
+                }
+
+                // create the next pipe instance before handling the client so other clients don't get rejected
+                let client = server;
+                server = match ServerOptions::new().create(PIPE_NAME) {
+                    Ok(server) => server,
+                    Err(e) => {
+                        log::error!("failed to create control pipe: {e}");
+                        return;
+                    }
+                };
+
+                async_runtime::spawn(handle_client(app_handle.clone(), client));
+            }
+        });
+    }
+
+    async fn handle_client(app_handle: AppHandle, client: NamedPipeServer) {
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match line.parse::<ControlCommand>() {
+                Ok(command) => {
+                    command.execute(&app_handle);
+                    "ok\n".to_owned()
+                }
+                Err(e) => format!("error: {e}\n"),
+            };
+            if writer.write_all(response.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Opt-in crash reports ('crashReports' setting) for bug reports on GitHub.
+//!
+//! A panic that ends the process or a crash of the process (e.g. an access violation in a native dependency) writes
+//! the recent log messages and a minidump (the stacks of all threads) into a folder next to the log files. Nothing gets
+//! uploaded, on the next start the user is asked to open a GitHub issue and attach the files.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use reqwest::Url;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+use crate::app::support::Sanitizer;
+use crate::constants::{APP_NAME, CURRENT_VERSION};
+use crate::state::{LogBuffer, SettingsWrapper};
+use crate::util;
+
+const CRASH_DIR: &str = "crash_reports";
+// contains the file name of the report of the last crash until it has been offered to the user
+const PENDING_FILE: &str = "pending";
+const MAX_LOG_ENTRIES: usize = 200;
+const NEW_ISSUE_URL: &str = "https://github.com/arasan95/league_record_custom/issues/new";
+
+struct Reporter {
+    crash_dir: PathBuf,
+    app_handle: AppHandle,
+}
+
+static REPORTER: OnceLock<Reporter> = OnceLock::new();
+
+/// Write crash reports from now on if 'crashReports' is enabled (only read on start) and offer the report of the last
+/// crash to the user
+pub fn init(app_handle: &AppHandle) {
+    if !app_handle.state::<SettingsWrapper>().crash_reports() {
+        return;
+    }
+
+    let crash_dir = match app_handle.path().app_log_dir() {
+        Ok(log_dir) => log_dir.join(CRASH_DIR),
+        Err(e) => {
+            log::warn!("crash reports disabled, unable to get the log folder: {e}");
+            return;
+        }
+    };
+    if let Err(e) = fs::create_dir_all(&crash_dir) {
+        log::warn!("crash reports disabled, failed to create {}: {e}", crash_dir.display());
+        return;
+    }
+    log::info!("writing crash reports to {}", crash_dir.display());
+    offer_pending_report(app_handle, &crash_dir);
+
+    let reporter = Reporter {
+        crash_dir,
+        app_handle: app_handle.clone(),
+    };
+    if REPORTER.set(reporter).is_err() {
+        return;
+    }
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let description = format!("thread '{}' {info}", thread.name().unwrap_or("<unnamed>"));
+        // panics of other threads (e.g. async tasks) are caught and don't end the process
+        if cfg!(panic = "abort") || thread.name() == Some("main") {
+            let report = write_report(&description);
+            #[cfg(target_os = "windows")]
+            if let Some(report) = report {
+                minidump::write(&report.with_extension("dmp"), std::ptr::null());
+            }
+        } else {
+            log::error!("{description}");
+        }
+        default_hook(info);
+    }));
+
+    #[cfg(target_os = "windows")]
+    minidump::install();
+}
+
+/// Write the description, backtrace and the recent log messages of a crash. Returns the path of the report.
+fn write_report(description: &str) -> Option<PathBuf> {
+    let reporter = REPORTER.get()?;
+
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let report_path = reporter.crash_dir.join(format!("crash_{timestamp}.txt"));
+
+    let mut report = format!(
+        "{APP_NAME} v{CURRENT_VERSION} ({} {})\n\n{description}\n\nbacktrace:\n{}\n\nrecent log messages:\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        Backtrace::force_capture()
+    );
+    for entry in reporter.app_handle.state::<LogBuffer>().try_recent(MAX_LOG_ENTRIES) {
+        report.push_str(&format!("[{:?}][{}]: {}\n", entry.level, entry.target, entry.message));
+    }
+
+    // same as in support bundles, the report is meant to be attached to a public issue
+    let report = Sanitizer::new(&reporter.app_handle).sanitize(&report);
+
+    fs::write(&report_path, report).ok()?;
+    _ = fs::write(
+        reporter.crash_dir.join(PENDING_FILE),
+        report_path.file_name()?.as_encoded_bytes(),
+    );
+    Some(report_path)
+}
+
+fn offer_pending_report(app_handle: &AppHandle, crash_dir: &Path) {
+    let pending = crash_dir.join(PENDING_FILE);
+    let Ok(report_name) = fs::read_to_string(&pending) else { return };
+    // only asked once per crash
+    if let Err(e) = fs::remove_file(&pending) {
+        log::warn!("failed to remove {}: {e}", pending.display());
+    }
+
+    let report = crash_dir.join(report_name.trim());
+    if !report.is_file() {
+        return;
+    }
+    log::info!("crash report of the last run: {}", report.display());
+
+    let message = format!(
+        "{APP_NAME} crashed the last time it ran. The crash report was saved in\n{}\n\n\
+        Do you want to open an issue on GitHub? Nothing is uploaded automatically, please attach the files with the \
+        same name as '{}' to the issue. The log messages can contain names of files and folders (your home folder is \
+        replaced with '%USERPROFILE%', credentials are removed).",
+        crash_dir.display(),
+        report_name.trim()
+    );
+    app_handle
+        .dialog()
+        .message(message)
+        .title("Crash report")
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Open issue".into(),
+            "Not now".into(),
+        ))
+        .show(move |open_issue| {
+            if !open_issue {
+                return;
+            }
+            if let Err(e) = util::reveal_in_file_manager(&report) {
+                log::warn!("failed to show the crash report: {e}");
+            }
+            let result = issue_url(&report).and_then(|url| Ok(util::open_url(&url)?));
+            if let Err(e) = result {
+                log::warn!("failed to open a GitHub issue: {e}");
+            }
+        });
+}
+
+fn issue_url(report: &Path) -> Result<Url> {
+    let report_name = report.file_name().unwrap_or_default().to_string_lossy();
+    let body = format!(
+        "**What was LeagueRecord doing when it crashed?**\n\n\n\
+        **Crash report**\nAttach '{report_name}' and the .dmp file with the same name (if there is one)."
+    );
+
+    let mut url = Url::parse(NEW_ISSUE_URL)?;
+    url.query_pairs_mut()
+        .append_pair("title", &format!("Crash in v{CURRENT_VERSION}"))
+        .append_pair("body", &body);
+    Ok(url)
+}
+
+#[cfg(target_os = "windows")]
+mod minidump {
+    use std::fs::File;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        MiniDumpWithThreadInfo, MiniDumpWriteDump, SetUnhandledExceptionFilter, EXCEPTION_POINTERS,
+        MINIDUMP_EXCEPTION_INFORMATION,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId};
+
+    /// Write a minidump for crashes that aren't panics (e.g. access violations)
+    pub fn install() {
+        unsafe { SetUnhandledExceptionFilter(Some(exception_filter)) };
+    }
+
+    unsafe extern "system" fn exception_filter(exception: *const EXCEPTION_POINTERS) -> i32 {
+        let code = (*(*exception).ExceptionRecord).ExceptionCode;
+        if let Some(report) = super::write_report(&format!("unhandled exception 0x{code:08X}")) {
+            write(&report.with_extension("dmp"), exception);
+        }
+        0 // EXCEPTION_CONTINUE_SEARCH, Windows ends the process as usual
+    }
+
+    /// Write a minidump of this process, `exception` is null for panics
+    pub fn write(dump_path: &Path, exception: *const EXCEPTION_POINTERS) {
+        let Ok(file) = File::create(dump_path) else { return };
+
+        let exception_info = MINIDUMP_EXCEPTION_INFORMATION {
+            ThreadId: unsafe { GetCurrentThreadId() },
+            ExceptionPointers: exception.cast_mut(),
+            ClientPointers: 0,
+        };
+        unsafe {
+            MiniDumpWriteDump(
+                GetCurrentProcess(),
+                GetCurrentProcessId(),
+                file.as_raw_handle(),
+                MiniDumpWithThreadInfo,
+                if exception.is_null() {
+                    std::ptr::null()
+                } else {
+                    &exception_info
+                },
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+    }
+}
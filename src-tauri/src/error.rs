@@ -0,0 +1,66 @@
+//! Failures the user can do something about.
+//!
+//! Everything else is only logged, these also get shown in the main window and as a notification. They are normal
+//! `std::error::Error`s so they can be returned through `anyhow` and recognized later with `downcast_ref`.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::app::{AppEvent, EventManager};
+use crate::notifier::{self, Toast};
+use crate::state::RecorderHistory;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RecorderError {
+    /// starting or configuring the recorder failed (e.g. no encoder for the GPU)
+    ObsInit { message: String },
+    /// the game window didn't show up in time
+    WindowNotFound,
+    /// the recording stopped because there is no space left for the video
+    DiskFull,
+    /// the League client isn't running or doesn't answer
+    LcuUnavailable,
+    /// the game data wasn't available in time after the game ended
+    MetadataTimeout,
+    /// ffmpeg isn't installed and 'ffmpegPath' isn't set
+    FfmpegMissing,
+}
+
+impl std::error::Error for RecorderError {}
+
+impl Display for RecorderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ObsInit { message } => write!(f, "Recording failed: {message}"),
+            Self::WindowNotFound => write!(f, "Recording failed: the game window wasn't found"),
+            Self::DiskFull => write!(f, "Recording stopped: the disk is full"),
+            Self::LcuUnavailable => write!(f, "The League client isn't running or doesn't respond"),
+            Self::MetadataTimeout => write!(f, "The game data wasn't available in time, try reloading it later"),
+            Self::FfmpegMissing => write!(f, "FFmpeg wasn't found, install it or set 'ffmpegPath' in the settings"),
+        }
+    }
+}
+
+impl RecorderError {
+    /// The `RecorderError` that caused `error`, if there is one
+    pub fn find(error: &anyhow::Error) -> Option<&Self> {
+        // `downcast_ref` also finds errors that were added with `context()`
+        error
+            .downcast_ref::<Self>()
+            .or_else(|| error.chain().find_map(|cause| cause.downcast_ref::<Self>()))
+    }
+
+    /// Log the error and tell the user about it
+    pub fn report(&self, app_handle: &AppHandle) {
+        log::error!("{self}");
+        app_handle.state::<RecorderHistory>().push_error(self);
+        if let Err(e) = app_handle.send_event(AppEvent::Error { payload: self.clone() }) {
+            log::error!("failed to send error event: {e}");
+        }
+        notifier::show_toast(app_handle, Toast::Error(self));
+    }
+}
@@ -1,23 +1,103 @@
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 use std::{ffi::OsStr, path::Path};
 
 use notify::event::{ModifyKind, RenameMode};
 use notify::{EventKind, Watcher};
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
-use crate::app::{AppEvent, EventManager};
+use crate::app::{action, AppEvent, EventManager, SystemTrayManager};
 use crate::state::CurrentlyRecording;
-use crate::state::FileWatcher;
+use crate::state::{FileWatcher, RecordingIndex, SettingsFile, SettingsWatcher, SettingsWrapper};
+
+// editors often write a file in multiple steps (truncate, write, rename) => wait until it stops changing
+const SETTINGS_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Recordings that were added, removed or renamed (video ids), sent as `RecordingsDelta` event
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingsDelta {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// (old video id, new video id)
+    pub renamed: Vec<(String, String)>,
+}
+
+impl RecordingsDelta {
+    fn new(kind: EventKind, video_paths: &[PathBuf], renamed: Option<&(PathBuf, PathBuf)>) -> Self {
+        let video_id = |path: &PathBuf| path.to_string_lossy().into_owned();
+        // segments are part of the recording they continue
+        let recordings = video_paths.iter().filter(|path| !action::is_segment(path));
+
+        let mut delta = Self::default();
+        match kind {
+            EventKind::Create(_) => delta.added = recordings.map(video_id).collect(),
+            EventKind::Remove(_) => delta.removed = recordings.map(video_id).collect(),
+            // the first half of a rename on Windows, the second half contains both names
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {}
+            _ => {
+                let is_mp4 = |path: &Path| path.extension().and_then(OsStr::to_str) == Some("mp4");
+                match renamed {
+                    Some((from, to)) if is_mp4(from) && is_mp4(to) => {
+                        delta.renamed.push((video_id(from), video_id(to)))
+                    }
+                    Some((from, _)) if is_mp4(from) => delta.removed.push(video_id(from)),
+                    Some((_, to)) if is_mp4(to) => delta.added.push(video_id(to)),
+                    _ => {}
+                }
+
+                let is_renamed = |path: &PathBuf| renamed.is_some_and(|(from, to)| path == from || path == to);
+                for path in recordings.filter(|path| !is_renamed(path)) {
+                    match path.is_file() {
+                        true => delta.added.push(video_id(path)),
+                        false => delta.removed.push(video_id(path)),
+                    }
+                }
+            }
+        }
+        delta
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+}
 
 pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
     let watcher = notify::recommended_watcher({
         let app_handle = app_handle.clone();
+        // Windows reports a rename as two events (old name, then new name)
+        let mut rename_from: Option<PathBuf> = None;
         move |res: notify::Result<notify::Event>| {
             let Ok(event) = res else { return };
 
             let currently_recording: Option<PathBuf> = app_handle.state::<CurrentlyRecording>().get();
 
-            let mut contains_mp4_path: bool = false;
+            let renamed = match event.kind {
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                    rename_from = event.paths.first().cloned();
+                    None
+                }
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                    rename_from.take().zip(event.paths.first().cloned())
+                }
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match event.paths.as_slice() {
+                    [from, to] => Some((from.clone(), to.clone())),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let renamed = renamed.filter(|(_, to)| Some(to) != currently_recording.as_ref());
+            if let Some((from, to)) = &renamed {
+                follow_rename(&app_handle, from, to);
+            }
+
+            let mut mp4_paths: Vec<PathBuf> = Vec::new();
             let mut json_paths: Vec<String> = Vec::new();
 
             for path in event.paths {
@@ -27,7 +107,9 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
 
                 let ext = path.extension().and_then(OsStr::to_str);
 
-                contains_mp4_path |= ext == Some("mp4");
+                if ext == Some("mp4") {
+                    mp4_paths.push(path.clone());
+                }
 
                 if ext == Some("json") {
                     if let Some(video_id) = path.file_name().and_then(OsStr::to_str).map(str::to_owned) {
@@ -36,14 +118,17 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
                 }
             }
 
+            let contains_mp4_path = !mp4_paths.is_empty();
+            let recordings_changed = contains_mp4_path
+                && matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+                );
+
             match event.kind {
                 EventKind::Create(_) => {
-                    if contains_mp4_path {
-                        log::info!("filewatcher event contains .mp4 path: {contains_mp4_path}");
-                        if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
-                            log::warn!("filewatcher failed to send event: {e:?}");
-                        }
-                    }
+                    // recordings that reappear (e.g. restored from the recycle bin) can reference files that are gone
+                    reconcile_recordings(&app_handle, &mp4_paths);
 
                     if !json_paths.is_empty() {
                         log::info!("filewatcher event json paths: {json_paths:?}");
@@ -53,13 +138,6 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
                     }
                 }
                 EventKind::Remove(_) => {
-                    if contains_mp4_path {
-                        log::info!("filewatcher event contains .mp4 path: {contains_mp4_path}");
-                        if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
-                            log::warn!("filewatcher failed to send event: {e:?}");
-                        }
-                    }
-
                     if !json_paths.is_empty() {
                         log::info!("filewatcher event json paths: {json_paths:?}");
                         if let Err(e) = app_handle.send_event(AppEvent::MetadataChanged { payload: json_paths }) {
@@ -70,12 +148,7 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
                 EventKind::Modify(ModifyKind::Name(
                     RenameMode::To | RenameMode::Both | RenameMode::Any | RenameMode::Other,
                 )) => {
-                    if contains_mp4_path {
-                        log::info!("filewatcher event contains .mp4 path: {contains_mp4_path}");
-                        if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
-                            log::warn!("filewatcher failed to send event: {e:?}");
-                        }
-                    }
+                    reconcile_recordings(&app_handle, &mp4_paths);
 
                     if !json_paths.is_empty() {
                         log::info!("filewatcher event json paths: {json_paths:?}");
@@ -86,12 +159,38 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
                 }
                 _ => {}
             }
+
+            if recordings_changed {
+                log::info!("filewatcher event contains .mp4 path: {contains_mp4_path}");
+                if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
+                    log::warn!("filewatcher failed to send event: {e:?}");
+                }
+
+                // only the recordings that changed, for listeners that don't want to reload the whole list
+                let delta = RecordingsDelta::new(event.kind, &mp4_paths, renamed.as_ref());
+                if !delta.is_empty() {
+                    log::info!("filewatcher recordings delta: {delta:?}");
+                    if let Err(e) = app_handle.send_event(AppEvent::RecordingsDelta { payload: delta }) {
+                        log::warn!("filewatcher failed to send event: {e:?}");
+                    }
+                }
+
+                // keep the recent games in the tray menu up to date
+                app_handle.refresh_tray_menu();
+            }
         }
     });
 
     match watcher {
         Ok(mut watcher) => {
-            _ = watcher.watch(recordings_path, notify::RecursiveMode::NonRecursive);
+            // recordings can be in subfolders (see 'organizeBy')
+            _ = watcher.watch(recordings_path, notify::RecursiveMode::Recursive);
+            // external libraries (e.g. of other capture tools) often use a folder per game
+            for external_folder in app_handle.state::<SettingsWrapper>().external_folders() {
+                if let Err(e) = watcher.watch(&external_folder, notify::RecursiveMode::Recursive) {
+                    log::warn!("failed to watch external folder {}: {e}", external_folder.display());
+                }
+            }
 
             // store Watcher so it doesn't drop and stop watching
             // also drop old watcher
@@ -104,3 +203,86 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
         Err(e) => log::error!("failed to start filewatcher: {e}"),
     }
 }
+
+/// Reload the settings when settings.json gets changed by something other than LeagueRecord (e.g. a text editor)
+pub fn watch_settings(app_handle: &AppHandle, settings_file: &Path) {
+    let watcher = notify::recommended_watcher({
+        let app_handle = app_handle.clone();
+        let file_name = settings_file.file_name().map(OsStr::to_owned);
+        // only the last event within `SETTINGS_DEBOUNCE` reloads the settings
+        let generation = Arc::new(AtomicU64::new(0));
+        move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else { return };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                || !event.paths.iter().any(|path| path.file_name() == file_name.as_deref())
+            {
+                return;
+            }
+
+            let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+            let generation = generation.clone();
+            let app_handle = app_handle.clone();
+            thread::spawn(move || {
+                thread::sleep(SETTINGS_DEBOUNCE);
+                if generation.load(Ordering::SeqCst) == current {
+                    let settings_file = app_handle.state::<SettingsFile>();
+                    app_handle
+                        .state::<SettingsWrapper>()
+                        .reload_if_changed(settings_file.get(), &app_handle);
+                }
+            });
+        }
+    });
+
+    match watcher {
+        Ok(mut watcher) => {
+            // watch the folder instead of the file since editors often replace the file instead of writing to it
+            let folder = settings_file.parent().unwrap_or(settings_file);
+            if let Err(e) = watcher.watch(folder, notify::RecursiveMode::NonRecursive) {
+                log::error!("failed to watch settings file: {e}");
+                return;
+            }
+            app_handle.manage(SettingsWatcher::new(watcher));
+        }
+        Err(e) => log::error!("failed to start settings filewatcher: {e}"),
+    }
+}
+
+/// Keep the metadata, highlights and thumbnail of a recording that got renamed outside of LeagueRecord (e.g. in the
+/// Explorer) with the video instead of orphaning them
+fn follow_rename(app_handle: &AppHandle, from: &Path, to: &Path) {
+    let is_mp4 = |path: &Path| path.extension().and_then(OsStr::to_str) == Some("mp4");
+    // only plain renames, moving recordings to another folder is handled by `action::move_recording`
+    if !is_mp4(from)
+        || !is_mp4(to)
+        || from.parent() != to.parent()
+        || app_handle.state::<SettingsWrapper>().is_external(to)
+    {
+        return;
+    }
+
+    match action::follow_rename(from, to) {
+        Ok(true) => {
+            log::info!("followed rename of {} to {}", from.display(), to.display());
+            app_handle.state::<RecordingIndex>().rename(from, to.to_path_buf());
+
+            let payload = (from.to_string_lossy().into_owned(), to.to_string_lossy().into_owned());
+            if let Err(e) = app_handle.send_event(AppEvent::RecordingRenamed { payload }) {
+                log::warn!("filewatcher failed to send event: {e:?}");
+            }
+        }
+        Ok(false) => {}
+        Err(e) => log::warn!("failed to follow rename of {}: {e}", from.display()),
+    }
+}
+
+fn reconcile_recordings(app_handle: &AppHandle, video_paths: &[PathBuf]) {
+    let settings = app_handle.state::<SettingsWrapper>();
+    for video_path in video_paths.iter().filter(|path| !settings.is_external(path)) {
+        match action::reconcile_recording(video_path) {
+            Ok(true) => log::info!("fixed stale metadata of {}", video_path.display()),
+            Ok(false) => {}
+            Err(e) => log::debug!("failed to reconcile {}: {e}", video_path.display()),
+        }
+    }
+}
@@ -10,21 +10,78 @@ fn generate_bindings() -> anyhow::Result<()> {
             commands::set_marker_flags,
             commands::get_recordings_path,
             commands::get_recordings_size,
+            commands::get_storage_report,
+            commands::run_first_time_checks,
+            commands::run_encoder_benchmark,
+            commands::check_for_updates,
+            commands::estimate_file_size,
+            commands::get_last_pipeline_timings,
+            commands::get_recent_logs,
+            commands::get_recorder_state,
+            commands::start_recording,
+            commands::stop_recording,
+            commands::add_highlight,
+            commands::get_overlay_status,
+            commands::take_pending_recording,
             commands::get_recordings_list,
+            commands::get_recordings_page,
+            commands::get_recordings_by_id,
+            commands::get_accounts,
+            commands::get_premade_stats,
+            commands::get_patch_stats,
+            commands::get_kill_heatmap,
+            commands::get_matchup_stats,
+            commands::get_review_trend,
+            commands::reindex_library,
+            commands::preview_cleanup,
             commands::open_recordings_folder,
             commands::delete_video,
+            commands::undo_delete,
+            commands::bulk_delete,
+            commands::bulk_favorite,
+            commands::bulk_move_to_folder,
+            commands::bulk_export,
+            commands::migrate_all_metadata,
+            commands::get_jobs,
+            commands::enqueue_job,
+            commands::cancel_job,
             commands::rename_video,
             commands::get_metadata,
+            commands::get_markers,
+            commands::export_timeline,
+            commands::import_recording,
+            commands::export_recording,
+            commands::import_recording_bundle,
+            commands::generate_support_bundle,
+            commands::export_metadata_trace,
+            commands::import_riot_metadata,
+            commands::download_replay,
+            commands::open_replay_at,
+            commands::get_postgame_screenshot,
             commands::toggle_favorite,
+            commands::set_protected,
+            commands::set_comments,
             commands::confirm_delete,
             commands::disable_confirm_delete,
             commands::get_settings,
             commands::save_settings,
+            commands::validate_settings,
+            commands::export_settings,
+            commands::import_settings,
+            commands::reset_settings,
+            commands::validate_hotkey,
+            commands::begin_hotkey_capture,
             commands::pick_recordings_folder,
             commands::create_clip,
             commands::pick_ffmpeg_path,
+            commands::export_with_preset,
+            commands::start_youtube_login,
+            commands::finish_youtube_login,
+            commands::upload_clip_youtube,
+            commands::get_pending_telemetry,
             commands::clear_cache,
             commands::download_image,
+            commands::get_static_data,
             commands::save_scoreboard_cache,
             commands::load_scoreboard_cache
         ])
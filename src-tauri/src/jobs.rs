@@ -0,0 +1,367 @@
+//! Background queue for post-processing jobs (thumbnails, highlight reels, remuxing, backups and uploads).
+//!
+//! Jobs run one after another on a worker thread with background priority (lower CPU and I/O priority), the ffmpeg
+//! processes they start run with below normal priority. The queue is paused while the `GameListener` records a game
+//! so jobs never compete with the game or the recorder. The queue is persisted in the app config folder so jobs that
+//! didn't run before the app was closed run on the next start.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Manager};
+
+use crate::app::{action, AppEvent, EventManager, RecordingManager};
+use crate::backup;
+use crate::recorder::MetadataFile;
+use crate::state::{CurrentlyRecording, SettingsWrapper, Shutdown};
+use crate::uploader::{self, Privacy};
+
+const QUEUE_FILE: &str = "job_queue.json";
+// finished jobs are kept for `get_jobs` until there are more than this many
+const MAX_FINISHED_JOBS: usize = 50;
+// how often the worker checks whether the queue got resumed before running the next job
+const RECORDING_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    /// post-game screenshot (see 'postgameScreenshot')
+    Thumbnail,
+    /// video of all highlights of the recording in the clips folder
+    HighlightReel,
+    /// rebuild the container of the video (e.g. after a crash)
+    Remux,
+    Backup,
+    /// game data that wasn't collected yet when the app was closed
+    Metadata,
+    /// upload of a clip to YouTube
+    Upload {
+        title: String,
+        privacy: Privacy,
+    },
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobState {
+    fn is_finished(&self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed(_) | JobState::Cancelled)
+    }
+}
+
+/// Sent as `JobProgress` event every time the state of a job changes
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: u32,
+    pub kind: JobKind,
+    pub video_id: String,
+    /// stable ID of the recording to find it again if it got renamed before the job ran
+    #[serde(default)]
+    recording_id: Option<String>,
+    pub state: JobState,
+    /// how long the job ran in seconds, set once it is finished
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+}
+
+pub struct Jobs {
+    queue: Mutex<Vec<Job>>,
+    queue_file: PathBuf,
+    running: AtomicBool,
+    // set while a game is being recorded
+    paused: AtomicBool,
+}
+
+impl Jobs {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let queue_file = app_handle.path().app_config_dir()?.join(QUEUE_FILE);
+        let mut queue: Vec<Job> = fs::read(&queue_file)
+            .ok()
+            .and_then(|queue| serde_json::from_slice(&queue).ok())
+            .unwrap_or_default();
+
+        // jobs that were running when the app was closed start over
+        queue.retain(|job| !job.state.is_finished());
+        for job in &mut queue {
+            job.state = JobState::Queued;
+        }
+
+        Ok(Self {
+            queue: Mutex::new(queue),
+            queue_file,
+            running: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+        })
+    }
+
+    fn save(&self, queue: &[Job]) {
+        let pending: Vec<_> = queue.iter().filter(|job| !job.state.is_finished()).collect();
+        let result = serde_json::to_vec_pretty(&pending)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&self.queue_file, json));
+        if let Err(e) = result {
+            log::warn!("failed to save job queue: {e}");
+        }
+    }
+
+    pub fn get(&self) -> Vec<Job> {
+        self.queue.lock().unwrap().clone()
+    }
+
+    /// Cancel a job that hasn't started yet. Returns false if there is no such job or it is already running.
+    pub fn cancel(&self, app_handle: &AppHandle, id: u32) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        let Some(job) = queue
+            .iter_mut()
+            .find(|job| job.id == id && job.state == JobState::Queued)
+        else {
+            return false;
+        };
+
+        job.state = JobState::Cancelled;
+        send_progress(app_handle, job);
+        self.save(&queue);
+        true
+    }
+
+    /// Mark the next queued job as running
+    fn start_next(&self, app_handle: &AppHandle) -> Option<Job> {
+        let mut queue = self.queue.lock().unwrap();
+        let job = queue.iter_mut().find(|job| job.state == JobState::Queued)?;
+        job.state = JobState::Running;
+        let job = job.clone();
+
+        send_progress(app_handle, &job);
+        self.save(&queue);
+        Some(job)
+    }
+
+    fn finish(&self, app_handle: &AppHandle, id: u32, state: JobState, duration: Duration) {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(job) = queue.iter_mut().find(|job| job.id == id) {
+            job.state = state;
+            job.duration_secs = Some(duration.as_secs_f64());
+            send_progress(app_handle, job);
+        }
+
+        let finished = queue.iter().filter(|job| job.state.is_finished()).count();
+        if finished > MAX_FINISHED_JOBS {
+            let mut excess = finished - MAX_FINISHED_JOBS;
+            queue.retain(|job| {
+                let remove = excess > 0 && job.state.is_finished();
+                if remove {
+                    excess -= 1;
+                }
+                !remove
+            });
+        }
+        self.save(&queue);
+    }
+
+    fn has_queued(&self) -> bool {
+        self.queue
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|job| job.state == JobState::Queued)
+    }
+}
+
+/// Queue a job for the recording and start the worker. Returns the ID of the job (or of the same job that is already
+/// waiting in the queue).
+pub fn enqueue(app_handle: &AppHandle, kind: JobKind, video_path: &Path) -> Option<u32> {
+    let Some(jobs) = app_handle.try_state::<Jobs>() else {
+        log::error!(
+            "job queue isn't initialized, dropped {kind:?} job for {}",
+            video_path.display()
+        );
+        return None;
+    };
+    let video_id = video_path.to_str()?.to_owned();
+
+    let id = {
+        let mut queue = jobs.queue.lock().unwrap();
+        if let Some(job) = queue
+            .iter()
+            .find(|job| job.state == JobState::Queued && job.kind == kind && job.video_id == video_id)
+        {
+            return Some(job.id);
+        }
+
+        let id = queue.iter().map(|job| job.id + 1).max().unwrap_or(1);
+        let recording_id = action::get_recording_metadata(video_path, false)
+            .ok()
+            .map(|metadata| metadata.id().to_owned());
+        let job = Job {
+            id,
+            kind,
+            video_id,
+            recording_id,
+            state: JobState::Queued,
+            duration_secs: None,
+        };
+        send_progress(app_handle, &job);
+        queue.push(job);
+        jobs.save(&queue);
+        id
+    };
+
+    resume(app_handle);
+    Some(id)
+}
+
+/// Pause the queue while a game is being recorded and resume it afterwards. A job that is already running finishes.
+pub fn set_paused(app_handle: &AppHandle, paused: bool) {
+    let Some(jobs) = app_handle.try_state::<Jobs>() else { return };
+    if jobs.paused.swap(paused, Ordering::SeqCst) == paused {
+        return;
+    }
+
+    log::info!("job queue {}", if paused { "paused" } else { "resumed" });
+    if !paused {
+        resume(app_handle);
+    }
+}
+
+/// Start the worker if there are queued jobs. Does nothing if the worker is already running or the app is shutting
+/// down (the queued jobs run on the next start).
+pub fn resume(app_handle: &AppHandle) {
+    let jobs = app_handle.state::<Jobs>();
+    if app_handle.state::<Shutdown>().get() || !jobs.has_queued() || jobs.running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        set_background_priority();
+
+        let jobs = app_handle.state::<Jobs>();
+        loop {
+            while jobs.paused.load(Ordering::SeqCst) || app_handle.state::<CurrentlyRecording>().get().is_some() {
+                thread::sleep(RECORDING_POLL_INTERVAL);
+            }
+
+            if app_handle.state::<Shutdown>().get() {
+                break;
+            }
+            let Some(job) = jobs.start_next(&app_handle) else { break };
+            log::info!("running {:?} job for {}", job.kind, job.video_id);
+            let start = Instant::now();
+            let state = match run(&app_handle, &job) {
+                Ok(()) => JobState::Done,
+                Err(e) => {
+                    log::warn!("{:?} job for {} failed: {e}", job.kind, job.video_id);
+                    JobState::Failed(e.to_string())
+                }
+            };
+            let duration = start.elapsed();
+            log::info!(
+                "{:?} job for {} took {:.1}s",
+                job.kind,
+                job.video_id,
+                duration.as_secs_f64()
+            );
+            jobs.finish(&app_handle, job.id, state, duration);
+        }
+
+        jobs.running.store(false, Ordering::SeqCst);
+        // a job could have been queued after the loop found the queue empty
+        resume(&app_handle);
+    });
+}
+
+fn run(app_handle: &AppHandle, job: &Job) -> Result<()> {
+    let mut video_path = PathBuf::from(&job.video_id);
+    if !video_path.is_file() {
+        if let Some(recording_id) = &job.recording_id {
+            video_path = app_handle.resolve_video_id(recording_id);
+        }
+    }
+    if !video_path.is_file() {
+        bail!("no such video");
+    }
+
+    let ffmpeg = app_handle
+        .state::<SettingsWrapper>()
+        .ffmpeg_path()
+        .unwrap_or_else(|| "ffmpeg".to_string());
+    match &job.kind {
+        JobKind::Thumbnail => {
+            action::create_postgame_screenshot(&video_path, &ffmpeg)?;
+            send_metadata_changed(app_handle, &video_path);
+        }
+        JobKind::HighlightReel => {
+            let clips_path = app_handle.state::<SettingsWrapper>().get_clips_path();
+            let reel_path = action::create_highlight_reel(&video_path, &clips_path, &ffmpeg)?;
+            log::info!("saved highlight reel to {}", reel_path.display());
+            if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
+                log::warn!("failed to send event: {e}");
+            }
+        }
+        JobKind::Remux => {
+            action::remux(&video_path, &ffmpeg)?;
+            send_metadata_changed(app_handle, &video_path);
+        }
+        JobKind::Backup => async_runtime::block_on(backup::run(app_handle, &video_path))?,
+        JobKind::Metadata => {
+            let MetadataFile::Metadata(game_metadata) = action::get_recording_metadata(&video_path, true)? else {
+                bail!("recording has no game to collect the data of");
+            };
+            // names with tokens like {champion} (see 'filenameFormat') can only be completed now
+            let safe_titles = app_handle.state::<SettingsWrapper>().safe_titles();
+            let video_path = action::apply_filename_tokens(&video_path, &game_metadata, safe_titles)?;
+            send_metadata_changed(app_handle, &video_path);
+        }
+        JobKind::Upload { title, privacy } => {
+            let url = async_runtime::block_on(uploader::upload_clip(app_handle, &video_path, title, *privacy))?;
+            log::info!("uploaded {} to {url}", video_path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Lower the CPU and I/O priority of the current thread so the jobs don't slow down the game or the recorder
+fn set_background_priority() {
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::System::Threading::{
+            GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN,
+        };
+
+        if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN) } == 0 {
+            log::warn!("failed to lower the priority of the job worker");
+        }
+    }
+}
+
+fn send_metadata_changed(app_handle: &AppHandle, video_path: &Path) {
+    let Some(video_id) = video_path.file_name().and_then(|name| name.to_str()) else { return };
+    let payload = vec![video_id.to_owned()];
+    if let Err(e) = app_handle.send_event(AppEvent::MetadataChanged { payload }) {
+        log::warn!("failed to send event: {e}");
+    }
+}
+
+fn send_progress(app_handle: &AppHandle, job: &Job) {
+    let payload = job.clone();
+    if let Err(e) = app_handle.send_event(AppEvent::JobProgress { payload }) {
+        log::warn!("failed to send job progress: {e}");
+    }
+}
@@ -4,17 +4,33 @@
 #![cfg_attr(all(not(debug_assertions), target_os = "windows"), windows_subsystem = "windows")]
 
 mod app;
+mod backup;
 mod commands;
 mod constants;
+mod control;
+mod crash_reporter;
+mod error;
 mod filewatcher;
 mod generate_bindings;
+mod jobs;
+mod notifier;
+mod onboarding;
+mod power;
+mod privacy;
 mod recorder;
 mod state;
+mod static_data;
+mod telemetry;
+mod updater;
+mod uploader;
 mod util;
 
 fn main() {
     use app::{AppManager, AppWindow, WindowManager};
-    use state::{CurrentlyRecording, Shutdown, TrayState, WindowState};
+    use state::{
+        CurrentlyRecording, DeletedRecordings, LastPipelineTimings, LogBuffer, OverlayState, RecorderHistory,
+        RecorderStatus, RecordingIndex, Shutdown, TrayState, WindowState,
+    };
     use tauri::Manager;
 
     #[cfg(feature = "tokio-console")]
@@ -26,16 +42,30 @@ fn main() {
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
-        .plugin(tauri_plugin_single_instance::init(|app, _, _| {
-            app.open_window(AppWindow::Main)
+        .plugin(tauri_plugin_single_instance::init(|app, args, _| {
+            // only one instance runs at a time (two would fight over the recorder)
+            // a second instance started with a command (e.g. '--command stop' or '--stop') only forwards the command
+            match control::ControlCommand::from_args(&args) {
+                Some(command) => command.execute(app),
+                None => app.open_window(AppWindow::Main),
+            }
         }))
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
-        // .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(WindowState::default())
         .manage(CurrentlyRecording::default())
+        .manage(DeletedRecordings::default())
+        .manage(RecordingIndex::default())
+        .manage(LastPipelineTimings::default())
+        .manage(LogBuffer::default())
+        .manage(RecorderHistory::default())
+        .manage(RecorderStatus::default())
+        .manage(OverlayState::default())
         .manage(TrayState::default())
+        .manage(updater::PendingUpdate::default())
         //.manage(windows_key_listener::KeyListener::new())
         .manage(Shutdown::default())
         .invoke_handler(tauri::generate_handler![
@@ -43,22 +73,79 @@ fn main() {
             commands::set_marker_flags,
             commands::get_recordings_path,
             commands::get_recordings_size,
+            commands::get_storage_report,
+            commands::run_first_time_checks,
+            commands::run_encoder_benchmark,
+            commands::check_for_updates,
+            commands::estimate_file_size,
+            commands::get_last_pipeline_timings,
+            commands::get_recent_logs,
+            commands::get_recorder_state,
+            commands::start_recording,
+            commands::stop_recording,
+            commands::add_highlight,
+            commands::get_overlay_status,
+            commands::take_pending_recording,
             commands::get_recordings_list,
+            commands::get_recordings_page,
+            commands::get_recordings_by_id,
+            commands::get_accounts,
+            commands::get_premade_stats,
+            commands::get_patch_stats,
+            commands::get_kill_heatmap,
+            commands::get_matchup_stats,
+            commands::get_review_trend,
+            commands::reindex_library,
+            commands::preview_cleanup,
             commands::open_recordings_folder,
             commands::delete_video,
+            commands::undo_delete,
+            commands::bulk_delete,
+            commands::bulk_favorite,
+            commands::bulk_move_to_folder,
+            commands::bulk_export,
+            commands::migrate_all_metadata,
+            commands::get_jobs,
+            commands::enqueue_job,
+            commands::cancel_job,
             commands::rename_video,
             commands::get_metadata,
+            commands::get_markers,
+            commands::export_timeline,
+            commands::import_recording,
+            commands::export_recording,
+            commands::import_recording_bundle,
+            commands::generate_support_bundle,
+            commands::export_metadata_trace,
+            commands::import_riot_metadata,
+            commands::download_replay,
+            commands::open_replay_at,
+            commands::get_postgame_screenshot,
             commands::toggle_favorite,
+            commands::set_protected,
+            commands::set_comments,
             commands::confirm_delete,
             commands::disable_confirm_delete,
             commands::get_settings,
             commands::save_settings,
+            commands::validate_settings,
+            commands::export_settings,
+            commands::import_settings,
+            commands::reset_settings,
+            commands::validate_hotkey,
+            commands::begin_hotkey_capture,
             commands::pick_recordings_folder,
             commands::create_clip,
             commands::pick_clips_folder,
             commands::pick_ffmpeg_path,
+            commands::export_with_preset,
+            commands::start_youtube_login,
+            commands::finish_youtube_login,
+            commands::upload_clip_youtube,
+            commands::get_pending_telemetry,
             commands::clear_cache,
             commands::download_image,
+            commands::get_static_data,
             commands::save_scoreboard_cache,
             commands::load_scoreboard_cache
         ])
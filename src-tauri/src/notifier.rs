@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use reqwest::multipart::{Form, Part};
+use serde_json::{json, Value};
+use tauri::{async_runtime, AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::app::action;
+use crate::constants::APP_NAME;
+use crate::error::RecorderError;
+use crate::recorder::{GameMetadata, HighlightCategory, Markers, MetadataFile};
+use crate::state::{DiscordWebhook, SettingsWrapper};
+
+// Discord rejects attachments above 8MB for webhooks of servers without boosts
+const MAX_ATTACHMENT_SIZE: u64 = 8 * 1024 * 1024;
+// the highlight clip starts a bit before the highlight hotkey was pressed since it usually gets pressed after the play
+const CLIP_LEAD_TIME: f64 = 20.0;
+const CLIP_DURATION: f64 = 25.0;
+
+const COLOR_WIN: u32 = 0x2e_cc_71;
+const COLOR_LOSS: u32 = 0xe7_4c_3c;
+const COLOR_REMAKE: u32 = 0x95_a5_a6;
+
+pub enum Toast<'a> {
+    RecordingStarted,
+    /// file name of the finished recording
+    RecordingFinished(&'a str),
+    /// category and ingame time of the highlight in seconds
+    HighlightSaved(HighlightCategory, f64),
+    /// category and ingame time of the highlight that got removed with the 'removeHighlightHotkey'
+    HighlightRemoved(HighlightCategory, f64),
+    /// always shown, the user has to do something about it
+    Error(&'a RecorderError),
+}
+
+/// Show a Windows toast notification if it is enabled in the 'notifications' setting.
+/// The tray icon is easy to miss while playing in fullscreen.
+pub fn show_toast(app_handle: &AppHandle, toast: Toast) {
+    let notifications = app_handle.state::<SettingsWrapper>().notifications();
+    let body = match toast {
+        Toast::RecordingStarted if notifications.recording_started => "Recording started".to_owned(),
+        Toast::RecordingFinished(video_name) if notifications.recording_finished => {
+            format!("Recording saved: {video_name}")
+        }
+        Toast::HighlightSaved(category, game_time) if notifications.highlight_saved => {
+            format!("{} saved at {}", category_name(category), format_game_time(game_time))
+        }
+        Toast::HighlightRemoved(category, game_time) if notifications.highlight_saved => {
+            format!("{} at {} removed", category_name(category), format_game_time(game_time))
+        }
+        Toast::Error(error) => error.to_string(),
+        _ => return,
+    };
+
+    if let Err(e) = app_handle.notification().builder().title(APP_NAME).body(body).show() {
+        log::warn!("failed to show notification: {e}");
+    }
+}
+
+fn category_name(category: HighlightCategory) -> &'static str {
+    match category {
+        HighlightCategory::Highlight => "Highlight",
+        HighlightCategory::Bookmark => "Bookmark",
+    }
+}
+
+fn format_game_time(game_time: f64) -> String {
+    let seconds = game_time as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Send a notification about the finished game of a recording to all configured notifiers.
+/// Only recordings with game data get notifications.
+pub async fn notify_game_finished(app_handle: &AppHandle, video_path: &Path) {
+    let Some(webhook) = app_handle.state::<SettingsWrapper>().discord_webhook() else { return };
+
+    let metadata = match action::get_recording_metadata(video_path, false) {
+        Ok(MetadataFile::Metadata(metadata)) => metadata,
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("failed to read metadata for notification: {e}");
+            return;
+        }
+    };
+
+    if webhook.only_ranked && !metadata.queue.is_ranked {
+        return;
+    }
+    if webhook.only_wins && !is_win(&metadata) {
+        return;
+    }
+
+    match send_discord_webhook(app_handle, &webhook, video_path, &metadata).await {
+        Ok(()) => log::info!("sent Discord notification for {}", metadata.match_id),
+        Err(e) => log::warn!("failed to send Discord notification: {e}"),
+    }
+}
+
+fn is_win(metadata: &GameMetadata) -> bool {
+    metadata.stats.win && !metadata.stats.game_ended_in_early_surrender
+}
+
+fn discord_embed(app_handle: &AppHandle, metadata: &GameMetadata) -> Value {
+    let stats = &metadata.stats;
+    let (result, color) = if stats.game_ended_in_early_surrender {
+        ("Remake", COLOR_REMAKE)
+    } else if stats.win {
+        ("Victory", COLOR_WIN)
+    } else {
+        ("Defeat", COLOR_LOSS)
+    };
+
+    let mut fields = vec![
+        json!({ "name": "KDA", "value": format!("{}/{}/{}", stats.kills, stats.deaths, stats.assists), "inline": true }),
+        json!({ "name": "Queue", "value": metadata.queue.name, "inline": true }),
+    ];
+    if let Some(lp_diff) = metadata.lp_diff {
+        fields.push(json!({ "name": "LP", "value": format!("{lp_diff:+}"), "inline": true }));
+    }
+
+    let mut embed = json!({
+        "title": format!("{result} - {}", metadata.champion_name),
+        "description": format!("{}#{}", metadata.player.game_name, metadata.player.tag_line),
+        "color": color,
+        "fields": fields,
+    });
+
+    if let Some(champion_id) = metadata.champion_id() {
+        embed["thumbnail"] =
+            json!({ "url": format!("https://cdn.communitydragon.org/latest/champion/{champion_id}/tile") });
+    }
+    if let Some(base_url) = app_handle.state::<SettingsWrapper>().match_history_base_url() {
+        embed["url"] = json!(format!("{}/{}", base_url.trim_end_matches('/'), metadata.match_id));
+    }
+
+    embed
+}
+
+async fn send_discord_webhook(
+    app_handle: &AppHandle,
+    webhook: &DiscordWebhook,
+    video_path: &Path,
+    metadata: &GameMetadata,
+) -> Result<()> {
+    let payload = json!({ "embeds": [discord_embed(app_handle, metadata)] });
+
+    let highlight = Markers::from_metadata(metadata)
+        .highlights
+        .into_iter()
+        .rev()
+        .find(|highlight| highlight.category == HighlightCategory::Highlight)
+        .map(|highlight| highlight.position);
+    let clip = match highlight {
+        Some(highlight) if webhook.upload_highlight_clip => {
+            let ffmpeg = app_handle
+                .state::<SettingsWrapper>()
+                .ffmpeg_path()
+                .unwrap_or_else(|| "ffmpeg".to_string());
+            let video_path = video_path.to_path_buf();
+            async_runtime::spawn_blocking(move || highlight_clip(&video_path, highlight.video_time, &ffmpeg))
+                .await
+                .ok()
+                .flatten()
+        }
+        _ => None,
+    };
+
+    let client = reqwest::Client::new();
+    let request = match &clip {
+        Some(clip) => {
+            let form = Form::new()
+                .text("payload_json", payload.to_string())
+                .part("files[0]", Part::bytes(fs::read(clip)?).file_name("highlight.mp4"));
+            client.post(&webhook.url).multipart(form)
+        }
+        None => client.post(&webhook.url).json(&payload),
+    };
+    let response = request.send().await;
+
+    if let Some(clip) = clip {
+        let _ = fs::remove_file(clip);
+    }
+
+    let response = response?;
+    if !response.status().is_success() {
+        bail!("webhook request failed: {}", response.status());
+    }
+    Ok(())
+}
+
+/// Cut a clip around a highlight at `video_time` into the temp directory.
+/// Returns None if the clip is too large to be uploaded.
+fn highlight_clip(video_path: &Path, video_time: f64, ffmpeg: &str) -> Option<PathBuf> {
+    let file_name = video_path.file_stem()?.to_string_lossy();
+    let clip_path = std::env::temp_dir().join(format!("{file_name}_highlight.mp4"));
+
+    let start = (video_time - CLIP_LEAD_TIME).max(0.0);
+    if let Err(e) = action::extract_clip(video_path, &clip_path, start, CLIP_DURATION, true, ffmpeg) {
+        log::warn!("failed to create highlight clip: {e}");
+        return None;
+    }
+
+    let size = fs::metadata(&clip_path).map(|m| m.len()).unwrap_or(u64::MAX);
+    if size > MAX_ATTACHMENT_SIZE {
+        log::info!("highlight clip too large for Discord ({size} bytes)");
+        let _ = fs::remove_file(&clip_path);
+        return None;
+    }
+
+    Some(clip_path)
+}
@@ -0,0 +1,391 @@
+//! Checks for everything a recording depends on, run by the frontend when LeagueRecord is started for the first time
+//! (or on demand) to guide new users instead of failing silently on their first recording.
+//!
+//! The encoder benchmark records a few seconds with every encoder of the GPU so users can pick one that keeps up with
+//! their resolution and framerate.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use libobs_recorder::settings::{
+    AudioSource, Encoder, RecorderSettings, RecorderStats, Resolution, StdResolution, Window,
+};
+use libobs_recorder::Recorder;
+use serde::Serialize;
+use shaco::rest::LcuRestClient;
+use tauri::path::BaseDirectory;
+use tauri::{async_runtime, AppHandle, Manager};
+
+use crate::recorder::{PrewarmedRecorder, RECORDER_EXECUTABLE};
+use crate::state::{CurrentlyRecording, SettingsWrapper};
+use crate::util;
+
+const GAMEFLOW_PHASE: &str = "/lol-gameflow/v1/gameflow-phase";
+// a 30 minute game at the default settings takes up ~2GB
+const MIN_FREE_DISK_SPACE_GB: f64 = 2.0;
+const RECOMMENDED_FREE_DISK_SPACE_GB: f64 = 20.0;
+// a window that doesn't exist, the benchmark encodes black frames
+const BENCHMARK_WINDOW: &str = "LeagueRecord Encoder Benchmark";
+// the first frames are slower while the encoder starts up
+const BENCHMARK_WARM_UP: Duration = Duration::from_secs(1);
+const BENCHMARK_DURATION: Duration = Duration::from_secs(5);
+// share of the frames that can get lost before an encoder counts as too slow
+const MAX_LAG_RATIO: f64 = 0.01;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Ok,
+    /// recording works but something is missing or could be better
+    Warning,
+    /// recording won't work until this is fixed
+    Failed,
+    /// couldn't be checked right now (e.g. while a game is being recorded)
+    Skipped,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check {
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl Check {
+    fn new(status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstTimeReport {
+    /// the recorder process and libobs start
+    pub obs_runtime: Check,
+    /// a hardware encoder is available for the GPU
+    pub encoder: Check,
+    pub available_encoders: Vec<Encoder>,
+    /// free space on the drive of the recordings folder
+    pub disk_space: Check,
+    /// FFmpeg is needed for clips, highlight reels, thumbnails and repairing recordings
+    pub ffmpeg: Check,
+    /// the system autostart matches the 'autostart' setting
+    pub autostart: Check,
+    /// the League client is running and answers requests
+    pub lcu: Check,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderBenchmark {
+    pub encoder: Encoder,
+    /// frames per second that got encoded
+    pub fps: f64,
+    /// share (0.0 - 1.0) of the frames that got lost because rendering or encoding didn't keep up
+    pub lag_ratio: f64,
+    /// usage (0.0 - 1.0) of all CPU cores during the benchmark, including other programs
+    pub cpu_usage: Option<f64>,
+    /// average time the GPU took to render a frame
+    pub frame_time_ms: f64,
+    /// fast enough for the framerate of the settings
+    pub keeps_up: bool,
+    /// why the encoder couldn't be benchmarked
+    pub error: Option<String>,
+}
+
+impl EncoderBenchmark {
+    fn failed(encoder: Encoder, error: String) -> Self {
+        Self {
+            encoder,
+            fps: 0.0,
+            lag_ratio: 1.0,
+            cpu_usage: None,
+            frame_time_ms: 0.0,
+            keeps_up: false,
+            error: Some(error),
+        }
+    }
+}
+
+pub async fn run_first_time_checks(app_handle: &AppHandle) -> FirstTimeReport {
+    let (obs_runtime, encoder, available_encoders) = async_runtime::spawn_blocking({
+        let app_handle = app_handle.clone();
+        move || check_recorder(&app_handle)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        let check = Check::new(CheckStatus::Failed, e.to_string());
+        (check.clone(), check, Vec::new())
+    });
+
+    let settings = app_handle.state::<SettingsWrapper>();
+    let ffmpeg = settings.ffmpeg_path().unwrap_or_else(|| "ffmpeg".to_string());
+    let report = FirstTimeReport {
+        obs_runtime,
+        encoder,
+        available_encoders,
+        disk_space: check_disk_space(&settings.get_recordings_path()),
+        ffmpeg: check_ffmpeg(&ffmpeg),
+        autostart: check_autostart(app_handle, settings.autostart()),
+        lcu: check_lcu().await,
+    };
+    log::info!("first time checks: {report:?}");
+    report
+}
+
+/// Why no recorder can be started right now, there can only be one libobs instance at a time
+fn recorder_in_use(app_handle: &AppHandle) -> Option<&'static str> {
+    if app_handle.state::<CurrentlyRecording>().get().is_some() {
+        Some("a game is being recorded")
+    } else if app_handle.state::<PrewarmedRecorder>().is_running() {
+        Some("the recorder is already started for the next game")
+    } else {
+        None
+    }
+}
+
+/// Start a recorder process like a recording would and ask it for the encoders of the GPU
+fn check_recorder(app_handle: &AppHandle) -> (Check, Check, Vec<Encoder>) {
+    if let Some(reason) = recorder_in_use(app_handle) {
+        let skipped = Check::new(CheckStatus::Skipped, reason);
+        return (skipped.clone(), skipped, Vec::new());
+    }
+
+    let not_checked = || Check::new(CheckStatus::Skipped, "the recorder didn't start");
+    let executable = match app_handle
+        .path()
+        .resolve(RECORDER_EXECUTABLE, BaseDirectory::Executable)
+    {
+        Ok(executable) if executable.is_file() => executable,
+        _ => {
+            let failed = Check::new(CheckStatus::Failed, format!("{RECORDER_EXECUTABLE} is missing"));
+            return (failed, not_checked(), Vec::new());
+        }
+    };
+    let mut recorder = match Recorder::new_with_paths(Some(executable), None, None, None) {
+        Ok(recorder) => recorder,
+        Err(e) => {
+            let failed = Check::new(CheckStatus::Failed, format!("failed to start the recorder: {e}"));
+            return (failed, not_checked(), Vec::new());
+        }
+    };
+
+    let adapter = recorder
+        .adapter_info()
+        .map(|adapter| adapter.name().to_owned())
+        .unwrap_or_else(|_| "unknown GPU".to_string());
+    let available_encoders = recorder.available_encoders().unwrap_or_default();
+    if let Err(e) = recorder.shutdown() {
+        log::warn!("failed to shut down the recorder: {e}");
+    }
+
+    let hardware_encoder = available_encoders.iter().find(|encoder| **encoder != Encoder::OBS_X264);
+    let encoder = match hardware_encoder {
+        Some(encoder) => Check::new(CheckStatus::Ok, format!("{encoder:?} on {adapter}")),
+        None if available_encoders.is_empty() => {
+            Check::new(CheckStatus::Failed, format!("no video encoder available on {adapter}"))
+        }
+        None => Check::new(
+            CheckStatus::Warning,
+            format!("no hardware encoder available on {adapter}, software encoding can lower the game's FPS"),
+        ),
+    };
+    let obs_runtime = Check::new(CheckStatus::Ok, "recorder started");
+    (obs_runtime, encoder, available_encoders)
+}
+
+/// Record a few seconds with every encoder of the GPU at the output resolution and framerate of the settings
+///
+/// Nothing is captured, so the numbers are an upper bound for a real recording.
+pub async fn run_encoder_benchmark(app_handle: &AppHandle) -> Result<Vec<EncoderBenchmark>> {
+    let app_handle = app_handle.clone();
+    async_runtime::spawn_blocking(move || benchmark_encoders(&app_handle)).await?
+}
+
+fn benchmark_encoders(app_handle: &AppHandle) -> Result<Vec<EncoderBenchmark>> {
+    if let Some(reason) = recorder_in_use(app_handle) {
+        bail!(reason);
+    }
+
+    let executable = app_handle
+        .path()
+        .resolve(RECORDER_EXECUTABLE, BaseDirectory::Executable)?;
+    let mut recorder = Recorder::new_with_paths(Some(executable), None, None, None)?;
+    let benchmarks = recorder.available_encoders().map(|encoders| {
+        let settings = app_handle.state::<SettingsWrapper>();
+        encoders
+            .into_iter()
+            .map(|encoder| benchmark_encoder(&mut recorder, &settings, encoder))
+            .collect::<Vec<_>>()
+    });
+    if let Err(e) = recorder.shutdown() {
+        log::warn!("failed to shut down the recorder: {e}");
+    }
+
+    let benchmarks = benchmarks?;
+    log::info!("encoder benchmark: {benchmarks:?}");
+    Ok(benchmarks)
+}
+
+fn benchmark_encoder(recorder: &mut Recorder, settings: &SettingsWrapper, encoder: Encoder) -> EncoderBenchmark {
+    let output_path = std::env::temp_dir().join(format!("league_record_benchmark_{encoder:?}.mp4"));
+    let benchmark = measure_encoder(recorder, settings, encoder, &output_path);
+    if output_path.exists() {
+        if let Err(e) = fs::remove_file(&output_path) {
+            log::warn!("failed to delete benchmark recording {}: {e}", output_path.display());
+        }
+    }
+
+    benchmark.unwrap_or_else(|e| EncoderBenchmark::failed(encoder, e.to_string()))
+}
+
+fn measure_encoder(
+    recorder: &mut Recorder,
+    settings: &SettingsWrapper,
+    encoder: Encoder,
+    output_path: &Path,
+) -> Result<EncoderBenchmark> {
+    let output_resolution = settings.get_output_resolution().unwrap_or(StdResolution::_1920x1080p);
+    let framerate = settings.get_framerate();
+    let encoder_settings = settings.encoder();
+
+    let mut recorder_settings = RecorderSettings::new(
+        Window::new(BENCHMARK_WINDOW, None, None),
+        Resolution::from(output_resolution),
+        output_resolution,
+        output_path,
+    );
+    recorder_settings.set_encoder(encoder);
+    recorder_settings.set_framerate(framerate);
+    recorder_settings.set_rate_control(encoder_settings.rate_control());
+    recorder_settings.set_encoder_preset(encoder_settings.preset);
+    recorder_settings.set_keyframe_interval(encoder_settings.keyframe_interval_secs);
+    recorder_settings.set_audio_source(AudioSource::NONE);
+    recorder.configure(&recorder_settings)?;
+
+    recorder.start_recording()?;
+    std::thread::sleep(BENCHMARK_WARM_UP);
+    let measurement = measure(recorder);
+    if let Err(e) = recorder.stop_recording() {
+        log::warn!("failed to stop the benchmark recording: {e}");
+    }
+    let (start, end, elapsed, cpu_usage) = measurement?;
+
+    let encoded_frames = end.encoded_frames().saturating_sub(start.encoded_frames());
+    let fps = f64::from(encoded_frames) / elapsed.as_secs_f64();
+    let lag_ratio = end.lag_ratio_since(&start);
+    let target_fps = f64::from(framerate.num()) / f64::from(framerate.den());
+    Ok(EncoderBenchmark {
+        encoder,
+        fps,
+        lag_ratio,
+        cpu_usage,
+        frame_time_ms: end.frame_time_ns() as f64 / 1_000_000.0,
+        // OBS renders at the exact framerate, a few frames less are just timing
+        keeps_up: lag_ratio <= MAX_LAG_RATIO && fps >= target_fps * (1.0 - MAX_LAG_RATIO),
+        error: None,
+    })
+}
+
+/// Stats at the start and end of the benchmark, how long it took and the CPU usage in between
+fn measure(recorder: &mut Recorder) -> Result<(RecorderStats, RecorderStats, Duration, Option<f64>)> {
+    let start_cpu = util::system_cpu_times();
+    let start = recorder.stats()?;
+    let start_time = Instant::now();
+
+    std::thread::sleep(BENCHMARK_DURATION);
+
+    let end = recorder.stats()?;
+    let elapsed = start_time.elapsed();
+    let cpu_usage =
+        start_cpu
+            .zip(util::system_cpu_times())
+            .and_then(|((start_idle, start_total), (end_idle, end_total))| {
+                let total = end_total.checked_sub(start_total).filter(|total| *total > 0)?;
+                let idle = end_idle.saturating_sub(start_idle);
+                Some(1.0 - idle as f64 / total as f64)
+            });
+    Ok((start, end, elapsed, cpu_usage))
+}
+
+fn check_disk_space(recordings_path: &Path) -> Check {
+    let Some(free) = util::free_disk_space(recordings_path) else {
+        return Check::new(CheckStatus::Warning, "unable to get the free disk space");
+    };
+
+    let free_gb = free as f64 / 1_000_000_000.0;
+    let message = format!("{free_gb:.1}GB free on the drive of the recordings folder");
+    if free_gb < MIN_FREE_DISK_SPACE_GB {
+        Check::new(CheckStatus::Failed, message)
+    } else if free_gb < RECOMMENDED_FREE_DISK_SPACE_GB {
+        Check::new(CheckStatus::Warning, message)
+    } else {
+        Check::new(CheckStatus::Ok, message)
+    }
+}
+
+fn check_ffmpeg(ffmpeg: &str) -> Check {
+    let mut command = Command::new(ffmpeg);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    match command.arg("-version").output() {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Check::new(CheckStatus::Ok, stdout.lines().next().unwrap_or_default())
+        }
+        Ok(output) => Check::new(
+            CheckStatus::Warning,
+            format!("'{ffmpeg} -version' exited with {}", output.status),
+        ),
+        Err(e) => Check::new(
+            CheckStatus::Warning,
+            format!("FFmpeg not found ({e}), clips, highlight reels and thumbnails aren't available"),
+        ),
+    }
+}
+
+fn check_autostart(app_handle: &AppHandle, autostart: bool) -> Check {
+    use tauri_plugin_autostart::ManagerExt;
+
+    match app_handle.autolaunch().is_enabled() {
+        Ok(enabled) if enabled == autostart => {
+            Check::new(CheckStatus::Ok, if enabled { "enabled" } else { "disabled" })
+        }
+        Ok(enabled) => Check::new(
+            CheckStatus::Warning,
+            format!(
+                "autostart is {} but the 'autostart' setting is {autostart}",
+                if enabled { "on" } else { "off" }
+            ),
+        ),
+        Err(e) => Check::new(CheckStatus::Warning, format!("unable to get the autostart state: {e}")),
+    }
+}
+
+async fn check_lcu() -> Check {
+    let Ok(credentials) = riot_local_auth::lcu::try_get_credentials() else {
+        return Check::new(
+            CheckStatus::Warning,
+            "the League client isn't running, games are only recorded while it is running",
+        );
+    };
+
+    match LcuRestClient::from(&credentials).get::<String>(GAMEFLOW_PHASE).await {
+        Ok(phase) => Check::new(CheckStatus::Ok, format!("connected to the League client ({phase})")),
+        Err(e) => Check::new(CheckStatus::Failed, format!("the League client doesn't answer: {e}")),
+    }
+}
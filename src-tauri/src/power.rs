@@ -0,0 +1,139 @@
+//! Sleep/resume and session lock notifications of Windows.
+//!
+//! A recording that is still running when the PC goes to sleep gets stopped so the video is finalized. After waking up
+//! the connection to the League client is established again since the client could have been restarted (new
+//! credentials) and the websocket connection doesn't survive the sleep. Unlocking the session only reconnects if the
+//! connection is gone.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// the PC is going to sleep or hibernate
+    Suspend,
+    /// the PC woke up
+    Resume,
+    /// the session got unlocked (the PC doesn't have to have been asleep)
+    Unlock,
+}
+
+#[cfg(target_os = "windows")]
+pub use listener::listen;
+
+#[cfg(target_os = "windows")]
+mod listener {
+    use std::sync::OnceLock;
+    use std::time::{Duration, Instant};
+
+    use tauri::{AppHandle, Manager};
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassExW, TranslateMessage, MSG,
+        PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WNDCLASSEXW, WS_POPUP,
+        WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+    };
+
+    use super::PowerEvent;
+    use crate::recorder::LeagueRecorder;
+    use crate::state::CurrentlyRecording;
+
+    static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+    // Windows goes to sleep about 2s after PBT_APMSUSPEND, no matter whether the message got handled
+    const SUSPEND_TIMEOUT: Duration = Duration::from_secs(2);
+    const SUSPEND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Listen for sleep/resume and session lock notifications and forward them to the `LeagueRecorder`
+    pub fn listen(app_handle: &AppHandle) {
+        if APP_HANDLE.set(app_handle.clone()).is_err() {
+            log::warn!("power listener already initialized");
+            return;
+        }
+
+        std::thread::spawn(|| unsafe {
+            let instance = GetModuleHandleW(std::ptr::null());
+            let class_name: Vec<u16> = "LeagueRecordPowerListener"
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+
+            let wc = WNDCLASSEXW {
+                cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+                lpfnWndProc: Some(wnd_proc),
+                hInstance: instance,
+                lpszClassName: class_name.as_ptr(),
+                ..Default::default()
+            };
+            if RegisterClassExW(&wc) == 0 {
+                log::error!("failed to register window class for power notifications");
+                return;
+            }
+
+            // message-only windows don't get the WM_POWERBROADCAST broadcasts => hidden top-level window
+            let hwnd = CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                class_name.as_ptr(),
+                WS_POPUP,
+                0,
+                0,
+                0,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null_mut(),
+            );
+            if hwnd.is_null() {
+                log::error!("failed to create window for power notifications");
+                return;
+            }
+
+            if WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) == 0 {
+                log::warn!("failed to register for session lock notifications");
+            }
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        });
+    }
+
+    unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        let event = match (msg, wparam as u32) {
+            (WM_POWERBROADCAST, PBT_APMSUSPEND) => Some(PowerEvent::Suspend),
+            // sent for every wake up, PBT_APMRESUMESUSPEND only if the user caused it
+            (WM_POWERBROADCAST, PBT_APMRESUMEAUTOMATIC) => Some(PowerEvent::Resume),
+            // a locked session keeps recording, the game keeps running
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => {
+                log::info!("session locked");
+                None
+            }
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => Some(PowerEvent::Unlock),
+            _ => None,
+        };
+
+        if let (Some(event), Some(app_handle)) = (event, APP_HANDLE.get()) {
+            log::info!("power event: {event:?}");
+            app_handle.state::<LeagueRecorder>().power_event(event);
+            if event == PowerEvent::Suspend {
+                wait_for_recording_stop(app_handle);
+            }
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    /// Keep Windows from going to sleep until the recording is stopped and the video finalized (as long as it lets us)
+    fn wait_for_recording_stop(app_handle: &AppHandle) {
+        let deadline = Instant::now() + SUSPEND_TIMEOUT;
+        let currently_recording = app_handle.state::<CurrentlyRecording>();
+        while currently_recording.get().is_some() {
+            if Instant::now() >= deadline {
+                log::warn!("recording didn't stop before the PC went to sleep");
+                return;
+            }
+            std::thread::sleep(SUSPEND_POLL_INTERVAL);
+        }
+    }
+}
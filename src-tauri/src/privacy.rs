@@ -0,0 +1,343 @@
+//! Privacy mode ('privacyMode' setting) for content creators who publish their recordings.
+//!
+//! The metadata files only contain aliases like 'Enemy Midlaner' instead of the Riot IDs of the other players, so they
+//! can be shared together with the videos (bundles, backups, timeline exports). The real names are kept in a file in
+//! the app data folder and put back whenever a metadata file gets read, so LeagueRecord itself still shows them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Manager};
+
+use crate::app::{action, RecordingManager};
+use crate::recorder::{CustomLobby, Deferred, GameMetadata, MetadataFile, Participant, ScoreboardSnapshot};
+use crate::state::{CurrentlyRecording, SettingsWrapper};
+
+const ALIASES_FILE: &str = "privacy_aliases.json";
+const BLUE_TEAM: i64 = 100;
+const RED_TEAM: i64 = 200;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ALIASES_PATH: OnceLock<PathBuf> = OnceLock::new();
+// recording id => aliases of the other players, loaded from `ALIASES_FILE` on first use
+static ALIASES: Mutex<Option<HashMap<String, Vec<Alias>>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Alias {
+    alias: String,
+    riot_id: String,
+    /// only known for the players of custom lobbies
+    #[serde(default)]
+    puuid: Option<String>,
+}
+
+/// Set the file the real names get stored in and redact the recordings that still contain names
+pub fn init(app_handle: &AppHandle) {
+    if let Ok(app_dir) = app_handle.path().app_local_data_dir() {
+        _ = ALIASES_PATH.set(app_dir.join(ALIASES_FILE));
+    }
+    set_enabled(app_handle, app_handle.state::<SettingsWrapper>().privacy_mode());
+}
+
+/// Turn privacy mode on or off. Turning it on redacts the metadata of all recordings in the background.
+pub fn set_enabled(app_handle: &AppHandle, enabled: bool) {
+    let was_enabled = ENABLED.swap(enabled, Ordering::AcqRel);
+    if enabled && !was_enabled {
+        let app_handle = app_handle.clone();
+        async_runtime::spawn_blocking(move || redact_library(&app_handle));
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Copy of `metadata_file` with aliases instead of the Riot IDs of the other players
+///
+/// The aliases get stored so [`reveal`] can put the names back.
+pub fn redact(metadata_file: &MetadataFile) -> MetadataFile {
+    let (id, redacted, aliases) = match metadata_file {
+        MetadataFile::Metadata(metadata) => {
+            let (redacted, aliases) = redact_metadata(metadata);
+            (&metadata.id, MetadataFile::Metadata(redacted), aliases)
+        }
+        MetadataFile::Deferred(deferred) => {
+            let (redacted, aliases) = redact_deferred(deferred);
+            (&deferred.id, MetadataFile::Deferred(redacted), aliases)
+        }
+        MetadataFile::NoData(_) => return metadata_file.clone(),
+    };
+
+    // the names couldn't be restored without an id to look them up
+    if id.is_empty() {
+        log::warn!("recording without id, not redacting the names of the players");
+        return metadata_file.clone();
+    }
+    if !aliases.is_empty() {
+        store(id, aliases);
+    }
+    redacted
+}
+
+/// Replace the aliases of the players with their real names
+pub fn reveal(metadata_file: &mut MetadataFile) {
+    let id = match metadata_file {
+        MetadataFile::Metadata(metadata) => &metadata.id,
+        MetadataFile::Deferred(deferred) => &deferred.id,
+        MetadataFile::NoData(_) => return,
+    };
+    let Some(aliases) = with_aliases(|all| all.get(id).cloned()) else { return };
+
+    let real_name = |name: &mut String| {
+        if let Some(alias) = aliases.iter().find(|alias| alias.alias == *name) {
+            *name = alias.riot_id.clone();
+        }
+    };
+    match metadata_file {
+        MetadataFile::Metadata(metadata) => {
+            metadata
+                .participants
+                .iter_mut()
+                .for_each(|participant| real_name(&mut participant.summoner_name));
+            if let Some(scoreboard) = &mut metadata.scoreboard {
+                scoreboard
+                    .players
+                    .iter_mut()
+                    .for_each(|player| real_name(&mut player.summoner_name));
+            }
+            if let Some(lobby) = &mut metadata.lobby {
+                reveal_lobby(lobby, &aliases);
+            }
+        }
+        MetadataFile::Deferred(deferred) => {
+            deferred.party.iter_mut().for_each(real_name);
+            if let Some(scoreboard) = &mut deferred.scoreboard {
+                scoreboard
+                    .players
+                    .iter_mut()
+                    .for_each(|player| real_name(&mut player.summoner_name));
+            }
+            if let Some(lobby) = &mut deferred.lobby {
+                reveal_lobby(lobby, &aliases);
+            }
+        }
+        MetadataFile::NoData(_) => {}
+    }
+}
+
+fn reveal_lobby(lobby: &mut CustomLobby, aliases: &[Alias]) {
+    for player in lobby.blue_team.iter_mut().chain(lobby.red_team.iter_mut()) {
+        if let Some(alias) = aliases.iter().find(|alias| alias.alias == player.riot_id) {
+            player.riot_id = alias.riot_id.clone();
+            player.puuid = alias.puuid.clone().unwrap_or_default();
+        }
+    }
+}
+
+fn redact_metadata(metadata: &GameMetadata) -> (GameMetadata, Vec<Alias>) {
+    let mut redacted = metadata.clone();
+    let own = metadata
+        .participants
+        .iter()
+        .find(|participant| participant.participant_id == metadata.participant_id);
+    let own_riot_id = own
+        .map(|participant| participant.summoner_name.clone())
+        .or_else(|| metadata.account.as_ref().map(|account| account.riot_id()));
+    let mut aliases = AliasBuilder::new(own_riot_id);
+    let own_team = own.map(|participant| participant.team_id);
+
+    for participant in &mut redacted.participants {
+        let name = format!("{} {}", side(own_team, participant.team_id), position(participant));
+        if let Some(alias) = aliases.alias(&participant.summoner_name, None, name) {
+            participant.summoner_name = alias;
+        }
+    }
+    if let Some(scoreboard) = &mut redacted.scoreboard {
+        redact_scoreboard(scoreboard, own_team, &mut aliases);
+    }
+    if let Some(lobby) = &mut redacted.lobby {
+        redact_lobby(lobby, own_team, &mut aliases);
+    }
+    (redacted, aliases.aliases)
+}
+
+fn redact_deferred(deferred: &Deferred) -> (Deferred, Vec<Alias>) {
+    let mut redacted = deferred.clone();
+    let own_riot_id = deferred.account.as_ref().map(|account| account.riot_id());
+    let own_team = own_riot_id.as_ref().and_then(|riot_id| {
+        let scoreboard = deferred.scoreboard.as_ref()?;
+        let own = scoreboard
+            .players
+            .iter()
+            .find(|player| player.summoner_name == *riot_id)?;
+        Some(own.team_id)
+    });
+    let mut aliases = AliasBuilder::new(own_riot_id);
+
+    if let Some(scoreboard) = &mut redacted.scoreboard {
+        redact_scoreboard(scoreboard, own_team, &mut aliases);
+    }
+    if let Some(lobby) = &mut redacted.lobby {
+        redact_lobby(lobby, own_team, &mut aliases);
+    }
+    // the party is always on the team of the recorded player
+    for riot_id in &mut redacted.party {
+        if let Some(alias) = aliases.alias(riot_id, None, String::from("Ally Player")) {
+            *riot_id = alias;
+        }
+    }
+    (redacted, aliases.aliases)
+}
+
+fn redact_scoreboard(scoreboard: &mut ScoreboardSnapshot, own_team: Option<i64>, aliases: &mut AliasBuilder) {
+    for player in &mut scoreboard.players {
+        let name = format!("{} Player", side(own_team, player.team_id));
+        if let Some(alias) = aliases.alias(&player.summoner_name, None, name) {
+            player.summoner_name = alias;
+        }
+    }
+}
+
+fn redact_lobby(lobby: &mut CustomLobby, own_team: Option<i64>, aliases: &mut AliasBuilder) {
+    let teams = [(BLUE_TEAM, &mut lobby.blue_team), (RED_TEAM, &mut lobby.red_team)];
+    for (team_id, players) in teams {
+        for player in players.iter_mut().filter(|player| !player.is_bot) {
+            let name = format!("{} Player", side(own_team, team_id));
+            if let Some(alias) = aliases.alias(&player.riot_id, Some(&player.puuid), name) {
+                player.riot_id = alias;
+                player.puuid.clear();
+            }
+        }
+    }
+}
+
+/// 'Ally' or 'Enemy', the color of the team if the team of the recorded player is unknown
+fn side(own_team: Option<i64>, team_id: i64) -> &'static str {
+    match own_team {
+        Some(own_team) if own_team == team_id => "Ally",
+        Some(_) => "Enemy",
+        None if team_id == BLUE_TEAM => "Blue",
+        None => "Red",
+    }
+}
+
+fn position(participant: &Participant) -> &'static str {
+    match (participant.lane.as_str(), participant.role.as_str()) {
+        ("TOP", _) => "Toplaner",
+        ("JUNGLE", _) => "Jungler",
+        ("MIDDLE" | "MID", _) => "Midlaner",
+        ("BOTTOM" | "BOT", "SUPPORT" | "DUO_SUPPORT") => "Support",
+        ("BOTTOM" | "BOT", "CARRY" | "DUO_CARRY") => "ADC",
+        ("BOTTOM" | "BOT", _) => "Botlaner",
+        // ARAM, Arena, ...
+        _ => "Player",
+    }
+}
+
+/// Gives every Riot ID (except the one of the recorded player) an alias that is unique within the recording
+struct AliasBuilder {
+    own_riot_id: Option<String>,
+    aliases: Vec<Alias>,
+}
+
+impl AliasBuilder {
+    fn new(own_riot_id: Option<String>) -> Self {
+        Self {
+            own_riot_id,
+            aliases: Vec::new(),
+        }
+    }
+
+    /// The alias for `riot_id` based on `name` (e.g. 'Enemy Player 2' if 'Enemy Player' is taken), None if the name
+    /// is kept
+    fn alias(&mut self, riot_id: &str, puuid: Option<&str>, name: String) -> Option<String> {
+        if riot_id.is_empty() || self.own_riot_id.as_deref() == Some(riot_id) {
+            return None;
+        }
+        if let Some(alias) = self.aliases.iter_mut().find(|alias| alias.riot_id == riot_id) {
+            if alias.puuid.is_none() {
+                alias.puuid = puuid.map(str::to_owned);
+            }
+            return Some(alias.alias.clone());
+        }
+
+        let mut alias = name.clone();
+        let mut number = 2;
+        while self.aliases.iter().any(|existing| existing.alias == alias) {
+            alias = format!("{name} {number}");
+            number += 1;
+        }
+        self.aliases.push(Alias {
+            alias: alias.clone(),
+            riot_id: riot_id.to_owned(),
+            puuid: puuid.filter(|puuid| !puuid.is_empty()).map(str::to_owned),
+        });
+        Some(alias)
+    }
+}
+
+fn with_aliases<T>(f: impl FnOnce(&mut HashMap<String, Vec<Alias>>) -> T) -> T {
+    let mut aliases = ALIASES.lock().unwrap();
+    f(aliases.get_or_insert_with(load))
+}
+
+fn load() -> HashMap<String, Vec<Alias>> {
+    let Some(data) = ALIASES_PATH.get().and_then(|path| fs::read(path).ok()) else { return HashMap::new() };
+    serde_json::from_slice(&data).unwrap_or_else(|e| {
+        log::error!("failed to read the names of the players: {e}");
+        HashMap::new()
+    })
+}
+
+fn store(id: &str, aliases: Vec<Alias>) {
+    with_aliases(|all| {
+        if all.get(id) == Some(&aliases) {
+            return;
+        }
+        all.insert(id.to_owned(), aliases);
+
+        let Some(path) = ALIASES_PATH.get() else { return };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, serde_json::to_vec(all)?));
+        if let Err(e) = result {
+            log::error!("failed to save the names of the players: {e}");
+        }
+    });
+}
+
+/// Save the metadata of every recording that still contains the names of other players again (redacted)
+fn redact_library(app_handle: &AppHandle) {
+    let currently_recording = app_handle.state::<CurrentlyRecording>().get();
+    let mut redacted = 0;
+    for recording in app_handle.get_recordings() {
+        // the recording task keeps writing the metadata of the running recording
+        if currently_recording.as_ref() == Some(&recording) || !action::is_native_recording(&recording) {
+            continue;
+        }
+        match redact_recording(&recording) {
+            Ok(true) => redacted += 1,
+            Ok(false) => {}
+            Err(e) => log::warn!("failed to redact the metadata of {}: {e}", recording.display()),
+        }
+    }
+    log::info!("privacy mode: redacted the metadata of {redacted} recordings");
+}
+
+fn redact_recording(recording: &Path) -> Result<bool> {
+    let stored = serde_json::from_slice::<serde_json::Value>(&fs::read(recording.with_extension("json"))?)?;
+    let metadata_file = action::get_recording_metadata(recording, false)?;
+    if serde_json::to_value(redact(&metadata_file))? == stored {
+        return Ok(false);
+    }
+    // redacts again while saving
+    action::save_recording_metadata(recording, &metadata_file)?;
+    Ok(true)
+}
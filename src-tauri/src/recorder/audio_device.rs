@@ -0,0 +1,32 @@
+use windows::Win32::Media::Audio::{eCapture, eConsole, eRender, EDataFlow, IMMDeviceEnumerator, MMDeviceEnumerator};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+/// IDs of the default output and input devices of Windows (same format as the WASAPI device ids of libobs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultAudioDevices {
+    pub output: Option<String>,
+    pub input: Option<String>,
+}
+
+impl DefaultAudioDevices {
+    pub fn get() -> Self {
+        Self {
+            output: default_device(eRender),
+            input: default_device(eCapture),
+        }
+    }
+}
+
+fn default_device(flow: EDataFlow) -> Option<String> {
+    unsafe {
+        // fails if COM is already initialized on this thread which is fine
+        _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
+        let device = enumerator.GetDefaultAudioEndpoint(flow, eConsole).ok()?;
+        let id = device.GetId().ok()?;
+        let device_id = id.to_string().ok();
+        CoTaskMemFree(Some(id.0 as *const _));
+        device_id
+    }
+}
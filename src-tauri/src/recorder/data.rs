@@ -1,5 +1,8 @@
+use std::path::PathBuf;
+
 use riot_datatypes::*;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 // allow large difference in enum Variant size because the big variant is the more common one
 #[allow(clippy::large_enum_variant)]
@@ -11,7 +14,45 @@ pub enum MetadataFile {
     NoData(NoData),
 }
 
+/// New stable ID for a recording (see `MetadataFile::id`)
+pub fn new_recording_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// ID in the same format as `new_recording_id` that is derived from `seed` instead of random.
+/// For recordings whose metadata file can't store their ID, so they still get the same one on every read.
+pub fn derived_recording_id(seed: &[u8]) -> String {
+    let hash = Sha256::digest(seed);
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&hash[..16]);
+    uuid::Builder::from_random_bytes(bytes).into_uuid().to_string()
+}
+
+/// '14.10.584.1234' => '14.10' (None for an empty or malformed version)
+pub fn patch(game_version: &str) -> Option<String> {
+    let mut parts = game_version.split('.');
+    let major = parts.next().filter(|part| part.parse::<u32>().is_ok())?;
+    let minor = parts.next().filter(|part| part.parse::<u32>().is_ok())?;
+    Some(format!("{major}.{minor}"))
+}
+
 impl MetadataFile {
+    pub fn id(&self) -> &str {
+        match self {
+            MetadataFile::Metadata(metadata) => &metadata.id,
+            MetadataFile::Deferred(deferred) => &deferred.id,
+            MetadataFile::NoData(no_data) => &no_data.id,
+        }
+    }
+
+    pub fn set_id(&mut self, id: String) {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.id = id,
+            MetadataFile::Deferred(deferred) => deferred.id = id,
+            MetadataFile::NoData(no_data) => no_data.id = id,
+        };
+    }
+
     pub fn is_favorite(&self) -> bool {
         match self {
             MetadataFile::Metadata(metadata) => metadata.favorite,
@@ -27,6 +68,78 @@ impl MetadataFile {
             MetadataFile::NoData(no_data) => no_data.favorite = favorite,
         };
     }
+
+    pub fn is_protected(&self) -> bool {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.protected,
+            MetadataFile::Deferred(deferred) => deferred.protected,
+            MetadataFile::NoData(no_data) => no_data.protected,
+        }
+    }
+
+    pub fn set_protected(&mut self, protected: bool) {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.protected = protected,
+            MetadataFile::Deferred(deferred) => deferred.protected = protected,
+            MetadataFile::NoData(no_data) => no_data.protected = protected,
+        };
+    }
+
+    pub fn stop_reason(&self) -> Option<&StopReason> {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.stop_reason.as_ref(),
+            MetadataFile::Deferred(deferred) => deferred.stop_reason.as_ref(),
+            MetadataFile::NoData(_) => None,
+        }
+    }
+
+    /// Riot account that was logged in when the game was recorded (None for recordings from before it was stored)
+    pub fn account(&self) -> Option<&Account> {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.account.as_ref(),
+            MetadataFile::Deferred(deferred) => deferred.account.as_ref(),
+            MetadataFile::NoData(_) => None,
+        }
+    }
+
+    pub fn clash(&self) -> Option<&Clash> {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.clash.as_ref(),
+            MetadataFile::Deferred(deferred) => deferred.clash.as_ref(),
+            MetadataFile::NoData(_) => None,
+        }
+    }
+
+    pub fn segments_mut(&mut self) -> Option<&mut Vec<Segment>> {
+        match self {
+            MetadataFile::Metadata(metadata) => Some(&mut metadata.segments),
+            MetadataFile::Deferred(deferred) => Some(&mut deferred.segments),
+            MetadataFile::NoData(_) => None,
+        }
+    }
+}
+
+/// Why a recording ended
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StopReason {
+    /// the game reached one of the configured stop phases (e.g. `FailedToLaunch` or `PreEndOfGame`)
+    Phase(lcu::GamePhase),
+    /// stopped via the stop-recording hotkey
+    Manual,
+    /// LeagueRecord was closed during the recording
+    Cancelled,
+    /// the recorder stopped on its own
+    Error(String),
+    /// the recorder stopped on its own while the disk of the recordings folder was (almost) full
+    DiskFull,
+    /// the app or PC crashed during the recording, the video was repaired on the next start
+    Interrupted,
+    /// the game got closed while the League client wasn't running (see 'recordWithoutClient') or had crashed during
+    /// the recording
+    GameClosed,
+    /// the PC went to sleep during the recording
+    Suspended,
 }
 
 #[cfg_attr(test, derive(specta::Type))]
@@ -47,6 +160,138 @@ pub struct Participant {
     pub summoner_name: String,
     #[serde(default)]
     pub lane_score: f64,
+    /// queued together with the recorded player (also set for the player if they didn't play solo)
+    #[serde(default)]
+    pub premade: bool,
+    /// items of the final inventory in the order they were bought
+    #[serde(default)]
+    pub build: Vec<BuildItem>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildItem {
+    pub item_id: i64,
+    /// when the item was bought
+    pub timestamp: Timestamp,
+}
+
+/// The lane opponent of the recorded player, e.g. Ahri vs Syndra in 'MIDDLE'
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Matchup {
+    /// lane of the match history ('TOP', 'JUNGLE', 'MIDDLE', 'BOTTOM' or 'NONE')
+    pub lane: String,
+    pub champion_id: ChampionId,
+    pub opponent_participant_id: ParticipantId,
+    pub opponent_champion_id: ChampionId,
+}
+
+/// Find the enemy that played the same lane (and role for bot lane) as `participant_id`.
+/// If the match history has no lane for the player, the enemy whose early kills and deaths happened closest to the ones
+/// of the player (see `Participant::lane_score`) is taken instead.
+pub fn lane_opponent(participants: &[Participant], participant_id: ParticipantId) -> Option<Matchup> {
+    let player = participants.iter().find(|p| p.participant_id == participant_id)?;
+    let enemies = participants.iter().filter(|p| p.team_id != player.team_id);
+
+    let known_lane = !player.lane.is_empty() && player.lane != "NONE";
+    let same_lane: Vec<_> = enemies
+        .clone()
+        .filter(|p| known_lane && p.lane == player.lane)
+        .collect();
+    let opponent = match same_lane.len() {
+        // 0.0 means there were no kills or deaths early on
+        0 if player.lane_score != 0.0 => closest_lane_score(enemies.filter(|p| p.lane_score != 0.0), player),
+        0 => None,
+        1 => same_lane.first().copied(),
+        // both bot laners have lane 'BOTTOM', the role tells carry and support apart
+        _ => match same_lane.iter().find(|p| p.role == player.role) {
+            Some(opponent) => Some(*opponent),
+            None => closest_lane_score(same_lane.into_iter(), player),
+        },
+    }?;
+
+    Some(Matchup {
+        lane: if known_lane {
+            player.lane.clone()
+        } else {
+            opponent.lane.clone()
+        },
+        champion_id: player.champion_id,
+        opponent_participant_id: opponent.participant_id,
+        opponent_champion_id: opponent.champion_id,
+    })
+}
+
+/// Common metrics for reviewing the early game, shown in the recordings list and trended in the stats
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewStats {
+    /// difference to the lane opponent at 10 minutes (None without a lane opponent or for shorter games)
+    pub at_10: Option<LaneDiff>,
+    /// difference to the lane opponent at 15 minutes
+    pub at_15: Option<LaneDiff>,
+    /// how the player took part in the first kill of the game (None if they weren't involved)
+    pub first_blood: Option<KillRole>,
+}
+
+/// Player minus lane opponent, positive if the player is ahead
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaneDiff {
+    pub cs: i64,
+    pub gold: i64,
+    /// None for games recorded by versions that didn't store the XP
+    pub xp: Option<i64>,
+}
+
+/// Compute the `ReviewStats` of `participant_id` from the timeline (`events` sorted by timestamp)
+pub fn review_stats(
+    gold_timeline: &[GoldFrame],
+    events: &[GameEvent],
+    matchup: Option<&Matchup>,
+    participant_id: ParticipantId,
+) -> ReviewStats {
+    let lane_diff = |minutes: i64| {
+        let matchup = matchup?;
+        // the timeline has a frame every minute, the timestamps are a few milliseconds late
+        let frame = gold_timeline
+            .iter()
+            .find(|frame| frame.timestamp >= minutes * 60 * 1000)?;
+        let stats = |id: ParticipantId| frame.participants.iter().find(|p| p.participant_id == id);
+        let player = stats(participant_id)?;
+        let opponent = stats(matchup.opponent_participant_id)?;
+
+        Some(LaneDiff {
+            cs: player.minions - opponent.minions,
+            gold: player.total_gold - opponent.total_gold,
+            xp: player.xp.zip(opponent.xp).map(|(player, opponent)| player - opponent),
+        })
+    };
+
+    let first_blood = events
+        .iter()
+        .find(|game_event| matches!(game_event.event, Event::ChampionKill { .. }))
+        .and_then(|first_kill| kill_positions(std::slice::from_ref(first_kill), participant_id).pop())
+        .map(|position| position.role);
+
+    ReviewStats {
+        at_10: lane_diff(10),
+        at_15: lane_diff(15),
+        first_blood,
+    }
+}
+
+fn closest_lane_score<'a>(
+    candidates: impl Iterator<Item = &'a Participant>,
+    player: &Participant,
+) -> Option<&'a Participant> {
+    let distance = |p: &Participant| (p.lane_score - player.lane_score).abs();
+    candidates.min_by(|a, b| distance(a).total_cmp(&distance(b)))
 }
 
 #[cfg_attr(test, derive(specta::Type))]
@@ -57,6 +302,9 @@ pub struct ParticipantGold {
     pub total_gold: i64,
     #[serde(default)]
     pub minions: i64,
+    /// None in metadata files of versions that didn't store the XP
+    #[serde(default)]
+    pub xp: Option<i64>,
 }
 
 #[cfg_attr(test, derive(specta::Type))]
@@ -71,11 +319,30 @@ pub struct GoldFrame {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GameMetadata {
+    /// stable ID of the recording that doesn't change when the video gets renamed
+    #[serde(default)]
+    pub id: String,
     pub favorite: bool,
+    /// never deleted by the retention rules
+    #[serde(default)]
+    pub protected: bool,
     pub match_id: MatchId,
+    #[serde(default)]
+    pub account: Option<Account>,
+    /// only set for custom games
+    #[serde(default)]
+    pub lobby: Option<CustomLobby>,
+    /// only set for Clash games
+    #[serde(default)]
+    pub clash: Option<Clash>,
     pub ingame_time_rec_start_offset: f64,
     #[serde(default)]
-    pub highlights: Vec<f64>,
+    pub raw_ingame_time_rec_start_offset: Option<f64>,
+    #[serde(default)]
+    pub highlights: Vec<Highlight>,
+    /// notes the user wrote while reviewing the recording
+    #[serde(default)]
+    pub comments: Vec<Comment>,
     pub queue: Queue,
     pub player: lcu::Player,
     pub champion_name: String,
@@ -84,30 +351,411 @@ pub struct GameMetadata {
     pub participants: Vec<Participant>,
     pub teams: Vec<lcu::MatchTeam>,
     pub events: Vec<GameEvent>,
+    /// where the player killed, died or assisted (see `kill_positions()`)
+    #[serde(default)]
+    pub kill_positions: Vec<KillPosition>,
+    /// enemy the player laned against (see `lane_opponent()`)
+    #[serde(default)]
+    pub matchup: Option<Matchup>,
+    #[serde(default)]
+    pub review_stats: ReviewStats,
     #[serde(default)]
     pub gold_timeline: Vec<GoldFrame>,
     #[serde(default)]
     pub game_version: String,
     #[serde(default)]
     pub lp_diff: Option<i32>,
+    #[serde(default)]
+    pub mastery: Option<Mastery>,
+    #[serde(default)]
+    pub pauses: Vec<Pause>,
+    #[serde(default)]
+    pub scoreboard: Option<ScoreboardSnapshot>,
+    /// official replay (.rofl) of the game downloaded through the LCU
+    #[serde(default)]
+    pub replay_path: Option<PathBuf>,
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+    /// length of the video in seconds, measured when the recording stopped
+    #[serde(default)]
+    pub video_duration: Option<f64>,
+    /// files that continue the video if the recording was split (see 'segmentation')
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    /// dropped frames, lag and bitrate of the whole recording
+    #[serde(default)]
+    pub recording_stats: Option<RecordingStats>,
+    /// see `migration::METADATA_SCHEMA_VERSION`
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+impl GameMetadata {
+    /// Flag the participants that were in the party of the recorded player (`party` are their Riot IDs)
+    pub fn mark_premades(&mut self, party: &[String]) {
+        for participant in &mut self.participants {
+            participant.premade = party.contains(&participant.summoner_name);
+        }
+    }
+
+    /// see `patch()`
+    pub fn patch(&self) -> Option<String> {
+        patch(&self.game_version)
+    }
+
+    /// champion played by the recorded player
+    pub fn champion_id(&self) -> Option<ChampionId> {
+        self.participants
+            .iter()
+            .find(|p| p.participant_id == self.participant_id)
+            .map(|p| p.champion_id)
+    }
+}
+
+/// Riot account that was logged into the client when a game started.
+/// Deserializes directly from the LCU summoner endpoint.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub puuid: String,
+    pub game_name: String,
+    pub tag_line: String,
+}
+
+impl Account {
+    /// 'name#tag'
+    pub fn riot_id(&self) -> String {
+        format!("{}#{}", self.game_name, self.tag_line)
+    }
+}
+
+/// Lobby of a custom game (e.g. a scrim) when the game started.
+/// Custom games often never show up in the match history, so this is the only way to tell who played.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomLobby {
+    /// scrims are usually named after the teams that play
+    pub name: String,
+    pub blue_team: Vec<LobbyPlayer>,
+    pub red_team: Vec<LobbyPlayer>,
+}
+
+/// Champion mastery of the recorded player on the played champion right after the game (including the points of the
+/// game). Comparing it to older recordings shows the first game on a champion or mastery levels that were reached.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mastery {
+    pub champion_level: i64,
+    pub champion_points: i64,
+    pub summoner_level: Option<i64>,
+}
+
+/// Clash tournament (one day of a Clash weekend) a game was played in
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Clash {
+    /// None if the tournament couldn't be looked up when the game started
+    pub tournament_id: Option<i64>,
+    /// e.g. 'Bilgewater Cup'
+    pub name: String,
+    /// e.g. 'Day 2'
+    pub day: String,
+    /// number of the game in the tournament (1 for the first one), None for recordings of older versions
+    #[serde(default)]
+    pub round: Option<u32>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyPlayer {
+    pub puuid: String,
+    /// 'name#tag' (empty for bots or if the account couldn't be looked up)
+    pub riot_id: String,
+    pub is_bot: bool,
+}
+
+/// Part of a recording that got split into multiple files. The video file of the recording is the first segment, the
+/// following segments are stored next to it and together form one timeline.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    pub file_name: String,
+    /// time in seconds in the timeline of the whole recording at which the segment starts
+    pub start: f64,
+}
+
+/// Sent as `RecordingDegraded` event if the recording couldn't keep up and continues with a lower framerate
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingDegraded {
+    pub requested_fps: f64,
+    pub fallback_fps: f64,
+    /// share of the frames (0.0 - 1.0) that got lost to render or encoding lag
+    pub lag_ratio: f64,
+}
+
+/// Sent as `RecordingFailed` event if the output of the recorder stopped during the game (e.g. after a GPU driver
+/// reset) and couldn't be restarted. The video contains everything up to the failure.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingFailed {
+    pub reason: String,
+    /// how often the output got restarted before giving up
+    pub output_restarts: u32,
+}
+
+/// Health of a recording from the libobs statistics. Sent as `RecordingStats` event while recording (with the current
+/// bitrate) and stored in the metadata once the recording stopped (with the average bitrate).
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStats {
+    pub total_frames: u32,
+    /// frames that weren't rendered in time (render lag)
+    pub lagged_frames: u32,
+    /// frames the encoder skipped because it couldn't keep up (encoding lag)
+    pub skipped_frames: u32,
+    /// frames dropped while writing the video
+    pub dropped_frames: u32,
+    pub bitrate_kbps: f64,
+    /// average time it took to render a frame
+    pub render_time_ms: f64,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HighlightCategory {
+    /// good play, set with the 'hightlightHotkey'
+    #[default]
+    Highlight,
+    /// mistake or anything else to look at again, set with the 'bookmarkHotkey'
+    Bookmark,
+}
+
+/// Marker that got set with a hotkey during the game
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Highlight {
+    /// ingame time in milliseconds
+    pub timestamp: f64,
+    #[serde(default)]
+    pub category: HighlightCategory,
+}
+
+/// Note of the user at a position in the video
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    /// position in the video in seconds
+    pub video_time: f64,
+    pub text: String,
+}
+
+/// Sent as `HighlightSaved` event to confirm a highlight or bookmark in the overlay
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedHighlight {
+    /// ingame time in seconds
+    pub game_time: f64,
+    pub category: HighlightCategory,
+}
+
+/// Pauses (e.g. in tournament or custom games) stop the ingame time while the recording continues.
+/// A marker at ingame time `t` is at `t - ingame_time_rec_start_offset + (duration of all pauses with game_time <= t)`
+/// in the video.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pause {
+    // ingame time in seconds at which the game was paused
+    pub game_time: f64,
+    // real time in seconds the game stayed paused
+    pub duration: f64,
 }
 
 #[cfg_attr(test, derive(specta::Type))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Deferred {
+    #[serde(default)]
+    pub id: String,
     pub favorite: bool,
+    /// never deleted by the retention rules
+    #[serde(default)]
+    pub protected: bool,
     pub match_id: MatchId,
+    #[serde(default)]
+    pub account: Option<Account>,
+    /// only set for custom games
+    #[serde(default)]
+    pub lobby: Option<CustomLobby>,
+    /// only set for Clash games
+    #[serde(default)]
+    pub clash: Option<Clash>,
+    /// Riot IDs of the party of the recorded player, turned into `Participant::premade` once the game data is available
+    #[serde(default)]
+    pub party: Vec<String>,
     pub ingame_time_rec_start_offset: f64,
     #[serde(default)]
-    pub highlights: Vec<f64>,
+    pub raw_ingame_time_rec_start_offset: Option<f64>,
+    #[serde(default)]
+    pub highlights: Vec<Highlight>,
+    #[serde(default)]
+    pub scoreboard: Option<ScoreboardSnapshot>,
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+    #[serde(default)]
+    pub video_duration: Option<f64>,
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    #[serde(default)]
+    pub recording_stats: Option<RecordingStats>,
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+/// Last scoreboard of the game from the Live Client API, taken right before the game window closed.
+/// This is the only game data available if the LCU match history doesn't have the game (common for custom games).
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreboardSnapshot {
+    pub game_time: f64,
+    pub players: Vec<ScoreboardPlayer>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreboardPlayer {
+    pub summoner_name: String,
+    pub champion_name: String,
+    pub team_id: i64,
+    pub level: i64,
+    pub items: Vec<i64>,
+    pub kills: i64,
+    pub deaths: i64,
+    pub assists: i64,
+    pub creep_score: i64,
+    pub ward_score: f64,
+    pub keystone_id: Option<i64>,
+    pub primary_rune_tree_id: Option<i64>,
+    pub secondary_rune_tree_id: Option<i64>,
+}
+
+impl ScoreboardSnapshot {
+    pub fn new(game_time: f64, players: &[shaco::model::ingame::Player]) -> Self {
+        use shaco::model::ingame::TeamId;
+
+        let players = players
+            .iter()
+            .map(|player| ScoreboardPlayer {
+                summoner_name: player
+                    .riot_id
+                    .riot_id
+                    .clone()
+                    .unwrap_or_else(|| player.summoner_name.clone()),
+                champion_name: player.champion_name.clone(),
+                team_id: match player.team {
+                    TeamId::Chaos => 200,
+                    _ => 100,
+                },
+                level: player.level as i64,
+                items: player.items.iter().map(|item| item.item_id as i64).collect(),
+                kills: player.scores.kills as i64,
+                deaths: player.scores.deaths as i64,
+                assists: player.scores.assists as i64,
+                creep_score: player.scores.creep_score as i64,
+                ward_score: player.scores.ward_score as f64,
+                keystone_id: player.runes.as_ref().map(|runes| runes.keystone.id as i64),
+                primary_rune_tree_id: player.runes.as_ref().map(|runes| runes.primary_rune_tree.id as i64),
+                secondary_rune_tree_id: player.runes.as_ref().map(|runes| runes.secondary_rune_tree.id as i64),
+            })
+            .collect();
+
+        Self { game_time, players }
+    }
 }
 
 #[cfg_attr(test, derive(specta::Type))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NoData {
+    #[serde(default)]
+    pub id: String,
     pub favorite: bool,
+    /// never deleted by the retention rules
+    #[serde(default)]
+    pub protected: bool,
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KillRole {
+    Kill,
+    Death,
+    Assist,
+}
+
+/// Position on the map (in game units, the minimap spans 0 to ~15000 on both axes) of a champion kill the player took
+/// part in
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillPosition {
+    pub role: KillRole,
+    pub x: i64,
+    pub y: i64,
+    pub timestamp: Timestamp,
+}
+
+/// The positions of the champion kills in the timeline `events` in which `participant_id` was the killer, the victim or
+/// assisted
+pub fn kill_positions(events: &[GameEvent], participant_id: ParticipantId) -> Vec<KillPosition> {
+    events
+        .iter()
+        .filter_map(|game_event| {
+            let Event::ChampionKill {
+                victim_id,
+                killer_id,
+                assisting_participant_ids,
+                position,
+            } = &game_event.event
+            else {
+                return None;
+            };
+
+            let role = if *killer_id == participant_id {
+                KillRole::Kill
+            } else if *victim_id == participant_id {
+                KillRole::Death
+            } else if assisting_participant_ids.contains(&participant_id) {
+                KillRole::Assist
+            } else {
+                return None;
+            };
+            Some(KillPosition {
+                role,
+                x: position.x,
+                y: position.y,
+                timestamp: game_event.timestamp,
+            })
+        })
+        .collect()
 }
 
 // seperate struct for frontend compatability since Specta is a bit limited for now and doesn't support some of the
@@ -162,6 +810,13 @@ pub enum Event {
         after_id: i64,
         gold_gain: i64,
     },
+    /// a jump in the creep score of the jungler between two polls of the live client API, which most likely is a jungle
+    /// camp. Neither the live API nor the match timeline report small monsters, so buffs, scuttle crabs and the other
+    /// camps can't be told apart.
+    JungleCampKill {
+        participant_id: ParticipantId,
+        creep_score_gained: i64,
+    },
 }
 
 #[derive(Debug, Clone)]
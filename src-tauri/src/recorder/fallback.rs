@@ -0,0 +1,113 @@
+//! Recording games while the League client isn't running (LeagueRecord got started after the game or the client
+//! crashed before it).
+//!
+//! The game is detected by its window and the Live Client Data API, which doesn't need the credentials of the client.
+//! Without the client there is no game id, so these recordings keep their Deferred metadata.
+
+use std::time::Duration;
+
+use riot_datatypes::MatchId;
+use shaco::ingame::IngameClient;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::{Receiver, Sender};
+use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+
+use super::game_listener::GameListener;
+use super::highlight_task::HighlightTask;
+use super::recording_task::{GameCtx, RecordingTask};
+use super::window;
+use super::StopReason;
+use crate::app::SystemTrayManager;
+use crate::jobs;
+use crate::power::PowerEvent;
+use crate::state::{ListenerState, RecorderStatus, TrayStatus};
+
+pub(super) const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// the API doesn't answer every request while the game lags (e.g. alt-tabbing out of fullscreen)
+pub(super) const MAX_MISSED_POLLS: u32 = 3;
+
+pub struct FallbackCtx {
+    pub app_handle: AppHandle,
+    pub cancel_token: CancellationToken,
+    pub webcam_toggle_tx: Sender<()>,
+    pub push_to_talk_tx: Sender<bool>,
+}
+
+/// A game is running that is played (not a replay or a spectated game)
+pub async fn game_running() -> bool {
+    if window::get_lol_window().is_none() {
+        return false;
+    }
+
+    let ingame_client = IngameClient::new();
+    ingame_client.active_game().await && matches!(ingame_client.is_spectator_mode().await, Ok(false))
+}
+
+/// Record the running game until it gets closed
+pub async fn record_without_client(
+    ctx: FallbackCtx,
+    mut manual_stop_rx: Receiver<()>,
+    mut power_rx: Receiver<PowerEvent>,
+) {
+    log::info!("game is running without the League client, recording without game data");
+
+    let recording_task = RecordingTask::new(GameCtx {
+        app_handle: ctx.app_handle.clone(),
+        match_id: MatchId {
+            game_id: 0,
+            platform_id: String::new(),
+        },
+        cancel_token: ctx.cancel_token.child_token(),
+        webcam_toggle_tx: ctx.webcam_toggle_tx,
+        push_to_talk_tx: ctx.push_to_talk_tx,
+        subfolders: Vec::new(),
+        account: None,
+        lobby: None,
+        clash: None,
+        party: Vec::new(),
+    });
+    let highlight_task = HighlightTask::new(ctx.app_handle.clone());
+    set_state(&ctx.app_handle, ListenerState::Recording { game_id: 0 });
+
+    let ingame_client = IngameClient::new();
+    let mut timer = interval(POLL_INTERVAL);
+    let mut missed_polls = 0;
+    let stop_reason = loop {
+        tokio::select! {
+            _ = timer.tick() => {
+                missed_polls = if ingame_client.active_game().await { 0 } else { missed_polls + 1 };
+                if missed_polls == MAX_MISSED_POLLS {
+                    break StopReason::GameClosed;
+                }
+            }
+            Ok(_) = manual_stop_rx.recv() => break StopReason::Manual,
+            Ok(PowerEvent::Suspend) = power_rx.recv() => break StopReason::Suspended,
+            _ = ctx.cancel_token.cancelled() => break StopReason::Cancelled,
+        }
+    };
+    log::info!("stopping recording without game data: {stop_reason:?}");
+
+    let highlights = highlight_task.stop().await;
+    match recording_task.stop(stop_reason.clone()).await {
+        Ok(metadata) => GameListener::save_stop_info(&metadata, highlights),
+        Err(e) => log::error!("stopped recording task: {e}"),
+    }
+    set_state(&ctx.app_handle, ListenerState::Idle);
+
+    // don't start another recording of the same game
+    if matches!(stop_reason, StopReason::Manual) {
+        while !ctx.cancel_token.is_cancelled() && ingame_client.active_game().await {
+            timer.tick().await;
+        }
+    }
+}
+
+fn set_state(app_handle: &AppHandle, state: ListenerState) {
+    app_handle.state::<RecorderStatus>().set_listener(state);
+    jobs::set_paused(app_handle, matches!(state, ListenerState::Recording { .. }));
+    app_handle.set_tray_status(match state {
+        ListenerState::Idle => TrayStatus::Idle,
+        _ => TrayStatus::WaitingForGame,
+    });
+}
@@ -2,31 +2,55 @@ use std::ffi::OsStr;
 use std::fmt::Display;
 
 use anyhow::Result;
-use futures_util::StreamExt;
-use riot_datatypes::lcu::{GameData, GamePhase, SessionEventData, SubscriptionResponse};
-use riot_datatypes::{GameId, MatchId, Queue};
+use futures_util::{future, StreamExt};
+use riot_datatypes::lcu::{
+    ClashTournament, GameData, GamePhase, Lobby, LobbyMember, SessionEventData, SubscriptionResponse,
+};
+use riot_datatypes::{GameId, MatchId, Queue, QueueId};
 use riot_local_auth::Credentials;
 
+use shaco::ingame::IngameClient;
 use shaco::model::ingame::GameEvent as LiveGameEvent;
 use shaco::model::ws::{EventType, LcuSubscriptionType};
 use shaco::{rest::LcuRestClient, ws::LcuWebsocketClient};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::async_runtime;
 use tauri::async_runtime::JoinHandle;
 use tauri::{AppHandle, Manager};
-use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::{Receiver, Sender};
 use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::instrument::WithSubscriber;
 
+use super::fallback;
 use super::highlight_task::HighlightTask;
+use super::live_events::{self, LivePlayerEvent, LivePlayerEventKind, LivePlayerKey};
 use super::metadata;
+use super::metadata_trace;
+use super::prewarm::PrewarmedRecorder;
 use super::recording_task::{GameCtx, Metadata, RecordingTask};
-use crate::app::{action, AppEvent, EventManager};
-use crate::recorder::MetadataFile;
-use crate::state::SettingsWrapper;
+use super::window;
+use super::PipelineTimings;
+use crate::app::{action, AppEvent, EventManager, RecordingManager, SystemTrayManager};
+use crate::backup;
+use crate::error::RecorderError;
+use crate::jobs::{self, JobKind};
+use crate::notifier::{self, Toast};
+use crate::power::PowerEvent;
+use crate::recorder::{
+    self, Account, Clash, CustomLobby, Highlight, LobbyPlayer, MetadataFile, ScoreboardSnapshot, StopReason,
+};
+use crate::state::{
+    LastPipelineTimings, ListenerState, OrganizeBy, RecorderHistory, RecorderStatus, SettingsWrapper, TrayStatus,
+};
+use crate::telemetry::{self, TelemetryEvent};
+use crate::util;
 
 use super::lp_helper::fetch_current_lp;
+use super::mastery_helper::fetch_mastery;
 
 #[derive(Clone)]
 pub struct ApiCtx {
@@ -34,10 +58,22 @@ pub struct ApiCtx {
     pub credentials: Credentials,
     pub platform_id: String,
     pub cancel_token: CancellationToken,
+    pub webcam_toggle_tx: Sender<()>,
+    pub push_to_talk_tx: Sender<bool>,
+    /// the post-game data collections, so stopping the LeagueRecorder can wait for them
+    pub game_data_tasks: TaskTracker,
 }
 
 impl ApiCtx {
-    fn game_ctx(&self, game_id: GameId) -> GameCtx {
+    fn game_ctx(
+        &self,
+        game_id: GameId,
+        subfolders: Vec<String>,
+        account: Option<Account>,
+        lobby: Option<CustomLobby>,
+        clash: Option<Clash>,
+        party: Vec<String>,
+    ) -> GameCtx {
         GameCtx {
             app_handle: self.app_handle.clone(),
             match_id: MatchId {
@@ -45,10 +81,25 @@ impl ApiCtx {
                 platform_id: self.platform_id.clone(),
             },
             cancel_token: self.cancel_token.child_token(),
+            webcam_toggle_tx: self.webcam_toggle_tx.clone(),
+            push_to_talk_tx: self.push_to_talk_tx.clone(),
+            subfolders,
+            account,
+            lobby,
+            clash,
+            party,
         }
     }
 }
 
+/// data collected by the live client API poller while the game is running
+#[derive(Default)]
+struct LiveData {
+    events: Vec<LiveGameEvent>,
+    player_events: Vec<LivePlayerEvent>,
+    scoreboard: Option<ScoreboardSnapshot>,
+}
+
 #[derive(Default)]
 enum State {
     #[default]
@@ -57,10 +108,17 @@ enum State {
         RecordingTask,
         HighlightTask,
         JoinHandle<Vec<LiveGameEvent>>,
-        Arc<Mutex<Vec<LiveGameEvent>>>,
+        Arc<Mutex<LiveData>>,
         Option<i32>, // start_lp
     ),
-    EndOfGame(Metadata, Vec<LiveGameEvent>, Option<i32>), // start_lp
+    EndOfGame(Metadata, LiveData, Option<i32>, PipelineTimings), // start_lp
+}
+
+/// What drives the state machine: the events of the League client and the triggers of the app itself
+enum ListenerEvent {
+    Lcu(SubscriptionResponse),
+    /// stop a running recording regardless of the game phase (hotkey, PC going to sleep, end of the grace period, ...)
+    ForceStop(StopReason),
 }
 
 impl Display for State {
@@ -68,7 +126,7 @@ impl Display for State {
         match self {
             State::Idle => f.write_str("Idle"),
             State::Recording(_, _, _, _, _) => f.write_str("Recording"),
-            State::EndOfGame(metadata, _, _) => f.write_fmt(format_args!("EndOfGame({metadata})")),
+            State::EndOfGame(metadata, _, _, _) => f.write_fmt(format_args!("EndOfGame({metadata})")),
         }
     }
 }
@@ -78,150 +136,219 @@ pub struct GameListener {
     state: State,
     manual_stop_rx: Receiver<()>,
     manual_start_rx: Receiver<()>,
+    power_rx: Receiver<PowerEvent>,
     last_stopped_game_id: Option<GameId>,
+    // delayed stop of the recording (see 'stopGracePeriodSecs')
+    pending_stop: Option<(Instant, GamePhase)>,
 }
 
 impl GameListener {
     const GAMEFLOW_SESSION: &'static str = "/lol-gameflow/v1/session";
     const EOG_STATS_BLOCK: &'static str = "/lol-end-of-game/v1/eog-stats-block";
-
-    pub fn new(ctx: ApiCtx, manual_stop_rx: Receiver<()>, manual_start_rx: Receiver<()>) -> Self {
+    const GAME_VERSION: &'static str = "/lol-patch/v1/game-version";
+    const CURRENT_SUMMONER: &'static str = "/lol-summoner/v1/current-summoner";
+    const GAMEFLOW_PHASE: &'static str = "/lol-gameflow/v1/gameflow-phase";
+    const LOBBY: &'static str = "/lol-lobby/v2/lobby";
+    const SUMMONER_BY_PUUID: &'static str = "/lol-summoner/v2/summoners/puuid/";
+    const CLASH_TOURNAMENT_IDS: &'static str = "/lol-clash/v1/currentTournamentIds";
+    const CLASH_TOURNAMENT: &'static str = "/lol-clash/v1/tournament/";
+    const CUSTOM_QUEUE_ID: QueueId = 0;
+    // Summoner's Rift and ARAM Clash
+    const CLASH_QUEUE_IDS: [QueueId; 2] = [700, 720];
+    const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+    // used instead of grace periods that are too long for an Instant, no game lasts this long
+    const MAX_GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+    // large jungle monsters are worth multiple creep score at once while last-hitting lane minions rarely yields
+    // this much within a single poll interval
+    const JUNGLE_CAMP_MIN_CREEP_SCORE: i32 = 4;
+    // the poll interval isn't exact so small differences between real time and ingame time are expected
+    const MIN_PAUSE_DURATION: f64 = 2.0;
+
+    pub fn new(
+        ctx: ApiCtx,
+        manual_stop_rx: Receiver<()>,
+        manual_start_rx: Receiver<()>,
+        power_rx: Receiver<PowerEvent>,
+    ) -> Self {
         Self {
             ctx,
             state: State::Idle,
             manual_stop_rx,
             manual_start_rx,
+            power_rx,
             last_stopped_game_id: None,
+            pending_stop: None,
         }
     }
 
-    async fn run_info_poller(live_events: Arc<Mutex<Vec<LiveGameEvent>>>) -> Vec<LiveGameEvent> {
+    async fn run_info_poller(live_data: Arc<Mutex<LiveData>>, jitter: Duration) -> Vec<LiveGameEvent> {
         let client = shaco::ingame::IngameClient::new();
-        let mut last_event_id = 0;
+        let mut seen_events = HashSet::new();
+        let mut last_event_id = None;
+        let mut failed_polls = 0;
         // Cache: ParticipantIndex -> List of Items
         let mut previous_inventory: HashMap<usize, Vec<shaco::model::ingame::PlayerItem>> = HashMap::new();
+        // Cache: ParticipantIndex -> Creep Score (junglers only)
+        let mut previous_creep_score: HashMap<usize, i32> = HashMap::new();
+        // (game_time, instant) of the last poll and of the last poll before the game time stopped advancing
+        let mut last_poll: Option<(f64, Instant)> = None;
+        let mut pause_start: Option<(f64, Instant)> = None;
 
         loop {
-            // Poll every 1 second
-            tokio::time::sleep(Duration::from_secs(1)).await;
+            tokio::time::sleep(live_events::poll_delay(failed_polls, jitter)).await;
 
-            match client.all_game_data(Some(last_event_id as u32)).await {
+            // after failed polls ask for the full event list to catch up on the events that happened in between
+            let event_id = if failed_polls == 0 { last_event_id } else { None };
+            match client.all_game_data(event_id).await {
                 Ok(data) => {
+                    if failed_polls > 0 {
+                        log::info!("live client API is available again after {failed_polls} failed polls");
+                        failed_polls = 0;
+                    }
+
                     let game_time = data.game_data.game_time;
+                    let now = Instant::now();
                     let mut new_events = Vec::new();
+                    let mut new_player_events = Vec::new();
+
+                    // 0. Detect Pauses (game time not advancing while real time does)
+                    if let Some((last_game_time, last_instant)) = last_poll {
+                        if game_time <= last_game_time {
+                            pause_start.get_or_insert((last_game_time, last_instant));
+                        } else if let Some((paused_game_time, paused_instant)) = pause_start.take() {
+                            let real_time = now.duration_since(paused_instant).as_secs_f64();
+                            let pause_duration = real_time - (game_time - paused_game_time);
+                            if pause_duration >= Self::MIN_PAUSE_DURATION {
+                                log::info!("detected pause at {paused_game_time:.1}s for {pause_duration:.1}s");
+                                new_events.push(LiveGameEvent::GamePaused(shaco::model::ingame::GamePaused {
+                                    event_id: 0, // Synthetic Only
+                                    event_time: paused_game_time,
+                                    pause_duration,
+                                }));
+                            }
+                        }
+                    }
+                    last_poll = Some((game_time, now));
 
                     // 1. Process Standard Events (Kill, Dragon, etc.)
-                    for event in data.events {
-                        let eid = event.get_event_id();
-                        if eid > last_event_id as u32 {
-                            last_event_id = eid as i32;
-                            new_events.push(event);
-                        }
+                    // the ids start over if the game client restarts (e.g. after reconnecting)
+                    if let Some(event_id) = data.events.iter().map(LiveGameEvent::get_event_id).max() {
+                        last_event_id = Some(event_id);
                     }
+                    new_events.extend(live_events::unseen_events(data.events, &mut seen_events));
 
                     // 2. Process Inventory Diffs (Synthetic Item Events)
                     for (i, player) in data.all_players.iter().enumerate() {
-                        let name = player.summoner_name.clone();
-                        let current_items = player.items.clone();
+                        let key = LivePlayerKey::new(i, player);
 
                         // Use Index as key to avoid duplicate name collision (Sivir Bot vs Sivir Bot)
                         let old_items = previous_inventory.entry(i).or_default();
-
-                        let mut old_counts: HashMap<i32, i32> = HashMap::new();
-                        for item in old_items.iter() {
-                            *old_counts.entry(item.item_id).or_default() += 1;
-                        }
-
-                        let mut new_counts: HashMap<i32, i32> = HashMap::new();
-                        for item in current_items.iter() {
-                            *new_counts.entry(item.item_id).or_default() += 1;
-                        }
-
-                        // Detect Sold (Old has it, New doesn't)
-                        for (id, count) in &old_counts {
-                            let new_c = new_counts.get(id).cloned().unwrap_or(0);
-                            if *count > new_c {
-                                // Sold (count - new_c) times
-                                let diff = count - new_c;
-                                // Find the full item struct from old_items
-                                if let Some(item_struct) = old_items.iter().find(|i| i.item_id == *id) {
-                                    for _ in 0..diff {
-                                        // Use Name + Team identifier.
-                                        // Team is required to disambiguate bots with same name on different teams.
-                                        let team_intro = match player.team {
-                                            shaco::model::ingame::TeamId::Chaos => "200",
-                                            shaco::model::ingame::TeamId::Order => "100",
-                                            _ => "100", // Default or Neutral?
-                                        };
-                                        // SkinID logic: ChampionID * 1000 + SkinIndex
-                                        let unique_name =
-                                            format!("{}#TEAM:{}#CNAME:{}", name, team_intro, player.champion_name);
-
-                                        // println!("DEBUG: Generated Event ItemSold for {}", unique_name);
-
-                                        new_events.push(LiveGameEvent::ItemSold(shaco::model::ingame::ItemSold {
-                                            event_id: 0, // Synthetic Only
-                                            event_time: game_time,
-                                            item: item_struct.clone(),
-                                            shopper_name: unique_name,
-                                        }));
-                                    }
-                                }
+                        let (purchased, sold) = live_events::inventory_diff(old_items, &player.items);
+                        for (items, purchase) in [(sold, false), (purchased, true)] {
+                            for item in items {
+                                let item_id = item.item_id as i64;
+                                let slot = item.slot as i64;
+                                new_player_events.push(LivePlayerEvent {
+                                    player: key.clone(),
+                                    event_time: game_time,
+                                    kind: if purchase {
+                                        LivePlayerEventKind::ItemPurchased { item_id, slot }
+                                    } else {
+                                        LivePlayerEventKind::ItemSold { item_id, slot }
+                                    },
+                                });
                             }
                         }
 
-                        // Detect Purchased (New has it, Old doesn't)
-                        for (id, count) in &new_counts {
-                            let old_c = old_counts.get(id).cloned().unwrap_or(0);
-                            if *count > old_c {
-                                // Purchased (count - old_c) times
-                                let diff = count - old_c;
-                                // Find the full item struct
-                                if let Some(item_struct) = current_items.iter().find(|i| i.item_id == *id) {
-                                    for _ in 0..diff {
-                                        // Use Name + Team identifier.
-                                        let team_intro = match player.team {
-                                            shaco::model::ingame::TeamId::Chaos => "200",
-                                            shaco::model::ingame::TeamId::Order => "100",
-                                            _ => "100",
-                                        };
-                                        let unique_name =
-                                            format!("{}#TEAM:{}#CNAME:{}", name, team_intro, player.champion_name);
-
-                                        // println!("DEBUG: Generated Event ItemPurchased for {}", unique_name);
-
-                                        new_events.push(LiveGameEvent::ItemPurchased(
-                                            shaco::model::ingame::ItemPurchased {
-                                                event_id: 0, // Synthetic Only
-                                                event_time: game_time,
-                                                item: item_struct.clone(),
-                                                shopper_name: unique_name,
-                                            },
-                                        ));
-                                    }
+                        // Update cache
+                        old_items.clone_from(&player.items);
+
+                        // 3. Process Jungler Creep Score Diffs (most likely jungle camps, the monster is unknown)
+                        if matches!(player.position, shaco::model::ingame::Position::Jungle) {
+                            let creep_score = player.scores.creep_score;
+                            if let Some(old_creep_score) = previous_creep_score.insert(i, creep_score) {
+                                let gained = creep_score - old_creep_score;
+                                if gained >= Self::JUNGLE_CAMP_MIN_CREEP_SCORE {
+                                    new_player_events.push(LivePlayerEvent {
+                                        player: key,
+                                        event_time: game_time,
+                                        kind: LivePlayerEventKind::JungleCampKill {
+                                            creep_score_gained: gained as i64,
+                                        },
+                                    });
                                 }
                             }
                         }
-
-                        // Update cache
-                        *old_items = current_items;
                     }
 
-                    if !new_events.is_empty() {
-                        if let Ok(mut events) = live_events.lock() {
-                            events.extend(new_events);
-                        }
+                    // 4. Keep the latest scoreboard (the last one before the game closes gets saved)
+                    let scoreboard = ScoreboardSnapshot::new(game_time, &data.all_players);
+
+                    if let Ok(mut live_data) = live_data.lock() {
+                        live_data.events.extend(new_events);
+                        live_data.player_events.extend(new_player_events);
+                        live_data.scoreboard = Some(scoreboard);
                     }
                 }
-                Err(_e) => {
-                    // Ignore errors (game loading, etc)
-                    // log::warn!("Poll failed: {}", e);
+                // the API isn't available while the game is loading, back off until it is
+                Err(e) => {
+                    failed_polls += 1;
+                    log::debug!("live client API poll failed ({failed_polls} in a row): {e}");
                 }
             }
         }
     }
 
+    /// Start polling the live client API, the poller adds everything it collects to the returned `LiveData`
+    fn spawn_live_poller(&self) -> (JoinHandle<Vec<LiveGameEvent>>, Arc<Mutex<LiveData>>) {
+        let live_data = Arc::new(Mutex::new(LiveData::default()));
+        let jitter = self.ctx.app_handle.state::<SettingsWrapper>().live_poll_jitter();
+        let live_task = async_runtime::spawn(Self::run_info_poller(live_data.clone(), jitter));
+        (live_task, live_data)
+    }
+
     pub async fn run(&mut self) -> Result<()> {
+        loop {
+            let connection_lost = match self.listen().await {
+                Ok(connection_lost) => connection_lost,
+                // without a game in progress the LeagueRecorder just starts a new listener
+                Err(e) if matches!(self.state, State::Idle) => return Err(e),
+                Err(e) => {
+                    log::warn!("failed to listen to the League client: {e}");
+                    true
+                }
+            };
+            // keep the recording (or the game data collection) going until the client is back after a crash or restart
+            if !connection_lost || matches!(self.state, State::Idle) || !self.reconnect().await {
+                break;
+            }
+        }
+
+        // when the app gets closed the game data gets collected by a job on the next start
+        let shutting_down = self.ctx.cancel_token.is_cancelled();
+        match std::mem::take(&mut self.state) {
+            State::Recording(recording_task, highlight_task, live_task, _, _) => {
+                let highlights = highlight_task.stop().await;
+                live_task.abort();
+                if let Ok(metadata) = recording_task.stop(StopReason::Cancelled).await {
+                    Self::save_stop_info(&metadata, highlights);
+                    if shutting_down {
+                        jobs::enqueue(&self.ctx.app_handle, JobKind::Metadata, &metadata.output_filepath);
+                    }
+                }
+            }
+            State::EndOfGame(metadata, ..) if shutting_down => {
+                jobs::enqueue(&self.ctx.app_handle, JobKind::Metadata, &metadata.output_filepath);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Handle the events of the League client until the websocket connection gets closed (returns true) or the
+    /// listener gets cancelled (returns false)
+    async fn listen(&mut self) -> Result<bool> {
         let mut lcu_ws_client = LcuWebsocketClient::connect_with(&self.ctx.credentials).await?;
         lcu_ws_client
             .subscribe(LcuSubscriptionType::JsonApiEvent(Self::GAMEFLOW_SESSION.into()))
@@ -233,22 +360,27 @@ impl GameListener {
         let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
         match lcu_rest_client.get::<SessionEventData>(Self::GAMEFLOW_SESSION).await {
             Ok(init_event_data) => {
-                self.state_transition(SubscriptionResponse::Session(init_event_data), false)
+                self.state_transition(ListenerEvent::Lcu(SubscriptionResponse::Session(init_event_data)))
                     .await
             }
             Err(e) => log::info!("no initial event-data: {e}"),
         }
+        // the end of game event got lost while the client was gone, after a restart the match history is available
+        if matches!(self.state, State::EndOfGame(..)) {
+            self.state_transition(ListenerEvent::Lcu(SubscriptionResponse::EogStatsBlock {}))
+                .await;
+        }
 
         loop {
             tokio::select! {
                 maybe_event = lcu_ws_client.next() => {
-                    let Some(event) = maybe_event else { break };
+                    let Some(event) = maybe_event else { return Ok(true) };
                     if event.payload.event_type != EventType::Update {
                         continue;
                     }
 
                     match serde_json::from_value::<SubscriptionResponse>(event.payload.data) {
-                        Ok(event_data) => self.state_transition(event_data, false).await,
+                        Ok(event_data) => self.state_transition(ListenerEvent::Lcu(event_data)).await,
                         Err(e) => {
                             log::error!("failed to deserialize event: {e}");
                             continue;
@@ -257,14 +389,7 @@ impl GameListener {
                 }
                 Ok(_) = self.manual_stop_rx.recv() => {
                     log::info!("Manual stop triggered via hotkey");
-                    self.state_transition(SubscriptionResponse::Session(SessionEventData {
-                        phase: GamePhase::PreEndOfGame,
-                        game_data: GameData {
-                            game_id: 0,
-                            queue: Queue { id: 0, is_ranked: false, name: "".into() },
-                            game_mode: None,
-                        },
-                    }), true).await;
+                    self.state_transition(ListenerEvent::ForceStop(StopReason::Manual)).await;
                 }
                 Ok(_) = self.manual_start_rx.recv() => {
                     log::info!("Manual start triggered via hotkey");
@@ -281,18 +406,31 @@ impl GameListener {
 
                                     if should_start {
                                         log::info!("Manual start: Game detected (ID: {}). Forcing start.", data.game_data.game_id);
-                                         let live_events = Arc::new(Mutex::new(Vec::new()));
-                                         let live_events_clone = live_events.clone();
-                                         let live_task = async_runtime::spawn(Self::run_info_poller(live_events_clone));
-
+                                         let (live_task, live_data) = self.spawn_live_poller();
+
+                                         let GameData { queue, game_mode, game_id } = &data.game_data;
+                                         // the LCU requests don't depend on each other, don't wait for them one by one
+                                         let (account, lobby, clash, party) = tokio::join!(
+                                            self.current_account(),
+                                            self.custom_lobby(queue),
+                                            self.clash_tournament(queue),
+                                            self.party(queue),
+                                         );
+                                         let game_mode = game_mode.as_deref();
+                                         let subfolders = self
+                                            .recording_subfolders(queue, game_mode, account.as_ref())
+                                            .await;
+                                         let game_ctx =
+                                            self.ctx.game_ctx(*game_id, subfolders, account, lobby, clash, party);
                                          self.state = State::Recording(
-                                            RecordingTask::new(self.ctx.game_ctx(data.game_data.game_id)),
+                                            RecordingTask::new(game_ctx),
                                             HighlightTask::new(self.ctx.app_handle.clone()),
                                             live_task,
-                                            live_events,
+                                            live_data,
                                             None, // start_lp (Manual start assumes no LP tracking or we could try fetch)
                                         );
-                                        log::info!("recorder state: {}", self.state);
+                                        self.publish_state();
+                                        self.update_tray_status();
                                     } else {
                                         log::info!("Manual start ignored: Already recording.");
                                     }
@@ -305,27 +443,106 @@ impl GameListener {
                         Err(e) => log::error!("Manual start failed to get session data: {e}"),
                     }
                 }
-                _ = self.ctx.cancel_token.cancelled() => break,
+                // disabled branches still get evaluated so there has to be some deadline
+                _ = tokio::time::sleep_until(self.pending_stop.map_or_else(Instant::now, |(deadline, _)| deadline).into()),
+                    if self.pending_stop.is_some() =>
+                {
+                    let Some((_, phase)) = self.pending_stop else { continue };
+                    log::info!("stop grace period is over");
+                    self.state_transition(ListenerEvent::ForceStop(StopReason::Phase(phase))).await;
+                }
+                Ok(event) = self.power_rx.recv() => match event {
+                    PowerEvent::Suspend => {
+                        log::info!("stopping recording before the PC goes to sleep");
+                        self.state_transition(ListenerEvent::ForceStop(StopReason::Suspended)).await;
+                    }
+                    // the websocket connection doesn't survive the sleep and the client could have been restarted
+                    PowerEvent::Resume => {
+                        // the notification before the sleep can get lost, don't keep recording a game that is gone
+                        if matches!(self.state, State::Recording(..)) && window::get_lol_window().is_none() {
+                            log::info!("game is gone after waking up, stopping recording");
+                            self.state_transition(ListenerEvent::ForceStop(StopReason::Suspended)).await;
+                        }
+                        return Ok(true);
+                    }
+                    // locking the session doesn't close the connection, only reconnect if it is gone anyway
+                    PowerEvent::Unlock => {
+                        let subscribed = lcu_ws_client
+                            .subscribe(LcuSubscriptionType::JsonApiEvent(Self::GAMEFLOW_SESSION.into()))
+                            .await
+                            .is_ok();
+                        if !subscribed || lcu_rest_client.get::<String>(Self::GAMEFLOW_PHASE).await.is_err() {
+                            log::info!("connection to the League client is gone after unlocking the session");
+                            return Ok(true);
+                        }
+                    }
+                },
+                _ = self.ctx.cancel_token.cancelled() => return Ok(false),
             }
         }
+    }
 
-        if let State::Recording(recording_task, highlight_task, live_task, _, _) = std::mem::take(&mut self.state) {
-            _ = recording_task.stop().await;
-            _ = highlight_task.stop().await;
-            live_task.abort();
+    /// Wait for the League client to come back after the connection got lost (e.g. the client crashed during the game).
+    /// Returns false if there is nothing to wait for anymore (cancelled or the recording got stopped and discarded).
+    async fn reconnect(&mut self) -> bool {
+        log::warn!(
+            "lost the connection to the League client ({}), waiting for it to come back",
+            self.state
+        );
+        self.ctx.app_handle.state::<RecorderStatus>().set_lcu_connected(false);
+
+        let mut interval = tokio::time::interval(Self::RECONNECT_INTERVAL);
+        // without the client nothing else notices that the game ended
+        let ingame_client = IngameClient::new();
+        let mut game_check = tokio::time::interval(fallback::POLL_INTERVAL);
+        let mut missed_polls = 0;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let Ok(credentials) = riot_local_auth::lcu::try_get_credentials() else { continue };
+                    // the client doesn't answer requests right after it started
+                    let lcu_rest_client = LcuRestClient::from(&credentials);
+                    if lcu_rest_client.get::<String>(Self::GAMEFLOW_PHASE).await.is_ok() {
+                        log::info!("reconnected to the League client");
+                        self.ctx.credentials = credentials;
+                        self.ctx.app_handle.state::<RecorderStatus>().set_lcu_connected(true);
+                        return true;
+                    }
+                }
+                Ok(_) = self.manual_stop_rx.recv() => {
+                    log::info!("Manual stop triggered via hotkey");
+                    self.state_transition(ListenerEvent::ForceStop(StopReason::Manual)).await;
+                    if matches!(self.state, State::Idle) {
+                        return false;
+                    }
+                }
+                Ok(PowerEvent::Suspend) = self.power_rx.recv() => {
+                    log::info!("stopping recording before the PC goes to sleep");
+                    self.state_transition(ListenerEvent::ForceStop(StopReason::Suspended)).await;
+                }
+                _ = game_check.tick(), if matches!(self.state, State::Recording(..)) => {
+                    missed_polls = if ingame_client.active_game().await { 0 } else { missed_polls + 1 };
+                    if missed_polls == fallback::MAX_MISSED_POLLS {
+                        log::info!("the game got closed while the League client was gone");
+                        self.state_transition(ListenerEvent::ForceStop(StopReason::GameClosed)).await;
+                        if matches!(self.state, State::Idle) {
+                            return false;
+                        }
+                    }
+                }
+                _ = self.ctx.cancel_token.cancelled() => return false,
+            }
         }
-
-        Ok(())
     }
 
-    async fn state_transition(&mut self, sub_resp: SubscriptionResponse, is_manual_stop: bool) {
+    async fn state_transition(&mut self, event: ListenerEvent) {
         self.state = match std::mem::take(&mut self.state) {
             // wait for game to record
-            State::Idle => match sub_resp {
-                SubscriptionResponse::Session(SessionEventData {
+            State::Idle => match event {
+                ListenerEvent::Lcu(SubscriptionResponse::Session(SessionEventData {
                     phase: GamePhase::GameStart | GamePhase::InProgress,
                     game_data: GameData { queue, game_id, game_mode },
-                }) if Some(game_id) != self.last_stopped_game_id => {
+                })) if Some(game_id) != self.last_stopped_game_id => {
                     log::info!("LCU Session Event detected. GameID: {}", game_id);
                     log::info!(
                         "Raw GameData: queue_id={}, queue_name='{}', is_ranked={}, game_mode='{:?}'",
@@ -346,54 +563,14 @@ impl GameListener {
                         log::error!("Failed to emit GameDetected event: {}", e);
                     }
 
-                    let mut is_mode_allowed = true;
-
-                    if let Some(modes) = allowed_modes {
-                        // Prioritize QueueID mapping for known queues to ensure consistency
-                        let mode_val = match queue.id {
-                            420 | 440 => "RANKED".to_string(),
-                            400 | 430 => "NORMAL".to_string(), // Removed 480/490 from NORMAL
-                            480 | 490 => "SWIFTPLAY".to_string(), // Explicit Swiftplay mapping
-                            450 | 100 => "ARAM".to_string(),
-                            3140 => "PRACTICE_TOOL".to_string(),
-                            1700 => "CHERRY".to_string(),
-                            830 | 840 | 850 | 890 => "COOP_VS_AI".to_string(),
-                            1090 | 1100 | 1130 | 1160 => "TFT".to_string(),
-                            0 => "CUSTOM".to_string(),
-                            _ => match game_mode.clone() {
-                                Some(s) => s,
-                                None => "UNKNOWN".to_string(),
-                            },
-                        };
-
-                        let mode_upper = mode_val.to_uppercase();
-
-                        // Check if allowed directly
-                        is_mode_allowed = modes.iter().any(|m| m.to_uppercase() == mode_upper);
-
-                        // Check "OTHER" category
-                        if !is_mode_allowed {
-                            let standard_modes = [
-                                "RANKED",
-                                "NORMAL",
-                                "ARAM",
-                                "PRACTICE_TOOL",
-                                "CHERRY",
-                                "COOP_VS_AI",
-                                "TFT",
-                                "CUSTOM",
-                                "SWIFTPLAY",
-                            ];
-                            let is_standard = standard_modes.contains(&mode_upper.as_str());
-
-                            if !is_standard && modes.iter().any(|m| m == "OTHER") {
-                                is_mode_allowed = true;
-                                log::info!("Game Mode '{}' allowed via OTHER category.", mode_upper);
-                            }
-                        }
+                    let is_mode_allowed = is_mode_allowed(allowed_modes.as_deref(), &queue, game_mode.as_deref());
 
+                    if allowed_modes.is_some() {
+                        let mode_upper = game_mode_category(&queue, game_mode.as_deref()).to_uppercase();
                         if !is_mode_allowed {
                             log::info!("Game Mode '{}' NOT in allowed list. Skipping recording.", mode_upper);
+                            // the recorder got started during champ select before the game mode was known for sure
+                            self.ctx.app_handle.state::<PrewarmedRecorder>().discard();
                         } else {
                             log::info!("Game Mode '{}' ALLOWED. Starting...", mode_upper);
                         }
@@ -405,62 +582,83 @@ impl GameListener {
                             self.last_stopped_game_id = None;
                         }
 
-                        let live_events = Arc::new(Mutex::new(Vec::new()));
-                        let live_events_clone = live_events.clone();
-                        let live_task = async_runtime::spawn(Self::run_info_poller(live_events_clone));
+                        let (live_task, live_data) = self.spawn_live_poller();
 
-                        let start_lp = if queue.is_ranked {
-                            fetch_current_lp(&self.ctx.credentials).await
-                        } else {
-                            None
+                        let start_lp = async {
+                            if queue.is_ranked {
+                                fetch_current_lp(&self.ctx.credentials).await
+                            } else {
+                                None
+                            }
                         };
+                        // the LCU requests don't depend on each other, don't wait for them one by one
+                        let (start_lp, account, lobby, clash, party) = tokio::join!(
+                            start_lp,
+                            self.current_account(),
+                            self.custom_lobby(&queue),
+                            self.clash_tournament(&queue),
+                            self.party(&queue),
+                        );
 
                         if let Some(lp) = start_lp {
                             log::info!("Ranked Game Detected. Start LP: {}", lp);
                         }
 
+                        let subfolders = self
+                            .recording_subfolders(&queue, game_mode.as_deref(), account.as_ref())
+                            .await;
+                        let game_ctx = self.ctx.game_ctx(game_id, subfolders, account, lobby, clash, party);
                         State::Recording(
-                            RecordingTask::new(self.ctx.game_ctx(game_id)),
+                            RecordingTask::new(game_ctx),
                             HighlightTask::new(self.ctx.app_handle.clone()),
                             live_task,
-                            live_events,
+                            live_data,
                             start_lp,
                         )
                     } else {
                         State::Idle
                     }
                 }
+                ListenerEvent::Lcu(SubscriptionResponse::Session(SessionEventData { phase, game_data })) => {
+                    self.prewarm_recorder(phase, &game_data);
+                    State::Idle
+                }
                 _ => State::Idle,
             },
 
             // wait for game to end => stop recording
-            State::Recording(recording_task, highlight_task, live_task, live_events_arc, start_lp) => match sub_resp {
-                SubscriptionResponse::Session(SessionEventData {
-                    phase:
-                        phase @ (GamePhase::FailedToLaunch
-                        | GamePhase::Reconnect
-                        | GamePhase::WaitingForStats
-                        | GamePhase::PreEndOfGame),
-                    ..
-                }) => {
-                    log::info!("stopping recording due to session event phase: {phase:?}");
+            State::Recording(recording_task, highlight_task, live_task, live_data_arc, start_lp) => match self
+                .stop_reason(event, recording_task.ctx.match_id.game_id)
+            {
+                None => State::Recording(recording_task, highlight_task, live_task, live_data_arc, start_lp),
+                Some(stop_reason) => {
+                    log::info!("stopping recording: {stop_reason:?}");
+                    self.pending_stop = None;
+                    let is_manual_stop = matches!(stop_reason, StopReason::Manual);
+                    // when stopping this late the game data is already available => don't wait for another event
+                    let game_data_available = matches!(
+                        stop_reason,
+                        StopReason::Phase(GamePhase::EndOfGame | GamePhase::TerminatedInError)
+                    );
 
                     // Capture game_id before consuming recording_task
                     let stopped_game_id = recording_task.ctx.match_id.game_id;
                     self.last_stopped_game_id = Some(stopped_game_id);
 
+                    let mut timings = PipelineTimings::new(recording_task.ctx.match_id.clone());
+
                     // make sure the task stops
-                    let highlight_data = highlight_task.stop().await;
+                    let highlight_data = timings.time("highlight_task_stop", highlight_task.stop()).await;
 
                     // Abort live task and get events (best effort, or we could signal it to stop)
                     // Abort live task
                     live_task.abort();
 
-                    // Since we share the Arc<Mutex<Vec>>, we can just read from the Arc we stored in State
-                    let collected_events = if let Ok(events) = live_events_arc.lock() {
-                        events.clone()
+                    // Since we share the Arc<Mutex<LiveData>>, we can just take the data from the Arc we stored in State
+                    let collected_data = if let Ok(mut live_data) = live_data_arc.lock() {
+                        std::mem::take(&mut *live_data)
                     } else {
-                        vec![]
+                        LiveData::default()
                     };
 
                     // Re-match to get access to fields safely
@@ -468,22 +666,22 @@ impl GameListener {
                     // So we should rely on the Arc.
                     // Let's modify the match arm to capture the Arc.
 
-                    match recording_task.stop().await {
-                        Ok(metadata) => {
-                            let mut metadata_filepath = metadata.output_filepath.clone();
-                            metadata_filepath.set_extension("json");
-
-                            if let Ok(MetadataFile::Deferred(mut deferred)) =
-                                action::get_recording_metadata(&metadata_filepath, false)
-                            {
-                                deferred.highlights = highlight_data;
-                                if let Err(e) = action::save_recording_metadata(
-                                    &metadata_filepath,
-                                    &MetadataFile::Deferred(deferred),
-                                ) {
-                                    log::warn!("failed to write highlight data to deferred metadata file: {e}");
-                                }
+                    match timings.time("stop_recording", recording_task.stop(stop_reason)).await {
+                        Ok(metadata) if self.is_too_short(&metadata) => {
+                            self.discard_recording(&metadata);
+                            State::Idle
+                        }
+                        // the recorder failed before it wrote anything (see RecordingFailed)
+                        Ok(metadata) if Self::is_empty(&metadata) => {
+                            log::warn!("discarding empty recording {}", metadata.output_filepath.display());
+                            if let Err(e) = action::discard_recording(&metadata.output_filepath, false) {
+                                log::error!("failed to discard recording: {e}");
                             }
+                            State::Idle
+                        }
+                        Ok(metadata) => {
+                            timings.time_sync("highlight_save", || Self::save_stop_info(&metadata, highlight_data));
+                            telemetry::record(&self.ctx.app_handle, TelemetryEvent::RecordingMade);
 
                             // EMIT RECORDING FINISHED
                             if let Some(video_name) = metadata.output_filepath.file_name().and_then(|n| n.to_str()) {
@@ -492,6 +690,7 @@ impl GameListener {
                                 }) {
                                     log::error!("failed to emit RecordingFinished: {e}");
                                 }
+                                notifier::show_toast(&self.ctx.app_handle, Toast::RecordingFinished(video_name));
 
                                 // Auto PopUp Logic (Server-side reliability)
                                 if !is_manual_stop {
@@ -511,88 +710,583 @@ impl GameListener {
                                 }
                             }
 
-                            State::EndOfGame(metadata, collected_events, start_lp)
+                            if game_data_available {
+                                self.collect_game_data(metadata, collected_data, start_lp, timings);
+                                State::Idle
+                            } else {
+                                State::EndOfGame(metadata, collected_data, start_lp, timings)
+                            }
                         }
                         Err(e) => {
                             log::error!("stopped recording task: {e}");
+                            // RecorderErrors were already reported by the recording task
+                            if RecorderError::find(&e).is_none() {
+                                self.ctx
+                                    .app_handle
+                                    .state::<RecorderHistory>()
+                                    .push_error(format!("stopped recording task: {e}"));
+                            }
                             State::Idle
                         }
                     }
                 }
-                _ => State::Recording(recording_task, highlight_task, live_task, live_events_arc, start_lp),
             },
 
             // wait for game-data to become available
-            State::EndOfGame(metadata, live_events, start_lp) => match sub_resp {
-                ws_msg @ (SubscriptionResponse::EogStatsBlock {}
-                | SubscriptionResponse::Session(SessionEventData {
-                    phase:
-                        GamePhase::EndOfGame | GamePhase::TerminatedInError | GamePhase::ChampSelect | GamePhase::GameStart,
-                    ..
-                })) => {
+            State::EndOfGame(metadata, live_data, start_lp, timings) => match event {
+                ListenerEvent::Lcu(
+                    ws_msg @ (SubscriptionResponse::EogStatsBlock {}
+                    | SubscriptionResponse::Session(SessionEventData {
+                        phase:
+                            GamePhase::EndOfGame
+                            | GamePhase::TerminatedInError
+                            | GamePhase::ChampSelect
+                            | GamePhase::GameStart,
+                        ..
+                    })),
+                ) => {
                     // ... (omitted similar logic for EndOfGame processing, using self.ctx)
                     // Re-implementing the block to ensure context is correct
                     log::info!("triggered game-data collection due to msg: {ws_msg:?}");
 
-                    let ctx = self.ctx.clone();
-                    async_runtime::spawn(async move {
-                        let Metadata {
-                            match_id,
-                            output_filepath,
-                            ingame_time_rec_start_offset,
-                        } = metadata;
-
-                        let mut metadata_filepath = output_filepath;
-                        let video_id = metadata_filepath.file_name().and_then(OsStr::to_str).map(str::to_owned);
-                        metadata_filepath.set_extension("json");
-
-                        match metadata::process_data_with_retry(
-                            ingame_time_rec_start_offset,
-                            match_id,
-                            &ctx.credentials,
-                            &ctx.cancel_token,
-                            live_events,
+                    self.collect_game_data(metadata, live_data, start_lp, timings);
+
+                    State::Idle
+                }
+                _ => State::EndOfGame(metadata, live_data, start_lp, timings),
+            },
+        };
+
+        self.publish_state();
+        self.update_tray_status();
+    }
+
+    /// Why the event stops the running recording of `game_id` (None = keep recording)
+    fn stop_reason(&mut self, event: ListenerEvent, game_id: GameId) -> Option<StopReason> {
+        let stop_phases = self.ctx.app_handle.state::<SettingsWrapper>().stop_phases();
+
+        match event {
+            ListenerEvent::ForceStop(stop_reason) => Some(stop_reason),
+            // keep recording the victory/defeat screen for a bit
+            ListenerEvent::Lcu(SubscriptionResponse::Session(SessionEventData { phase, .. }))
+                if stop_phases.contains(&phase) && self.in_stop_grace_period(phase) =>
+            {
+                None
+            }
+            // reconnected to the same game in time => keep recording into the same file
+            ListenerEvent::Lcu(SubscriptionResponse::Session(SessionEventData {
+                phase: GamePhase::GameStart | GamePhase::InProgress,
+                game_data,
+            })) if matches!(self.pending_stop, Some((_, GamePhase::Reconnect))) && game_data.game_id == game_id => {
+                log::info!("reconnected to the game, continuing the recording");
+                self.pending_stop = None;
+                None
+            }
+            // a new game starting cuts the grace period short
+            ListenerEvent::Lcu(SubscriptionResponse::Session(SessionEventData { phase, .. }))
+                if stop_phases.contains(&phase)
+                    || (self.pending_stop.is_some()
+                        && matches!(phase, GamePhase::ChampSelect | GamePhase::GameStart)) =>
+            {
+                Some(StopReason::Phase(phase))
+            }
+            _ => None,
+        }
+    }
+
+    /// Riot account that is logged into the client, stored in the metadata to tell the recordings of different accounts
+    /// on the same PC apart
+    async fn current_account(&self) -> Option<Account> {
+        let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
+        match lcu_rest_client.get::<Account>(Self::CURRENT_SUMMONER).await {
+            Ok(account) => Some(account),
+            Err(e) => {
+                log::warn!("failed to get the current account: {e}");
+                None
+            }
+        }
+    }
+
+    /// Name and teams of the lobby if the game is a custom game
+    async fn custom_lobby(&self, queue: &Queue) -> Option<CustomLobby> {
+        if queue.id != Self::CUSTOM_QUEUE_ID {
+            return None;
+        }
+
+        let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
+        let game_config = match lcu_rest_client.get::<Lobby>(Self::LOBBY).await {
+            Ok(lobby) if lobby.game_config.is_custom => lobby.game_config,
+            Ok(_) => return None,
+            Err(e) => {
+                log::warn!("failed to get the custom game lobby: {e}");
+                return None;
+            }
+        };
+
+        let (blue_team, red_team) = tokio::join!(
+            Self::lobby_players(&lcu_rest_client, game_config.custom_team100),
+            Self::lobby_players(&lcu_rest_client, game_config.custom_team200),
+        );
+        Some(CustomLobby {
+            name: game_config.custom_lobby_name,
+            blue_team,
+            red_team,
+        })
+    }
+
+    /// Name and day of the Clash tournament if the game is a Clash game
+    async fn clash_tournament(&self, queue: &Queue) -> Option<Clash> {
+        if !Self::CLASH_QUEUE_IDS.contains(&queue.id) {
+            return None;
+        }
+
+        let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
+        let tournament = async {
+            let tournament_ids = lcu_rest_client.get::<Vec<i64>>(Self::CLASH_TOURNAMENT_IDS).await?;
+            // only one tournament (day) runs at a time
+            let Some(tournament_id) = tournament_ids.first() else { return Ok(None) };
+            let path = format!("{}{tournament_id}", Self::CLASH_TOURNAMENT);
+            lcu_rest_client.get::<ClashTournament>(path).await.map(Some)
+        };
+
+        let mut clash = match tournament.await {
+            Ok(Some(tournament)) => Clash {
+                tournament_id: Some(tournament.id),
+                name: format!("{} Cup", loc_key_to_title(&tournament.name_loc_key)),
+                day: loc_key_to_title(&tournament.name_loc_key_secondary),
+                round: None,
+            },
+            result => {
+                if let Err(e) = result {
+                    log::warn!("failed to get the Clash tournament: {e}");
+                }
+                Clash {
+                    tournament_id: None,
+                    name: String::from("Clash"),
+                    day: String::new(),
+                    round: None,
+                }
+            }
+        };
+
+        let app_handle = self.ctx.app_handle.clone();
+        let tournament_id = clash.tournament_id;
+        match async_runtime::spawn_blocking(move || Self::clash_round(&app_handle, tournament_id)).await {
+            Ok(round) => clash.round = Some(round),
+            Err(e) => log::warn!("failed to count the games of the Clash tournament: {e}"),
+        }
+        Some(clash)
+    }
+
+    /// Number of the next game in the Clash tournament: the games of it that are already in the library + 1. Without
+    /// the tournament the Clash games of today belong together.
+    fn clash_round(app_handle: &AppHandle, tournament_id: Option<i64>) -> u32 {
+        let today = chrono::Local::now().date_naive();
+        let played_today = |recording: &Path| {
+            util::created_at(recording)
+                .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+                .is_some_and(|time| time.with_timezone(&chrono::Local).date_naive() == today)
+        };
+
+        let earlier_games = app_handle
+            .get_recordings()
+            .into_iter()
+            .filter(|recording| {
+                let Ok(metadata) = action::get_recording_metadata(recording, false) else { return false };
+                metadata.clash().is_some_and(|clash| {
+                    clash.tournament_id == tournament_id && (tournament_id.is_some() || played_today(recording))
+                })
+            })
+            .count();
+        earlier_games as u32 + 1
+    }
+
+    /// Riot IDs of the party of the player (including the player, empty when playing solo)
+    async fn party(&self, queue: &Queue) -> Vec<String> {
+        // everyone in the lobby of a custom game is in the "party", see `custom_lobby`
+        if queue.id == Self::CUSTOM_QUEUE_ID {
+            return Vec::new();
+        }
+
+        let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
+        let members = match lcu_rest_client.get::<Lobby>(Self::LOBBY).await {
+            Ok(lobby) if lobby.members.len() > 1 => lobby.members,
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                log::warn!("failed to get the party: {e}");
+                return Vec::new();
+            }
+        };
+
+        Self::lobby_players(&lcu_rest_client, members)
+            .await
+            .into_iter()
+            .map(|player| player.riot_id)
+            .filter(|riot_id| !riot_id.is_empty())
+            .collect()
+    }
+
+    /// Looks up the Riot IDs of all members at the same time (in member order)
+    async fn lobby_players(lcu_rest_client: &LcuRestClient, members: Vec<LobbyMember>) -> Vec<LobbyPlayer> {
+        let players = members.into_iter().map(|member| async move {
+            let riot_id = if member.is_bot {
+                String::new()
+            } else {
+                let path = format!("{}{}", Self::SUMMONER_BY_PUUID, member.puuid);
+                match lcu_rest_client.get::<Account>(path).await {
+                    Ok(account) => account.riot_id(),
+                    Err(_) => member.summoner_name,
+                }
+            };
+            LobbyPlayer {
+                puuid: member.puuid,
+                riot_id,
+                is_bot: member.is_bot,
+            }
+        });
+        future::join_all(players).await
+    }
+
+    /// Subfolders of the recordings folder the recording gets saved in (see 'accountSubfolders' and 'organizeBy')
+    async fn recording_subfolders(
+        &self,
+        queue: &Queue,
+        game_mode: Option<&str>,
+        account: Option<&Account>,
+    ) -> Vec<String> {
+        let settings = self.ctx.app_handle.state::<SettingsWrapper>();
+        let account_folder = account.filter(|_| settings.account_subfolders()).map(Account::riot_id);
+        account_folder
+            .into_iter()
+            .chain(self.organize_by_subfolder(queue, game_mode).await)
+            .collect()
+    }
+
+    async fn organize_by_subfolder(&self, queue: &Queue, game_mode: Option<&str>) -> Option<String> {
+        match self.ctx.app_handle.state::<SettingsWrapper>().organize_by() {
+            OrganizeBy::None => None,
+            OrganizeBy::Month => Some(chrono::Local::now().format("%Y-%m").to_string()),
+            OrganizeBy::Queue => Some(game_mode_category(queue, game_mode)),
+            OrganizeBy::Patch => {
+                let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
+                match lcu_rest_client.get::<String>(Self::GAME_VERSION).await {
+                    Ok(version) => recorder::patch(&version),
+                    Err(e) => {
+                        log::warn!("failed to get game version for the recording subfolder: {e}");
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start the recorder during champ select so the recording starts as soon as the game window shows up (only for
+    /// game modes that get recorded)
+    fn prewarm_recorder(&self, phase: GamePhase, game_data: &GameData) {
+        let prewarmed = self.ctx.app_handle.state::<PrewarmedRecorder>();
+        let game_modes = self.ctx.app_handle.state::<SettingsWrapper>().game_modes();
+        match phase {
+            GamePhase::ChampSelect
+                if is_mode_allowed(game_modes.as_deref(), &game_data.queue, game_data.game_mode.as_deref()) =>
+            {
+                prewarmed.warm_up(&self.ctx.app_handle)
+            }
+            GamePhase::ChampSelect => {}
+            // the recording task takes the recorder when the game starts, otherwise the lobby got dodged or left
+            _ => prewarmed.discard(),
+        }
+    }
+
+    /// Start the grace period on the first stop phase (the debounce for Reconnect). Returns true as long as it lasts.
+    fn in_stop_grace_period(&mut self, phase: GamePhase) -> bool {
+        // any other stop phase after Reconnect means the game ended while disconnected => normal grace period
+        if let Some((deadline, pending_phase)) = self.pending_stop {
+            if pending_phase == phase || pending_phase != GamePhase::Reconnect {
+                return Instant::now() < deadline;
+            }
+        }
+
+        let settings = self.ctx.app_handle.state::<SettingsWrapper>();
+        let grace_period = if phase == GamePhase::Reconnect {
+            settings.reconnect_debounce()
+        } else {
+            settings.stop_grace_period()
+        };
+        if grace_period.is_zero() {
+            return false;
+        }
+
+        log::info!("keep recording for {}s after {phase:?}", grace_period.as_secs());
+        let now = Instant::now();
+        let deadline = now.checked_add(grace_period).unwrap_or(now + Self::MAX_GRACE_PERIOD);
+        self.pending_stop = Some((deadline, phase));
+        true
+    }
+
+    /// Log the state and make it available to `get_recorder_state` and support bundles
+    fn publish_state(&self) {
+        log::info!("recorder state: {}", self.state);
+        let listener = match &self.state {
+            State::Idle => ListenerState::Idle,
+            State::Recording(recording_task, ..) => ListenerState::Recording {
+                game_id: recording_task.ctx.match_id.game_id,
+            },
+            State::EndOfGame(metadata, ..) => ListenerState::EndOfGame {
+                game_id: metadata.match_id.game_id,
+            },
+        };
+        self.ctx.app_handle.state::<RecorderStatus>().set_listener(listener);
+        self.ctx.app_handle.state::<RecorderHistory>().push_state(&self.state);
+        // post-processing of earlier games (ffmpeg) waits until this game is recorded
+        jobs::set_paused(&self.ctx.app_handle, matches!(self.state, State::Recording(..)));
+    }
+
+    fn update_tray_status(&self) {
+        let tray_status = match self.state {
+            State::Idle => TrayStatus::Idle,
+            // the tray shows 'Recording' once the game started and the recording actually runs
+            State::Recording(..) => TrayStatus::WaitingForGame,
+            State::EndOfGame(..) => TrayStatus::ProcessingMetadata,
+        };
+        self.ctx.app_handle.set_tray_status(tray_status);
+    }
+
+    /// Recordings shorter than 'minGameDurationSecs' (remakes, failed launches, ...) don't get kept in the library
+    fn is_too_short(&self, metadata: &Metadata) -> bool {
+        let min_duration = self.ctx.app_handle.state::<SettingsWrapper>().min_game_duration_secs();
+        match (min_duration, metadata.video_duration) {
+            (Some(min_duration), Some(video_duration)) => video_duration < min_duration as f64,
+            _ => false,
+        }
+    }
+
+    fn is_empty(metadata: &Metadata) -> bool {
+        metadata.segments.is_empty() && metadata.output_filepath.metadata().is_ok_and(|file| file.len() == 0)
+    }
+
+    fn discard_recording(&self, metadata: &Metadata) {
+        let keep = self
+            .ctx
+            .app_handle
+            .state::<SettingsWrapper>()
+            .keep_discarded_recordings();
+        log::info!(
+            "discarding recording shorter than the minimum duration: {} ({:?}s)",
+            metadata.output_filepath.display(),
+            metadata.video_duration
+        );
+
+        if let Err(e) = action::discard_recording(&metadata.output_filepath, keep) {
+            log::error!("failed to discard recording: {e}");
+        }
+    }
+
+    /// write the highlights and the reason the recording stopped to the deferred metadata file
+    pub(super) fn save_stop_info(metadata: &Metadata, highlights: Vec<Highlight>) {
+        let mut metadata_filepath = metadata.output_filepath.clone();
+        metadata_filepath.set_extension("json");
+
+        if let Ok(MetadataFile::Deferred(mut deferred)) = action::get_recording_metadata(&metadata_filepath, false) {
+            deferred.highlights = highlights;
+            deferred.stop_reason = metadata.stop_reason.clone();
+            deferred.video_duration = metadata.video_duration;
+            deferred.segments = metadata.segments.clone();
+            deferred.recording_stats = metadata.recording_stats.clone();
+            if let Err(e) = action::save_recording_metadata(&metadata_filepath, &MetadataFile::Deferred(deferred)) {
+                log::warn!("failed to write highlight data to deferred metadata file: {e}");
+            }
+        }
+    }
+
+    /// spawn a task that collects the game data once it is available and writes it to the metadata file
+    fn collect_game_data(
+        &self,
+        metadata: Metadata,
+        live_data: LiveData,
+        start_lp: Option<i32>,
+        mut timings: PipelineTimings,
+    ) {
+        let ctx = self.ctx.clone();
+        async_runtime::spawn(self.ctx.game_data_tasks.track_future(async move {
+            let Metadata {
+                match_id,
+                output_filepath,
+                ingame_time_rec_start_offset,
+                raw_ingame_time_rec_start_offset,
+                stop_reason,
+                video_duration,
+                segments,
+                recording_stats,
+                ..
+            } = metadata;
+
+            let mut video_path = output_filepath.clone();
+            let mut metadata_filepath = output_filepath;
+            let mut video_id = metadata_filepath.file_name().and_then(OsStr::to_str).map(str::to_owned);
+            metadata_filepath.set_extension("json");
+
+            let trace = metadata_trace::dispatch(&ctx.app_handle, match_id.game_id);
+            let process_data = metadata::process_data_with_retry(
+                ingame_time_rec_start_offset,
+                match_id,
+                &ctx.credentials,
+                &ctx.cancel_token,
+                live_data.events,
+                live_data.player_events,
+                &mut timings,
+            );
+            let result = match trace {
+                Some(trace) => process_data.with_subscriber(trace).await,
+                None => process_data.await,
+            };
+
+            match result {
+                Ok(mut game_metadata) => {
+                    if let Ok(MetadataFile::Deferred(deferred)) =
+                        action::get_recording_metadata(&metadata_filepath, false)
+                    {
+                        game_metadata.id = deferred.id;
+                        game_metadata.favorite = deferred.favorite;
+                        game_metadata.protected = deferred.protected;
+                        game_metadata.highlights = deferred.highlights;
+                        game_metadata.account = deferred.account;
+                        game_metadata.lobby = deferred.lobby;
+                        game_metadata.clash = deferred.clash;
+                        game_metadata.mark_premades(&deferred.party);
+                    }
+                    game_metadata.raw_ingame_time_rec_start_offset = Some(raw_ingame_time_rec_start_offset);
+                    game_metadata.stop_reason = stop_reason;
+                    game_metadata.video_duration = video_duration;
+                    game_metadata.segments = segments;
+                    game_metadata.recording_stats = recording_stats;
+                    game_metadata.scoreboard = live_data.scoreboard;
+
+                    // Calculate LP Diff
+                    if let Some(s_lp) = start_lp {
+                        // Wait a bit for LCU to update before fetching end LP?
+                        // Actually process_dataWithRetry already takes some time.
+                        // But user asked for "wait a few seconds after game end".
+                        // The EndOfGame state transition happens immediately on EOG session event.
+                        // process_data_with_retry does retries, but maybe we should explicitly wait/fetch here?
+                        // Let's try fetching current LP now.
+
+                        let end_lp = timings
+                            .time("lp_fetch", async {
+                                // Wait 3 seconds to be safe (User requested wait)
+                                tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+                                fetch_current_lp(&ctx.credentials).await
+                            })
+                            .await;
+
+                        if let Some(end_lp) = end_lp {
+                            let diff = end_lp - s_lp;
+                            log::info!("LP Update: Start={}, End={}, Diff={}", s_lp, end_lp, diff);
+                            game_metadata.lp_diff = Some(diff);
+                        } else {
+                            log::warn!("Could not fetch End LP");
+                        }
+                    }
+
+                    if let Some(champion_id) = game_metadata.champion_id() {
+                        game_metadata.mastery = timings
+                            .time("mastery_fetch", fetch_mastery(&ctx.credentials, champion_id))
+                            .await;
+                    }
+
+                    // default thumbnail for the recordings list until the post-game screenshot exists
+                    let splash = timings
+                        .time(
+                            "champion_splash",
+                            action::cache_champion_splash(&ctx.app_handle, &game_metadata),
                         )
-                        .await
+                        .await;
+                    if let Err(e) = splash {
+                        log::warn!("failed to download champion splash: {e}");
+                    }
+
+                    let result = timings.time_sync("json_write", || {
+                        action::save_recording_metadata(
+                            &metadata_filepath,
+                            &crate::recorder::MetadataFile::Metadata(game_metadata),
+                        )
+                    });
+                    log::info!("writing game metadata to ({metadata_filepath:?}): {result:?}");
+                }
+                Err(e) => {
+                    log::error!("unable to process data: {e}");
+                    if let Some(error) = RecorderError::find(&e) {
+                        error.report(&ctx.app_handle);
+                    }
+
+                    // keep at least the live client scoreboard so the recording isn't completely without data
+                    if let Ok(MetadataFile::Deferred(mut deferred)) =
+                        action::get_recording_metadata(&metadata_filepath, false)
+                    {
+                        deferred.scoreboard = live_data.scoreboard;
+                        if let Err(e) =
+                            action::save_recording_metadata(&metadata_filepath, &MetadataFile::Deferred(deferred))
                         {
-                            Ok(mut game_metadata) => {
-                                if let Ok(MetadataFile::Deferred(deferred)) =
-                                    action::get_recording_metadata(&metadata_filepath, false)
-                                {
-                                    game_metadata.favorite = deferred.favorite;
-                                    game_metadata.highlights = deferred.highlights;
-                                }
+                            log::warn!("failed to write scoreboard to deferred metadata file: {e}");
+                        }
+                    }
 
-                                // Calculate LP Diff
-                                if let Some(s_lp) = start_lp {
-                                    // Wait a bit for LCU to update before fetching end LP?
-                                    // Actually process_dataWithRetry already takes some time.
-                                    // But user asked for "wait a few seconds after game end".
-                                    // The EndOfGame state transition happens immediately on EOG session event.
-                                    // process_data_with_retry does retries, but maybe we should explicitly wait/fetch here?
-                                    // Let's try fetching current LP now.
-
-                                    // Wait 3 seconds to be safe (User requested wait)
-                                    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-
-                                    if let Some(end_lp) = fetch_current_lp(&ctx.credentials).await {
-                                        let diff = end_lp - s_lp;
-                                        log::info!("LP Update: Start={}, End={}, Diff={}", s_lp, end_lp, diff);
-                                        game_metadata.lp_diff = Some(diff);
-                                    } else {
-                                        log::warn!("Could not fetch End LP");
-                                    }
-                                }
+                    // the app is shutting down, the rest happens once the game data is available
+                    if ctx.cancel_token.is_cancelled() {
+                        jobs::enqueue(&ctx.app_handle, JobKind::Metadata, &video_path);
+                        return;
+                    }
+                }
+            }
 
-                                let result = action::save_recording_metadata(
-                                    &metadata_filepath,
-                                    &crate::recorder::MetadataFile::Metadata(game_metadata),
-                                );
-                                log::info!("writing game metadata to ({metadata_filepath:?}): {result:?}");
-                            }
-                            Err(e) => log::error!("unable to process data: {e}"),
+            // names with tokens like {champion} (see 'filenameFormat') can only be completed now
+            match action::get_recording_metadata(&video_path, false) {
+                Ok(MetadataFile::Metadata(game_metadata)) => {
+                    let safe_titles = ctx.app_handle.state::<SettingsWrapper>().safe_titles();
+                    match action::apply_filename_tokens(&video_path, &game_metadata, safe_titles) {
+                        Ok(new_video_path) if new_video_path != video_path => {
+                            log::info!("renamed recording to {}", new_video_path.display());
+                            video_id = new_video_path.file_name().and_then(OsStr::to_str).map(str::to_owned);
+                            video_path = new_video_path;
                         }
+                        Ok(_) => {}
+                        Err(e) => log::warn!("failed to apply the filename format to the recording: {e}"),
+                    }
+                }
+                // keep the tokens until the game data is available, the Metadata job renames the recording then
+                _ if action::has_filename_tokens(&video_path) => {
+                    jobs::enqueue(&ctx.app_handle, JobKind::Metadata, &video_path);
+                }
+                _ => {}
+            }
 
+            log::info!("post-game pipeline timings: {timings}");
+            ctx.app_handle.state::<LastPipelineTimings>().set(timings);
+
+            if let Some(video_id) = &video_id {
+                if let Err(e) = ctx.app_handle.send_event(AppEvent::MetadataChanged {
+                    payload: vec![video_id.clone()],
+                }) {
+                    log::error!("GameListener failed to send event: {e}");
+                }
+            }
+
+            // the screenshot is named after the video so it has to wait for the final name
+            if ctx.app_handle.state::<SettingsWrapper>().postgame_screenshot() {
+                jobs::enqueue(&ctx.app_handle, JobKind::Thumbnail, &video_path);
+            }
+            // uploading a highlight clip to Discord can take a while, the replay download doesn't have to wait for it
+            async_runtime::spawn({
+                let app_handle = ctx.app_handle.clone();
+                let video_path = video_path.clone();
+                async move { notifier::notify_game_finished(&app_handle, &video_path).await }
+            });
+            backup::enqueue(&ctx.app_handle, &video_path);
+
+            // the replay only becomes available a while after the game so this runs after the metadata is written
+            if ctx.app_handle.state::<SettingsWrapper>().auto_download_replay() {
+                match action::download_replay(&video_path).await {
+                    Ok(replay_path) => {
+                        log::info!("downloaded replay to {replay_path:?}");
                         if let Some(video_id) = video_id {
                             if let Err(e) = ctx
                                 .app_handle
@@ -601,14 +1295,64 @@ impl GameListener {
                                 log::error!("GameListener failed to send event: {e}");
                             }
                         }
-                    });
-
-                    State::Idle
+                    }
+                    Err(e) => log::warn!("failed to download replay: {e}"),
                 }
-                _ => State::EndOfGame(metadata, live_events, start_lp),
-            },
-        };
+            }
+        }));
+    }
+}
 
-        log::info!("recorder state: {}", self.state);
+/// 'day_2' => 'Day 2'
+fn loc_key_to_title(loc_key: &str) -> String {
+    loc_key
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether games of this mode get recorded according to the 'gameModes' setting (None: all of them).
+/// Modes that aren't one of the standard categories are recorded if 'OTHER' is allowed.
+fn is_mode_allowed(allowed_modes: Option<&[String]>, queue: &Queue, game_mode: Option<&str>) -> bool {
+    const STANDARD_MODES: [&str; 9] = [
+        "RANKED",
+        "NORMAL",
+        "ARAM",
+        "PRACTICE_TOOL",
+        "CHERRY",
+        "COOP_VS_AI",
+        "TFT",
+        "CUSTOM",
+        "SWIFTPLAY",
+    ];
+
+    let Some(modes) = allowed_modes else { return true };
+    let mode_upper = game_mode_category(queue, game_mode).to_uppercase();
+    modes.iter().any(|m| m.to_uppercase() == mode_upper)
+        || (!STANDARD_MODES.contains(&mode_upper.as_str()) && modes.iter().any(|m| m == "OTHER"))
+}
+
+/// Category of the game as used in the 'gameModes' setting (e.g. 'RANKED', 'ARAM', ...)
+fn game_mode_category(queue: &Queue, game_mode: Option<&str>) -> String {
+    // Prioritize QueueID mapping for known queues to ensure consistency
+    match queue.id {
+        420 | 440 => "RANKED".to_string(),
+        400 | 430 => "NORMAL".to_string(),    // Removed 480/490 from NORMAL
+        480 | 490 => "SWIFTPLAY".to_string(), // Explicit Swiftplay mapping
+        450 | 100 => "ARAM".to_string(),
+        3140 => "PRACTICE_TOOL".to_string(),
+        1700 => "CHERRY".to_string(),
+        830 | 840 | 850 | 890 => "COOP_VS_AI".to_string(),
+        1090 | 1100 | 1130 | 1160 => "TFT".to_string(),
+        0 => "CUSTOM".to_string(),
+        _ => game_mode.unwrap_or("UNKNOWN").to_string(),
     }
 }
@@ -0,0 +1,129 @@
+//! Recording health (dropped frames, render and encoding lag, bitrate) from the statistics of libobs.
+//!
+//! libobs resets some of its counters when a new output file gets started, so the tracker adds up the statistics of
+//! every segment of a recording.
+
+use std::time::Instant;
+
+use libobs_recorder::settings::RecorderStats;
+
+use super::RecordingStats;
+
+pub struct HealthTracker {
+    recording_start: Instant,
+    // totals of the segments that are already finished
+    finished: Totals,
+    // stats at the start of the current segment
+    segment_start: RecorderStats,
+    last_stats: RecorderStats,
+    last_update: Instant,
+    render_time_ms_sum: f64,
+    updates: u32,
+}
+
+impl HealthTracker {
+    pub fn new(stats: RecorderStats, recording_start: Instant) -> Self {
+        Self {
+            recording_start,
+            finished: Totals::default(),
+            segment_start: stats,
+            last_stats: stats,
+            last_update: recording_start,
+            render_time_ms_sum: 0.0,
+            updates: 0,
+        }
+    }
+
+    /// Add a new sample. Returns the stats of the whole recording so far with the bitrate since the last sample.
+    pub fn update(&mut self, stats: RecorderStats) -> RecordingStats {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_update).as_secs_f64();
+        let bytes = counter_diff(self.last_stats.output_bytes(), stats.output_bytes());
+        let bitrate_kbps = if elapsed > 0.0 {
+            bytes as f64 * 8.0 / 1000.0 / elapsed
+        } else {
+            0.0
+        };
+
+        let render_time_ms = stats.frame_time_ns() as f64 / 1_000_000.0;
+        self.render_time_ms_sum += render_time_ms;
+        self.updates += 1;
+        self.last_stats = stats;
+        self.last_update = now;
+
+        self.totals().into_stats(bitrate_kbps, render_time_ms)
+    }
+
+    /// The recording continues in a new output file. The samples of the old file up to the last update are kept.
+    pub fn start_segment(&mut self, stats: RecorderStats) {
+        self.finished = self.totals();
+        self.segment_start = stats;
+        self.last_stats = stats;
+        self.last_update = Instant::now();
+    }
+
+    /// Stats of the whole recording (up to the last update) with the average bitrate
+    pub fn summary(&self) -> RecordingStats {
+        let totals = self.totals();
+        let elapsed = self.last_update.duration_since(self.recording_start).as_secs_f64();
+        let bitrate_kbps = if elapsed > 0.0 {
+            totals.output_bytes as f64 * 8.0 / 1000.0 / elapsed
+        } else {
+            0.0
+        };
+        let render_time_ms = if self.updates > 0 {
+            self.render_time_ms_sum / f64::from(self.updates)
+        } else {
+            0.0
+        };
+        totals.into_stats(bitrate_kbps, render_time_ms)
+    }
+
+    fn totals(&self) -> Totals {
+        let segment = Totals {
+            frames: counter_diff(self.segment_start.rendered_frames(), self.last_stats.rendered_frames()),
+            lagged: counter_diff(self.segment_start.lagged_frames(), self.last_stats.lagged_frames()),
+            skipped: counter_diff(self.segment_start.skipped_frames(), self.last_stats.skipped_frames()),
+            dropped: counter_diff(self.segment_start.dropped_frames(), self.last_stats.dropped_frames()),
+            output_bytes: counter_diff(self.segment_start.output_bytes(), self.last_stats.output_bytes()),
+        };
+        Totals {
+            frames: self.finished.frames + segment.frames,
+            lagged: self.finished.lagged + segment.lagged,
+            skipped: self.finished.skipped + segment.skipped,
+            dropped: self.finished.dropped + segment.dropped,
+            output_bytes: self.finished.output_bytes + segment.output_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    frames: u32,
+    lagged: u32,
+    skipped: u32,
+    dropped: u32,
+    output_bytes: u64,
+}
+
+impl Totals {
+    fn into_stats(self, bitrate_kbps: f64, render_time_ms: f64) -> RecordingStats {
+        RecordingStats {
+            total_frames: self.frames,
+            lagged_frames: self.lagged,
+            skipped_frames: self.skipped,
+            dropped_frames: self.dropped,
+            bitrate_kbps,
+            render_time_ms,
+        }
+    }
+}
+
+/// Difference of a counter between two samples. A counter that got reset in between counts from 0.
+fn counter_diff<T: PartialOrd + std::ops::Sub<Output = T> + Copy>(earlier: T, later: T) -> T {
+    if later >= earlier {
+        later - earlier
+    } else {
+        later
+    }
+}
@@ -1,11 +1,25 @@
 use shaco::ingame::IngameClient;
-use tauri::{async_runtime::JoinHandle, AppHandle, Listener};
+use tauri::async_runtime::{JoinHandle, Sender};
+use tauri::{AppHandle, Emitter, Listener};
 use tokio_util::sync::CancellationToken;
 
+use super::{Highlight, HighlightCategory, SavedHighlight};
+use crate::app::{AppEvent, EventManager};
 use crate::cancellable;
+use crate::notifier::{self, Toast};
+
+// emitted by the hotkeys and control commands
+const SAVE_EVENT: &str = "shortcut-event";
+const REMOVE_EVENT: &str = "remove-highlight-event";
+
+enum HighlightAction {
+    Save(HighlightCategory),
+    /// undo the last highlight (e.g. if the hotkey got pressed by accident)
+    RemoveLast,
+}
 
 pub struct HighlightTask {
-    join_handle: JoinHandle<Vec<f64>>,
+    join_handle: JoinHandle<Vec<Highlight>>,
     cancel_token: CancellationToken,
 }
 
@@ -18,26 +32,39 @@ impl HighlightTask {
 
             async move {
                 let (tx, mut rx) = tauri::async_runtime::channel(128);
-                app_handle.listen("shortcut-event", {
-                    let app_handle = app_handle.clone();
-                    move |event| {
-                        let sent = tx.blocking_send(());
-                        if tx.is_closed() || sent.is_err() {
-                            app_handle.unlisten(event.id());
-                        }
-                    }
+                // the payload is the category of the marker (highlight if there is none)
+                Self::forward(&app_handle, SAVE_EVENT, tx.clone(), |payload| {
+                    HighlightAction::Save(serde_json::from_str(payload).unwrap_or_default())
                 });
+                Self::forward(&app_handle, REMOVE_EVENT, tx, |_| HighlightAction::RemoveLast);
 
                 let ingame_client = IngameClient::new();
-                let mut highlight_timestamps = Vec::new();
+                let mut highlights = Vec::new();
                 loop {
                     match cancellable!(rx.recv(), cancel_token, Option) {
-                        Some(()) => {
-                            if let Ok(timestamp) =
-                                ingame_client.game_stats().await.map(|stats| stats.game_time * 1000.0)
-                            {
-                                highlight_timestamps.push(timestamp);
+                        Some(HighlightAction::Save(category)) => {
+                            if let Ok(game_time) = ingame_client.game_stats().await.map(|stats| stats.game_time) {
+                                highlights.push(Highlight {
+                                    timestamp: game_time * 1000.0,
+                                    category,
+                                });
+
+                                let payload = SavedHighlight { game_time, category };
+                                if let Err(e) = app_handle.send_event(AppEvent::HighlightSaved { payload }) {
+                                    log::warn!("failed to emit HighlightSaved event: {e}");
+                                }
+                                notifier::show_toast(&app_handle, Toast::HighlightSaved(category, game_time));
+                            }
+                        }
+                        Some(HighlightAction::RemoveLast) => {
+                            let Some(Highlight { timestamp, category }) = highlights.pop() else { continue };
+
+                            let game_time = timestamp / 1000.0;
+                            let payload = SavedHighlight { game_time, category };
+                            if let Err(e) = app_handle.send_event(AppEvent::HighlightRemoved { payload }) {
+                                log::warn!("failed to emit HighlightRemoved event: {e}");
                             }
+                            notifier::show_toast(&app_handle, Toast::HighlightRemoved(category, game_time));
                         }
                         _ => {
                             rx.close();
@@ -46,14 +73,37 @@ impl HighlightTask {
                     }
                 }
 
-                highlight_timestamps
+                highlights
             }
         });
 
         Self { join_handle, cancel_token }
     }
 
-    pub async fn stop(self) -> Vec<f64> {
+    /// Save a marker in the running recording, same as pressing the hotkey of `category`
+    pub fn save(app_handle: &AppHandle, category: HighlightCategory) -> tauri::Result<()> {
+        app_handle.emit(SAVE_EVENT, category)
+    }
+
+    /// Send every `event` to the highlight task until it stops
+    fn forward(
+        app_handle: &AppHandle,
+        event: &'static str,
+        tx: Sender<HighlightAction>,
+        action: impl Fn(&str) -> HighlightAction + Send + 'static,
+    ) {
+        app_handle.listen(event, {
+            let app_handle = app_handle.clone();
+            move |event| {
+                let sent = tx.blocking_send(action(event.payload()));
+                if tx.is_closed() || sent.is_err() {
+                    app_handle.unlisten(event.id());
+                }
+            }
+        });
+    }
+
+    pub async fn stop(self) -> Vec<Highlight> {
         self.cancel_token.cancel();
         match self.join_handle.await {
             Ok(highlight_data) => highlight_data,
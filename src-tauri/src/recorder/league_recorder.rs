@@ -2,32 +2,51 @@ use std::time::Duration;
 
 use shaco::rest::LcuRestClient;
 use tauri::async_runtime::{self, JoinHandle, Mutex};
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use tokio::time::{sleep, timeout};
 use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
 
+use super::fallback::{self, FallbackCtx};
 use super::game_listener::{ApiCtx, GameListener};
 use crate::cancellable;
+use crate::power::PowerEvent;
+use crate::state::{RecorderHistory, RecorderStatus, SettingsWrapper};
 
 pub struct LeagueRecorder {
     cancel_token: CancellationToken,
     task: Mutex<JoinHandle<()>>,
+    game_data_tasks: TaskTracker,
     manual_stop_tx: tokio::sync::broadcast::Sender<()>,
     manual_start_tx: tokio::sync::broadcast::Sender<()>,
+    webcam_toggle_tx: tokio::sync::broadcast::Sender<()>,
+    push_to_talk_tx: tokio::sync::broadcast::Sender<bool>,
+    power_tx: tokio::sync::broadcast::Sender<PowerEvent>,
 }
 
 impl LeagueRecorder {
     const PLATFORM_ID: &'static str = "/lol-platform-config/v1/namespaces/LoginDataPacket/platformId";
+    // stopping the recorder waits until the video is finalized, which can take a while for long games on slow disks
+    const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+    const GAME_DATA_STOP_TIMEOUT: Duration = Duration::from_secs(5);
 
     pub fn new(app_handle: AppHandle) -> Self {
         let cancel_token = CancellationToken::new();
         let (manual_stop_tx, _) = tokio::sync::broadcast::channel(1);
         let (manual_start_tx, _) = tokio::sync::broadcast::channel(1);
+        let (webcam_toggle_tx, _) = tokio::sync::broadcast::channel(1);
+        let (push_to_talk_tx, _) = tokio::sync::broadcast::channel(4);
+        let (power_tx, _) = tokio::sync::broadcast::channel(4);
+        let game_data_tasks = TaskTracker::new();
 
         let task = async_runtime::spawn({
             let cancel_token = cancel_token.child_token();
             let manual_stop_tx = manual_stop_tx.clone();
             let manual_start_tx = manual_start_tx.clone();
+            let webcam_toggle_tx = webcam_toggle_tx.clone();
+            let push_to_talk_tx = push_to_talk_tx.clone();
+            let game_data_tasks = game_data_tasks.clone();
+            let power_tx = power_tx.clone();
 
             async move {
                 log::info!("waiting for LCU API");
@@ -42,12 +61,36 @@ impl LeagueRecorder {
                                 credentials,
                                 platform_id,
                                 cancel_token: cancel_token.clone(),
+                                webcam_toggle_tx: webcam_toggle_tx.clone(),
+                                push_to_talk_tx: push_to_talk_tx.clone(),
+                                game_data_tasks: game_data_tasks.clone(),
                             };
 
-                            if let Err(e) = GameListener::new(ctx, manual_stop_tx.subscribe(), manual_start_tx.subscribe()).run().await {
+                            app_handle.state::<RecorderStatus>().set_lcu_connected(true);
+                            let mut listener = GameListener::new(
+                                ctx,
+                                manual_stop_tx.subscribe(),
+                                manual_start_tx.subscribe(),
+                                power_tx.subscribe(),
+                            );
+                            let result = listener.run().await;
+                            app_handle.state::<RecorderStatus>().set_lcu_connected(false);
+                            if let Err(e) = result {
                                 log::error!("stopped listening for games: {e}");
+                                let history = app_handle.state::<RecorderHistory>();
+                                history.push_error(format!("stopped listening for games: {e}"));
                             }
                         }
+                    } else if app_handle.state::<SettingsWrapper>().record_without_client()
+                        && fallback::game_running().await
+                    {
+                        let ctx = FallbackCtx {
+                            app_handle: app_handle.clone(),
+                            cancel_token: cancel_token.clone(),
+                            webcam_toggle_tx: webcam_toggle_tx.clone(),
+                            push_to_talk_tx: push_to_talk_tx.clone(),
+                        };
+                        fallback::record_without_client(ctx, manual_stop_tx.subscribe(), power_tx.subscribe()).await;
                     }
 
                     let cancelled = cancellable!(sleep(Duration::from_secs(1)), cancel_token, ());
@@ -62,19 +105,33 @@ impl LeagueRecorder {
         Self {
             cancel_token,
             task: Mutex::new(task),
+            game_data_tasks,
             manual_stop_tx,
             manual_start_tx,
+            webcam_toggle_tx,
+            push_to_talk_tx,
+            power_tx,
         }
     }
 
+    /// Stop listening for games. A running recording gets stopped and its video finalized, the collection of game
+    /// data that didn't finish yet gets queued as a job for the next start.
     pub async fn stop(&self) {
         self.cancel_token.cancel();
+        self.game_data_tasks.close();
 
         let Ok(mut task) = self.task.try_lock() else { return };
-        if timeout(Duration::from_secs(2), &mut *task).await.is_err() {
+        if timeout(Self::STOP_TIMEOUT, &mut *task).await.is_err() {
             log::warn!("RecordingTask stop() ran into timeout - aborting task");
             task.abort();
         }
+
+        if timeout(Self::GAME_DATA_STOP_TIMEOUT, self.game_data_tasks.wait())
+            .await
+            .is_err()
+        {
+            log::warn!("game data collection didn't stop in time");
+        }
     }
 
     pub fn manual_stop(&self) {
@@ -89,4 +146,22 @@ impl LeagueRecorder {
             log::debug!("failed to send manual start signal (no receivers?): {e}");
         }
     }
+
+    pub fn toggle_webcam(&self) {
+        if let Err(e) = self.webcam_toggle_tx.send(()) {
+            log::debug!("failed to send webcam toggle signal (no receivers?): {e}");
+        }
+    }
+
+    pub fn push_to_talk(&self, pressed: bool) {
+        if let Err(e) = self.push_to_talk_tx.send(pressed) {
+            log::debug!("failed to send push-to-talk signal (no receivers?): {e}");
+        }
+    }
+
+    pub fn power_event(&self, event: PowerEvent) {
+        if let Err(e) = self.power_tx.send(event) {
+            log::debug!("failed to send power event (no receivers?): {e}");
+        }
+    }
 }
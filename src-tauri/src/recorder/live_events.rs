@@ -0,0 +1,280 @@
+//! Events the live client API poller generates from the differences between two polls (items and the creep score of
+//! junglers).
+//!
+//! The API only names the players, which is ambiguous for bots (two 'Sivir Bot's on different teams) and doesn't
+//! survive name changes. These events carry a [`LivePlayerKey`] instead, which gets matched to the participants of the
+//! LCU match data once the game is over.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use riot_datatypes::lcu::ParticipantIdentity;
+use riot_datatypes::{Champion, ParticipantId};
+use shaco::model::ingame::{EventId, GameEvent, ItemId, Player, PlayerItem, TeamId, Time};
+
+const CHAMPION_NAME_PREFIX: &str = "game_character_displayname_";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_POLL_BACKOFF: Duration = Duration::from_secs(8);
+
+/// Everything the live client API knows about who a player is
+#[derive(Debug, Clone, PartialEq)]
+pub struct LivePlayerKey {
+    /// 'GameName#TagLine', not set for bots
+    pub riot_id: Option<String>,
+    /// index in the player list of the API, which is sorted like the participant ids (blue side first)
+    pub position: usize,
+    /// alias of the champion (e.g. 'MonkeyKing'), or the localized name if the alias is unknown
+    pub champion: String,
+    pub team_id: i64,
+}
+
+impl LivePlayerKey {
+    pub fn new(position: usize, player: &Player) -> Self {
+        let riot_id = match (&player.riot_id.game_name, &player.riot_id.tag_line) {
+            _ if player.is_bot => None,
+            (Some(game_name), Some(tag_line)) => Some(format!("{game_name}#{tag_line}")),
+            _ => player.riot_id.riot_id.clone(),
+        };
+        let champion = player
+            .raw_champion_name
+            .strip_prefix(CHAMPION_NAME_PREFIX)
+            .unwrap_or(&player.champion_name)
+            .to_owned();
+        let team_id = match player.team {
+            TeamId::Chaos => 200,
+            _ => 100,
+        };
+
+        Self {
+            riot_id,
+            position,
+            champion,
+            team_id,
+        }
+    }
+
+    /// The participant this player is in the match data of the LCU
+    ///
+    /// Tries the Riot ID first, then the champion of the team (bots and renamed players) and finally the position.
+    pub fn participant_id(
+        &self,
+        participant_identities: &[ParticipantIdentity],
+        pid_to_team: &HashMap<ParticipantId, i64>,
+        pid_to_champ: &HashMap<ParticipantId, Champion>,
+    ) -> Option<ParticipantId> {
+        let on_team = |participant_id: &ParticipantId| {
+            pid_to_team
+                .get(participant_id)
+                .is_none_or(|team_id| *team_id == self.team_id)
+        };
+
+        if let Some(riot_id) = &self.riot_id {
+            let participant_id = participant_identities
+                .iter()
+                .find(|pi| format!("{}#{}", pi.player.game_name, pi.player.tag_line) == *riot_id)
+                .map(|pi| pi.participant_id);
+            if participant_id.is_some() {
+                return participant_id;
+            }
+        }
+
+        // only unique outside of special game modes that allow the same champion more than once per team
+        let mut same_champion = pid_to_champ
+            .iter()
+            .filter(|(participant_id, champion)| {
+                on_team(participant_id) && (champion.alias == self.champion || champion.name == self.champion)
+            })
+            .map(|(participant_id, _)| *participant_id);
+        if let (Some(participant_id), None) = (same_champion.next(), same_champion.next()) {
+            return Some(participant_id);
+        }
+
+        let participant_id = self.position as ParticipantId + 1;
+        participant_identities
+            .iter()
+            .any(|pi| pi.participant_id == participant_id && on_team(&pi.participant_id))
+            .then_some(participant_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LivePlayerEventKind {
+    ItemPurchased {
+        item_id: i64,
+        slot: i64,
+    },
+    ItemSold {
+        item_id: i64,
+        slot: i64,
+    },
+    /// the API doesn't report small monsters at all, only how much creep score the jungler gained (which monster it
+    /// was, e.g. a buff or a scuttle crab, is unknown)
+    JungleCampKill {
+        creep_score_gained: i64,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct LivePlayerEvent {
+    pub player: LivePlayerKey,
+    /// ingame time in seconds
+    pub event_time: Time,
+    pub kind: LivePlayerEventKind,
+}
+
+/// Time until the next poll: the poll interval (doubled for every failed poll) plus a random part of `jitter`
+pub fn poll_delay(failed_polls: u32, jitter: Duration) -> Duration {
+    let backoff = POLL_INTERVAL
+        .saturating_mul(2u32.saturating_pow(failed_polls))
+        .min(MAX_POLL_BACKOFF);
+    backoff + jitter.mul_f64(random_fraction())
+}
+
+fn random_fraction() -> f64 {
+    // only has to differ from poll to poll
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    f64::from(nanos) / 1_000_000_000.0
+}
+
+/// The events that weren't returned by an earlier poll
+///
+/// Events are identified by their id and time since the ids start over if the game client restarts.
+pub fn unseen_events(
+    events: Vec<GameEvent>,
+    seen_events: &mut HashSet<(EventId, u64)>,
+) -> impl Iterator<Item = GameEvent> + '_ {
+    events
+        .into_iter()
+        .filter(move |event| seen_events.insert((event.get_event_id(), event.get_event_time().to_bits())))
+}
+
+/// The items that were bought and sold (in that order) between two polls of the inventory of a player
+pub fn inventory_diff<'a>(
+    old_items: &'a [PlayerItem],
+    new_items: &'a [PlayerItem],
+) -> (Vec<&'a PlayerItem>, Vec<&'a PlayerItem>) {
+    // the items of `items` that don't have a counterpart in `other`
+    fn missing<'a>(items: &'a [PlayerItem], other: &[PlayerItem]) -> Vec<&'a PlayerItem> {
+        let mut other_counts = HashMap::<ItemId, usize>::new();
+        for item in other {
+            *other_counts.entry(item.item_id).or_default() += 1;
+        }
+
+        items
+            .iter()
+            .filter(|item| match other_counts.get_mut(&item.item_id) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect()
+    }
+
+    (missing(new_items, old_items), missing(old_items, new_items))
+}
+
+#[cfg(test)]
+mod tests {
+    use shaco::model::ingame::{GameStart, MinionsSpawning};
+
+    use super::*;
+
+    fn item(item_id: ItemId, slot: i32) -> PlayerItem {
+        PlayerItem {
+            can_use: false,
+            consumable: false,
+            count: 1,
+            display_name: item_id.to_string(),
+            item_id,
+            price: 0,
+            raw_description: String::new(),
+            raw_display_name: String::new(),
+            slot,
+        }
+    }
+
+    fn item_ids(items: &[&PlayerItem]) -> Vec<ItemId> {
+        items.iter().map(|item| item.item_id).collect()
+    }
+
+    fn event(event_id: EventId, event_time: Time) -> GameEvent {
+        GameEvent::MinionsSpawning(MinionsSpawning { event_id, event_time })
+    }
+
+    #[test]
+    fn inventory_diff_counts_duplicate_items() {
+        let old_items = [item(1055, 0), item(2003, 1)];
+        let new_items = [item(1055, 0), item(2003, 1), item(2003, 2), item(1036, 3)];
+        let (bought, sold) = inventory_diff(&old_items, &new_items);
+        assert_eq!(item_ids(&bought), [2003, 1036]);
+        assert!(sold.is_empty());
+
+        let old_items = [item(2003, 1), item(2003, 2), item(1036, 3)];
+        let new_items = [item(2003, 1), item(3133, 3)];
+        let (bought, sold) = inventory_diff(&old_items, &new_items);
+        assert_eq!(item_ids(&bought), [3133]);
+        assert_eq!(item_ids(&sold), [2003, 1036]);
+    }
+
+    #[test]
+    fn inventory_diff_ignores_moved_items() {
+        let old_items = [item(1055, 0), item(2003, 1)];
+        let new_items = [item(2003, 0), item(1055, 5)];
+        let (bought, sold) = inventory_diff(&old_items, &new_items);
+        assert!(bought.is_empty());
+        assert!(sold.is_empty());
+    }
+
+    #[test]
+    fn unseen_events_skips_events_of_earlier_polls() {
+        let mut seen_events = HashSet::new();
+        let first_poll = vec![
+            GameEvent::GameStart(GameStart { event_id: 0, event_time: 0.05 }),
+            event(1, 65.0),
+        ];
+        assert_eq!(unseen_events(first_poll.clone(), &mut seen_events).count(), 2);
+
+        let mut second_poll = first_poll;
+        second_poll.push(event(2, 95.0));
+        let unseen: Vec<_> = unseen_events(second_poll, &mut seen_events).collect();
+        assert_eq!(unseen.len(), 1);
+        assert_eq!(unseen[0].get_event_id(), 2);
+    }
+
+    #[test]
+    fn unseen_events_after_client_restart() {
+        let mut seen_events = HashSet::new();
+        let before_restart = vec![event(0, 0.05), event(1, 65.0), event(2, 95.0)];
+        assert_eq!(unseen_events(before_restart, &mut seen_events).count(), 3);
+
+        // the ids start over after the game client restarted (e.g. reconnect)
+        let after_restart = vec![event(0, 310.5), event(1, 312.0)];
+        let unseen: Vec<_> = unseen_events(after_restart.clone(), &mut seen_events).collect();
+        assert_eq!(unseen.len(), 2);
+        assert_eq!(unseen[0].get_event_time(), 310.5);
+
+        assert_eq!(unseen_events(after_restart, &mut seen_events).count(), 0);
+    }
+
+    #[test]
+    fn poll_delay_doubles_per_failed_poll() {
+        assert_eq!(poll_delay(0, Duration::ZERO), POLL_INTERVAL);
+        assert_eq!(poll_delay(1, Duration::ZERO), POLL_INTERVAL * 2);
+        assert_eq!(poll_delay(2, Duration::ZERO), POLL_INTERVAL * 4);
+    }
+
+    #[test]
+    fn poll_delay_is_capped() {
+        assert_eq!(poll_delay(4, Duration::ZERO), MAX_POLL_BACKOFF);
+        assert_eq!(poll_delay(u32::MAX, Duration::ZERO), MAX_POLL_BACKOFF);
+
+        let jitter = Duration::from_millis(500);
+        let delay = poll_delay(u32::MAX, jitter);
+        assert!(delay >= MAX_POLL_BACKOFF && delay < MAX_POLL_BACKOFF + jitter);
+    }
+}
@@ -0,0 +1,126 @@
+use riot_datatypes::Timestamp;
+use serde::Serialize;
+
+use super::{GameEvent, GameMetadata, Highlight, HighlightCategory, MetadataFile, Pause};
+
+/// Position of a marker on the seek bar.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkerPosition {
+    /// seconds into the video
+    pub video_time: f64,
+    /// `video_time` relative to the video duration in the range 0..=1 (None if the duration is unknown)
+    pub position: Option<f64>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventMarker {
+    pub event: GameEvent,
+    pub position: MarkerPosition,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightMarker {
+    pub category: HighlightCategory,
+    pub position: MarkerPosition,
+}
+
+/// All markers of a recording with their position in the video.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Markers {
+    /// duration of the video in seconds as measured when the recording stopped
+    pub duration: Option<f64>,
+    pub events: Vec<EventMarker>,
+    pub highlights: Vec<HighlightMarker>,
+}
+
+impl Markers {
+    /// Markers of a recording. Recordings without any game data have no markers.
+    pub fn new(metadata_file: &MetadataFile) -> Option<Self> {
+        match metadata_file {
+            MetadataFile::Metadata(metadata) => Some(Self::from_metadata(metadata)),
+            // without game data only the highlights are known and there is no info about pauses
+            MetadataFile::Deferred(deferred) => {
+                let timeline = MarkerTimeline {
+                    offset: deferred.ingame_time_rec_start_offset,
+                    pauses: &[],
+                    duration: deferred.video_duration,
+                };
+                Some(Self {
+                    duration: deferred.video_duration,
+                    events: vec![],
+                    highlights: timeline.highlights(&deferred.highlights),
+                })
+            }
+            MetadataFile::NoData(_) => None,
+        }
+    }
+
+    pub fn from_metadata(metadata: &GameMetadata) -> Self {
+        let timeline = MarkerTimeline {
+            offset: metadata.ingame_time_rec_start_offset,
+            pauses: &metadata.pauses,
+            duration: metadata.video_duration,
+        };
+
+        let events = metadata
+            .events
+            .iter()
+            .map(|event| EventMarker {
+                event: event.clone(),
+                position: timeline.position(event.timestamp),
+            })
+            .collect();
+
+        Self {
+            duration: metadata.video_duration,
+            events,
+            highlights: timeline.highlights(&metadata.highlights),
+        }
+    }
+}
+
+struct MarkerTimeline<'a> {
+    offset: f64,
+    pauses: &'a [Pause],
+    duration: Option<f64>,
+}
+
+impl MarkerTimeline<'_> {
+    // highlights are stored as ingame time in milliseconds just like the event timestamps
+    fn highlights(&self, highlights: &[Highlight]) -> Vec<HighlightMarker> {
+        highlights
+            .iter()
+            .map(|h| HighlightMarker {
+                category: h.category,
+                position: self.position(h.timestamp as Timestamp),
+            })
+            .collect()
+    }
+
+    /// see [`Pause`] for how ingame time maps to video time
+    fn position(&self, timestamp: Timestamp) -> MarkerPosition {
+        let game_time = timestamp as f64 / 1000.0;
+        let paused: f64 = self
+            .pauses
+            .iter()
+            .filter(|pause| pause.game_time <= game_time)
+            .map(|pause| pause.duration)
+            .sum();
+        let video_time = (game_time - self.offset + paused).max(0.0);
+
+        let position = self
+            .duration
+            .filter(|&duration| duration > 0.0)
+            .map(|duration| (video_time / duration).clamp(0.0, 1.0));
+
+        MarkerPosition { video_time, position }
+    }
+}
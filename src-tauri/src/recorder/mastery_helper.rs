@@ -0,0 +1,34 @@
+use riot_datatypes::lcu::{ChampionMastery, SummonerLevel};
+use riot_datatypes::ChampionId;
+use riot_local_auth::Credentials;
+use shaco::rest::LcuRestClient;
+
+use super::Mastery;
+
+const CHAMPION_MASTERY: &str = "/lol-champion-mastery/v1/local-player/champion-mastery";
+const CURRENT_SUMMONER: &str = "/lol-summoner/v1/current-summoner";
+
+/// Mastery of the logged in player on `champion_id` and their summoner level
+pub async fn fetch_mastery(credentials: &Credentials, champion_id: ChampionId) -> Option<Mastery> {
+    let client = LcuRestClient::from(credentials);
+    let masteries = match client.get::<Vec<ChampionMastery>>(CHAMPION_MASTERY).await {
+        Ok(masteries) => masteries,
+        Err(e) => {
+            log::warn!("failed to fetch champion mastery: {e}");
+            return None;
+        }
+    };
+    let summoner_level = client
+        .get::<SummonerLevel>(CURRENT_SUMMONER)
+        .await
+        .map(|summoner| summoner.summoner_level)
+        .ok();
+
+    // champions that were never played have no entry
+    let mastery = masteries.into_iter().find(|mastery| mastery.champion_id == champion_id);
+    Some(Mastery {
+        champion_level: mastery.as_ref().map_or(0, |mastery| mastery.champion_level),
+        champion_points: mastery.as_ref().map_or(0, |mastery| mastery.champion_points),
+        summoner_level,
+    })
+}
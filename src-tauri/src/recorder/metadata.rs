@@ -1,35 +1,47 @@
 use std::time::Duration;
 
-use std::fs::{self, OpenOptions};
-use std::io::Write;
-
 use anyhow::{bail, Context, Result};
-use riot_datatypes::lcu::{Game, Player};
-use riot_datatypes::{Champion, MatchId, Queue, Timeline};
+use riot_datatypes::lcu::{Ban, Game, MatchTeam, Player, Stats};
+use riot_datatypes::riot_api::{ParticipantDto, TeamDto};
+use riot_datatypes::{Champion, MatchId, ParticipantId, Queue};
 use riot_local_auth::Credentials;
 use shaco::model::ingame::GameEvent as LiveGameEvent;
 use shaco::rest::LcuRestClient;
 use tokio::{time::sleep, try_join};
 use tokio_util::sync::CancellationToken;
 
-use super::{GameEvent, GameMetadata, GoldFrame, Participant, ParticipantGold};
+use super::live_events::{LivePlayerEvent, LivePlayerEventKind};
+use super::migration::METADATA_SCHEMA_VERSION;
+use super::riot_api::RiotApiClient;
+use super::timeline::ProcessedTimeline;
+use super::PipelineTimings;
+use super::{
+    kill_positions, lane_opponent, new_recording_id, review_stats, Account, BuildItem, GameEvent, GameMetadata,
+    Participant, Pause,
+};
 use crate::cancellable;
+use crate::error::RecorderError;
+use crate::static_data;
+use crate::util;
 
 pub async fn process_data(
     ingame_time_rec_start_offset: f64,
     match_id: MatchId,
     live_events: Vec<LiveGameEvent>,
+    player_events: Vec<LivePlayerEvent>,
 ) -> Result<GameMetadata> {
-    let lcu_rest_client = LcuRestClient::new()?;
+    let lcu_rest_client = LcuRestClient::new().context(RecorderError::LcuUnavailable)?;
 
     let (player, game) = try_join!(
         lcu_rest_client.get::<Player>("/lol-summoner/v1/current-summoner"),
         lcu_rest_client.get::<Game>(format!("/lol-match-history/v1/games/{}", match_id.game_id)),
     )?;
+    log_memory_usage("before timeline processing");
     let timeline = lcu_rest_client
-        .get::<Timeline>(format!("/lol-match-history/v1/game-timelines/{}", match_id.game_id))
+        .get::<ProcessedTimeline>(format!("/lol-match-history/v1/game-timelines/{}", match_id.game_id))
         .await
         .unwrap_or_default();
+    log_memory_usage("after timeline processing");
 
     let queue = match game.queue_id {
         -1 => Queue {
@@ -62,80 +74,13 @@ pub async fn process_data(
         .find(|p| p.participant_id == participant_id)
         .context("player participant_id not found in game info")?;
 
-    // manually fill data for swarm champions because the client somehow doesn't have info on them
-    // https://raw.communitydragon.org/latest/plugins/rcp-be-lol-game-data/global/default/v1/champion-summary.json
-    let champion_name = match participant.champion_id {
-        3147 => "Riven".into(),
-        3151 => "Jinx".into(),
-        3152 => "Leona".into(),
-        3153 => "Seraphine".into(),
-        3156 => "Briar".into(),
-        3157 => "Yasuo".into(),
-        3159 => "Aurora".into(),
-        3678 => "Illaoi".into(),
-        3947 => "Xayah".into(),
-        _ => "Unknown Champion".into(),
-    };
-
-    // Create .error directory if it doesn't exist (relative to sandbox root, goes to project root)
-    let _ = fs::create_dir_all("../../.error");
-
-    // Open log file (append mode)
-    let log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("../../.error/events_debug.log");
-
-    let events: Vec<GameEvent> = timeline
-        .frames
-        .iter()
-        .flat_map(|frame| {
-            frame.events.iter().filter_map(|event| {
-                // Debug: Check for item events
-                if let Ok(mut file) = log_file.as_ref() {
-                    let _ = writeln!(file, "Processing Event: {:?}", event);
-                }
-
-                match TryInto::<GameEvent>::try_into(event.clone()) {
-                    Ok(e) => {
-                        if let Ok(mut file) = log_file.as_ref() {
-                            let _ = writeln!(file, " -> Converted: {:?}", e);
-                        }
-                        Some(e)
-                    }
-                    Err(err) => {
-                        if let Ok(mut file) = log_file.as_ref() {
-                            let _ = writeln!(file, " -> Error converting entry: {}", err);
-                        }
-                        None
-                    }
-                }
-            })
-        })
-        .collect();
-
-    // Create PID -> Champion Map
-    let mut pid_to_champ = std::collections::HashMap::new();
-    if let Some(sum_id) = player.summoner_id {
-        for p in &game.participants {
-            // We need to fetch champion info for each participant to get Name/Alias
-            // Note: This is 10 async calls. Should be fast enough.
-            let result = lcu_rest_client
-                .get::<Champion>(format!(
-                    "/lol-champions/v1/inventories/{}/champions/{}",
-                    sum_id, p.champion_id
-                ))
-                .await;
-
-            if let Ok(champ) = result {
-                pid_to_champ.insert(p.participant_id, champ);
-            }
-        }
-    }
+    let pid_to_champ = participant_champions(&game).await;
+    let champion_name = champion_name(&pid_to_champ, participant_id);
 
+    let pauses = collect_pauses(&live_events);
     let merged_events = merge_live_events(
-        events,
-        live_events,
+        timeline.events,
+        player_events,
         &game.participant_identities,
         &game.participants,
         &pid_to_champ,
@@ -173,47 +118,54 @@ pub async fn process_data(
                     .unwrap_or_else(|| "NONE".to_string()),
                 summoner_name: name,
                 lane_score: *lane_scores.get(&p.participant_id).unwrap_or(&0.0),
+                premade: false,
+                build: build_path(&merged_events, p.participant_id, &p.stats),
             }
         })
         .collect();
-
-    let gold_timeline: Vec<GoldFrame> = timeline
-        .frames
-        .iter()
-        .map(|frame| {
-            let participants = frame
-                .participant_frames
-                .iter()
-                .map(|(pid, pf)| ParticipantGold {
-                    participant_id: *pid,
-                    total_gold: pf.total_gold,
-                    minions: (pf.minions_killed + pf.jungle_minions_killed) as i64,
-                })
-                .collect();
-
-            GoldFrame {
-                timestamp: frame.timestamp,
-                participants,
-            }
-        })
-        .collect();
+    let matchup = lane_opponent(&participants, participant_id);
+    let review_stats = review_stats(
+        &timeline.gold_timeline,
+        &merged_events,
+        matchup.as_ref(),
+        participant_id,
+    );
 
     Ok(GameMetadata {
+        id: new_recording_id(),
         favorite: false,
+        protected: false,
         match_id,
         ingame_time_rec_start_offset,
+        raw_ingame_time_rec_start_offset: None,
         highlights: vec![],
+        comments: vec![],
         queue,
         player,
         champion_name,
         stats: participant.stats.clone(),
         participant_id,
+        matchup,
+        review_stats,
         participants,
         teams: game.teams,
+        kill_positions: kill_positions(&merged_events, participant_id),
         events: merged_events,
-        gold_timeline,
+        gold_timeline: timeline.gold_timeline,
         game_version: game.game_version,
         lp_diff: None,
+        mastery: None,
+        pauses,
+        scoreboard: None,
+        replay_path: None,
+        stop_reason: None,
+        video_duration: None,
+        segments: vec![],
+        recording_stats: None,
+        account: None,
+        lobby: None,
+        clash: None,
+        schema_version: METADATA_SCHEMA_VERSION,
     })
 }
 
@@ -223,34 +175,65 @@ pub async fn process_data_with_retry(
     credentials: &Credentials,
     cancel_token: &CancellationToken,
     live_events: Vec<LiveGameEvent>,
+    player_events: Vec<LivePlayerEvent>,
+    timings: &mut PipelineTimings,
 ) -> Result<GameMetadata> {
-    let lcu_rest_client = LcuRestClient::from(credentials);
-
-    let mut player_info = None;
-    let mut timeline_data = None;
-    for _ in 0..60 {
-        player_info = try_join!(
-            lcu_rest_client.get::<Player>("/lol-summoner/v1/current-summoner"),
-            lcu_rest_client.get::<Game>(format!("/lol-match-history/v1/games/{}", match_id.game_id)),
-        )
-        .ok();
-
-        timeline_data = lcu_rest_client
-            .get::<Timeline>(format!("/lol-match-history/v1/game-timelines/{}", match_id.game_id))
-            .await
-            .ok();
+    let mut credentials = credentials.clone();
+    let mut lcu_rest_client = LcuRestClient::from(&credentials);
+
+    let (player_info, timeline_data) = timings
+        .time("lcu_retries", async {
+            let mut player_info = None;
+            let mut timeline_data = None;
+            for _ in 0..60 {
+                // only re-request what is still missing so the (big) timeline doesn't get downloaded and parsed again
+                if player_info.is_none() {
+                    player_info = try_join!(
+                        lcu_rest_client.get::<Player>("/lol-summoner/v1/current-summoner"),
+                        lcu_rest_client.get::<Game>(format!("/lol-match-history/v1/games/{}", match_id.game_id)),
+                    )
+                    .ok();
+                }
 
-        if player_info.is_some() && timeline_data.is_some() {
-            break;
-        }
+                if timeline_data.is_none() {
+                    log_memory_usage("before timeline processing");
+                    timeline_data = lcu_rest_client
+                        .get::<ProcessedTimeline>(format!("/lol-match-history/v1/game-timelines/{}", match_id.game_id))
+                        .await
+                        .ok();
+                    log_memory_usage("after timeline processing");
+                }
 
-        let cancelled = cancellable!(sleep(Duration::from_secs(1)), cancel_token, ());
-        if cancelled {
-            bail!("task cancelled (process_data)");
-        }
-    }
+                if player_info.is_some() && timeline_data.is_some() {
+                    break;
+                }
 
-    let Some((player, game)) = player_info else { bail!("unable to collect game data") };
+                // the client could have been restarted after the game => retry with its new port and token
+                if let Ok(new_credentials) = riot_local_auth::lcu::try_get_credentials() {
+                    if new_credentials.port != credentials.port || new_credentials.token != credentials.token {
+                        log::info!("League client restarted, retrying with the new credentials");
+                        lcu_rest_client = LcuRestClient::from(&new_credentials);
+                        credentials = new_credentials;
+                    }
+                }
+
+                let cancelled = cancellable!(sleep(Duration::from_secs(1)), cancel_token, ());
+                if cancelled {
+                    bail!("task cancelled (process_data)");
+                }
+            }
+
+            Ok::<_, anyhow::Error>((player_info, timeline_data))
+        })
+        .await?;
+
+    let Some((player, game)) = player_info else {
+        // the client could also have been closed after the game
+        if riot_local_auth::lcu::try_get_credentials().is_err() {
+            bail!(RecorderError::LcuUnavailable);
+        }
+        bail!(RecorderError::MetadataTimeout);
+    };
     let timeline = timeline_data.unwrap_or_default();
 
     let queue = match game.queue_id {
@@ -284,88 +267,13 @@ pub async fn process_data_with_retry(
         .find(|p| p.participant_id == participant_id)
         .context("player participant_id not found in game info")?;
 
-    // manually fill data for swarm champions because the client somehow doesn't have info on them
-    // https://raw.communitydragon.org/latest/plugins/rcp-be-lol-game-data/global/default/v1/champion-summary.json
-    let champion_name = match participant.champion_id {
-        3147 => "Riven".into(),
-        3151 => "Jinx".into(),
-        3152 => "Leona".into(),
-        3153 => "Seraphine".into(),
-        3156 => "Briar".into(),
-        3157 => "Yasuo".into(),
-        3159 => "Aurora".into(),
-        3678 => "Illaoi".into(),
-        3947 => "Xayah".into(),
-        _ => {
-            lcu_rest_client
-                .get::<Champion>(format!(
-                    "/lol-champions/v1/inventories/{}/champions/{}",
-                    player.summoner_id.unwrap(),
-                    participant.champion_id
-                ))
-                .await?
-                .name
-        }
-    };
-
-    // Create .log directory if it doesn't exist
-    let _ = fs::create_dir_all(".log");
-
-    // Open log file (append mode)
-    let log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(".log/events_debug.log");
-
-    let events: Vec<GameEvent> = timeline
-        .frames
-        .iter()
-        .flat_map(|frame| {
-            frame.events.iter().filter_map(|event| {
-                // Debug: Check for item events
-                if let Ok(mut file) = log_file.as_ref() {
-                    let _ = writeln!(file, "Processing Event: {:?}", event);
-                }
-
-                match TryInto::<GameEvent>::try_into(event.clone()) {
-                    Ok(e) => {
-                        if let Ok(mut file) = log_file.as_ref() {
-                            let _ = writeln!(file, " -> Converted: {:?}", e);
-                        }
-                        Some(e)
-                    }
-                    Err(err) => {
-                        if let Ok(mut file) = log_file.as_ref() {
-                            let _ = writeln!(file, " -> Error converting entry: {}", err);
-                        }
-                        None
-                    }
-                }
-            })
-        })
-        .collect();
-
-    // Create PID -> Champion Map for retry logic
-    let mut pid_to_champ = std::collections::HashMap::new();
-    if let Some(sum_id) = player.summoner_id {
-        for p in &game.participants {
-            // Fetch Alias/Name
-            let result = lcu_rest_client
-                .get::<Champion>(format!(
-                    "/lol-champions/v1/inventories/{}/champions/{}",
-                    sum_id, p.champion_id
-                ))
-                .await;
-
-            if let Ok(champ) = result {
-                pid_to_champ.insert(p.participant_id, champ);
-            }
-        }
-    }
+    let pid_to_champ = timings.time("champion_lookups", participant_champions(&game)).await;
+    let champion_name = champion_name(&pid_to_champ, participant_id);
 
+    let pauses = collect_pauses(&live_events);
     let merged_events = merge_live_events(
-        events,
-        live_events,
+        timeline.events,
+        player_events,
         &game.participant_identities,
         &game.participants,
         &pid_to_champ,
@@ -403,74 +311,337 @@ pub async fn process_data_with_retry(
                     .unwrap_or_else(|| "NONE".to_string()),
                 summoner_name: name,
                 lane_score: *lane_scores.get(&p.participant_id).unwrap_or(&0.0),
+                premade: false,
+                build: build_path(&merged_events, p.participant_id, &p.stats),
             }
         })
         .collect();
-
-    let gold_timeline: Vec<GoldFrame> = timeline
-        .frames
-        .iter()
-        .map(|frame| {
-            let participants = frame
-                .participant_frames
-                .iter()
-                .map(|(pid, pf)| ParticipantGold {
-                    participant_id: *pid,
-                    total_gold: pf.total_gold,
-                    minions: (pf.minions_killed + pf.jungle_minions_killed) as i64,
-                })
-                .collect();
-
-            GoldFrame {
-                timestamp: frame.timestamp,
-                participants,
-            }
-        })
-        .collect();
+    let matchup = lane_opponent(&participants, participant_id);
+    let review_stats = review_stats(
+        &timeline.gold_timeline,
+        &merged_events,
+        matchup.as_ref(),
+        participant_id,
+    );
 
     Ok(GameMetadata {
+        id: new_recording_id(),
         favorite: false,
+        protected: false,
         match_id,
         ingame_time_rec_start_offset,
+        raw_ingame_time_rec_start_offset: None,
         highlights: vec![],
+        comments: vec![],
         queue,
         player,
         champion_name,
         stats: participant.stats.clone(),
         participant_id,
+        matchup,
+        review_stats,
         participants,
         teams: game.teams,
+        kill_positions: kill_positions(&merged_events, participant_id),
         events: merged_events,
-        gold_timeline,
+        gold_timeline: timeline.gold_timeline,
         game_version: game.game_version,
         lp_diff: None,
+        mastery: None,
+        pauses,
+        scoreboard: None,
+        replay_path: None,
+        stop_reason: None,
+        video_duration: None,
+        segments: vec![],
+        recording_stats: None,
+        account: None,
+        lobby: None,
+        clash: None,
+        schema_version: METADATA_SCHEMA_VERSION,
     })
 }
 
+/// Build the metadata from the public Riot API (Match-V5) instead of the LCU.
+/// Used for recordings whose game isn't in the LCU match history anymore. Live events (item purchases, pauses, ...)
+/// were not recorded for these games.
+///
+/// The player is found by the `account` the game was recorded with. Recordings from before the account was stored
+/// need the League client to be running and logged into the account that played the game.
+pub async fn process_riot_api_data(
+    ingame_time_rec_start_offset: f64,
+    match_id: MatchId,
+    account: Option<&Account>,
+    api_key: &str,
+) -> Result<GameMetadata> {
+    let riot_api_client = RiotApiClient::new(api_key)?;
+    // not required if the account is known, only used for the name of the queue then
+    let lcu_rest_client = LcuRestClient::new().ok();
+
+    let (match_dto, timeline) = try_join!(
+        riot_api_client.get_match(&match_id),
+        riot_api_client.get_timeline(&match_id),
+    )?;
+    let info = match_dto.info;
+
+    if info.game_id != match_id.game_id || !info.platform_id.eq_ignore_ascii_case(&match_id.platform_id) {
+        bail!(
+            "Riot API returned a different game ({}_{})",
+            info.platform_id,
+            info.game_id
+        );
+    }
+
+    let player = match account {
+        Some(account) => Player {
+            game_name: account.game_name.clone(),
+            tag_line: account.tag_line.clone(),
+            summoner_id: None,
+        },
+        None => {
+            let lcu_rest_client = lcu_rest_client.as_ref().context(
+                "the recording doesn't know its account, the League client has to be running and logged into the \
+                account that played the game",
+            )?;
+            lcu_rest_client
+                .get::<Player>("/lol-summoner/v1/current-summoner")
+                .await?
+        }
+    };
+    let participant = info
+        .participants
+        .iter()
+        .find(|p| {
+            account.is_some_and(|account| p.puuid == account.puuid)
+                || (p.riot_id_game_name == player.game_name && p.riot_id_tagline == player.tag_line)
+        })
+        .with_context(|| format!("{}#{} didn't play in this game", player.game_name, player.tag_line))?;
+
+    let queue = match &lcu_rest_client {
+        Some(lcu_rest_client) => lcu_rest_client
+            .get::<Queue>(format!("/lol-game-queues/v1/queues/{}", info.queue_id))
+            .await
+            .ok(),
+        None => None,
+    };
+    let queue = match queue {
+        Some(queue) => queue,
+        None => Queue {
+            id: info.queue_id,
+            name: "Unknown".into(),
+            is_ranked: matches!(info.queue_id, 420 | 440),
+        },
+    };
+
+    let lane_scores = calculate_lane_scores(&timeline.events);
+
+    let participants = info
+        .participants
+        .iter()
+        .map(|p| {
+            let stats = match_v5_stats(p);
+            let build = build_path(&timeline.events, p.participant_id, &stats);
+            Participant {
+                participant_id: p.participant_id,
+                team_id: p.team_id,
+                champion_id: p.champion_id,
+                spell1_id: p.summoner1_id,
+                spell2_id: p.summoner2_id,
+                stats,
+                lane: non_empty_or_none(&p.lane),
+                role: non_empty_or_none(&p.role),
+                summoner_name: format!("{}#{}", p.riot_id_game_name, p.riot_id_tagline),
+                lane_score: *lane_scores.get(&p.participant_id).unwrap_or(&0.0),
+                premade: false,
+                build,
+            }
+        })
+        .collect();
+    let matchup = lane_opponent(&participants, participant.participant_id);
+    let review_stats = review_stats(
+        &timeline.gold_timeline,
+        &timeline.events,
+        matchup.as_ref(),
+        participant.participant_id,
+    );
+
+    Ok(GameMetadata {
+        id: new_recording_id(),
+        favorite: false,
+        protected: false,
+        match_id,
+        ingame_time_rec_start_offset,
+        raw_ingame_time_rec_start_offset: None,
+        highlights: vec![],
+        comments: vec![],
+        queue,
+        player,
+        // Match-V5 only has the champion key (e.g. 'MonkeyKing') which is good enough for displaying
+        champion_name: participant.champion_name.clone(),
+        stats: match_v5_stats(participant),
+        participant_id: participant.participant_id,
+        matchup,
+        review_stats,
+        participants,
+        teams: info.teams.iter().map(match_v5_team).collect(),
+        kill_positions: kill_positions(&timeline.events, participant.participant_id),
+        events: timeline.events,
+        gold_timeline: timeline.gold_timeline,
+        game_version: info.game_version,
+        lp_diff: None,
+        mastery: None,
+        pauses: vec![],
+        scoreboard: None,
+        replay_path: None,
+        stop_reason: None,
+        video_duration: None,
+        segments: vec![],
+        recording_stats: None,
+        account: None,
+        lobby: None,
+        clash: None,
+        schema_version: METADATA_SCHEMA_VERSION,
+    })
+}
+
+fn match_v5_stats(p: &ParticipantDto) -> Stats {
+    let primary = p.perks.styles.first();
+    let secondary = p.perks.styles.get(1);
+    let mut perks = primary
+        .into_iter()
+        .chain(secondary)
+        .flat_map(|style| style.selections.iter().map(|selection| selection.perk));
+
+    Stats {
+        kills: p.kills,
+        deaths: p.deaths,
+        assists: p.assists,
+        largest_multi_kill: p.largest_multi_kill,
+        neutral_minions_killed: p.neutral_minions_killed,
+        neutral_minions_killed_enemy_jungle: p.total_enemy_jungle_minions_killed,
+        neutral_minions_killed_team_jungle: p.total_ally_jungle_minions_killed,
+        total_minions_killed: p.total_minions_killed,
+        vision_score: p.vision_score,
+        vision_wards_bought_in_game: p.vision_wards_bought_in_game,
+        wards_placed: p.wards_placed,
+        wards_killed: p.wards_killed,
+        game_ended_in_early_surrender: p.game_ended_in_early_surrender,
+        game_ended_in_surrender: p.game_ended_in_surrender,
+        win: p.win,
+        item0: p.item0,
+        item1: p.item1,
+        item2: p.item2,
+        item3: p.item3,
+        item4: p.item4,
+        item5: p.item5,
+        item6: p.item6,
+        perk0: perks.next().unwrap_or_default(),
+        perk1: perks.next().unwrap_or_default(),
+        perk2: perks.next().unwrap_or_default(),
+        perk3: perks.next().unwrap_or_default(),
+        perk4: perks.next().unwrap_or_default(),
+        perk5: perks.next().unwrap_or_default(),
+        perk_primary_style: primary.map(|style| style.style).unwrap_or_default(),
+        perk_sub_style: secondary.map(|style| style.style).unwrap_or_default(),
+        gold_earned: p.gold_earned,
+    }
+}
+
+fn match_v5_team(team: &TeamDto) -> MatchTeam {
+    MatchTeam {
+        team_id: team.team_id,
+        win: Some(if team.win { "Win" } else { "Fail" }.into()),
+        tower_kills: team.objectives.tower.kills,
+        inhibitor_kills: team.objectives.inhibitor.kills,
+        baron_kills: team.objectives.baron.kills,
+        dragon_kills: team.objectives.dragon.kills,
+        vilemaw_kills: 0,
+        rift_herald_kills: team.objectives.rift_herald.kills,
+        dominion_victory_score: 0,
+        bans: team
+            .bans
+            .iter()
+            .map(|ban| Ban {
+                champion_id: ban.champion_id,
+                pick_turn: ban.pick_turn,
+            })
+            .collect(),
+    }
+}
+
+fn non_empty_or_none(value: &str) -> String {
+    if value.is_empty() {
+        "NONE".into()
+    } else {
+        value.into()
+    }
+}
+
+/// Champion of every participant from the static data of the patch of the game
+async fn participant_champions(game: &Game) -> std::collections::HashMap<ParticipantId, Champion> {
+    let catalog = match static_data::catalog(&game.game_version).await {
+        Ok(catalog) => catalog,
+        Err(e) => {
+            log::warn!("failed to load champion data: {e}");
+            return std::collections::HashMap::new();
+        }
+    };
+
+    game.participants
+        .iter()
+        .filter_map(|p| Some((p.participant_id, catalog.champion(p.champion_id)?.clone())))
+        .collect()
+}
+
+fn champion_name(
+    pid_to_champ: &std::collections::HashMap<ParticipantId, Champion>,
+    participant_id: ParticipantId,
+) -> String {
+    pid_to_champ
+        .get(&participant_id)
+        .map(|champion| champion.name.clone())
+        .unwrap_or_else(|| "Unknown Champion".into())
+}
+
+fn log_memory_usage(stage: &str) {
+    match util::memory_usage() {
+        Some(bytes) => log::info!("memory usage {stage}: {:.1} MB", bytes as f64 / 1_000_000.0),
+        None => log::info!("memory usage {stage}: unknown"),
+    }
+}
+
+fn collect_pauses(live_events: &[LiveGameEvent]) -> Vec<Pause> {
+    live_events
+        .iter()
+        .filter_map(|live_event| match live_event {
+            LiveGameEvent::GamePaused(e) => Some(Pause {
+                game_time: e.event_time,
+                duration: e.pause_duration,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 fn merge_live_events(
     mut current_events: Vec<GameEvent>,
-    live_events: Vec<LiveGameEvent>,
+    player_events: Vec<LivePlayerEvent>,
     participant_identities: &[riot_datatypes::lcu::ParticipantIdentity],
     participants_info: &[riot_datatypes::lcu::Participant],
     pid_to_champ: &std::collections::HashMap<riot_datatypes::ParticipantId, riot_datatypes::Champion>,
 ) -> Vec<GameEvent> {
-    // Open log file for debugging
-    let log_file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("../../.error/events_debug.log");
-
-    if let Ok(mut file) = log_file.as_ref() {
-        let _ = writeln!(file, "--- Merge Live Events Start ---");
-        let _ = writeln!(file, "Live Events Count: {}", live_events.len());
-        let _ = writeln!(file, "Participant Identities Count: {}", participant_identities.len());
-        for pi in participant_identities {
-            let _ = writeln!(
-                file,
-                "Identity: ID={}, Name='{}', Tag='{}'",
-                pi.participant_id, pi.player.game_name, pi.player.tag_line
-            );
-        }
+    tracing::debug!(
+        player_events = player_events.len(),
+        participants = participant_identities.len(),
+        "merging live events"
+    );
+    for pi in participant_identities {
+        tracing::trace!(
+            participant_id = pi.participant_id,
+            game_name = %pi.player.game_name,
+            tag_line = %pi.player.tag_line,
+            "participant identity"
+        );
     }
 
     // Create PID -> TeamID Map for fast lookup
@@ -479,155 +650,124 @@ fn merge_live_events(
         pid_to_team.insert(p.participant_id, p.team_id);
     }
 
-    for live_event in live_events {
-        let (event_time, shopper_name, item, is_undo, is_sell, undo_gold_gain, undo_item_before) = match &live_event {
-            LiveGameEvent::ItemPurchased(e) => (e.event_time, &e.shopper_name, &e.item, false, false, None, None),
-            LiveGameEvent::ItemSold(e) => (e.event_time, &e.shopper_name, &e.item, false, true, None, None),
-            LiveGameEvent::ItemUndo(e) => (
-                e.event_time,
-                &e.shopper_name,
-                &e.item_after,
-                true,
-                false,
-                Some(e.gold_gain as i64),
-                Some(&e.item_before),
-            ),
-            _ => continue,
-        };
-
-        // Parse optional tags: "Name#TEAM:<Side>#CNAME:<Name>"
-        // CNAME Check
-        let (intermediate_name, target_cname) = if let Some(idx_start) = shopper_name.rfind("#CNAME:") {
-            let (name_part, cname_part) = shopper_name.split_at(idx_start);
-            if let Some(cname_str) = cname_part.strip_prefix("#CNAME:") {
-                (name_part, Some(cname_str))
-            } else {
-                (name_part, None)
+    // the same player has the same key in every event
+    let mut participant_ids = std::collections::HashMap::new();
+    for player_event in player_events {
+        let participant_id = *participant_ids.entry(player_event.player.position).or_insert_with(|| {
+            let participant_id = player_event
+                .player
+                .participant_id(participant_identities, &pid_to_team, pid_to_champ);
+            match participant_id {
+                Some(participant_id) => {
+                    tracing::debug!(player = ?player_event.player, participant_id, "matched live player")
+                }
+                None => tracing::warn!(player = ?player_event.player, "no participant found for live player"),
             }
-        } else {
-            (shopper_name.as_str(), None)
-        };
+            participant_id
+        });
 
-        // Team Check
-        let (actual_name, target_team_side) = if let Some(idx_start) = intermediate_name.rfind("#TEAM:") {
-            let (name_part, team_part) = intermediate_name.split_at(idx_start);
-            if let Some(team_str) = team_part.strip_prefix("#TEAM:") {
-                let team_id = match team_str {
-                    "100" | "ORDER" | "Order" => Some(100i64),
-                    "200" | "CHAOS" | "Chaos" => Some(200i64),
-                    _ => None,
-                };
-                (name_part, team_id)
-            } else {
-                (intermediate_name, None)
-            }
-        } else {
-            (intermediate_name, None)
+        let Some(participant_id) = participant_id else { continue };
+        let timestamp = (player_event.event_time * 1000.0) as i64;
+
+        let event = match player_event.kind {
+            LivePlayerEventKind::ItemPurchased { item_id, slot } => super::Event::ItemPurchased {
+                participant_id,
+                item_id,
+                slot: Some(slot),
+            },
+            LivePlayerEventKind::ItemSold { item_id, slot } => super::Event::ItemSold {
+                participant_id,
+                item_id,
+                slot: Some(slot),
+            },
+            // jungle camps are not part of the LCU timeline
+            LivePlayerEventKind::JungleCampKill { creep_score_gained } => super::Event::JungleCampKill {
+                participant_id,
+                creep_score_gained,
+            },
         };
+        current_events.push(super::GameEvent { event, timestamp });
+    }
 
-        // Match Logic
-        let identity = participant_identities.iter().find(|pi| {
-            let pid = pi.participant_id;
-
-            // 1. CNAME Check (Primary Identity)
-            if let Some(req_cname) = target_cname {
-                if let Some(champ) = pid_to_champ.get(&pid) {
-                    // Check if requested CNAME matches Alias (Key) or Name (Localized)
-                    let cname_match = champ.alias == req_cname || champ.name == req_cname;
-
-                    if cname_match {
-                        // Check Team as well for sanity
-                        if let Some(req_team) = target_team_side {
-                            if let Some(&real_team) = pid_to_team.get(&pid) {
-                                if real_team == req_team {
-                                    return true;
-                                }
-                            }
-                        } else {
-                            // If exact CNAME match, we trust it.
-                            return true;
-                        }
-                    }
-                }
-                // If CNAME is present, we strict match on it.
-                return false;
-            }
+    current_events.sort_by_key(|e| e.timestamp);
+    current_events
+}
 
-            // 2. Fallback: Name + Team Check
-            let full_riot_id = format!("{}#{}", pi.player.game_name, pi.player.tag_line);
-            let name_matches = pi.player.game_name == actual_name || full_riot_id == actual_name;
-            let partial_match = !actual_name.is_empty()
-                && (actual_name.contains(&pi.player.game_name) || pi.player.game_name.contains(actual_name));
+/// Replay the item events of a participant to find out when the items of their final inventory were bought
+///
+/// Sold and undone purchases get dropped, as well as components and consumables that didn't make it into the final
+/// inventory.
+fn build_path(events: &[GameEvent], participant_id: ParticipantId, stats: &Stats) -> Vec<BuildItem> {
+    fn remove_last(items: &mut Vec<BuildItem>, item_id: i64) -> Option<BuildItem> {
+        let idx = items.iter().rposition(|item| item.item_id == item_id)?;
+        Some(items.remove(idx))
+    }
 
-            if !name_matches && !partial_match {
-                return false;
-            }
+    let mut owned: Vec<BuildItem> = Vec::new();
+    let mut sold: Vec<BuildItem> = Vec::new();
 
-            if let Some(req_team) = target_team_side {
-                if let Some(&real_team) = pid_to_team.get(&pid) {
-                    if real_team == req_team {
-                        return true;
-                    } else {
-                        return false;
-                    }
-                } else {
-                    let inferred_team = if pid <= 5 { 100 } else { 200 };
-                    return inferred_team == req_team;
-                }
+    for event in events {
+        match event.event {
+            super::Event::ItemPurchased {
+                participant_id: pid, item_id, ..
+            } if pid == participant_id => {
+                owned.push(BuildItem {
+                    item_id,
+                    timestamp: event.timestamp,
+                });
             }
-
-            // Legacy
-            true
-        });
-
-        if identity.is_none() {
-            println!("   -> NO MATCH FOUND for '{}'", shopper_name);
-        }
-        if let Ok(mut file) = log_file.as_ref() {
-            let status = if identity.is_some() { "MATCHED" } else { "NO MATCH" };
-            let _ = writeln!(
-                file,
-                "Event: {:?}, Shopper: '{}' -> {}",
-                live_event, shopper_name, status
-            );
-        }
-
-        if let Some(identity) = identity {
-            let timestamp = (event_time * 1000.0) as i64;
-
-            let event_enum = if is_undo {
-                let item_after = item;
-                let item_before = undo_item_before.unwrap();
-                riot_datatypes::Event::ItemUndo {
-                    participant_id: identity.participant_id,
-                    before_id: item_before.item_id as i64,
-                    after_id: item_after.item_id as i64,
-                    gold_gain: undo_gold_gain.unwrap_or(0),
+            super::Event::ItemSold {
+                participant_id: pid, item_id, ..
+            } if pid == participant_id => {
+                if let Some(item) = remove_last(&mut owned, item_id) {
+                    sold.push(item);
                 }
-            } else if is_sell {
-                let item = item;
-                riot_datatypes::Event::ItemSold {
-                    participant_id: identity.participant_id,
-                    item_id: item.item_id as i64,
-                    slot: Some(item.slot as i64),
+            }
+            super::Event::ItemUndo {
+                participant_id: pid,
+                before_id,
+                after_id,
+                ..
+            } if pid == participant_id => {
+                // undoing a purchase: before_id is the bought item
+                // undoing a sale: after_id is the sold item that is back in the inventory
+                if before_id != 0 {
+                    remove_last(&mut owned, before_id);
                 }
-            } else {
-                let item = item;
-                riot_datatypes::Event::ItemPurchased {
-                    participant_id: identity.participant_id,
-                    item_id: item.item_id as i64,
-                    slot: Some(item.slot as i64),
+                if after_id != 0 {
+                    if let Some(item) = remove_last(&mut sold, after_id) {
+                        let idx = owned.partition_point(|owned| owned.timestamp <= item.timestamp);
+                        owned.insert(idx, item);
+                    }
                 }
-            };
-
-            if let Ok(local_event) = TryInto::<super::Event>::try_into(event_enum) {
-                current_events.push(super::GameEvent { event: local_event, timestamp });
             }
+            _ => {}
         }
     }
 
-    current_events.sort_by_key(|e| e.timestamp);
-    current_events
+    let mut final_items = vec![
+        stats.item0,
+        stats.item1,
+        stats.item2,
+        stats.item3,
+        stats.item4,
+        stats.item5,
+        stats.item6,
+    ];
+    final_items.retain(|item_id| *item_id != 0);
+
+    // keep the last purchase of every final item
+    let mut build: Vec<BuildItem> = owned
+        .into_iter()
+        .rev()
+        .filter(|item| {
+            let Some(idx) = final_items.iter().position(|item_id| *item_id == item.item_id) else { return false };
+            final_items.swap_remove(idx);
+            true
+        })
+        .collect();
+    build.reverse();
+    build
 }
 
 fn calculate_lane_scores(events: &[GameEvent]) -> std::collections::HashMap<i64, f64> {
@@ -0,0 +1,63 @@
+//! Traces of how the metadata of a game got put together (e.g. which player of the live client API was matched to
+//! which participant), for debugging missing or wrong events.
+//!
+//! Only written in developer mode. Every game gets its own file in the log folder of the app.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use riot_datatypes::GameId;
+use tauri::{AppHandle, Manager};
+use tracing::{Dispatch, Level};
+
+use crate::state::SettingsWrapper;
+
+const TRACE_DIR: &str = "metadata_traces";
+
+/// Subscriber that writes everything traced while processing the game into its trace file
+///
+/// None if developer mode is off.
+pub fn dispatch(app_handle: &AppHandle, game_id: GameId) -> Option<Dispatch> {
+    if !app_handle.state::<SettingsWrapper>().developer_mode() {
+        return None;
+    }
+
+    let trace_file = trace_file(app_handle, game_id)?;
+    let file = trace_file
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| File::create(&trace_file));
+    let file = match file {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!("failed to create metadata trace {}: {e}", trace_file.display());
+            return None;
+        }
+    };
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_max_level(Level::TRACE)
+        .finish();
+    Some(Dispatch::new(subscriber))
+}
+
+/// Copy the trace of the game into `destination` (a folder). Returns the path of the copy.
+pub fn export(app_handle: &AppHandle, game_id: GameId, destination: &Path) -> Result<PathBuf> {
+    let trace_file = trace_file(app_handle, game_id).context("failed to get the log folder")?;
+    if !trace_file.exists() {
+        bail!("there is no metadata trace of game {game_id} (only written in developer mode)");
+    }
+
+    fs::create_dir_all(destination)?;
+    let export_path = destination.join(format!("metadata_trace_{game_id}.log"));
+    fs::copy(&trace_file, &export_path)?;
+    Ok(export_path)
+}
+
+fn trace_file(app_handle: &AppHandle, game_id: GameId) -> Option<PathBuf> {
+    let log_dir = app_handle.path().app_log_dir().ok()?;
+    Some(log_dir.join(TRACE_DIR).join(format!("{game_id}.log")))
+}
@@ -0,0 +1,189 @@
+//! Upgrades metadata files written by older versions of LeagueRecord to the current schema.
+//!
+//! Every metadata file stores the version of the schema it was written with in `schemaVersion` (missing = 0).
+//! Files get migrated step by step in memory on every read so the frontend always gets the current format. The
+//! migrated files are only written by an explicit step (`action::migrate_metadata`) when the library gets reindexed on
+//! start or all recordings get migrated at once.
+
+use anyhow::{bail, Result};
+use serde_json::{json, Map, Value};
+
+use super::{
+    kill_positions, lane_opponent, new_recording_id, review_stats, GameEvent, GoldFrame, Matchup, Participant,
+};
+
+pub const METADATA_SCHEMA_VERSION: u32 = 6;
+
+const VARIANTS: [&str; 3] = ["Metadata", "Deferred", "NoData"];
+// the only fields an untagged NoData file could have
+const NO_DATA_KEYS: [&str; 4] = ["favorite", "id", "protected", "schemaVersion"];
+
+/// `MIGRATIONS[n]` upgrades the content of a metadata file from version `n` to `n + 1`
+const MIGRATIONS: [fn(&str, &mut Map<String, Value>); METADATA_SCHEMA_VERSION as usize] =
+    [v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6];
+
+/// Upgrade the JSON of a metadata file to the current schema. Returns whether anything had to be changed.
+/// Files from newer versions of LeagueRecord are left as they are.
+pub fn migrate(json: &mut Value) -> Result<bool> {
+    let mut changed = wrap_untagged(json)?;
+
+    let Some((variant, content)) = json.as_object_mut().and_then(|object| object.iter_mut().next()) else {
+        bail!("invalid metadata file");
+    };
+    let Some(content) = content.as_object_mut() else { bail!("invalid metadata file") };
+
+    let version = content.get("schemaVersion").and_then(Value::as_u64).unwrap_or(0) as usize;
+    for migration in MIGRATIONS.iter().skip(version) {
+        migration(variant, content);
+        changed = true;
+    }
+    if changed {
+        content.insert(
+            "schemaVersion".into(),
+            json!(METADATA_SCHEMA_VERSION.max(version as u32)),
+        );
+    }
+    Ok(changed)
+}
+
+/// Schema version of the content of a metadata file before it got migrated
+pub fn schema_version(json: &Value) -> u32 {
+    let content = match json.as_object() {
+        Some(object) if is_tagged(object) => object.values().next().unwrap_or(json),
+        _ => json,
+    };
+    content.get("schemaVersion").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+/// Whether the JSON is a metadata file written by any version of LeagueRecord (and not e.g. the sidecar file of
+/// another program)
+pub fn is_metadata_file(json: &Value) -> bool {
+    json.as_object()
+        .is_some_and(|object| is_tagged(object) || untagged_variant(object).is_some())
+}
+
+/// The name of the variant is the only key of the object
+fn is_tagged(object: &Map<String, Value>) -> bool {
+    object.len() == 1 && object.keys().all(|key| VARIANTS.contains(&key.as_str()))
+}
+
+/// The variant an untagged metadata file of the first versions was
+fn untagged_variant(object: &Map<String, Value>) -> Option<&'static str> {
+    if object.contains_key("queue") && object.contains_key("player") {
+        Some("Metadata")
+    } else if object.contains_key("matchId") && object.contains_key("ingameTimeRecStartOffset") {
+        Some("Deferred")
+    } else if !object.is_empty() && object.keys().all(|key| NO_DATA_KEYS.contains(&key.as_str())) {
+        // an empty object isn't anything LeagueRecord wrote
+        Some("NoData")
+    } else {
+        None
+    }
+}
+
+/// The first versions stored the content of the metadata file without the name of the variant.
+/// Fails for JSON objects that don't look like any of the variants (e.g. the sidecar file of another program).
+fn wrap_untagged(json: &mut Value) -> Result<bool> {
+    let Some(object) = json.as_object() else { return Ok(false) };
+    if is_tagged(object) {
+        return Ok(false);
+    }
+
+    let Some(variant) = untagged_variant(object) else { bail!("not a LeagueRecord metadata file") };
+    let mut wrapped = Map::new();
+    wrapped.insert(variant.into(), json.take());
+    *json = Value::Object(wrapped);
+    Ok(true)
+}
+
+/// Fields that were added after the first release
+fn v0_to_v1(variant: &str, content: &mut Map<String, Value>) {
+    let defaults = match variant {
+        "Metadata" => vec![
+            ("favorite", json!(false)),
+            ("highlights", json!([])),
+            ("goldTimeline", json!([])),
+            ("gameVersion", json!("")),
+            ("lpDiff", Value::Null),
+            ("pauses", json!([])),
+            ("segments", json!([])),
+        ],
+        "Deferred" => vec![
+            ("favorite", json!(false)),
+            ("highlights", json!([])),
+            ("segments", json!([])),
+        ],
+        _ => vec![("favorite", json!(false))],
+    };
+
+    for (field, default) in defaults {
+        content.entry(field).or_insert(default);
+    }
+}
+
+/// Stable IDs that don't change when a recording gets renamed
+fn v1_to_v2(_variant: &str, content: &mut Map<String, Value>) {
+    content.entry("id").or_insert_with(|| json!(new_recording_id()));
+}
+
+/// Highlights have a category (highlight or bookmark) instead of only the ingame time
+fn v2_to_v3(_variant: &str, content: &mut Map<String, Value>) {
+    let Some(Value::Array(highlights)) = content.get_mut("highlights") else { return };
+    for highlight in highlights {
+        if highlight.is_number() {
+            *highlight = json!({ "timestamp": highlight.take(), "category": "highlight" });
+        }
+    }
+}
+
+/// Positions of the kills, deaths and assists of the player for the heatmap (the timeline events already contain them)
+fn v3_to_v4(variant: &str, content: &mut Map<String, Value>) {
+    if variant != "Metadata" || content.contains_key("killPositions") {
+        return;
+    }
+
+    let events = content
+        .get("events")
+        .cloned()
+        .and_then(|events| serde_json::from_value::<Vec<GameEvent>>(events).ok());
+    let participant_id = content.get("participantId").and_then(Value::as_i64);
+    let positions = match (events, participant_id) {
+        (Some(events), Some(participant_id)) => kill_positions(&events, participant_id),
+        _ => vec![],
+    };
+    content.insert("killPositions".into(), json!(positions));
+}
+
+/// Lane opponent of the player for the matchup stats
+fn v4_to_v5(variant: &str, content: &mut Map<String, Value>) {
+    if variant != "Metadata" || content.contains_key("matchup") {
+        return;
+    }
+
+    let participants = content
+        .get("participants")
+        .cloned()
+        .and_then(|participants| serde_json::from_value::<Vec<Participant>>(participants).ok());
+    let participant_id = content.get("participantId").and_then(Value::as_i64);
+    let matchup = match (participants, participant_id) {
+        (Some(participants), Some(participant_id)) => lane_opponent(&participants, participant_id),
+        _ => None,
+    };
+    content.insert("matchup".into(), json!(matchup));
+}
+
+/// CS, gold and XP difference to the lane opponent and the first blood participation (old files have no XP)
+fn v5_to_v6(variant: &str, content: &mut Map<String, Value>) {
+    if variant != "Metadata" || content.contains_key("reviewStats") {
+        return;
+    }
+
+    let field = |name: &str| content.get(name).cloned().unwrap_or(Value::Null);
+    let gold_timeline = serde_json::from_value::<Vec<GoldFrame>>(field("goldTimeline")).unwrap_or_default();
+    let events = serde_json::from_value::<Vec<GameEvent>>(field("events")).unwrap_or_default();
+    let matchup = serde_json::from_value::<Option<Matchup>>(field("matchup")).unwrap_or_default();
+    let Some(participant_id) = content.get("participantId").and_then(Value::as_i64) else { return };
+
+    let stats = review_stats(&gold_timeline, &events, matchup.as_ref(), participant_id);
+    content.insert("reviewStats".into(), json!(stats));
+}
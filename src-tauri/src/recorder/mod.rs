@@ -1,13 +1,34 @@
+#[cfg(target_os = "windows")]
+mod audio_device;
 mod data;
+mod fallback;
 mod game_listener;
+mod health;
 mod highlight_task;
 mod league_recorder;
+mod live_events;
 mod lp_helper;
+mod markers;
+mod mastery_helper;
 mod metadata;
+pub mod metadata_trace;
+pub mod migration;
+mod pipeline_timings;
+mod prewarm;
 mod recording_task;
+mod replay;
+mod riot_api;
+mod timeline;
+mod timeline_export;
 #[cfg(target_os = "windows")]
 mod window;
 
 pub use data::*;
+pub use highlight_task::HighlightTask;
 pub use league_recorder::LeagueRecorder;
-pub use metadata::process_data;
+pub use markers::{EventMarker, HighlightMarker, MarkerPosition, Markers};
+pub use metadata::{process_data, process_riot_api_data};
+pub use pipeline_timings::PipelineTimings;
+pub use prewarm::{PrewarmedRecorder, RECORDER_EXECUTABLE};
+pub use replay::{download_replay, game_time_at, watch_replay_at};
+pub use timeline_export::{TimelineDocument, TimelineFormat};
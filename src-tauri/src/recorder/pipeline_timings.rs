@@ -0,0 +1,69 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Instant;
+
+use riot_datatypes::MatchId;
+use serde::Serialize;
+use tracing::Instrument;
+
+/// Durations of the individual steps between the end of a game and the finished metadata file.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineTimings {
+    pub match_id: MatchId,
+    pub steps: Vec<PipelineStep>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStep {
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+impl PipelineTimings {
+    pub fn new(match_id: MatchId) -> Self {
+        Self { match_id, steps: vec![] }
+    }
+
+    pub async fn time<F: Future>(&mut self, step: &'static str, future: F) -> F::Output {
+        let span = tracing::info_span!("post_game_pipeline", step, game_id = self.match_id.game_id);
+
+        let start = Instant::now();
+        let output = future.instrument(span).await;
+        self.add_step(step, start);
+        output
+    }
+
+    pub fn time_sync<T>(&mut self, step: &'static str, f: impl FnOnce() -> T) -> T {
+        let span = tracing::info_span!("post_game_pipeline", step, game_id = self.match_id.game_id);
+
+        let start = Instant::now();
+        let output = span.in_scope(f);
+        self.add_step(step, start);
+        output
+    }
+
+    pub fn total_ms(&self) -> u64 {
+        self.steps.iter().map(|step| step.duration_ms).sum()
+    }
+
+    fn add_step(&mut self, step: &'static str, start: Instant) {
+        self.steps.push(PipelineStep {
+            name: step.into(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+impl Display for PipelineTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("match_id={}:", self.match_id))?;
+        for step in &self.steps {
+            f.write_fmt(format_args!(" {}={}ms", step.name, step.duration_ms))?;
+        }
+        f.write_fmt(format_args!(" total={}ms", self.total_ms()))
+    }
+}
@@ -0,0 +1,118 @@
+//! Starting the recorder process and initializing libobs (graphics, plugins and encoders) takes a few seconds.
+//!
+//! During champ select there is enough time to do that ahead of the game, so the recording task only has to configure
+//! the already running recorder once the game window shows up.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use libobs_recorder::Recorder;
+use tauri::async_runtime;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::state::SettingsWrapper;
+
+pub const RECORDER_EXECUTABLE: &str = "libobs/extprocess_recorder.exe";
+
+#[derive(Default)]
+enum Slot {
+    #[default]
+    Empty,
+    Starting,
+    Ready(Recorder),
+}
+
+#[derive(Default)]
+pub struct PrewarmedRecorder(Mutex<Slot>);
+
+impl PrewarmedRecorder {
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Start a recorder in the background unless one is already running (or starting)
+    pub fn warm_up(&self, app_handle: &AppHandle) {
+        let settings = app_handle.state::<SettingsWrapper>();
+        if !settings.prewarm_recorder() || settings.dry_run() {
+            return;
+        }
+
+        {
+            let mut slot = self.0.lock().unwrap();
+            if !matches!(*slot, Slot::Empty) {
+                return;
+            }
+            *slot = Slot::Starting;
+        }
+
+        log::info!("starting recorder ahead of the game");
+        let app_handle = app_handle.clone();
+        async_runtime::spawn_blocking(move || {
+            let recorder = match start_recorder(&app_handle) {
+                Ok(recorder) => recorder,
+                Err(e) => {
+                    log::warn!("failed to start recorder ahead of the game: {e}");
+                    *app_handle.state::<PrewarmedRecorder>().0.lock().unwrap() = Slot::Empty;
+                    return;
+                }
+            };
+
+            let mut slot = app_handle.state::<PrewarmedRecorder>().0.lock().unwrap();
+            if matches!(*slot, Slot::Starting) {
+                log::info!("recorder is ready");
+                *slot = Slot::Ready(recorder);
+            } else {
+                // got taken or discarded while starting
+                drop(slot);
+                shutdown(recorder);
+            }
+        });
+    }
+
+    /// The prewarmed recorder, waits for a recorder that is still starting instead of starting a second one
+    pub async fn take(&self) -> Option<Recorder> {
+        loop {
+            {
+                let mut slot = self.0.lock().unwrap();
+                if !matches!(*slot, Slot::Starting) {
+                    return match std::mem::take(&mut *slot) {
+                        Slot::Ready(recorder) => Some(recorder),
+                        _ => None,
+                    };
+                }
+            }
+            tokio::time::sleep(Self::POLL_INTERVAL).await;
+        }
+    }
+
+    /// Whether a recorder is ready or starting for the next game
+    pub fn is_running(&self) -> bool {
+        !matches!(*self.0.lock().unwrap(), Slot::Empty)
+    }
+
+    /// Shut down the recorder (e.g. champ select got dodged), a recorder that is still starting gets shut down as soon
+    /// as it is ready
+    pub fn discard(&self) {
+        if let Slot::Ready(recorder) = std::mem::take(&mut *self.0.lock().unwrap()) {
+            log::info!("shutting down unused recorder");
+            async_runtime::spawn_blocking(move || shutdown(recorder));
+        }
+    }
+}
+
+pub fn start_recorder(app_handle: &AppHandle) -> libobs_recorder::Result<Recorder> {
+    Recorder::new_with_paths(
+        app_handle
+            .path()
+            .resolve(RECORDER_EXECUTABLE, BaseDirectory::Executable)
+            .ok(),
+        None,
+        None,
+        None,
+    )
+}
+
+fn shutdown(recorder: Recorder) {
+    if let Err(e) = recorder.shutdown() {
+        log::warn!("failed to shut down recorder: {e}");
+    }
+}
@@ -1,30 +1,62 @@
-use std::{fmt::Display, path::PathBuf, time::Duration};
-
-use anyhow::{bail, Result};
-use libobs_recorder::settings::{RateControl, RecorderSettings, Resolution, StdResolution, Window};
+use std::time::{Duration, Instant};
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Result};
+use futures_util::FutureExt;
+use libobs_recorder::settings::{
+    AudioSource, Framerate, RecorderSettings, RecorderStats, Resolution, StdResolution, Window,
+};
 use libobs_recorder::Recorder;
 use shaco::ingame::IngameClient;
 use tauri::async_runtime::{self, JoinHandle};
-use tauri::path::BaseDirectory;
 use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::Sender;
 use tokio::time::{interval, sleep};
 use tokio_util::sync::CancellationToken;
 
 use riot_datatypes::MatchId;
 
-use crate::app::{action, AppEvent, EventManager, RecordingManager, SystemTrayManager};
+use crate::app::{action, AppEvent, EventManager, RecordingManager, SystemTrayManager, WindowManager};
 use crate::cancellable;
-use crate::recorder::Deferred;
-use crate::state::{CurrentlyRecording, SettingsWrapper};
-
+use crate::error::RecorderError;
+use crate::jobs::{self, JobKind};
+use crate::notifier::{self, Toast};
+use crate::recorder::{
+    new_recording_id, Account, Clash, CustomLobby, Deferred, RecordingDegraded, RecordingFailed, RecordingStats,
+    Segment, StopReason,
+};
+use crate::state::{CurrentlyRecording, HdrMode, RecorderHistory, Segmentation, SettingsWrapper};
+use crate::util;
+
+use super::audio_device::DefaultAudioDevices;
+use super::health::HealthTracker;
+use super::migration::METADATA_SCHEMA_VERSION;
+use super::prewarm::{self, PrewarmedRecorder};
 use super::window::{self, WINDOW_CLASS, WINDOW_PROCESS, WINDOW_TITLE};
 use super::MetadataFile;
 
+// name of new recordings with 'safeTitles' instead of 'filenameFormat', prefixed with the game id
+const SAFE_FILENAME_FORMAT: &str = "%Y-%m-%d_%H-%M.mp4";
+
 #[derive(Clone)]
 pub struct GameCtx {
     pub app_handle: AppHandle,
     pub match_id: MatchId,
     pub cancel_token: CancellationToken,
+    pub webcam_toggle_tx: Sender<()>,
+    // true while the push-to-talk key is held down
+    pub push_to_talk_tx: Sender<bool>,
+    // subfolders of the recordings folder (see 'accountSubfolders' and 'organizeBy')
+    pub subfolders: Vec<String>,
+    pub account: Option<Account>,
+    pub lobby: Option<CustomLobby>,
+    pub clash: Option<Clash>,
+    // Riot IDs of the party of the player (empty for solo queue)
+    pub party: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -32,38 +64,105 @@ pub struct Metadata {
     pub match_id: MatchId,
     pub output_filepath: PathBuf,
     pub ingame_time_rec_start_offset: f64,
+    // offset measured once right after the recording started (before calibration)
+    pub raw_ingame_time_rec_start_offset: f64,
+    // set once the recording task stopped
+    pub stop_reason: Option<StopReason>,
+    rec_start_instant: Instant,
+    // set once the recording task stopped (None in dry run mode)
+    pub video_duration: Option<f64>,
+    // files after the first one if the recording got split
+    pub segments: Vec<Segment>,
+    // set once the recording task stopped (None in dry run mode)
+    pub recording_stats: Option<RecordingStats>,
+    output_start: OutputStart,
+    // length of the video in the finished output files (the time between stopping and starting an output is missing)
+    finished_outputs_duration: f64,
+}
+
+/// Start of the current output file, to measure the length of the video in it
+#[derive(Debug, Clone, Copy)]
+struct OutputStart {
+    instant: Instant,
+    // frames the recorder encoded so far (None in dry run mode or if the stats aren't available)
+    encoded_frames: Option<u32>,
+    fps: Option<f64>,
 }
 
 impl Display for Metadata {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!(
-            "match_id={}, filepath={}, rec_offset={}",
+            "match_id={}, filepath={}, rec_offset={}, raw_rec_offset={}",
             self.match_id,
             self.output_filepath.display(),
-            self.ingame_time_rec_start_offset
+            self.ingame_time_rec_start_offset,
+            self.raw_ingame_time_rec_start_offset
         ))
     }
 }
 
 pub struct RecordingTask {
-    join_handle: JoinHandle<Result<(Recorder, Metadata)>>,
+    // no Recorder in dry run mode
+    join_handle: JoinHandle<Result<(Option<Recorder>, Metadata)>>,
     pub ctx: GameCtx,
 }
 
 impl RecordingTask {
+    const CALIBRATION_SAMPLES: usize = 15;
+    const CALIBRATION_INTERVAL: Duration = Duration::from_secs(2);
+    // below this the recorder stopping by itself is most likely caused by the disk running full
+    const MIN_FREE_DISK_SPACE: u64 = 256 * 1024 * 1024;
+    const SEGMENT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+    const STATS_INTERVAL: Duration = Duration::from_secs(5);
+    const AUDIO_DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+    // libobs stops the output on its own if encoding fails (e.g. after a GPU driver reset)
+    const OUTPUT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+    const MAX_OUTPUT_RESTARTS: u32 = 3;
+    // if more than MAX_LAG_RATIO of the frames get lost during the first minute the recording continues with
+    // FALLBACK_FPS instead of dropping frames for the whole game
+    const FRAMERATE_CHECK_DURATION: Duration = Duration::from_secs(60);
+    const FRAMERATE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+    const MAX_LAG_RATIO: f64 = 0.05;
+    const FALLBACK_FPS: u32 = 30;
+
     pub fn new(ctx: GameCtx) -> Self {
-        let join_handle = async_runtime::spawn(Self::record(ctx.clone()));
+        let app_handle = ctx.app_handle.clone();
+        let join_handle = async_runtime::spawn(Self::record(ctx.clone()).inspect(move |result| {
+            // right away instead of once the game is over
+            if let Some(error) = result.as_ref().err().and_then(RecorderError::find) {
+                error.report(&app_handle);
+            }
+        }));
         Self { join_handle, ctx }
     }
 
-    pub async fn stop(self) -> Result<Metadata> {
+    pub async fn stop(self, reason: StopReason) -> Result<Metadata> {
         self.ctx.cancel_token.cancel();
-        let (mut recorder, metadata) = self.join_handle.await??;
+        self.ctx.app_handle.close_overlay();
+        let (recorder, mut metadata) = self.join_handle.await??;
 
         async_runtime::spawn_blocking(move || {
-            let stopped = recorder.stop_recording();
-            let shutdown = recorder.shutdown();
-            log::info!("stopping recording: stopped={stopped:?}, shutdown={shutdown:?}");
+            let mut stop_reason = reason;
+            if let Some(mut recorder) = recorder {
+                // the recorder only stops on its own if writing the output failed
+                if let Ok(false) = recorder.is_recording() {
+                    // set if the output died during the recording and couldn't be restarted
+                    stop_reason = metadata
+                        .stop_reason
+                        .take()
+                        .unwrap_or_else(|| Self::recorder_failure(&metadata.output_filepath));
+                    if matches!(stop_reason, StopReason::DiskFull) {
+                        RecorderError::DiskFull.report(&self.ctx.app_handle);
+                    }
+                }
+
+                let stopped = recorder.stop_recording();
+                metadata.video_duration = Some(metadata.rec_start_instant.elapsed().as_secs_f64());
+                let shutdown = recorder.shutdown();
+                log::info!("stopping recording: stopped={stopped:?}, shutdown={shutdown:?}");
+            }
+            log::info!("recording stop reason: {stop_reason:?}");
+            metadata.stop_reason = Some(stop_reason);
 
             self.ctx.app_handle.state::<CurrentlyRecording>().set(None);
             self.ctx.app_handle.set_tray_menu_recording(false);
@@ -83,8 +182,21 @@ impl RecordingTask {
         .await?
     }
 
-    async fn record(ctx: GameCtx) -> Result<(Recorder, Metadata)> {
-        let (mut recorder, output_filepath) = cancellable!(Self::setup_recorder(&ctx), ctx.cancel_token, Result)?;
+    async fn record(ctx: GameCtx) -> Result<(Option<Recorder>, Metadata)> {
+        let mut webcam_toggle_rx = ctx.webcam_toggle_tx.subscribe();
+        let mut push_to_talk_rx = ctx.push_to_talk_tx.subscribe();
+
+        // in dry run mode everything except the actual video output runs => only the .json metadata file gets created
+        let dry_run = ctx.app_handle.state::<SettingsWrapper>().dry_run();
+        let (mut recorder, mut recorder_settings, output_filepath) = if dry_run {
+            log::info!("dry run: skipping recorder setup");
+            let output_filepath = Self::output_filepath(&ctx.app_handle.state::<SettingsWrapper>(), &ctx);
+            (None, None, output_filepath)
+        } else {
+            let (recorder, recorder_settings) = cancellable!(Self::setup_recorder(&ctx), ctx.cancel_token, Result)?;
+            let output_filepath = PathBuf::from(recorder_settings.get_output_path());
+            (Some(recorder), Some(recorder_settings), output_filepath)
+        };
 
         // ingame_client timeout is 200ms, so no need to make cancellable with token
         let ingame_client = IngameClient::new();
@@ -94,7 +206,7 @@ impl RecordingTask {
         while !ingame_client.active_game().await {
             let cancelled = cancellable!(timer.tick(), ctx.cancel_token, ());
             if cancelled {
-                let shutdown = recorder.shutdown();
+                let shutdown = recorder.as_mut().map(Recorder::shutdown);
                 bail!("waiting for game cancelled - recorder shutdown: {shutdown:?}");
             }
         }
@@ -113,18 +225,44 @@ impl RecordingTask {
         let pre_start_stats = ingame_client.game_stats().await.ok();
         let pre_start_instant = std::time::Instant::now();
 
+        // with push-to-talk the microphone stays muted until the key gets pressed
+        let push_to_talk = recorder_settings.as_ref().is_some_and(|settings| {
+            matches!(settings.get_audio_source(), Some(AudioSource::ALL))
+                && ctx
+                    .app_handle
+                    .state::<SettingsWrapper>()
+                    .push_to_talk_hotkey()
+                    .is_some()
+        });
+        if let (true, Some(recorder)) = (push_to_talk, recorder.as_mut()) {
+            if let Err(e) = recorder.set_mic_muted(true) {
+                log::warn!("failed to mute microphone for push-to-talk: {e}");
+            }
+        }
+
         // if initial game_data is successful => start recording
-        if let Err(e) = recorder.start_recording() {
-            ctx.app_handle.state::<CurrentlyRecording>().set(None);
-            ctx.app_handle.set_tray_menu_recording(false);
-            let _ = recorder.stop_recording();
-            bail!("failed to start recording: {e}");
+        if let Some(recorder) = recorder.as_mut() {
+            if let Err(e) = recorder.start_recording() {
+                ctx.app_handle.state::<CurrentlyRecording>().set(None);
+                ctx.app_handle.set_tray_menu_recording(false);
+                let _ = recorder.stop_recording();
+                bail!(RecorderError::ObsInit { message: e.to_string() });
+            }
         }
+        let rec_start_instant = Instant::now();
+        let output_start = Self::output_start(recorder.as_mut(), recorder_settings.as_ref());
+        let mut framerate_check = Self::start_framerate_check(&ctx, recorder.as_mut(), recorder_settings.as_ref());
+        let mut health = recorder
+            .as_mut()
+            .and_then(|recorder| recorder.stats().ok())
+            .map(|stats| HealthTracker::new(stats, rec_start_instant));
 
         // Emit RecordingStarted event immediately (UI feedback) - syncing happens below
         if let Err(e) = ctx.app_handle.send_event(AppEvent::RecordingStarted) {
             log::error!("failed to emit RecordingStarted event: {e}");
         }
+        ctx.app_handle.show_overlay();
+        notifier::show_toast(&ctx.app_handle, Toast::RecordingStarted);
 
         log::info!("Recorder started. Calculating sync offset...");
 
@@ -132,7 +270,7 @@ impl RecordingTask {
         let final_stats = ingame_client.game_stats().await.ok();
 
         // Robust offset calculation with fallback
-        let ingame_time_rec_start_offset = if let Some(stats) = final_stats {
+        let raw_ingame_time_rec_start_offset = if let Some(stats) = final_stats {
             stats.game_time
         } else {
             // Fallback: Use pre-start stats + total elapsed time since then
@@ -145,30 +283,548 @@ impl RecordingTask {
         };
 
         log::info!(
-            "Recording setup complete. Final Offset: {:.3}",
-            ingame_time_rec_start_offset
+            "Recording setup complete. Raw Offset: {:.3}",
+            raw_ingame_time_rec_start_offset
         );
 
-        let metadata_file = MetadataFile::Deferred(Deferred {
+        let mut deferred = Deferred {
+            id: new_recording_id(),
             favorite: false,
+            protected: false,
             match_id: ctx.match_id.clone(),
-            ingame_time_rec_start_offset,
+            account: ctx.account.clone(),
+            lobby: ctx.lobby.clone(),
+            clash: ctx.clash.clone(),
+            party: ctx.party.clone(),
+            ingame_time_rec_start_offset: raw_ingame_time_rec_start_offset,
+            raw_ingame_time_rec_start_offset: Some(raw_ingame_time_rec_start_offset),
             highlights: vec![],
-        });
-        if let Err(e) = action::save_recording_metadata(&output_filepath, &metadata_file) {
+            scoreboard: None,
+            stop_reason: None,
+            video_duration: None,
+            segments: vec![],
+            recording_stats: None,
+            schema_version: METADATA_SCHEMA_VERSION,
+        };
+        if let Err(e) = action::save_recording_metadata(&output_filepath, &MetadataFile::Deferred(deferred.clone())) {
             log::info!("failed to save MetadataFile: {e}")
         }
 
-        let metadata = Metadata {
+        // takes several seconds, the recording loop below has to run in the meantime (hotkeys, output checks, ...)
+        let calibration = Self::calibrate_offset(
+            &ingame_client,
+            rec_start_instant,
+            raw_ingame_time_rec_start_offset,
+            &ctx.cancel_token,
+        );
+        tokio::pin!(calibration);
+        let mut calibrated = false;
+
+        // uses the raw offset until the calibration is done
+        let mut metadata = Metadata {
             match_id: ctx.match_id,
             output_filepath,
-            ingame_time_rec_start_offset,
+            ingame_time_rec_start_offset: raw_ingame_time_rec_start_offset,
+            raw_ingame_time_rec_start_offset,
+            stop_reason: None,
+            rec_start_instant,
+            video_duration: None,
+            segments: vec![],
+            recording_stats: None,
+            output_start,
+            finished_outputs_duration: 0.0,
         };
 
+        // keep the recorder until the recording gets stopped so the webcam can be toggled and the output can be split
+        let mut webcam_visible = ctx.app_handle.state::<SettingsWrapper>().get_webcam().is_some();
+        let segmentation = ctx.app_handle.state::<SettingsWrapper>().segmentation();
+        let mut segment_path = metadata.output_filepath.clone();
+        let mut segment_start = rec_start_instant;
+        let mut segment_timer = interval(Self::SEGMENT_CHECK_INTERVAL);
+        let mut framerate_timer = interval(Self::FRAMERATE_CHECK_INTERVAL);
+        let mut stats_timer = interval(Self::STATS_INTERVAL);
+        // the system audio and microphone need to be reconnected if the default device changes (e.g. headset turned on)
+        let mut audio_devices = recorder_settings
+            .as_ref()
+            .filter(|settings| {
+                matches!(
+                    settings.get_audio_source(),
+                    Some(AudioSource::SYSTEM | AudioSource::ALL)
+                )
+            })
+            .map(|_| DefaultAudioDevices::get());
+        let mut audio_device_timer = interval(Self::AUDIO_DEVICE_CHECK_INTERVAL);
+        let mut output_check_timer = interval(Self::OUTPUT_CHECK_INTERVAL);
+        let mut output_restarts = 0;
+        loop {
+            tokio::select! {
+                _ = ctx.cancel_token.cancelled() => break,
+                offset = &mut calibration, if !calibrated => {
+                    calibrated = true;
+                    metadata.ingame_time_rec_start_offset = offset;
+                    deferred.ingame_time_rec_start_offset = offset;
+                    let result = Self::update_deferred(&metadata.output_filepath, &deferred, |current| {
+                        current.ingame_time_rec_start_offset = offset;
+                    });
+                    if let Err(e) = result {
+                        log::info!("failed to save calibrated MetadataFile: {e}")
+                    }
+                }
+                Ok(()) = webcam_toggle_rx.recv() => {
+                    let Some(recorder) = recorder.as_mut() else { continue };
+                    match recorder.set_webcam_visible(!webcam_visible) {
+                        Ok(()) => webcam_visible = !webcam_visible,
+                        Err(e) => log::warn!("failed to toggle webcam: {e}"),
+                    }
+                }
+                _ = segment_timer.tick(), if segmentation.is_some() => {
+                    let (Some(recorder), Some(recorder_settings), Some(segmentation)) =
+                        (recorder.as_mut(), recorder_settings.as_mut(), segmentation.as_ref())
+                    else {
+                        continue;
+                    };
+                    if !Self::segment_full(segmentation, &segment_path, segment_start) {
+                        continue;
+                    }
+
+                    let next_segment = Self::next_segment(
+                        recorder,
+                        recorder_settings,
+                        &mut metadata,
+                        &mut deferred,
+                        health.as_mut(),
+                        webcam_visible,
+                    );
+                    match next_segment {
+                        Ok(next_segment_path) => {
+                            segment_start = Instant::now();
+                            segment_path = next_segment_path;
+                        }
+                        Err(e) => {
+                            let error = format!("failed to start segment {}: {e}", metadata.segments.len() + 2);
+                            log::error!("{error}");
+                            ctx.app_handle.state::<RecorderHistory>().push_error(error);
+                        }
+                    }
+                }
+                _ = framerate_timer.tick(), if framerate_check.is_some() => {
+                    let (Some(recorder), Some(recorder_settings), Some(previous_stats)) =
+                        (recorder.as_mut(), recorder_settings.as_mut(), framerate_check.take())
+                    else {
+                        continue;
+                    };
+                    let stats = match recorder.stats() {
+                        Ok(stats) => stats,
+                        Err(e) => {
+                            log::warn!("failed to get recorder stats: {e}");
+                            continue;
+                        }
+                    };
+
+                    let lag_ratio = stats.lag_ratio_since(&previous_stats);
+                    if lag_ratio <= Self::MAX_LAG_RATIO {
+                        if rec_start_instant.elapsed() < Self::FRAMERATE_CHECK_DURATION {
+                            framerate_check = Some(stats);
+                        }
+                        continue;
+                    }
+
+                    // the new framerate only applies to the new output file
+                    let requested_fps = recorder_settings.get_framerate().map(Self::fps).unwrap_or_default();
+                    let fallback = Framerate::new(Self::FALLBACK_FPS, 1);
+                    log::warn!(
+                        "{:.1}% of the frames got lost, falling back to {} fps",
+                        lag_ratio * 100.0,
+                        Self::FALLBACK_FPS
+                    );
+                    recorder_settings.set_framerate(fallback);
+                    let next_segment = Self::next_segment(
+                        recorder,
+                        recorder_settings,
+                        &mut metadata,
+                        &mut deferred,
+                        health.as_mut(),
+                        webcam_visible,
+                    );
+                    match next_segment {
+                        Ok(next_segment_path) => {
+                            segment_start = Instant::now();
+                            segment_path = next_segment_path;
+                        }
+                        Err(e) => {
+                            log::error!("failed to continue the recording with {} fps: {e}", Self::FALLBACK_FPS);
+                            continue;
+                        }
+                    }
+
+                    let payload = RecordingDegraded { requested_fps, fallback_fps: Self::fps(&fallback), lag_ratio };
+                    if let Err(e) = ctx.app_handle.send_event(AppEvent::RecordingDegraded { payload }) {
+                        log::error!("failed to send RecordingDegraded event: {e}");
+                    }
+                }
+                Ok(pressed) = push_to_talk_rx.recv(), if push_to_talk => {
+                    let Some(recorder) = recorder.as_mut() else { continue };
+                    if let Err(e) = recorder.set_mic_muted(!pressed) {
+                        log::warn!("failed to {} microphone: {e}", if pressed { "unmute" } else { "mute" });
+                    }
+                }
+                _ = audio_device_timer.tick(), if audio_devices.is_some() => {
+                    let (Some(recorder), Some(recorder_settings)) = (recorder.as_mut(), recorder_settings.as_ref())
+                    else {
+                        continue;
+                    };
+                    let current = DefaultAudioDevices::get();
+                    if audio_devices.as_ref() == Some(&current) {
+                        continue;
+                    }
+
+                    log::info!("default audio devices changed to {current:?}, reconnecting audio");
+                    if let Err(e) = recorder.reconnect_audio(recorder_settings.get_audio_device()) {
+                        log::warn!("failed to reconnect audio devices: {e}");
+                    }
+                    audio_devices = Some(current);
+                }
+                _ = output_check_timer.tick(), if recorder.is_some() => {
+                    let Some(recorder_settings) = recorder_settings.as_mut() else { continue };
+                    // an error means the recorder process is gone
+                    if let Some(Ok(true)) = recorder.as_mut().map(Recorder::is_recording) {
+                        continue;
+                    }
+                    // restarting doesn't help, stop() reports the full disk
+                    if matches!(Self::recorder_failure(&segment_path), StopReason::DiskFull) {
+                        break;
+                    }
+
+                    let restarted = if output_restarts < Self::MAX_OUTPUT_RESTARTS {
+                        output_restarts += 1;
+                        log::warn!(
+                            "recorder output stopped unexpectedly, restarting it ({output_restarts}/{})",
+                            Self::MAX_OUTPUT_RESTARTS
+                        );
+                        Self::restart_output(
+                            &ctx.app_handle,
+                            &mut recorder,
+                            recorder_settings,
+                            &mut metadata,
+                            &mut deferred,
+                            health.as_mut(),
+                            webcam_visible,
+                        )
+                    } else {
+                        Err(anyhow!("the output stopped again after {output_restarts} restarts"))
+                    };
+
+                    let dead_output = segment_path.clone();
+                    match restarted {
+                        Ok(next_segment_path) => {
+                            segment_start = Instant::now();
+                            segment_path = next_segment_path;
+                            Self::finalize_output(&ctx.app_handle, &dead_output);
+                        }
+                        Err(e) => {
+                            if let Some(recorder) = recorder.as_mut() {
+                                _ = recorder.stop_recording();
+                            }
+                            Self::finalize_output(&ctx.app_handle, &dead_output);
+
+                            let reason = format!("the recorder stopped and couldn't be restarted: {e}");
+                            log::error!("{reason}");
+                            ctx.app_handle.state::<RecorderHistory>().push_error(&reason);
+                            metadata.stop_reason = Some(StopReason::Error(reason.clone()));
+                            let payload = RecordingFailed { reason, output_restarts };
+                            if let Err(e) = ctx.app_handle.send_event(AppEvent::RecordingFailed { payload }) {
+                                log::error!("failed to send RecordingFailed event: {e}");
+                            }
+                            break;
+                        }
+                    }
+                }
+                _ = stats_timer.tick(), if health.is_some() => {
+                    let (Some(recorder), Some(health)) = (recorder.as_mut(), health.as_mut()) else { continue };
+                    match recorder.stats() {
+                        Ok(stats) => {
+                            let payload = health.update(stats);
+                            if let Err(e) = ctx.app_handle.send_event(AppEvent::RecordingStats { payload }) {
+                                log::warn!("failed to send RecordingStats event: {e}");
+                            }
+                        }
+                        Err(e) => log::warn!("failed to get recorder stats: {e}"),
+                    }
+                }
+            }
+        }
+
+        if let (Some(recorder), Some(health)) = (recorder.as_mut(), health.as_mut()) {
+            if let Ok(stats) = recorder.stats() {
+                health.update(stats);
+            }
+            let summary = health.summary();
+            log::info!("recording stats: {summary:?}");
+            metadata.recording_stats = Some(summary);
+        }
+
         Ok((recorder, metadata))
     }
 
-    async fn setup_recorder(ctx: &GameCtx) -> Result<(Recorder, PathBuf)> {
+    fn segment_full(segmentation: &Segmentation, segment_path: &Path, segment_start: Instant) -> bool {
+        let too_long = segmentation
+            .max_duration_mins
+            .filter(|&mins| mins > 0)
+            .is_some_and(|mins| segment_start.elapsed() >= Duration::from_secs(mins.saturating_mul(60)));
+        let too_big = segmentation.max_size_mb.filter(|&mb| mb > 0).is_some_and(|mb| {
+            segment_path
+                .metadata()
+                .is_ok_and(|file| file.len() >= mb.saturating_mul(1_000_000))
+        });
+        too_long || too_big
+    }
+
+    /// Returns the stats to compare against if the recording should fall back to a lower framerate when it lags
+    fn start_framerate_check(
+        ctx: &GameCtx,
+        recorder: Option<&mut Recorder>,
+        recorder_settings: Option<&RecorderSettings>,
+    ) -> Option<RecorderStats> {
+        let framerate = recorder_settings?.get_framerate()?;
+        if !ctx.app_handle.state::<SettingsWrapper>().framerate_fallback()
+            || Self::fps(framerate) <= f64::from(Self::FALLBACK_FPS)
+        {
+            return None;
+        }
+
+        match recorder?.stats() {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                log::warn!("failed to get recorder stats, framerate fallback disabled: {e}");
+                None
+            }
+        }
+    }
+
+    fn fps(framerate: &Framerate) -> f64 {
+        f64::from(framerate.num()) / f64::from(framerate.den())
+    }
+
+    /// Continue the recording in the next segment and store the segment in the metadata.
+    /// Returns the path of the new segment.
+    fn next_segment(
+        recorder: &mut Recorder,
+        recorder_settings: &mut RecorderSettings,
+        metadata: &mut Metadata,
+        deferred: &mut Deferred,
+        mut health: Option<&mut HealthTracker>,
+        webcam_visible: bool,
+    ) -> Result<PathBuf> {
+        let segment_index = metadata.segments.len() + 2;
+        let segment_path = action::segment_path(&metadata.output_filepath, segment_index);
+
+        // libobs resets its counters for the new output file
+        if let (Some(health), Ok(stats)) = (health.as_deref_mut(), recorder.stats()) {
+            health.update(stats);
+        }
+        let output_duration = Self::output_duration(recorder, &metadata.output_start);
+        Self::start_segment(recorder, recorder_settings, &segment_path, webcam_visible)?;
+        if let (Some(health), Ok(stats)) = (health, recorder.stats()) {
+            health.start_segment(stats);
+        }
+        metadata.finished_outputs_duration += output_duration;
+        metadata.output_start = Self::output_start(Some(recorder), Some(&*recorder_settings));
+
+        let Some(file_name) = segment_path.file_name().and_then(|name| name.to_str()) else {
+            return Ok(segment_path);
+        };
+        metadata.segments.push(Segment {
+            file_name: file_name.to_owned(),
+            start: metadata.finished_outputs_duration,
+        });
+        log::info!("recording continues in segment {}", segment_path.display());
+
+        // write the chaining info right away so the segments stay linked even if the app crashes
+        deferred.segments = metadata.segments.clone();
+        let segments = metadata.segments.clone();
+        let result = Self::update_deferred(&metadata.output_filepath, deferred, |current| {
+            current.segments = segments;
+        });
+        if let Err(e) = result {
+            log::warn!("failed to save segments to MetadataFile: {e}");
+        }
+        Ok(segment_path)
+    }
+
+    fn output_start(recorder: Option<&mut Recorder>, recorder_settings: Option<&RecorderSettings>) -> OutputStart {
+        OutputStart {
+            instant: Instant::now(),
+            encoded_frames: recorder
+                .and_then(|recorder| recorder.stats().ok())
+                .map(|stats| stats.encoded_frames()),
+            fps: recorder_settings
+                .and_then(RecorderSettings::get_framerate)
+                .map(Self::fps),
+        }
+    }
+
+    /// Length of the video in the current output file. The wall clock would also count the time it took to start the
+    /// output and drift away from the video a little with every segment, so it's only used without the frame count.
+    fn output_duration(recorder: &mut Recorder, output_start: &OutputStart) -> f64 {
+        let encoded_frames = recorder
+            .stats()
+            .ok()
+            .zip(output_start.encoded_frames)
+            .and_then(|(stats, start_frames)| stats.encoded_frames().checked_sub(start_frames));
+        match (encoded_frames, output_start.fps) {
+            (Some(frames), Some(fps)) if fps > 0.0 => f64::from(frames) / fps,
+            _ => output_start.instant.elapsed().as_secs_f64(),
+        }
+    }
+
+    /// Change the metadata file of the running recording. The file is read again first so changes the user made in the
+    /// meantime (e.g. favorite or protected) are kept. `deferred` is only used if the file can't be read anymore.
+    fn update_deferred(output_filepath: &Path, deferred: &Deferred, update: impl FnOnce(&mut Deferred)) -> Result<()> {
+        let mut current = match action::get_recording_metadata(output_filepath, false) {
+            Ok(MetadataFile::Deferred(current)) => current,
+            _ => deferred.clone(),
+        };
+        update(&mut current);
+        action::save_recording_metadata(output_filepath, &MetadataFile::Deferred(current))
+    }
+
+    /// Finish the current output file and continue recording into `segment_path`.
+    fn start_segment(
+        recorder: &mut Recorder,
+        recorder_settings: &mut RecorderSettings,
+        segment_path: &Path,
+        webcam_visible: bool,
+    ) -> Result<()> {
+        recorder.stop_recording()?;
+        recorder_settings.set_output_path(segment_path.to_string_lossy());
+        recorder.configure(recorder_settings)?;
+        recorder.start_recording()?;
+
+        // configuring resets the webcam to the settings which might differ from what got toggled by the hotkey
+        if recorder_settings.get_webcam().is_some() {
+            if let Err(e) = recorder.set_webcam_visible(webcam_visible) {
+                log::warn!("failed to restore webcam visibility: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Continue the recording in the next segment after the output died. If the recorder itself doesn't work anymore
+    /// (e.g. its process crashed with the GPU driver) it gets replaced by a new one.
+    /// Returns the path of the new segment.
+    fn restart_output(
+        app_handle: &AppHandle,
+        recorder: &mut Option<Recorder>,
+        recorder_settings: &mut RecorderSettings,
+        metadata: &mut Metadata,
+        deferred: &mut Deferred,
+        mut health: Option<&mut HealthTracker>,
+        webcam_visible: bool,
+    ) -> Result<PathBuf> {
+        if let Some(recorder) = recorder.as_mut() {
+            match Self::next_segment(
+                recorder,
+                recorder_settings,
+                metadata,
+                deferred,
+                health.as_deref_mut(),
+                webcam_visible,
+            ) {
+                Ok(segment_path) => return Ok(segment_path),
+                Err(e) => log::warn!("failed to restart the output, starting a new recorder: {e}"),
+            }
+        }
+
+        if let Some(recorder) = recorder.take() {
+            if let Err(e) = recorder.shutdown() {
+                log::warn!("failed to shut down the old recorder: {e}");
+            }
+        }
+        let new_recorder = recorder.insert(prewarm::start_recorder(app_handle)?);
+        // the frame counter of the new recorder starts at 0
+        metadata.output_start.encoded_frames = None;
+        // the recorder process has to be configured before the output can be stopped and started
+        new_recorder.configure(recorder_settings)?;
+        Self::next_segment(
+            new_recorder,
+            recorder_settings,
+            metadata,
+            deferred,
+            health,
+            webcam_visible,
+        )
+    }
+
+    /// The index of an MP4 file gets written when the output stops. If the output died it could be missing, which
+    /// makes the file unplayable => rebuild the container with a job.
+    fn finalize_output(app_handle: &AppHandle, output: &Path) {
+        // an empty file has nothing to save
+        if output.metadata().is_ok_and(|file| file.len() == 0) {
+            return;
+        }
+
+        match util::is_mp4_finalized(output) {
+            Ok(true) => {}
+            Ok(false) => {
+                log::info!("{} wasn't finalized, queueing a remux", output.display());
+                jobs::enqueue(app_handle, JobKind::Remux, output);
+            }
+            Err(e) => log::warn!("failed to check if {} was finalized: {e}", output.display()),
+        }
+    }
+
+    fn recorder_failure(output_filepath: &Path) -> StopReason {
+        let free_disk_space = output_filepath.parent().and_then(util::free_disk_space);
+        if free_disk_space.is_some_and(|free| free < Self::MIN_FREE_DISK_SPACE) {
+            StopReason::DiskFull
+        } else {
+            StopReason::Error("recorder stopped unexpectedly".into())
+        }
+    }
+
+    /// The single offset measurement right after the recording started includes the latency of the ingame API request.
+    /// Take multiple samples of the ingame time against the time passed since the recording started (using the midpoint
+    /// of each request) and use the median to get an offset that doesn't depend on one possibly slow request.
+    ///
+    /// The samples are only taken during the first 30s of the recording, drift later on isn't corrected. The video and
+    /// the ingame time both advance in real time, game pauses are stored separately in the metadata.
+    async fn calibrate_offset(
+        ingame_client: &IngameClient,
+        rec_start_instant: Instant,
+        raw_offset: f64,
+        cancel_token: &CancellationToken,
+    ) -> f64 {
+        let mut samples = Vec::with_capacity(Self::CALIBRATION_SAMPLES);
+        let mut timer = interval(Self::CALIBRATION_INTERVAL);
+
+        while samples.len() < Self::CALIBRATION_SAMPLES {
+            let cancelled = cancellable!(timer.tick(), cancel_token, ());
+            if cancelled {
+                break;
+            }
+
+            let request_start = rec_start_instant.elapsed().as_secs_f64();
+            let Ok(stats) = ingame_client.game_stats().await else { continue };
+            let request_end = rec_start_instant.elapsed().as_secs_f64();
+
+            samples.push(stats.game_time - (request_start + request_end) / 2.0);
+        }
+
+        if samples.is_empty() {
+            log::warn!("no calibration samples for recording offset - using raw offset {raw_offset:.3}");
+            return raw_offset;
+        }
+
+        samples.sort_by(f64::total_cmp);
+        let offset = samples[samples.len() / 2];
+        log::info!(
+            "calibrated recording offset from {} samples: raw={raw_offset:.3}, corrected={offset:.3}",
+            samples.len()
+        );
+        offset
+    }
+
+    async fn setup_recorder(ctx: &GameCtx) -> Result<(Recorder, RecorderSettings)> {
         let settings_state = ctx.app_handle.state::<SettingsWrapper>();
 
         let window_size = Self::get_window_size().await?;
@@ -178,16 +834,7 @@ impl RecordingTask {
 
         log::info!("Using resolution ({output_resolution:?}) for window ({window_size:?})");
 
-        let mut filename = settings_state.get_filename_format();
-        if !filename.ends_with(".mp4") {
-            filename.push_str(".mp4");
-        }
-        let formatted_filename = format!("{}", chrono::Local::now().format(&filename))
-            .replace(":", "-")
-            .replace("/", "-")
-            .replace("\\", "-");
-
-        let filename_path = settings_state.get_recordings_path().join(formatted_filename);
+        let filename_path = Self::output_filepath(&settings_state, ctx);
 
         let mut settings = RecorderSettings::new(
             Window::new(WINDOW_TITLE, Some(WINDOW_CLASS.into()), Some(WINDOW_PROCESS.into())),
@@ -196,28 +843,76 @@ impl RecordingTask {
             &filename_path,
         );
         settings.set_framerate(settings_state.get_framerate());
-        settings.set_rate_control(RateControl::CQP(settings_state.get_encoding_quality()));
+        let encoder = settings_state.encoder();
+        settings.set_rate_control(encoder.rate_control());
+        settings.set_encoder_preset(encoder.preset);
+        settings.set_keyframe_interval(encoder.keyframe_interval_secs);
         settings.set_audio_source(settings_state.get_audio_source());
+        settings.set_audio_device(settings_state.get_audio_device());
+        settings.set_noise_suppression(settings_state.noise_suppression());
+        settings.set_capture_mode(settings_state.get_capture_mode());
+        if window::is_hdr_enabled() {
+            let hdr = settings_state.hdr() == HdrMode::Hdr;
+            log::info!(
+                "HDR is enabled on the display, recording in {}",
+                if hdr { "HDR" } else { "SDR" }
+            );
+            settings.set_hdr(hdr);
+        }
+        settings.set_webcam(settings_state.get_webcam());
 
-        let mut recorder = Recorder::new_with_paths(
-            ctx.app_handle
-                .path()
-                .resolve("libobs/extprocess_recorder.exe", BaseDirectory::Executable)
-                .ok(),
-            None,
-            None,
-            None,
-        )?;
+        // started during champ select if possible
+        let mut recorder = match ctx.app_handle.state::<PrewarmedRecorder>().take().await {
+            Some(recorder) => recorder,
+            None => prewarm::start_recorder(&ctx.app_handle)
+                .map_err(|e| RecorderError::ObsInit { message: e.to_string() })?,
+        };
 
         log::info!("recorder settings: {settings:?}");
-        recorder.configure(&settings)?;
+        recorder
+            .configure(&settings)
+            .map_err(|e| RecorderError::ObsInit { message: e.to_string() })?;
         log::info!("recorder configured");
 
-        log::info!("Selected adapter: {:?}", recorder.adapter_info());
+        let adapter = recorder.adapter_info();
+        log::info!("Selected adapter: {adapter:?}");
+        if let Ok(adapter) = adapter {
+            ctx.app_handle
+                .state::<RecorderHistory>()
+                .set_adapter(adapter.name().to_owned());
+        }
         log::info!("Available encoders for adapter: {:?}", recorder.available_encoders());
         log::info!("Selected encoder: {:?}", recorder.selected_encoder());
 
-        Ok((recorder, filename_path))
+        Ok((recorder, settings))
+    }
+
+    fn output_filepath(settings: &SettingsWrapper, ctx: &GameCtx) -> PathBuf {
+        // the name of the recording can't give away the account on stream (no {champion} token)
+        let mut filename = if settings.safe_titles() {
+            format!("{}_{SAFE_FILENAME_FORMAT}", ctx.match_id.game_id)
+        } else {
+            settings.get_filename_format()
+        };
+        if !filename.ends_with(".mp4") {
+            filename.push_str(".mp4");
+        }
+        let formatted_filename = format!("{}", chrono::Local::now().format(&filename))
+            .replace(":", "-")
+            .replace("/", "-")
+            .replace("\\", "-");
+
+        let mut folder = settings.get_recordings_path();
+        if !ctx.subfolders.is_empty() {
+            for subfolder in &ctx.subfolders {
+                folder.push(subfolder.replace(['<', '>', ':', '"', '/', '\\', '|', '?', '*'], "_"));
+            }
+            if let Err(e) = fs::create_dir_all(&folder) {
+                log::warn!("failed to create recordings subfolder: {e}");
+                folder = settings.get_recordings_path();
+            }
+        }
+        folder.join(formatted_filename)
     }
 
     async fn get_window_size() -> Result<Resolution> {
@@ -229,6 +924,6 @@ impl RecordingTask {
             sleep(Duration::from_millis(500)).await;
         }
 
-        bail!("unable to get window size");
+        bail!(RecorderError::WindowNotFound);
     }
 }
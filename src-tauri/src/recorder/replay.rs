@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use riot_datatypes::MatchId;
+use serde::Deserialize;
+use serde_json::json;
+use shaco::ingame::IngameClient;
+use shaco::rest::LcuRestClient;
+use tokio::time::sleep;
+
+use super::{GameMetadata, Pause};
+
+// replays usually become available a minute or two after the game ended
+const REPLAY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const REPLAY_POLL_ATTEMPTS: u32 = 60;
+// loading into a replay takes about as long as loading into a normal game
+const REPLAY_LOAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const REPLAY_LOAD_POLL_ATTEMPTS: u32 = 180;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayMetadata {
+    state: String,
+}
+
+/// Download the official replay (.rofl) of a game through the LCU and return the path of the file.
+pub async fn download_replay(lcu_rest_client: &LcuRestClient, match_id: &MatchId) -> Result<PathBuf> {
+    let game_id = match_id.game_id;
+
+    for _ in 0..REPLAY_POLL_ATTEMPTS {
+        let metadata = lcu_rest_client
+            .get::<ReplayMetadata>(format!("/lol-replays/v1/metadata/{game_id}"))
+            .await?;
+
+        match metadata.state.as_str() {
+            "watch" => return replay_path(lcu_rest_client, match_id).await,
+            "download" | "retryDownload" => {
+                // the LCU answers with an empty body which can't be decoded - only the status code matters
+                let result = lcu_rest_client
+                    .post::<_, serde_json::Value>(
+                        format!("/lol-replays/v1/rofls/{game_id}/download"),
+                        json!({ "componentType": "replay-button_match-history" }),
+                    )
+                    .await;
+                if let Err(e) = result {
+                    if !e.is_decode() {
+                        return Err(e.into());
+                    }
+                }
+            }
+            "checking" | "downloading" | "loading" => {}
+            state => bail!("replay for game {game_id} is not available (state: {state})"),
+        }
+
+        sleep(REPLAY_POLL_INTERVAL).await;
+    }
+
+    bail!("timed out waiting for the replay download of game {game_id}")
+}
+
+async fn replay_path(lcu_rest_client: &LcuRestClient, match_id: &MatchId) -> Result<PathBuf> {
+    let replays_folder = lcu_rest_client.get::<PathBuf>("/lol-replays/v1/rofls/path").await?;
+    let replay_path = replays_folder.join(format!("{}-{}.rofl", match_id.platform_id, match_id.game_id));
+    if !replay_path.is_file() {
+        bail!("replay file not found at {}", replay_path.display());
+    }
+    Ok(replay_path)
+}
+
+/// Launch the downloaded replay of a game in the League client and jump to `game_time` (in seconds) once it loaded.
+pub async fn watch_replay_at(lcu_rest_client: &LcuRestClient, match_id: &MatchId, game_time: f64) -> Result<()> {
+    let game_id = match_id.game_id;
+    let result = lcu_rest_client
+        .post::<_, serde_json::Value>(
+            format!("/lol-replays/v1/rofls/{game_id}/watch"),
+            json!({ "componentType": "replay-button_match-history" }),
+        )
+        .await;
+    if let Err(e) = result {
+        if !e.is_decode() {
+            return Err(e.into());
+        }
+    }
+
+    // seeking only works after the replay has loaded, which is when the replay API starts accepting requests
+    let ingame_client = IngameClient::new();
+    for _ in 0..REPLAY_LOAD_POLL_ATTEMPTS {
+        if ingame_client.replay_seek(game_time).await.is_ok() {
+            return Ok(());
+        }
+        sleep(REPLAY_LOAD_POLL_INTERVAL).await;
+    }
+
+    bail!("replay didn't accept the seek request - is the replay API enabled ('EnableReplayApi=1' in game.cfg)?")
+}
+
+/// Ingame time at `video_time` (in seconds) of the recording. Inverse of the marker position described on [`Pause`].
+pub fn game_time_at(metadata: &GameMetadata, video_time: f64) -> f64 {
+    let mut game_time = video_time + metadata.ingame_time_rec_start_offset;
+    for Pause { game_time: paused_at, duration } in &metadata.pauses {
+        if *paused_at >= game_time {
+            break;
+        }
+        // a video time during the pause itself maps to the moment the game was paused
+        game_time = (game_time - duration).max(*paused_at);
+    }
+    game_time.max(0.0)
+}
@@ -0,0 +1,72 @@
+use anyhow::{bail, Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue};
+use riot_datatypes::riot_api::MatchDto;
+use riot_datatypes::MatchId;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::timeline::ProcessedTimeline;
+
+/// Minimal client for the public Riot Games API (Match-V5) using a user supplied API key.
+pub struct RiotApiClient {
+    client: reqwest::Client,
+}
+
+// the Match-V5 timeline has the same frame format as the LCU timeline, just wrapped in 'info'
+#[derive(Deserialize)]
+struct TimelineDto {
+    info: ProcessedTimeline,
+}
+
+impl RiotApiClient {
+    pub fn new(api_key: &str) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Riot-Token",
+            HeaderValue::from_str(api_key).context("invalid Riot API key")?,
+        );
+
+        let client = reqwest::Client::builder().default_headers(headers).build()?;
+        Ok(Self { client })
+    }
+
+    pub async fn get_match(&self, match_id: &MatchId) -> Result<MatchDto> {
+        self.get(match_id, &format!("/lol/match/v5/matches/{match_id}")).await
+    }
+
+    pub async fn get_timeline(&self, match_id: &MatchId) -> Result<ProcessedTimeline> {
+        let timeline = self
+            .get::<TimelineDto>(match_id, &format!("/lol/match/v5/matches/{match_id}/timeline"))
+            .await?;
+        Ok(timeline.info)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, match_id: &MatchId, path: &str) -> Result<T> {
+        let Some(region) = regional_route(&match_id.platform_id) else {
+            bail!("unknown platform: {}", match_id.platform_id);
+        };
+
+        let response = self
+            .client
+            .get(format!("https://{region}.api.riotgames.com{path}"))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("Riot API request failed: {}", response.status());
+        }
+
+        Ok(response.json::<T>().await?)
+    }
+}
+
+// https://developer.riotgames.com/docs/lol#routing-values
+fn regional_route(platform_id: &str) -> Option<&'static str> {
+    let region = match platform_id.to_uppercase().as_str() {
+        "NA1" | "BR1" | "LA1" | "LA2" => "americas",
+        "KR" | "JP1" => "asia",
+        "EUN1" | "EUW1" | "TR1" | "RU" | "ME1" => "europe",
+        "OC1" | "PH2" | "SG2" | "TH2" | "TW2" | "VN2" => "sea",
+        _ => return None,
+    };
+    Some(region)
+}
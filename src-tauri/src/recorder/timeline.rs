@@ -0,0 +1,99 @@
+use std::fmt;
+
+use riot_datatypes::Frame;
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use super::{GameEvent, GoldFrame, ParticipantGold};
+
+/// The parts of the LCU match timeline that end up in the metadata file.
+///
+/// Deserializing this directly from the response processes the timeline one frame at a time: every frame is converted
+/// as soon as it has been parsed and dropped afterwards. This avoids holding the complete `riot_datatypes::Timeline`
+/// (plus a copy of all its events) in memory, which gets large for very long games.
+#[derive(Debug, Default)]
+pub struct ProcessedTimeline {
+    pub events: Vec<GameEvent>,
+    pub gold_timeline: Vec<GoldFrame>,
+}
+
+impl ProcessedTimeline {
+    fn add_frame(&mut self, frame: Frame) {
+        let Frame {
+            events,
+            participant_frames,
+            timestamp,
+        } = frame;
+
+        for event in events {
+            match GameEvent::try_from(event) {
+                Ok(event) => self.events.push(event),
+                Err(e) => log::debug!("skipping timeline event: {e}"),
+            }
+        }
+
+        let participants = participant_frames
+            .into_iter()
+            .map(|(participant_id, pf)| ParticipantGold {
+                participant_id,
+                total_gold: pf.total_gold,
+                minions: pf.minions_killed + pf.jungle_minions_killed,
+                xp: Some(pf.xp),
+            })
+            .collect();
+        self.gold_timeline.push(GoldFrame { timestamp, participants });
+    }
+}
+
+impl<'de> Deserialize<'de> for ProcessedTimeline {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(TimelineVisitor)
+    }
+}
+
+struct TimelineVisitor;
+
+impl<'de> Visitor<'de> for TimelineVisitor {
+    type Value = ProcessedTimeline;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a match timeline")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut timeline = ProcessedTimeline::default();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "frames" {
+                map.next_value_seed(FramesSeed(&mut timeline))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(timeline)
+    }
+}
+
+struct FramesSeed<'a>(&'a mut ProcessedTimeline);
+
+impl<'de> DeserializeSeed<'de> for FramesSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FramesSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of timeline frames")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(frame) = seq.next_element::<Frame>()? {
+            self.0.add_frame(frame);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anyhow::Result;
+use riot_datatypes::{ChampionId, ParticipantId, QueueId, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use super::{Event, GameMetadata, HighlightCategory, Markers};
+
+/// Bump whenever a field of the exported document changes its meaning or gets removed. Adding fields is fine.
+const SCHEMA_VERSION: u32 = 1;
+const CSV_HEADER: &str = "gameTime,videoTime,kind,participantId,detail";
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineFormat {
+    /// the complete [`TimelineDocument`]
+    Json,
+    /// one row per event, highlight, bookmark and comment (`CSV_HEADER`)
+    Csv,
+}
+
+/// Timeline of a recording for external analysis tools.
+///
+/// This is deliberately decoupled from the metadata file layout so scripts using it keep working when the metadata
+/// changes. All times are in seconds: `gameTime` is the ingame time, `videoTime` the position in the recording.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineDocument {
+    pub schema_version: u32,
+    pub match_id: String,
+    pub queue_id: QueueId,
+    pub game_version: String,
+    /// participant id of the recorded player
+    pub participant_id: ParticipantId,
+    pub video_duration: Option<f64>,
+    pub participants: Vec<TimelineParticipant>,
+    pub events: Vec<TimelineEvent>,
+    pub gold: Vec<TimelineGold>,
+    pub builds: Vec<TimelineBuild>,
+    pub highlights: Vec<TimelineMarker>,
+    pub bookmarks: Vec<TimelineMarker>,
+    pub comments: Vec<TimelineComment>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineParticipant {
+    pub participant_id: ParticipantId,
+    pub team_id: i64,
+    pub champion_id: ChampionId,
+    pub summoner_name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEvent {
+    pub game_time: f64,
+    pub video_time: f64,
+    /// `championKill`, `buildingKill`, `eliteMonsterKill` or `jungleCampKill`
+    pub kind: &'static str,
+    /// killer of champions, buildings, monsters and camps
+    pub participant_id: ParticipantId,
+    pub victim_id: Option<ParticipantId>,
+    pub assisting_participant_ids: Vec<ParticipantId>,
+    /// human readable details (building or monster type, creep score gained)
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineGold {
+    pub game_time: f64,
+    pub participant_id: ParticipantId,
+    pub total_gold: i64,
+    pub minions: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineBuild {
+    pub participant_id: ParticipantId,
+    pub items: Vec<TimelineItem>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineItem {
+    pub game_time: f64,
+    pub item_id: i64,
+    /// `purchased`, `sold` or `undone`
+    pub action: &'static str,
+}
+
+/// highlight or bookmark that got set with a hotkey during the game
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineMarker {
+    pub game_time: f64,
+    pub video_time: f64,
+}
+
+/// note the user wrote while reviewing the recording, only the position in the video is known
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineComment {
+    pub video_time: f64,
+    pub text: String,
+}
+
+fn seconds(timestamp: Timestamp) -> f64 {
+    timestamp as f64 / 1000.0
+}
+
+impl TimelineDocument {
+    pub fn new(metadata: &GameMetadata) -> Self {
+        let markers = Markers::from_metadata(metadata);
+
+        let mut events = Vec::new();
+        let mut builds = BTreeMap::<ParticipantId, Vec<TimelineItem>>::new();
+        for marker in markers.events {
+            let game_time = seconds(marker.event.timestamp);
+            let video_time = marker.position.video_time;
+            let event = match marker.event.event {
+                Event::ChampionKill {
+                    victim_id,
+                    killer_id,
+                    assisting_participant_ids,
+                    ..
+                } => TimelineEvent {
+                    game_time,
+                    video_time,
+                    kind: "championKill",
+                    participant_id: killer_id,
+                    victim_id: Some(victim_id),
+                    assisting_participant_ids,
+                    detail: None,
+                },
+                Event::BuildingKill {
+                    killer_id,
+                    building_type,
+                    assisting_participant_ids,
+                    ..
+                } => TimelineEvent {
+                    game_time,
+                    video_time,
+                    kind: "buildingKill",
+                    participant_id: killer_id,
+                    victim_id: None,
+                    assisting_participant_ids,
+                    detail: Some(format!("{building_type:?}")),
+                },
+                Event::EliteMonsterKill {
+                    killer_id,
+                    monster_type,
+                    assisting_participant_ids,
+                } => TimelineEvent {
+                    game_time,
+                    video_time,
+                    kind: "eliteMonsterKill",
+                    participant_id: killer_id,
+                    victim_id: None,
+                    assisting_participant_ids,
+                    detail: Some(format!("{monster_type:?}")),
+                },
+                Event::JungleCampKill {
+                    participant_id,
+                    creep_score_gained,
+                } => TimelineEvent {
+                    game_time,
+                    video_time,
+                    kind: "jungleCampKill",
+                    participant_id,
+                    victim_id: None,
+                    assisting_participant_ids: vec![],
+                    detail: Some(format!("{creep_score_gained} creep score")),
+                },
+                Event::ItemPurchased { participant_id, item_id, .. } => {
+                    builds.entry(participant_id).or_default().push(TimelineItem {
+                        game_time,
+                        item_id,
+                        action: "purchased",
+                    });
+                    continue;
+                }
+                Event::ItemSold { participant_id, item_id, .. } => {
+                    builds.entry(participant_id).or_default().push(TimelineItem {
+                        game_time,
+                        item_id,
+                        action: "sold",
+                    });
+                    continue;
+                }
+                Event::ItemUndo { participant_id, before_id, .. } => {
+                    builds.entry(participant_id).or_default().push(TimelineItem {
+                        game_time,
+                        item_id: before_id,
+                        action: "undone",
+                    });
+                    continue;
+                }
+            };
+            events.push(event);
+        }
+
+        let gold = metadata
+            .gold_timeline
+            .iter()
+            .flat_map(|frame| {
+                frame.participants.iter().map(|p| TimelineGold {
+                    game_time: seconds(frame.timestamp),
+                    participant_id: p.participant_id,
+                    total_gold: p.total_gold,
+                    minions: p.minions,
+                })
+            })
+            .collect();
+
+        let mut highlights = Vec::new();
+        let mut bookmarks = Vec::new();
+        for (highlight, marker) in metadata.highlights.iter().zip(markers.highlights) {
+            let timeline_marker = TimelineMarker {
+                game_time: highlight.timestamp / 1000.0,
+                video_time: marker.position.video_time,
+            };
+            match highlight.category {
+                HighlightCategory::Highlight => highlights.push(timeline_marker),
+                HighlightCategory::Bookmark => bookmarks.push(timeline_marker),
+            }
+        }
+
+        let comments = metadata
+            .comments
+            .iter()
+            .map(|comment| TimelineComment {
+                video_time: comment.video_time,
+                text: comment.text.clone(),
+            })
+            .collect();
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            match_id: metadata.match_id.to_string(),
+            queue_id: metadata.queue.id,
+            game_version: metadata.game_version.clone(),
+            participant_id: metadata.participant_id,
+            video_duration: metadata.video_duration,
+            participants: metadata
+                .participants
+                .iter()
+                .map(|p| TimelineParticipant {
+                    participant_id: p.participant_id,
+                    team_id: p.team_id,
+                    champion_id: p.champion_id,
+                    summoner_name: p.summoner_name.clone(),
+                })
+                .collect(),
+            events,
+            gold,
+            builds: builds
+                .into_iter()
+                .map(|(participant_id, items)| TimelineBuild { participant_id, items })
+                .collect(),
+            highlights,
+            bookmarks,
+            comments,
+        }
+    }
+
+    pub fn to_format(&self, format: TimelineFormat) -> Result<String> {
+        match format {
+            TimelineFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            TimelineFormat::Csv => Ok(self.to_csv()),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+
+        for event in &self.events {
+            let detail = event.detail.as_deref().unwrap_or_default().replace('"', "\"\"");
+            _ = writeln!(
+                csv,
+                "{:.3},{:.3},{},{},\"{detail}\"",
+                event.game_time, event.video_time, event.kind, event.participant_id
+            );
+        }
+        let markers = self.highlights.iter().map(|marker| ("highlight", marker));
+        for (kind, marker) in markers.chain(self.bookmarks.iter().map(|marker| ("bookmark", marker))) {
+            _ = writeln!(
+                csv,
+                "{:.3},{:.3},{kind},{},\"\"",
+                marker.game_time, marker.video_time, self.participant_id
+            );
+        }
+        // comments aren't tied to the ingame time
+        for comment in &self.comments {
+            let text = comment.text.replace('"', "\"\"");
+            _ = writeln!(
+                csv,
+                ",{:.3},comment,{},\"{text}\"",
+                comment.video_time, self.participant_id
+            );
+        }
+
+        csv
+    }
+}
@@ -1,5 +1,10 @@
 use libobs_recorder::settings::Resolution;
-use windows_sys::Win32::Foundation::{HWND, RECT};
+use windows_sys::Win32::Devices::Display::{
+    DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
+    DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO, DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO, DISPLAYCONFIG_MODE_INFO,
+    DISPLAYCONFIG_PATH_INFO, QDC_ONLY_ACTIVE_PATHS,
+};
+use windows_sys::Win32::Foundation::{ERROR_SUCCESS, HWND, RECT};
 use windows_sys::Win32::UI::WindowsAndMessaging::{FindWindowA, GetClientRect};
 
 pub const WINDOW_TITLE: &str = "League of Legends (TM) Client";
@@ -43,3 +48,41 @@ pub fn get_window_size(hwnd: HWND) -> Option<Resolution> {
         None
     }
 }
+
+/// Returns true if HDR ('advanced color') is enabled on any active display
+pub fn is_hdr_enabled() -> bool {
+    let mut path_count = 0;
+    let mut mode_count = 0;
+    if unsafe { GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count) } != ERROR_SUCCESS
+    {
+        return false;
+    }
+
+    let mut paths: Vec<DISPLAYCONFIG_PATH_INFO> = vec![unsafe { std::mem::zeroed() }; path_count as usize];
+    let mut modes: Vec<DISPLAYCONFIG_MODE_INFO> = vec![unsafe { std::mem::zeroed() }; mode_count as usize];
+    let result = unsafe {
+        QueryDisplayConfig(
+            QDC_ONLY_ACTIVE_PATHS,
+            &mut path_count,
+            paths.as_mut_ptr(),
+            &mut mode_count,
+            modes.as_mut_ptr(),
+            std::ptr::null_mut(),
+        )
+    };
+    if result != ERROR_SUCCESS {
+        return false;
+    }
+
+    paths.iter().take(path_count as usize).any(|path| {
+        let mut color_info: DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO = unsafe { std::mem::zeroed() };
+        color_info.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO;
+        color_info.header.size = std::mem::size_of::<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO>() as u32;
+        color_info.header.adapterId = path.targetInfo.adapterId;
+        color_info.header.id = path.targetInfo.id;
+
+        // bit 1 of the bitfield is 'advancedColorEnabled'
+        let success = unsafe { DisplayConfigGetDeviceInfo(&mut color_info.header) } == ERROR_SUCCESS as i32;
+        success && unsafe { color_info.Anonymous.value } & 0b10 != 0
+    })
+}
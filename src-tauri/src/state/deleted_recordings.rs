@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Recordings that got moved to the recycle bin in this session so the deletion can be undone
+#[derive(Debug, Default)]
+pub struct DeletedRecordings(Mutex<Vec<DeletedRecording>>);
+
+#[derive(Debug)]
+pub struct DeletedRecording {
+    pub video_path: PathBuf,
+    /// the video and every file that belongs to it (segments, metadata, screenshot, ...)
+    pub files: Vec<PathBuf>,
+}
+
+impl DeletedRecordings {
+    pub fn push(&self, deleted_recording: DeletedRecording) {
+        self.0.lock().unwrap().push(deleted_recording);
+    }
+
+    /// the most recently deleted recording
+    pub fn pop(&self) -> Option<DeletedRecording> {
+        self.0.lock().unwrap().pop()
+    }
+}
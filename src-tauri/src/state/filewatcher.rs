@@ -13,3 +13,13 @@ impl FileWatcher {
         drop(std::mem::replace(&mut *self.0.lock().unwrap(), watcher));
     }
 }
+
+/// Watches the settings file for changes from outside of LeagueRecord (stops watching when dropped)
+#[derive(Debug)]
+pub struct SettingsWatcher(#[allow(dead_code)] notify::RecommendedWatcher);
+
+impl SettingsWatcher {
+    pub fn new(watcher: notify::RecommendedWatcher) -> Self {
+        SettingsWatcher(watcher)
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+use windows_sys::Win32::UI::Input::XboxController::{
+    XInputGetState, XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B, XINPUT_GAMEPAD_BACK, XINPUT_GAMEPAD_DPAD_DOWN,
+    XINPUT_GAMEPAD_DPAD_LEFT, XINPUT_GAMEPAD_DPAD_RIGHT, XINPUT_GAMEPAD_DPAD_UP, XINPUT_GAMEPAD_LEFT_SHOULDER,
+    XINPUT_GAMEPAD_LEFT_THUMB, XINPUT_GAMEPAD_RIGHT_SHOULDER, XINPUT_GAMEPAD_RIGHT_THUMB, XINPUT_GAMEPAD_START,
+    XINPUT_GAMEPAD_TRIGGER_THRESHOLD, XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y, XINPUT_STATE,
+};
+
+use crate::recorder::{HighlightCategory, LeagueRecorder};
+use crate::state::{CurrentlyRecording, SettingsWrapper};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// polling disconnected controllers is slow => only look for new controllers every few seconds
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+// nothing to poll if no gamepad hotkey is set
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_CONTROLLERS: u32 = 4;
+
+// the triggers are analog => they get their own bits above the 16 bits of the digital buttons
+const LEFT_TRIGGER: u32 = 1 << 16;
+const RIGHT_TRIGGER: u32 = 1 << 17;
+
+const BUTTONS: [(&str, u32); 16] = [
+    ("A", XINPUT_GAMEPAD_A as u32),
+    ("B", XINPUT_GAMEPAD_B as u32),
+    ("X", XINPUT_GAMEPAD_X as u32),
+    ("Y", XINPUT_GAMEPAD_Y as u32),
+    ("LB", XINPUT_GAMEPAD_LEFT_SHOULDER as u32),
+    ("RB", XINPUT_GAMEPAD_RIGHT_SHOULDER as u32),
+    ("LT", LEFT_TRIGGER),
+    ("RT", RIGHT_TRIGGER),
+    ("Back", XINPUT_GAMEPAD_BACK as u32),
+    ("Start", XINPUT_GAMEPAD_START as u32),
+    ("LS", XINPUT_GAMEPAD_LEFT_THUMB as u32),
+    ("RS", XINPUT_GAMEPAD_RIGHT_THUMB as u32),
+    ("Up", XINPUT_GAMEPAD_DPAD_UP as u32),
+    ("Down", XINPUT_GAMEPAD_DPAD_DOWN as u32),
+    ("Left", XINPUT_GAMEPAD_DPAD_LEFT as u32),
+    ("Right", XINPUT_GAMEPAD_DPAD_RIGHT as u32),
+];
+
+/// Buttons of an XInput controller that have to be held down together, e.g. "Back+A" or "LB+RB+Y"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GamepadCombo(u32);
+
+impl GamepadCombo {
+    /// The combo got pressed if all of its buttons are down now but weren't before
+    fn pressed(&self, previous: u32, current: u32) -> bool {
+        current & self.0 == self.0 && previous & self.0 != self.0
+    }
+}
+
+impl FromStr for GamepadCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut buttons = 0;
+        for name in s.split('+').map(str::trim) {
+            let (_, button) = BUTTONS
+                .iter()
+                .find(|(button_name, _)| button_name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("unknown gamepad button '{name}'"))?;
+            buttons |= button;
+        }
+        Ok(Self(buttons))
+    }
+}
+
+impl fmt::Display for GamepadCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = BUTTONS
+            .iter()
+            .filter(|(_, button)| self.0 & button != 0)
+            .map(|(name, _)| *name)
+            .collect();
+        f.write_str(&names.join("+"))
+    }
+}
+
+pub struct GamepadListener;
+
+impl GamepadListener {
+    /// Poll all XInput controllers for the combos of the 'gamepadHotkeys' setting
+    pub fn start(app_handle: AppHandle) {
+        thread::spawn(move || {
+            log::info!("Starting gamepad listener thread");
+
+            let mut connected = [false; MAX_CONTROLLERS as usize];
+            let mut buttons = [0; MAX_CONTROLLERS as usize];
+            let mut last_reconnect = None::<Instant>;
+            loop {
+                let hotkeys = app_handle.state::<SettingsWrapper>().gamepad_hotkeys();
+                let combos = [
+                    (hotkeys.highlight, Action::Highlight),
+                    (hotkeys.bookmark, Action::Bookmark),
+                    (hotkeys.stop_recording, Action::StopRecording),
+                ]
+                .into_iter()
+                .filter_map(|(combo, action)| Some((combo?.parse::<GamepadCombo>().ok()?, action)))
+                .collect::<Vec<_>>();
+                if combos.is_empty() {
+                    thread::sleep(IDLE_INTERVAL);
+                    continue;
+                }
+
+                let reconnect = !matches!(last_reconnect, Some(instant) if instant.elapsed() < RECONNECT_INTERVAL);
+                if reconnect {
+                    last_reconnect = Some(Instant::now());
+                }
+
+                for index in 0..MAX_CONTROLLERS {
+                    let slot = index as usize;
+                    if !connected[slot] && !reconnect {
+                        continue;
+                    }
+
+                    let current = Self::buttons(index);
+                    connected[slot] = current.is_some();
+                    let current = current.unwrap_or(0);
+                    for (combo, action) in &combos {
+                        if combo.pressed(buttons[slot], current) {
+                            log::info!("Gamepad: {action:?} combo triggered ({combo})");
+                            action.execute(&app_handle);
+                        }
+                    }
+                    buttons[slot] = current;
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+
+    /// Pressed buttons of the controller (None if it isn't connected)
+    fn buttons(index: u32) -> Option<u32> {
+        let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+        if unsafe { XInputGetState(index, &mut state) } != 0 {
+            return None;
+        }
+
+        let gamepad = state.Gamepad;
+        let mut buttons = gamepad.wButtons as u32;
+        if gamepad.bLeftTrigger as u32 > XINPUT_GAMEPAD_TRIGGER_THRESHOLD as u32 {
+            buttons |= LEFT_TRIGGER;
+        }
+        if gamepad.bRightTrigger as u32 > XINPUT_GAMEPAD_TRIGGER_THRESHOLD as u32 {
+            buttons |= RIGHT_TRIGGER;
+        }
+        Some(buttons)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Highlight,
+    Bookmark,
+    StopRecording,
+}
+
+impl Action {
+    /// Do the same thing as the corresponding keyboard hotkey
+    fn execute(self, app_handle: &AppHandle) {
+        if app_handle.state::<CurrentlyRecording>().get().is_none() {
+            return;
+        }
+
+        match self {
+            Action::Highlight => _ = app_handle.emit("shortcut-event", HighlightCategory::Highlight),
+            Action::Bookmark => _ = app_handle.emit("shortcut-event", HighlightCategory::Bookmark),
+            Action::StopRecording => app_handle.state::<LeagueRecorder>().manual_stop(),
+        }
+    }
+}
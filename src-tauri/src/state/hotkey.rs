@@ -0,0 +1,120 @@
+use std::fmt;
+use std::str::FromStr;
+
+// virtual-key codes of keys that have a name which isn't just a letter, digit or F1-F24
+// (the left and right mouse buttons are deliberately missing, they would trigger on every click)
+const NAMED_KEYS: [(&str, u16); 18] = [
+    ("Mouse3", 0x04),
+    ("Mouse4", 0x05),
+    ("Mouse5", 0x06),
+    ("Backspace", 0x08),
+    ("Tab", 0x09),
+    ("Enter", 0x0D),
+    ("Pause", 0x13),
+    ("CapsLock", 0x14),
+    ("Space", 0x20),
+    ("PageUp", 0x21),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("PrintScreen", 0x2C),
+    ("Insert", 0x2D),
+    ("Delete", 0x2E),
+    ("NumLock", 0x90),
+    ("ScrollLock", 0x91),
+];
+const VK_0: u16 = 0x30;
+const VK_A: u16 = 0x41;
+const VK_NUMPAD0: u16 = 0x60;
+const VK_F1: u16 = 0x70;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+/// Key or mouse button with optional modifiers, e.g. "F9", "Ctrl+Shift+H" or "Mouse4".
+/// Parsing is case insensitive and ignores whitespace, formatting always gives the canonical form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: Modifiers,
+    /// virtual-key code of the key (or mouse button)
+    pub vkey: u16,
+}
+
+impl Hotkey {
+    /// Name of a key that can be used in a hotkey. Returns None for modifiers and unsupported keys.
+    pub fn key_name(vkey: u16) -> Option<String> {
+        match vkey {
+            VK_0..=0x39 | VK_A..=0x5A => Some(char::from(vkey as u8).to_string()),
+            VK_NUMPAD0..=0x69 => Some(format!("Numpad{}", vkey - VK_NUMPAD0)),
+            VK_F1..=0x87 => Some(format!("F{}", vkey - VK_F1 + 1)),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(_, key)| *key == vkey)
+                .map(|(name, _)| (*name).to_owned()),
+        }
+    }
+
+    fn vkey(name: &str) -> Option<u16> {
+        let upper = name.to_ascii_uppercase();
+        if let [c] = upper.as_bytes() {
+            if c.is_ascii_alphanumeric() {
+                return Some(*c as u16);
+            }
+        }
+        if let Some(n) = upper.strip_prefix("NUMPAD").and_then(|n| n.parse::<u16>().ok()) {
+            return (n <= 9).then_some(VK_NUMPAD0 + n);
+        }
+        if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u16>().ok()) {
+            return (1..=24).contains(&n).then_some(VK_F1 + n - 1);
+        }
+        NAMED_KEYS
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|(_, vkey)| *vkey)
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<_> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or("missing key")?;
+
+        let mut modifiers = Modifiers::default();
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut modifiers.ctrl,
+                "shift" => &mut modifiers.shift,
+                "alt" => &mut modifiers.alt,
+                _ => return Err(format!("unknown modifier '{modifier}'")),
+            };
+            if *flag {
+                return Err(format!("duplicate modifier '{modifier}'"));
+            }
+            *flag = true;
+        }
+
+        let vkey = Self::vkey(key).ok_or_else(|| format!("unknown key '{key}'"))?;
+        Ok(Self { modifiers, vkey })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.shift {
+            f.write_str("Shift+")?;
+        }
+        if self.modifiers.alt {
+            f.write_str("Alt+")?;
+        }
+        f.write_str(&Self::key_name(self.vkey).unwrap_or_else(|| format!("{:#04x}", self.vkey)))
+    }
+}
@@ -0,0 +1,16 @@
+use std::sync::Mutex;
+
+use crate::recorder::PipelineTimings;
+
+#[derive(Debug, Default)]
+pub struct LastPipelineTimings(Mutex<Option<PipelineTimings>>);
+
+impl LastPipelineTimings {
+    pub fn set(&self, timings: PipelineTimings) {
+        *self.0.lock().unwrap() = Some(timings);
+    }
+
+    pub fn get(&self) -> Option<PipelineTimings> {
+        self.0.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_ENTRIES: usize = 1000;
+
+/// Same order as `log::Level` (most severe first)
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub level: LogLevel,
+    /// module the message was logged from, e.g. 'LeagueRecord::recorder::game_listener'
+    pub target: String,
+    /// formatted the same way as a line of the log file
+    pub message: String,
+}
+
+/// The last log messages in memory, filled by the log plugin while 'debugLog' is enabled
+#[derive(Debug, Default)]
+pub struct LogBuffer(Mutex<VecDeque<LogEntry>>);
+
+impl LogBuffer {
+    pub fn push(&self, entry: LogEntry) {
+        let mut entries = self.0.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// The newest `limit` entries with at least the severity of `level` (oldest first)
+    pub fn recent(&self, level: LogLevel, limit: usize) -> Vec<LogEntry> {
+        let entries = self.0.lock().unwrap();
+        let mut recent = entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.level <= level)
+            .take(limit)
+            .cloned()
+            .collect::<Vec<_>>();
+        recent.reverse();
+        recent
+    }
+
+    /// The newest `limit` entries (oldest first) without waiting for the lock
+    ///
+    /// For the crash reporter: the crashed thread could hold the lock. Empty if the lock isn't free.
+    pub fn try_recent(&self, limit: usize) -> Vec<LogEntry> {
+        let Ok(entries) = self.0.try_lock() else { return Vec::new() };
+        entries
+            .iter()
+            .skip(entries.len().saturating_sub(limit))
+            .cloned()
+            .collect()
+    }
+}
@@ -1,13 +1,33 @@
 mod currently_recording;
+mod deleted_recordings;
 mod filewatcher;
+mod gamepad;
+mod hotkey;
+mod last_pipeline_timings;
+mod log_buffer;
+mod overlay_state;
+mod recorder_history;
+mod recorder_status;
+mod recording_index;
 mod settings;
+mod settings_validation;
 mod shutdown;
 mod tray_state;
 mod window_state;
 
 pub use currently_recording::*;
+pub use deleted_recordings::*;
 pub use filewatcher::*;
+pub use gamepad::*;
+pub use hotkey::*;
+pub use last_pipeline_timings::*;
+pub use log_buffer::*;
+pub use overlay_state::*;
+pub use recorder_history::*;
+pub use recorder_status::*;
+pub use recording_index::*;
 pub use settings::*;
+pub use settings_validation::*;
 pub use shutdown::*;
 pub use tray_state::*;
 pub use window_state::*;
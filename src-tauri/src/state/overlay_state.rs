@@ -0,0 +1,15 @@
+use std::sync::Mutex;
+
+/// Start of the recording the overlay is shown for (unix timestamp in ms)
+#[derive(Debug, Default)]
+pub struct OverlayState(Mutex<Option<i64>>);
+
+impl OverlayState {
+    pub fn set_recording_since(&self, recording_since: Option<i64>) {
+        *self.0.lock().unwrap() = recording_since;
+    }
+
+    pub fn recording_since(&self) -> Option<i64> {
+        *self.0.lock().unwrap()
+    }
+}
@@ -1,14 +1,17 @@
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
 use windows_sys::Win32::{
     Foundation::{HWND, LPARAM, LRESULT, WPARAM},
     System::LibraryLoader::GetModuleHandleW,
     UI::{
         Input::{
             GetRawInputData,
-            KeyboardAndMouse::{VK_F1, VK_F10, VK_F11, VK_F12, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9},
+            KeyboardAndMouse::{GetAsyncKeyState, VK_CONTROL, VK_MENU, VK_SHIFT},
             RegisterRawInputDevices, RAWINPUT, RAWINPUTDEVICE, RAWINPUTHEADER, RIDEV_INPUTSINK, RID_INPUT,
-            RIM_TYPEKEYBOARD,
+            RIM_TYPEKEYBOARD, RIM_TYPEMOUSE,
         },
         WindowsAndMessaging::{
             CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassExW, TranslateMessage,
@@ -17,16 +20,36 @@ use windows_sys::Win32::{
     },
 };
 
-use crate::recorder::LeagueRecorder;
-use crate::state::SettingsWrapper;
+use crate::recorder::{HighlightCategory, LeagueRecorder};
+use crate::state::{Hotkey, Modifiers, SettingsWrapper};
 
 static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+// raw input repeats key down events while a key is held => only forward changes of the push-to-talk key
+static PUSH_TO_TALK_HELD: AtomicBool = AtomicBool::new(false);
+// set while the settings UI waits for the user to press a new hotkey
+static CAPTURE: Mutex<Option<oneshot::Sender<Hotkey>>> = Mutex::new(None);
 
 pub struct RawInputListener;
 
 const RI_KEY_BREAK: u32 = 1; // Manually defined as it's missing in windows-sys imports sometimes
 
+// (button down flag, button up flag, virtual-key code) of the mouse buttons that can be used as hotkeys
+const MOUSE_BUTTONS: [(u16, u16, u16); 3] = [(0x0010, 0x0020, 0x04), (0x0040, 0x0080, 0x05), (0x0100, 0x0200, 0x06)];
+
 impl RawInputListener {
+    /// Wait for the next key (combination) the user presses instead of triggering the hotkeys for it.
+    /// Returns None if no key got pressed within `timeout` or another capture started in the meantime.
+    pub async fn capture(timeout: Duration) -> Option<Hotkey> {
+        let (tx, rx) = oneshot::channel();
+        *CAPTURE.lock().unwrap() = Some(tx);
+
+        let hotkey = tokio::time::timeout(timeout, rx).await.ok()?.ok();
+        if hotkey.is_none() {
+            log::info!("no hotkey captured");
+        }
+        hotkey
+    }
+
     pub fn start(app_handle: AppHandle) {
         if APP_HANDLE.set(app_handle.clone()).is_err() {
             log::warn!("RawInputListener already initialized");
@@ -79,15 +102,21 @@ impl RawInputListener {
                     return;
                 }
 
-                let rid = RAWINPUTDEVICE {
+                // keyboard (0x06) and mouse (0x02) of the generic desktop controls usage page
+                let rid = [0x06, 0x02].map(|usage| RAWINPUTDEVICE {
                     usUsagePage: 0x01,
-                    usUsage: 0x06,
+                    usUsage: usage,
                     dwFlags: RIDEV_INPUTSINK,
                     hwndTarget: hwnd,
-                };
+                });
 
                 // RegisterRawInputDevices takes pointer to array
-                if RegisterRawInputDevices(&rid, 1, std::mem::size_of::<RAWINPUTDEVICE>() as u32) == 0 {
+                if RegisterRawInputDevices(
+                    rid.as_ptr(),
+                    rid.len() as u32,
+                    std::mem::size_of::<RAWINPUTDEVICE>() as u32,
+                ) == 0
+                {
                     log::error!("Failed to register raw input devices");
                     return;
                 }
@@ -128,16 +157,24 @@ unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
 
             if bytes_read == size {
                 let raw: &RAWINPUT = &*(buffer.as_ptr() as *const RAWINPUT);
-                if raw.header.dwType == RIM_TYPEKEYBOARD {
-                    let kb = &raw.data.keyboard;
-
-                    // RI_KEY_BREAK = 1 (Key Up).
-                    // Make = 0.
-                    let is_key_down = (kb.Flags & RI_KEY_BREAK as u16) == 0;
+                if let Some(app) = APP_HANDLE.get() {
+                    if raw.header.dwType == RIM_TYPEKEYBOARD {
+                        let kb = &raw.data.keyboard;
 
-                    if is_key_down {
-                        if let Some(app) = APP_HANDLE.get() {
-                            handle_hotkey(app, kb.VKey);
+                        // RI_KEY_BREAK = 1 (Key Up).
+                        // Make = 0.
+                        let is_key_down = (kb.Flags & RI_KEY_BREAK as u16) == 0;
+                        handle_key(app, kb.VKey, is_key_down);
+                    } else if raw.header.dwType == RIM_TYPEMOUSE {
+                        // a single event can contain transitions of multiple buttons
+                        let button_flags = raw.data.mouse.Anonymous.Anonymous.usButtonFlags;
+                        for (down_flag, up_flag, vkey) in MOUSE_BUTTONS {
+                            if button_flags & down_flag != 0 {
+                                handle_key(app, vkey, true);
+                            }
+                            if button_flags & up_flag != 0 {
+                                handle_key(app, vkey, false);
+                            }
                         }
                     }
                 }
@@ -147,31 +184,68 @@ unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam:
     DefWindowProcW(hwnd, msg, wparam, lparam)
 }
 
+/// Compare a hotkey from the settings with the pressed key. Invalid hotkeys never match.
+fn matches(setting: Option<String>, pressed: &Hotkey) -> bool {
+    setting.and_then(|hotkey| hotkey.parse::<Hotkey>().ok()).as_ref() == Some(pressed)
+}
+
+fn pressed_modifiers() -> Modifiers {
+    // the most significant bit is set while the key is down
+    let is_down = |vkey| unsafe { GetAsyncKeyState(vkey as i32) } < 0;
+    Modifiers {
+        ctrl: is_down(VK_CONTROL),
+        shift: is_down(VK_SHIFT),
+        alt: is_down(VK_MENU),
+    }
+}
+
+fn handle_key(app: &AppHandle, vkey: u16, is_key_down: bool) {
+    if is_key_down && Hotkey::key_name(vkey).is_some() {
+        if let Some(capture) = CAPTURE.lock().unwrap().take() {
+            let hotkey = Hotkey {
+                modifiers: pressed_modifiers(),
+                vkey,
+            };
+            // don't trigger anything with the key that just got assigned
+            if capture.send(hotkey).is_ok() {
+                return;
+            }
+        }
+    }
+
+    handle_push_to_talk(app, vkey, is_key_down);
+    if is_key_down {
+        handle_hotkey(app, vkey);
+    }
+}
+
+fn handle_push_to_talk(app: &AppHandle, vkey: u16, is_key_down: bool) {
+    // modifiers are ignored so talking still works while e.g. Shift is held in game
+    let is_push_to_talk_key = app
+        .state::<SettingsWrapper>()
+        .push_to_talk_hotkey()
+        .and_then(|hotkey| hotkey.parse::<Hotkey>().ok())
+        .is_some_and(|hotkey| hotkey.vkey == vkey);
+
+    if is_push_to_talk_key && PUSH_TO_TALK_HELD.swap(is_key_down, Ordering::Relaxed) != is_key_down {
+        app.state::<LeagueRecorder>().push_to_talk(is_key_down);
+    }
+}
+
 fn handle_hotkey(app: &AppHandle, vkey: u16) {
-    let key_name = match vkey {
-        k if k == VK_F1 => "F1",
-        k if k == VK_F2 => "F2",
-        k if k == VK_F3 => "F3",
-        k if k == VK_F4 => "F4",
-        k if k == VK_F5 => "F5",
-        k if k == VK_F6 => "F6",
-        k if k == VK_F7 => "F7",
-        k if k == VK_F8 => "F8",
-        k if k == VK_F9 => "F9",
-        k if k == VK_F10 => "F10",
-        k if k == VK_F11 => "F11",
-        k if k == VK_F12 => "F12",
-        _ => return,
+    if Hotkey::key_name(vkey).is_none() {
+        return;
+    }
+    let pressed = Hotkey {
+        modifiers: pressed_modifiers(),
+        vkey,
     };
 
     let recording_state = app.state::<crate::state::CurrentlyRecording>();
     let settings = app.state::<SettingsWrapper>();
 
     // Check if key matches start recording hotkey
-    let is_start_hotkey = settings
-        .start_recording_hotkey()
-        .map(|h| h.eq_ignore_ascii_case(key_name))
-        .unwrap_or(false);
+    let is_start_hotkey = matches(settings.start_recording_hotkey(), &pressed);
 
     // If not recording and not start hotkey, ignore
     if recording_state.get().is_none() && !is_start_hotkey {
@@ -182,21 +256,32 @@ fn handle_hotkey(app: &AppHandle, vkey: u16) {
         // Only trigger start if NOT recording (though GameListener handles idempotency, better to check here too?)
         // Actually, if we are already recording, maybe user wants to restart?
         // But manual_start logic in GameListener checks for State::Idle.
-        log::info!("RawInput: Start Recording Hotkey Triggered ({})", key_name);
+        log::info!("RawInput: Start Recording Hotkey Triggered ({pressed})");
         app.state::<LeagueRecorder>().manual_start();
     }
 
-    if let Some(hotkey) = settings.stop_recording_hotkey() {
-        if hotkey.eq_ignore_ascii_case(key_name) {
-            log::info!("RawInput: Stop Recording Hotkey Triggered ({})", key_name);
-            app.state::<LeagueRecorder>().manual_stop();
-        }
+    if matches(settings.stop_recording_hotkey(), &pressed) {
+        log::info!("RawInput: Stop Recording Hotkey Triggered ({pressed})");
+        app.state::<LeagueRecorder>().manual_stop();
     }
 
-    if let Some(hotkey) = settings.hightlight_hotkey() {
-        if hotkey.eq_ignore_ascii_case(key_name) {
-            log::info!("RawInput: Highlight Hotkey Triggered ({})", key_name);
-            let _ = app.emit("shortcut-event", "");
-        }
+    if matches(settings.webcam_hotkey(), &pressed) {
+        log::info!("RawInput: Webcam Hotkey Triggered ({pressed})");
+        app.state::<LeagueRecorder>().toggle_webcam();
+    }
+
+    if matches(settings.hightlight_hotkey(), &pressed) {
+        log::info!("RawInput: Highlight Hotkey Triggered ({pressed})");
+        let _ = app.emit("shortcut-event", HighlightCategory::Highlight);
+    }
+
+    if matches(settings.bookmark_hotkey(), &pressed) {
+        log::info!("RawInput: Bookmark Hotkey Triggered ({pressed})");
+        let _ = app.emit("shortcut-event", HighlightCategory::Bookmark);
+    }
+
+    if matches(settings.remove_highlight_hotkey(), &pressed) {
+        log::info!("RawInput: Remove Highlight Hotkey Triggered ({pressed})");
+        let _ = app.emit("remove-highlight-event", ());
     }
 }
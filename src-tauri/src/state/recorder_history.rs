@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+const MAX_STATES: usize = 50;
+const MAX_ERRORS: usize = 20;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// local time, e.g. '2024-05-01 20:15:03'
+    pub timestamp: String,
+    pub message: String,
+}
+
+impl HistoryEntry {
+    fn now(message: String) -> Self {
+        Self {
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            message,
+        }
+    }
+}
+
+/// The last states of the recorder and the last errors that stopped a recording, for bug reports
+#[derive(Debug, Default)]
+pub struct RecorderHistory {
+    states: Mutex<VecDeque<HistoryEntry>>,
+    errors: Mutex<VecDeque<HistoryEntry>>,
+    adapter: Mutex<Option<String>>,
+}
+
+impl RecorderHistory {
+    pub fn push_state(&self, state: impl Display) {
+        let state = state.to_string();
+        let mut states = self.states.lock().unwrap();
+        // the state gets logged after every event, only keep the changes
+        if states.back().is_some_and(|last| last.message == state) {
+            return;
+        }
+        Self::push(&mut states, HistoryEntry::now(state), MAX_STATES);
+    }
+
+    pub fn push_error(&self, error: impl Display) {
+        Self::push(
+            &mut self.errors.lock().unwrap(),
+            HistoryEntry::now(error.to_string()),
+            MAX_ERRORS,
+        );
+    }
+
+    pub fn set_adapter(&self, adapter: String) {
+        *self.adapter.lock().unwrap() = Some(adapter);
+    }
+
+    pub fn states(&self) -> Vec<HistoryEntry> {
+        self.states.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn errors(&self) -> Vec<HistoryEntry> {
+        self.errors.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn last_error(&self) -> Option<HistoryEntry> {
+        self.errors.lock().unwrap().back().cloned()
+    }
+
+    /// name of the GPU the last recording was made with
+    pub fn adapter(&self) -> Option<String> {
+        self.adapter.lock().unwrap().clone()
+    }
+
+    fn push(entries: &mut VecDeque<HistoryEntry>, entry: HistoryEntry, max: usize) {
+        if entries.len() == max {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
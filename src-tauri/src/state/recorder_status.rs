@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use riot_datatypes::GameId;
+use serde::Serialize;
+
+use super::HistoryEntry;
+
+/// What the game listener is doing right now
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum ListenerState {
+    Idle,
+    /// waiting for the game to start or recording it
+    #[serde(rename_all = "camelCase")]
+    Recording {
+        game_id: GameId,
+    },
+    /// the recording is finished and the game data gets collected
+    #[serde(rename_all = "camelCase")]
+    EndOfGame {
+        game_id: GameId,
+    },
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecorderState {
+    #[serde(flatten)]
+    pub listener: ListenerState,
+    /// seconds since the listener is in its current state
+    pub elapsed_secs: f64,
+    /// the video file that is being written (only set once the recorder actually runs)
+    pub recording: Option<PathBuf>,
+    pub lcu_connected: bool,
+    pub last_error: Option<HistoryEntry>,
+}
+
+#[derive(Debug)]
+pub struct RecorderStatus {
+    listener: Mutex<(ListenerState, Instant)>,
+    lcu_connected: AtomicBool,
+}
+
+impl Default for RecorderStatus {
+    fn default() -> Self {
+        Self {
+            listener: Mutex::new((ListenerState::Idle, Instant::now())),
+            lcu_connected: AtomicBool::new(false),
+        }
+    }
+}
+
+impl RecorderStatus {
+    pub fn set_listener(&self, state: ListenerState) {
+        let mut listener = self.listener.lock().unwrap();
+        // the state gets set after every event, keep the time it was entered
+        if listener.0 != state {
+            *listener = (state, Instant::now());
+        }
+    }
+
+    pub fn set_lcu_connected(&self, connected: bool) {
+        self.lcu_connected.store(connected, Ordering::Relaxed);
+    }
+
+    /// The current state and how long the listener is in it already
+    pub fn listener(&self) -> (ListenerState, Duration) {
+        let (state, since) = *self.listener.lock().unwrap();
+        (state, since.elapsed())
+    }
+
+    pub fn lcu_connected(&self) -> bool {
+        self.lcu_connected.load(Ordering::Relaxed)
+    }
+}
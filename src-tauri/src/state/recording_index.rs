@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::app::{video_info, VideoInfo};
+
+/// Stable ID (see `MetadataFile::id`) of every recording by its current path.
+/// Lets commands, clips and backups reference a recording by its ID even after the video got renamed.
+///
+/// Also keeps the `VideoInfo` of the videos so they only get probed again when the file changes.
+#[derive(Debug, Default)]
+pub struct RecordingIndex {
+    ids: Mutex<HashMap<PathBuf, String>>,
+    // (modification time, size) of the video when it was probed
+    videos: Mutex<HashMap<PathBuf, (SystemTime, u64, VideoInfo)>>,
+}
+
+impl RecordingIndex {
+    pub fn replace(&self, index: HashMap<PathBuf, String>) {
+        *self.ids.lock().unwrap() = index;
+    }
+
+    pub fn insert(&self, video_path: PathBuf, id: String) {
+        self.ids.lock().unwrap().insert(video_path, id);
+    }
+
+    pub fn remove(&self, video_path: &Path) -> Option<String> {
+        self.videos.lock().unwrap().remove(video_path);
+        self.ids.lock().unwrap().remove(video_path)
+    }
+
+    pub fn id_of(&self, video_path: &Path) -> Option<String> {
+        self.ids.lock().unwrap().get(video_path).cloned()
+    }
+
+    pub fn path_of(&self, id: &str) -> Option<PathBuf> {
+        self.ids
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, recording_id)| *recording_id == id)
+            .map(|(video_path, _)| video_path.clone())
+    }
+
+    /// Move the ID of the recording at `old_path` to `new_path` after a rename
+    pub fn rename(&self, old_path: &Path, new_path: PathBuf) {
+        let mut videos = self.videos.lock().unwrap();
+        if let Some(video) = videos.remove(old_path) {
+            videos.insert(new_path.clone(), video);
+        }
+
+        let mut index = self.ids.lock().unwrap();
+        if let Some(id) = index.remove(old_path) {
+            index.insert(new_path, id);
+        }
+    }
+
+    /// Duration, resolution, codec and bitrate of the video, None if it can't be read (e.g. no MP4 index yet)
+    pub fn video_info(&self, video_path: &Path) -> Option<VideoInfo> {
+        let file = fs::metadata(video_path).ok()?;
+        let version = (file.modified().ok()?, file.len());
+        if let Some((modified, len, video)) = self.videos.lock().unwrap().get(video_path) {
+            if (*modified, *len) == version {
+                return Some(video.clone());
+            }
+        }
+
+        // probed without holding the lock, the recordings list probes the videos in parallel
+        let video = match video_info::probe(video_path) {
+            Ok(video) => video,
+            Err(e) => {
+                log::debug!("failed to probe {}: {e}", video_path.display());
+                return None;
+            }
+        };
+        self.videos
+            .lock()
+            .unwrap()
+            .insert(video_path.to_path_buf(), (version.0, version.1, video.clone()));
+        Some(video)
+    }
+}
@@ -2,16 +2,31 @@ use std::fmt::Display;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::RwLock;
+use std::time::Duration;
 use std::{fmt, fs};
 
 use anyhow::Result;
-use libobs_recorder::settings::{AudioSource, Framerate, StdResolution};
+use libobs_recorder::settings::{
+    AudioSource, CaptureMode, EncoderPreset, Framerate, RateControl, StdResolution, Webcam,
+};
+use riot_datatypes::lcu::GamePhase;
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use tauri::{async_runtime, AppHandle, Manager};
 
-use crate::app::{AppEvent, AppManager, EventManager, RecordingManager};
+use crate::app::{AppEvent, AppManager, CacheManager, EventManager, RecordingManager, SystemTrayManager};
 use crate::filewatcher;
+use crate::privacy;
+use crate::state::{validate_settings, SettingsWarning};
+use crate::telemetry;
+use crate::util;
+
+const DEFAULT_SETTINGS_JSON: &str = include_str!("../../default-settings.json");
+// timestamped copies of settings.json from before it got overwritten by an import or reset
+const SETTINGS_BACKUP_FOLDER: &str = "settings_backups";
+const MAX_SETTINGS_BACKUPS: usize = 10;
+// shown instead of credentials in the debug output of the settings
+const REDACTED: &str = "<redacted>";
 
 #[derive(Debug)]
 pub struct SettingsFile(PathBuf);
@@ -48,11 +63,19 @@ impl SettingsWrapper {
         Ok(Self(RwLock::new(settings)))
     }
 
-    pub fn load_from_file(&self, settings_file: &Path, app_handle: &AppHandle) {
+    /// Returns false if settings.json can't be read or parsed, the current settings are kept in that case
+    pub fn load_from_file(&self, settings_file: &Path, app_handle: &AppHandle) -> bool {
         let Ok(json) = fs::read_to_string(settings_file) else {
-            return;
+            return false;
+        };
+        // the file is left alone so a syntax error while the user is still editing doesn't reset every setting
+        let mut settings = match serde_json::from_str::<Settings>(json.as_str()) {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::warn!("failed to parse settings.json, keeping the current settings: {e}");
+                return false;
+            }
         };
-        let mut settings = serde_json::from_str::<Settings>(json.as_str()).unwrap_or_default();
 
         // if recordings_folder is relative the path gets appened to the system video directory
         if settings.recordings_folder.is_relative() {
@@ -81,6 +104,7 @@ impl SettingsWrapper {
         // write parsed settings back to file so the internal settings and the content of the file stay in sync
         // to avoid confusing the user when editing the file
         self.write_to_file(settings_file);
+        true
     }
 
     pub fn write_to_file(&self, settings_path: &Path) {
@@ -97,34 +121,77 @@ impl SettingsWrapper {
         async_runtime::spawn_blocking(move || {
             let settings_file = app_handle.state::<SettingsFile>();
             let settings_file = settings_file.get();
+            if !SettingsWrapper::ensure_settings_exist(settings_file) {
+                return;
+            }
 
-            if SettingsWrapper::ensure_settings_exist(settings_file) {
-                // hardcode 'notepad' since league_record currently only works on windows anyways
-                if let Err(e) = Command::new("notepad").arg(settings_file).status() {
-                    log::error!("failed to start text editor: {e}");
-                    return;
+            let settings = app_handle.state::<SettingsWrapper>();
+            // 'notepad' is always there on Windows
+            let editors = settings.editor_path().into_iter().chain([String::from("notepad")]);
+            for editor in editors {
+                match Command::new(&editor).arg(settings_file).status() {
+                    Ok(_) => {
+                        // usually the settings file watcher already reloaded the settings when the file got saved
+                        settings.reload_if_changed(settings_file, &app_handle);
+                        return;
+                    }
+                    Err(e) => log::warn!("failed to start text editor '{editor}': {e}"),
                 }
+            }
 
-                let settings = app_handle.state::<SettingsWrapper>();
-                settings.update_from_file(settings_file, &app_handle);
+            // the settings file watcher reloads the settings as soon as the file gets saved.
+            // 'explorer' exits with an error code even when it opened the file, so only a failed start counts.
+            if let Err(e) = util::open_with_default_app(settings_file) {
+                log::warn!("failed to open settings file: {e}");
+                if let Err(e) = util::reveal_in_file_manager(settings_file) {
+                    log::error!("failed to show settings file: {e}");
+                }
             }
         });
     }
 
+    /// Reload the settings if settings.json doesn't contain the current settings anymore.
+    /// The settings are written back to the file after every reload, so this ignores LeagueRecord's own writes.
+    pub fn reload_if_changed(&self, settings_file: &Path, app_handle: &AppHandle) {
+        let Ok(json) = fs::read_to_string(settings_file) else { return };
+        let current = serde_json::to_string_pretty(&*self.0.read().unwrap()).unwrap();
+        if json != current {
+            log::info!("settings.json changed");
+            self.update_from_file(settings_file, app_handle);
+        }
+    }
+
     pub fn update_from_file(&self, settings_file: &Path, app_handle: &AppHandle) {
+        let old_settings = self.inner();
         let old_recordings_path = self.get_recordings_path();
+        let old_external_folders = self.external_folders();
         let old_marker_flags = self.get_marker_flags();
         let old_log = self.debug_log();
         let old_hightlight_hotkey = self.hightlight_hotkey();
         let old_stop_recording_hotkey = self.stop_recording_hotkey();
 
         // reload settings from settings.json
-        self.load_from_file(settings_file, &app_handle);
+        Self::report_problems(settings_file, app_handle);
+        if !self.load_from_file(settings_file, &app_handle) {
+            return;
+        }
         log::info!("Settings updated: {:?}", self.inner());
+        telemetry::record_settings_changes(app_handle, &old_settings, &self.inner());
+        if let Err(e) = app_handle.send_event(AppEvent::SettingsChanged { payload: self.inner() }) {
+            log::error!("failed to emit 'settings_changed' event: {e}");
+        }
 
         // check and update autostart if necessary
         app_handle.sync_autostart();
 
+        // redacts the existing recordings if privacy mode got turned on
+        privacy::set_enabled(app_handle, self.privacy_mode());
+
+        // the labels of the recent games in the tray menu depend on 'safeTitles'
+        if old_settings.safe_titles != self.safe_titles() {
+            app_handle.refresh_tray_menu();
+        }
+
         // add / remove logs plugin if needed
         if old_log != self.debug_log() {
             if self.debug_log() {
@@ -140,7 +207,7 @@ impl SettingsWrapper {
 
         // check if UI window needs to be updated
         let recordings_path = self.get_recordings_path();
-        if recordings_path != old_recordings_path {
+        if recordings_path != old_recordings_path || self.external_folders() != old_external_folders {
             filewatcher::replace(&app_handle, &recordings_path);
             if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
                 log::error!("failed to emit 'recordings_changed' event: {e}");
@@ -161,6 +228,26 @@ impl SettingsWrapper {
         }
 
         app_handle.cleanup_recordings();
+        app_handle.prune_cache();
+    }
+
+    /// Tell the user about everything in settings.json that gets ignored or replaced with a default
+    fn report_problems(settings_file: &Path, app_handle: &AppHandle) {
+        let warnings = fs::read_to_string(settings_file)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| validate_settings(app_handle, &json));
+        let warnings = match warnings {
+            Ok(warnings) if warnings.is_empty() => return,
+            Ok(warnings) => warnings,
+            Err(e) => {
+                log::warn!("failed to validate settings.json: {e}");
+                vec![SettingsWarning::invalid_file(e)]
+            }
+        };
+        log::warn!("problems in settings.json: {warnings:?}");
+        if let Err(e) = app_handle.send_event(AppEvent::SettingsWarnings { payload: warnings }) {
+            log::error!("failed to emit 'settings_warnings' event: {e}");
+        }
     }
 
     pub fn get_recordings_path(&self) -> PathBuf {
@@ -171,12 +258,38 @@ impl SettingsWrapper {
         self.0.read().unwrap().clips_folder.clone()
     }
 
+    pub fn external_folders(&self) -> Vec<PathBuf> {
+        self.0.read().unwrap().external_folders.clone()
+    }
+
+    /// Videos in the external folders are only displayed, LeagueRecord never changes them
+    pub fn is_external(&self, path: &Path) -> bool {
+        self.0
+            .read()
+            .unwrap()
+            .external_folders
+            .iter()
+            .any(|folder| path.starts_with(folder))
+    }
+
+    pub fn organize_by(&self) -> OrganizeBy {
+        self.0.read().unwrap().organize_by
+    }
+
+    pub fn account_subfolders(&self) -> bool {
+        self.0.read().unwrap().account_subfolders
+    }
+
     pub fn get_filename_format(&self) -> String {
         self.0.read().unwrap().filename_format.clone()
     }
 
-    pub fn get_encoding_quality(&self) -> u32 {
-        self.0.read().unwrap().encoding_quality
+    pub fn safe_titles(&self) -> bool {
+        self.0.read().unwrap().safe_titles
+    }
+
+    pub fn encoder(&self) -> EncoderSettings {
+        self.0.read().unwrap().encoder.clone()
     }
 
     pub fn get_output_resolution(&self) -> Option<StdResolution> {
@@ -187,10 +300,38 @@ impl SettingsWrapper {
         self.0.read().unwrap().framerate
     }
 
+    pub fn framerate_fallback(&self) -> bool {
+        self.0.read().unwrap().framerate_fallback
+    }
+
     pub fn get_audio_source(&self) -> AudioSource {
         self.0.read().unwrap().record_audio
     }
 
+    pub fn get_audio_device(&self) -> Option<String> {
+        self.0.read().unwrap().audio_device.clone()
+    }
+
+    pub fn noise_suppression(&self) -> bool {
+        self.0.read().unwrap().noise_suppression
+    }
+
+    pub fn push_to_talk_hotkey(&self) -> Option<String> {
+        self.0.read().unwrap().push_to_talk_hotkey.clone()
+    }
+
+    pub fn get_capture_mode(&self) -> CaptureMode {
+        self.0.read().unwrap().capture_mode
+    }
+
+    pub fn hdr(&self) -> HdrMode {
+        self.0.read().unwrap().hdr
+    }
+
+    pub fn get_webcam(&self) -> Option<Webcam> {
+        self.0.read().unwrap().webcam.clone()
+    }
+
     pub fn get_marker_flags(&self) -> MarkerFlags {
         self.0.read().unwrap().marker_flags.clone()
     }
@@ -203,6 +344,22 @@ impl SettingsWrapper {
         self.0.read().unwrap().autostart
     }
 
+    pub fn check_for_updates(&self) -> bool {
+        self.0.read().unwrap().check_for_updates
+    }
+
+    pub fn update_channel(&self) -> UpdateChannel {
+        self.0.read().unwrap().update_channel
+    }
+
+    pub fn crash_reports(&self) -> bool {
+        self.0.read().unwrap().crash_reports
+    }
+
+    pub fn privacy_mode(&self) -> bool {
+        self.0.read().unwrap().privacy_mode
+    }
+
     pub fn max_recording_age(&self) -> Option<u64> {
         self.0.read().unwrap().max_recording_age_days
     }
@@ -211,6 +368,22 @@ impl SettingsWrapper {
         self.0.read().unwrap().max_recordings_size_gb
     }
 
+    pub fn retention(&self) -> RetentionRules {
+        self.0.read().unwrap().retention.clone()
+    }
+
+    pub fn max_cache_size_mb(&self) -> Option<u64> {
+        self.0.read().unwrap().max_cache_size_mb
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.0.read().unwrap().dry_run
+    }
+
+    pub fn developer_mode(&self) -> bool {
+        self.0.read().unwrap().developer_mode
+    }
+
     pub fn debug_log(&self) -> bool {
         self.0.read().unwrap().debug_log || std::env::args().any(|e| e == "-d" || e == "--debug")
     }
@@ -223,10 +396,26 @@ impl SettingsWrapper {
         self.0.write().unwrap().confirm_delete = confirm_delete;
     }
 
+    pub fn use_recycle_bin(&self) -> bool {
+        self.0.read().unwrap().use_recycle_bin
+    }
+
+    pub fn webcam_hotkey(&self) -> Option<String> {
+        self.0.read().unwrap().webcam_hotkey.clone()
+    }
+
     pub fn hightlight_hotkey(&self) -> Option<String> {
         self.0.read().unwrap().hightlight_hotkey.clone()
     }
 
+    pub fn bookmark_hotkey(&self) -> Option<String> {
+        self.0.read().unwrap().bookmark_hotkey.clone()
+    }
+
+    pub fn remove_highlight_hotkey(&self) -> Option<String> {
+        self.0.read().unwrap().remove_highlight_hotkey.clone()
+    }
+
     pub fn start_recording_hotkey(&self) -> Option<String> {
         self.0.read().unwrap().start_recording_hotkey.clone()
     }
@@ -235,6 +424,42 @@ impl SettingsWrapper {
         self.0.read().unwrap().stop_recording_hotkey.clone()
     }
 
+    pub fn stop_phases(&self) -> Vec<GamePhase> {
+        self.0.read().unwrap().stop_phases.clone()
+    }
+
+    pub fn min_game_duration_secs(&self) -> Option<u64> {
+        self.0.read().unwrap().min_game_duration_secs
+    }
+
+    pub fn keep_discarded_recordings(&self) -> bool {
+        self.0.read().unwrap().keep_discarded_recordings
+    }
+
+    pub fn segmentation(&self) -> Option<Segmentation> {
+        self.0.read().unwrap().segmentation.clone()
+    }
+
+    pub fn stop_grace_period(&self) -> Duration {
+        Duration::from_secs(self.0.read().unwrap().stop_grace_period_secs)
+    }
+
+    pub fn reconnect_debounce(&self) -> Duration {
+        Duration::from_secs(self.0.read().unwrap().reconnect_debounce_secs)
+    }
+
+    pub fn record_without_client(&self) -> bool {
+        self.0.read().unwrap().record_without_client
+    }
+
+    pub fn prewarm_recorder(&self) -> bool {
+        self.0.read().unwrap().prewarm_recorder
+    }
+
+    pub fn live_poll_jitter(&self) -> Duration {
+        Duration::from_millis(self.0.read().unwrap().live_poll_jitter_ms)
+    }
+
     pub fn game_modes(&self) -> Option<Vec<String>> {
         self.0.read().unwrap().game_modes.clone()
     }
@@ -243,6 +468,88 @@ impl SettingsWrapper {
         self.0.read().unwrap().ffmpeg_path.clone()
     }
 
+    pub fn editor_path(&self) -> Option<String> {
+        self.0.read().unwrap().editor_path.clone()
+    }
+
+    pub fn auto_download_replay(&self) -> bool {
+        self.0.read().unwrap().auto_download_replay
+    }
+
+    pub fn postgame_screenshot(&self) -> bool {
+        self.0.read().unwrap().postgame_screenshot
+    }
+
+    pub fn telemetry(&self) -> bool {
+        self.0.read().unwrap().telemetry
+    }
+
+    pub fn overlay(&self) -> Option<OverlaySettings> {
+        self.0.read().unwrap().overlay.clone()
+    }
+
+    pub fn notifications(&self) -> Notifications {
+        self.0.read().unwrap().notifications.clone()
+    }
+
+    pub fn gamepad_hotkeys(&self) -> GamepadHotkeys {
+        self.0.read().unwrap().gamepad_hotkeys.clone()
+    }
+
+    pub fn riot_api_key(&self) -> Option<String> {
+        self.0
+            .read()
+            .unwrap()
+            .riot_api_key
+            .clone()
+            .filter(|key| !key.is_empty())
+    }
+
+    pub fn get_export_preset(&self, name: &str) -> Option<ExportPreset> {
+        self.0
+            .read()
+            .unwrap()
+            .export_presets
+            .iter()
+            .find(|preset| preset.name == name)
+            .cloned()
+    }
+
+    pub fn backup(&self) -> Option<BackupSettings> {
+        self.0.read().unwrap().backup.clone()
+    }
+
+    pub fn youtube_client_id(&self) -> Option<String> {
+        self.0
+            .read()
+            .unwrap()
+            .youtube_client_id
+            .clone()
+            .filter(|id| !id.is_empty())
+    }
+
+    pub fn youtube_client_secret(&self) -> Option<String> {
+        self.0
+            .read()
+            .unwrap()
+            .youtube_client_secret
+            .clone()
+            .filter(|secret| !secret.is_empty())
+    }
+
+    pub fn match_history_base_url(&self) -> Option<String> {
+        self.0.read().unwrap().match_history_base_url.clone()
+    }
+
+    pub fn discord_webhook(&self) -> Option<DiscordWebhook> {
+        self.0
+            .read()
+            .unwrap()
+            .discord_webhook
+            .clone()
+            .filter(|webhook| !webhook.url.is_empty())
+    }
+
     #[allow(dead_code)]
     pub fn auto_stop_playback(&self) -> bool {
         self.0.read().unwrap().auto_stop_playback
@@ -264,13 +571,78 @@ impl SettingsWrapper {
                 return false;
             };
             // create the settings file with the default settings json
-            let Ok(_) = fs::write(settings_file, include_str!("../../default-settings.json")) else {
+            let Ok(_) = fs::write(settings_file, DEFAULT_SETTINGS_JSON) else {
                 return false;
             };
         }
         true
     }
 
+    /// Write the current settings to a file, e.g. to move them to another PC
+    pub fn export_to(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&*self.0.read().unwrap())?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Replace settings.json with an exported settings file. Returns everything in the imported file that gets ignored
+    /// or replaced with a default.
+    pub fn import_from(
+        &self,
+        path: &Path,
+        settings_file: &Path,
+        app_handle: &AppHandle,
+    ) -> Result<Vec<SettingsWarning>> {
+        let json = fs::read_to_string(path)?;
+        // fails if the file isn't a settings file at all
+        let warnings = validate_settings(app_handle, &json)?;
+
+        Self::backup_settings_file(settings_file)?;
+        fs::write(settings_file, json)?;
+        self.update_from_file(settings_file, app_handle);
+        Ok(warnings)
+    }
+
+    /// Replace settings.json with the default settings
+    pub fn reset(&self, settings_file: &Path, app_handle: &AppHandle) -> Result<()> {
+        Self::backup_settings_file(settings_file)?;
+        fs::write(settings_file, DEFAULT_SETTINGS_JSON)?;
+        self.update_from_file(settings_file, app_handle);
+        Ok(())
+    }
+
+    /// Copy settings.json to a timestamped file in the backup folder next to it and delete the oldest backups
+    fn backup_settings_file(settings_file: &Path) -> Result<()> {
+        if !settings_file.is_file() {
+            return Ok(());
+        }
+
+        let Some(backup_folder) = settings_file.parent().map(|parent| parent.join(SETTINGS_BACKUP_FOLDER)) else {
+            return Ok(());
+        };
+        fs::create_dir_all(&backup_folder)?;
+        let backup_file = backup_folder.join(format!(
+            "settings_{}.json",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+        ));
+        fs::copy(settings_file, &backup_file)?;
+        log::info!("saved backup of settings.json to {}", backup_file.display());
+
+        // the timestamp in the name makes the oldest backups come first
+        let mut backups: Vec<_> = fs::read_dir(&backup_folder)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        backups.sort();
+        let excess = backups.len().saturating_sub(MAX_SETTINGS_BACKUPS);
+        for backup in backups.into_iter().take(excess) {
+            if let Err(e) = fs::remove_file(&backup) {
+                log::warn!("failed to remove old settings backup {}: {e}", backup.display());
+            }
+        }
+        Ok(())
+    }
+
     pub fn set_settings(&self, settings: Settings) {
         *self.0.write().unwrap() = settings;
     }
@@ -281,7 +653,7 @@ impl SettingsWrapper {
 }
 
 #[cfg_attr(test, derive(specta::Type))]
-#[derive(Debug, Clone, Serialize)]
+#[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Settings {
     pub marker_flags: MarkerFlags,
@@ -289,48 +661,203 @@ pub struct Settings {
     pub debug_log: bool,
     pub recordings_folder: PathBuf,
     pub clips_folder: PathBuf,
+    pub external_folders: Vec<PathBuf>,
+    pub organize_by: OrganizeBy,
+    pub account_subfolders: bool,
     pub filename_format: String,
-    pub encoding_quality: u32,
+    pub safe_titles: bool,
+    pub encoder: EncoderSettings,
     pub output_resolution: Option<StdResolution>,
     pub framerate: Framerate,
+    pub framerate_fallback: bool,
     pub record_audio: AudioSource,
+    pub audio_device: Option<String>,
+    pub noise_suppression: bool,
+    pub push_to_talk_hotkey: Option<String>,
+    pub capture_mode: CaptureMode,
+    pub hdr: HdrMode,
+    pub webcam: Option<Webcam>,
 
     pub autostart: bool,
+    pub check_for_updates: bool,
+    pub update_channel: UpdateChannel,
+    pub crash_reports: bool,
+    pub privacy_mode: bool,
     pub max_recording_age_days: Option<u64>,
     pub max_recordings_size_gb: Option<u64>,
+    pub max_cache_size_mb: Option<u64>,
+    pub retention: RetentionRules,
     pub confirm_delete: bool,
+    pub use_recycle_bin: bool,
     pub hightlight_hotkey: Option<String>,
+    pub bookmark_hotkey: Option<String>,
+    pub remove_highlight_hotkey: Option<String>,
+    pub gamepad_hotkeys: GamepadHotkeys,
     pub start_recording_hotkey: Option<String>,
     pub stop_recording_hotkey: Option<String>,
+    pub webcam_hotkey: Option<String>,
     pub game_modes: Option<Vec<String>>,
+    pub stop_phases: Vec<GamePhase>,
+    pub stop_grace_period_secs: u64,
+    pub reconnect_debounce_secs: u64,
+    pub record_without_client: bool,
+    pub prewarm_recorder: bool,
+    pub live_poll_jitter_ms: u64,
+    pub min_game_duration_secs: Option<u64>,
+    pub keep_discarded_recordings: bool,
+    pub segmentation: Option<Segmentation>,
     pub autoplay_video: bool,
     pub auto_stop_playback: bool,
     pub auto_select_recording: bool,
     pub auto_popup_on_end: bool,
     pub ffmpeg_path: Option<String>,
+    pub editor_path: Option<String>,
     pub developer_mode: bool,
+    pub dry_run: bool,
+    pub auto_download_replay: bool,
+    pub postgame_screenshot: bool,
     pub match_history_base_url: Option<String>,
+    pub riot_api_key: Option<String>,
+    pub discord_webhook: Option<DiscordWebhook>,
+    pub youtube_client_id: Option<String>,
+    pub youtube_client_secret: Option<String>,
+    pub export_presets: Vec<ExportPreset>,
+    pub backup: Option<BackupSettings>,
+    pub telemetry: bool,
+    pub overlay: Option<OverlaySettings>,
+    pub notifications: Notifications,
     pub scroll_frame_step_modifier: Option<String>,
     pub scoreboard_scale: Option<f64>,
     pub play_recording_sounds: bool,
     pub language: String,
 }
 
+// credentials are replaced so the settings can be logged
+impl fmt::Debug for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Settings")
+            .field("marker_flags", &self.marker_flags)
+            .field("debug_log", &self.debug_log)
+            .field("recordings_folder", &self.recordings_folder)
+            .field("clips_folder", &self.clips_folder)
+            .field("external_folders", &self.external_folders)
+            .field("organize_by", &self.organize_by)
+            .field("account_subfolders", &self.account_subfolders)
+            .field("filename_format", &self.filename_format)
+            .field("safe_titles", &self.safe_titles)
+            .field("encoder", &self.encoder)
+            .field("output_resolution", &self.output_resolution)
+            .field("framerate", &self.framerate)
+            .field("framerate_fallback", &self.framerate_fallback)
+            .field("record_audio", &self.record_audio)
+            .field("audio_device", &self.audio_device)
+            .field("noise_suppression", &self.noise_suppression)
+            .field("push_to_talk_hotkey", &self.push_to_talk_hotkey)
+            .field("capture_mode", &self.capture_mode)
+            .field("hdr", &self.hdr)
+            .field("webcam", &self.webcam)
+            .field("autostart", &self.autostart)
+            .field("check_for_updates", &self.check_for_updates)
+            .field("update_channel", &self.update_channel)
+            .field("crash_reports", &self.crash_reports)
+            .field("privacy_mode", &self.privacy_mode)
+            .field("max_recording_age_days", &self.max_recording_age_days)
+            .field("max_recordings_size_gb", &self.max_recordings_size_gb)
+            .field("max_cache_size_mb", &self.max_cache_size_mb)
+            .field("retention", &self.retention)
+            .field("confirm_delete", &self.confirm_delete)
+            .field("use_recycle_bin", &self.use_recycle_bin)
+            .field("hightlight_hotkey", &self.hightlight_hotkey)
+            .field("bookmark_hotkey", &self.bookmark_hotkey)
+            .field("remove_highlight_hotkey", &self.remove_highlight_hotkey)
+            .field("gamepad_hotkeys", &self.gamepad_hotkeys)
+            .field("start_recording_hotkey", &self.start_recording_hotkey)
+            .field("stop_recording_hotkey", &self.stop_recording_hotkey)
+            .field("webcam_hotkey", &self.webcam_hotkey)
+            .field("game_modes", &self.game_modes)
+            .field("stop_phases", &self.stop_phases)
+            .field("stop_grace_period_secs", &self.stop_grace_period_secs)
+            .field("reconnect_debounce_secs", &self.reconnect_debounce_secs)
+            .field("record_without_client", &self.record_without_client)
+            .field("prewarm_recorder", &self.prewarm_recorder)
+            .field("live_poll_jitter_ms", &self.live_poll_jitter_ms)
+            .field("min_game_duration_secs", &self.min_game_duration_secs)
+            .field("keep_discarded_recordings", &self.keep_discarded_recordings)
+            .field("segmentation", &self.segmentation)
+            .field("autoplay_video", &self.autoplay_video)
+            .field("auto_stop_playback", &self.auto_stop_playback)
+            .field("auto_select_recording", &self.auto_select_recording)
+            .field("auto_popup_on_end", &self.auto_popup_on_end)
+            .field("ffmpeg_path", &self.ffmpeg_path)
+            .field("editor_path", &self.editor_path)
+            .field("developer_mode", &self.developer_mode)
+            .field("dry_run", &self.dry_run)
+            .field("auto_download_replay", &self.auto_download_replay)
+            .field("postgame_screenshot", &self.postgame_screenshot)
+            .field("match_history_base_url", &self.match_history_base_url)
+            .field("riot_api_key", &redact(&self.riot_api_key))
+            .field("discord_webhook", &self.discord_webhook)
+            .field("youtube_client_id", &redact(&self.youtube_client_id))
+            .field("youtube_client_secret", &redact(&self.youtube_client_secret))
+            .field("export_presets", &self.export_presets)
+            .field("backup", &self.backup)
+            .field("telemetry", &self.telemetry)
+            .field("overlay", &self.overlay)
+            .field("notifications", &self.notifications)
+            .field("scroll_frame_step_modifier", &self.scroll_frame_step_modifier)
+            .field("scoreboard_scale", &self.scoreboard_scale)
+            .field("play_recording_sounds", &self.play_recording_sounds)
+            .field("language", &self.language)
+            .finish()
+    }
+}
+
 const DEFAULT_DEBUG_LOG: bool = false;
+const DEFAULT_ACCOUNT_SUBFOLDERS: bool = false;
+const DEFAULT_SAFE_TITLES: bool = false;
 const DEFAULT_ENCODING_QUALITY: u32 = 25;
+const DEFAULT_BITRATE_KBPS: u32 = 10_000;
+const DEFAULT_KEYFRAME_INTERVAL_SECS: u32 = 2;
+const DEFAULT_FRAMERATE_FALLBACK: bool = true;
 const DEFAULT_RECORD_AUDIO: AudioSource = AudioSource::APPLICATION;
+const DEFAULT_NOISE_SUPPRESSION: bool = false;
 
 const DEFAULT_AUTOSTART: bool = false;
+const DEFAULT_CHECK_FOR_UPDATES: bool = true;
+const DEFAULT_CRASH_REPORTS: bool = false;
+const DEFAULT_PRIVACY_MODE: bool = false;
 const DEFAULT_MAX_RECORDING_AGE_DAYS: Option<u64> = None;
 const DEFAULT_MAX_RECORDINGS_SIZE_GB: Option<u64> = None;
+const DEFAULT_MAX_CACHE_SIZE_MB: Option<u64> = Some(512);
 const DEFAULT_CONFIRM_DELETE: bool = true;
+const DEFAULT_USE_RECYCLE_BIN: bool = true;
 const DEFAULT_GAME_MODES: Option<Vec<String>> = None;
+const DEFAULT_STOP_GRACE_PERIOD_SECS: u64 = 0;
+const DEFAULT_RECONNECT_DEBOUNCE_SECS: u64 = 60;
+// longer grace periods don't make sense, no game lasts this long
+const MAX_GRACE_PERIOD_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_RECORD_WITHOUT_CLIENT: bool = true;
+const DEFAULT_PREWARM_RECORDER: bool = true;
+const DEFAULT_LIVE_POLL_JITTER_MS: u64 = 250;
+const DEFAULT_MIN_GAME_DURATION_SECS: Option<u64> = None;
+const DEFAULT_KEEP_DISCARDED_RECORDINGS: bool = false;
+const DEFAULT_SEGMENTATION: Option<Segmentation> = None;
 const DEFAULT_AUTOPLAY_VIDEO: bool = false;
 const DEFAULT_AUTO_STOP_PLAYBACK: bool = false;
 const DEFAULT_AUTO_SELECT_RECORDING: bool = false;
 const DEFAULT_AUTO_POPUP_ON_END: bool = false;
 const DEFAULT_FFMPEG_PATH: Option<String> = None;
 const DEFAULT_MATCH_HISTORY_BASE_URL: Option<String> = None;
+const DEFAULT_DRY_RUN: bool = false;
+const DEFAULT_AUTO_DOWNLOAD_REPLAY: bool = false;
+const DEFAULT_POSTGAME_SCREENSHOT: bool = true;
+const DEFAULT_RIOT_API_KEY: Option<String> = None;
+const DEFAULT_DISCORD_WEBHOOK: Option<DiscordWebhook> = None;
+const DEFAULT_YOUTUBE_CLIENT_ID: Option<String> = None;
+const DEFAULT_YOUTUBE_CLIENT_SECRET: Option<String> = None;
+const DEFAULT_BACKUP: Option<BackupSettings> = None;
+const DEFAULT_TELEMETRY: bool = false;
+const DEFAULT_OVERLAY: Option<OverlaySettings> = None;
 
 #[inline]
 fn default_recordings_folder() -> PathBuf {
@@ -352,6 +879,43 @@ fn default_framerate() -> Framerate {
     Framerate::new(30, 1)
 }
 
+/// The framerate is either a fraction `[numerator, denominator]` or a whole number of fps (e.g. 60 or 120)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FramerateSetting {
+    Fps(u32),
+    Fraction(Framerate),
+}
+
+impl From<FramerateSetting> for Framerate {
+    fn from(setting: FramerateSetting) -> Self {
+        match setting {
+            FramerateSetting::Fps(fps) => Framerate::new(fps, 1),
+            FramerateSetting::Fraction(framerate) => framerate,
+        }
+    }
+}
+
+#[inline]
+fn default_stop_phases() -> Vec<GamePhase> {
+    vec![
+        GamePhase::FailedToLaunch,
+        GamePhase::Reconnect,
+        GamePhase::WaitingForStats,
+        GamePhase::PreEndOfGame,
+    ]
+}
+
+// phases that come after the game started - anything else as a stop trigger would end the recording right away or never
+const VALID_STOP_PHASES: [GamePhase; 6] = [
+    GamePhase::FailedToLaunch,
+    GamePhase::Reconnect,
+    GamePhase::WaitingForStats,
+    GamePhase::PreEndOfGame,
+    GamePhase::EndOfGame,
+    GamePhase::TerminatedInError,
+];
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -359,27 +923,71 @@ impl Default for Settings {
             debug_log: DEFAULT_DEBUG_LOG,
             recordings_folder: default_recordings_folder(),
             clips_folder: default_clips_folder(),
+            external_folders: Vec::new(),
+            organize_by: OrganizeBy::default(),
+            account_subfolders: DEFAULT_ACCOUNT_SUBFOLDERS,
             filename_format: default_filename_format(),
-            encoding_quality: DEFAULT_ENCODING_QUALITY,
+            safe_titles: DEFAULT_SAFE_TITLES,
+            encoder: EncoderSettings::default(),
             output_resolution: None,
             framerate: default_framerate(),
+            framerate_fallback: DEFAULT_FRAMERATE_FALLBACK,
             record_audio: DEFAULT_RECORD_AUDIO,
+            audio_device: None,
+            noise_suppression: DEFAULT_NOISE_SUPPRESSION,
+            push_to_talk_hotkey: None,
+            capture_mode: CaptureMode::default(),
+            hdr: HdrMode::default(),
+            webcam: None,
 
             autostart: DEFAULT_AUTOSTART,
+            check_for_updates: DEFAULT_CHECK_FOR_UPDATES,
+            update_channel: UpdateChannel::default(),
+            crash_reports: DEFAULT_CRASH_REPORTS,
+            privacy_mode: DEFAULT_PRIVACY_MODE,
             max_recording_age_days: DEFAULT_MAX_RECORDING_AGE_DAYS,
             max_recordings_size_gb: DEFAULT_MAX_RECORDINGS_SIZE_GB,
+            max_cache_size_mb: DEFAULT_MAX_CACHE_SIZE_MB,
+            retention: RetentionRules::default(),
             confirm_delete: DEFAULT_CONFIRM_DELETE,
+            use_recycle_bin: DEFAULT_USE_RECYCLE_BIN,
             hightlight_hotkey: None,
+            bookmark_hotkey: None,
+            remove_highlight_hotkey: None,
+            gamepad_hotkeys: GamepadHotkeys::default(),
             start_recording_hotkey: Some("F9".to_string()),
             stop_recording_hotkey: Some("F12".to_string()),
+            webcam_hotkey: None,
             game_modes: DEFAULT_GAME_MODES,
+            stop_phases: default_stop_phases(),
+            stop_grace_period_secs: DEFAULT_STOP_GRACE_PERIOD_SECS,
+            reconnect_debounce_secs: DEFAULT_RECONNECT_DEBOUNCE_SECS,
+            record_without_client: DEFAULT_RECORD_WITHOUT_CLIENT,
+            prewarm_recorder: DEFAULT_PREWARM_RECORDER,
+            live_poll_jitter_ms: DEFAULT_LIVE_POLL_JITTER_MS,
+            min_game_duration_secs: DEFAULT_MIN_GAME_DURATION_SECS,
+            keep_discarded_recordings: DEFAULT_KEEP_DISCARDED_RECORDINGS,
+            segmentation: DEFAULT_SEGMENTATION,
             autoplay_video: DEFAULT_AUTOPLAY_VIDEO,
             auto_stop_playback: DEFAULT_AUTO_STOP_PLAYBACK,
             auto_select_recording: DEFAULT_AUTO_SELECT_RECORDING,
             auto_popup_on_end: DEFAULT_AUTO_POPUP_ON_END,
             ffmpeg_path: DEFAULT_FFMPEG_PATH,
+            editor_path: None,
             developer_mode: false,
+            dry_run: DEFAULT_DRY_RUN,
+            auto_download_replay: DEFAULT_AUTO_DOWNLOAD_REPLAY,
+            postgame_screenshot: DEFAULT_POSTGAME_SCREENSHOT,
             match_history_base_url: DEFAULT_MATCH_HISTORY_BASE_URL,
+            riot_api_key: DEFAULT_RIOT_API_KEY,
+            discord_webhook: DEFAULT_DISCORD_WEBHOOK,
+            youtube_client_id: DEFAULT_YOUTUBE_CLIENT_ID,
+            youtube_client_secret: DEFAULT_YOUTUBE_CLIENT_SECRET,
+            export_presets: Vec::new(),
+            backup: DEFAULT_BACKUP,
+            telemetry: DEFAULT_TELEMETRY,
+            overlay: DEFAULT_OVERLAY,
+            notifications: Notifications::default(),
             scroll_frame_step_modifier: Some("Shift".to_string()),
             scoreboard_scale: None,
             play_recording_sounds: false,
@@ -407,6 +1015,9 @@ impl<'de> Deserialize<'de> for Settings {
                 V: MapAccess<'de>,
             {
                 let mut settings = Settings::default();
+                // 'encodingQuality' was replaced by 'encoder.quality'
+                let mut encoding_quality = None;
+                let mut has_encoder = false;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -421,24 +1032,77 @@ impl<'de> Deserialize<'de> for Settings {
                         "clipsFolder" => {
                             settings.clips_folder = map.next_value().unwrap_or_else(|_| default_clips_folder());
                         }
+                        "externalFolders" => {
+                            settings.external_folders = map.next_value().unwrap_or_default();
+                        }
+                        "organizeBy" => {
+                            settings.organize_by = map.next_value().unwrap_or_default();
+                        }
+                        "accountSubfolders" => {
+                            settings.account_subfolders = map.next_value().unwrap_or(DEFAULT_ACCOUNT_SUBFOLDERS);
+                        }
                         "filenameFormat" => {
                             settings.filename_format = map.next_value().unwrap_or_else(|_| default_filename_format());
                         }
-                        "encodingQuality" => {
-                            settings.encoding_quality = map.next_value().unwrap_or(DEFAULT_ENCODING_QUALITY);
+                        "safeTitles" => {
+                            settings.safe_titles = map.next_value().unwrap_or(DEFAULT_SAFE_TITLES);
+                        }
+                        "encoder" => {
+                            settings.encoder = map
+                                .next_value::<EncoderSettings>()
+                                .map(EncoderSettings::validated)
+                                .unwrap_or_default();
+                            has_encoder = true;
                         }
+                        "encodingQuality" => encoding_quality = map.next_value().ok(),
                         "outputResolution" => {
                             settings.output_resolution = map.next_value().unwrap_or(None);
                         }
                         "framerate" => {
-                            settings.framerate = map.next_value().unwrap_or_else(|_| default_framerate());
+                            settings.framerate = map
+                                .next_value::<FramerateSetting>()
+                                .map(Framerate::from)
+                                .unwrap_or_else(|_| default_framerate());
+                        }
+                        "framerateFallback" => {
+                            settings.framerate_fallback = map.next_value().unwrap_or(DEFAULT_FRAMERATE_FALLBACK);
                         }
                         "recordAudio" => {
                             settings.record_audio = map.next_value().unwrap_or(DEFAULT_RECORD_AUDIO);
                         }
+                        "audioDevice" => {
+                            settings.audio_device = map.next_value().ok();
+                        }
+                        "noiseSuppression" => {
+                            settings.noise_suppression = map.next_value().unwrap_or(DEFAULT_NOISE_SUPPRESSION);
+                        }
+                        "pushToTalkHotkey" => {
+                            settings.push_to_talk_hotkey = map.next_value().ok();
+                        }
+                        "captureMode" => {
+                            settings.capture_mode = map.next_value().unwrap_or_default();
+                        }
+                        "hdr" => {
+                            settings.hdr = map.next_value().unwrap_or_default();
+                        }
+                        "webcam" => {
+                            settings.webcam = map.next_value().ok();
+                        }
                         "autostart" => {
                             settings.autostart = map.next_value().unwrap_or(DEFAULT_AUTOSTART);
                         }
+                        "checkForUpdates" => {
+                            settings.check_for_updates = map.next_value().unwrap_or(DEFAULT_CHECK_FOR_UPDATES);
+                        }
+                        "updateChannel" => {
+                            settings.update_channel = map.next_value().unwrap_or_default();
+                        }
+                        "crashReports" => {
+                            settings.crash_reports = map.next_value().unwrap_or(DEFAULT_CRASH_REPORTS);
+                        }
+                        "privacyMode" => {
+                            settings.privacy_mode = map.next_value().unwrap_or(DEFAULT_PRIVACY_MODE);
+                        }
                         "maxRecordingAgeDays" => {
                             settings.max_recording_age_days =
                                 map.next_value().unwrap_or(DEFAULT_MAX_RECORDING_AGE_DAYS);
@@ -447,12 +1111,33 @@ impl<'de> Deserialize<'de> for Settings {
                             settings.max_recordings_size_gb =
                                 map.next_value().unwrap_or(DEFAULT_MAX_RECORDINGS_SIZE_GB);
                         }
+                        "maxCacheSizeMb" => {
+                            settings.max_cache_size_mb = map.next_value().unwrap_or(DEFAULT_MAX_CACHE_SIZE_MB);
+                        }
+                        "retention" => {
+                            settings.retention = map.next_value().unwrap_or_default();
+                        }
                         "confirmDelete" => {
                             settings.confirm_delete = map.next_value().unwrap_or(DEFAULT_CONFIRM_DELETE);
                         }
+                        "useRecycleBin" => {
+                            settings.use_recycle_bin = map.next_value().unwrap_or(DEFAULT_USE_RECYCLE_BIN);
+                        }
+                        "webcamHotkey" => {
+                            settings.webcam_hotkey = map.next_value().ok();
+                        }
                         "hightlightHotkey" => {
                             settings.hightlight_hotkey = map.next_value().ok();
                         }
+                        "bookmarkHotkey" => {
+                            settings.bookmark_hotkey = map.next_value().ok();
+                        }
+                        "removeHighlightHotkey" => {
+                            settings.remove_highlight_hotkey = map.next_value().ok();
+                        }
+                        "gamepadHotkeys" => {
+                            settings.gamepad_hotkeys = map.next_value().unwrap_or_default();
+                        }
                         "startRecordingHotkey" => {
                             settings.start_recording_hotkey = map.next_value().ok();
                         }
@@ -462,6 +1147,53 @@ impl<'de> Deserialize<'de> for Settings {
                         "gameModes" => {
                             settings.game_modes = map.next_value().unwrap_or(DEFAULT_GAME_MODES);
                         }
+                        "stopPhases" => {
+                            // parsed one by one so an unknown phase (e.g. a typo) only drops that entry
+                            let mut stop_phases = map
+                                .next_value::<Vec<serde_json::Value>>()
+                                .unwrap_or_default()
+                                .into_iter()
+                                .filter_map(|phase| serde_json::from_value::<GamePhase>(phase).ok())
+                                .collect::<Vec<_>>();
+                            stop_phases.retain(|phase| VALID_STOP_PHASES.contains(phase));
+                            // there has to be at least one phase that stops the recording
+                            if stop_phases.is_empty() {
+                                stop_phases = default_stop_phases();
+                            }
+                            settings.stop_phases = stop_phases;
+                        }
+                        "minGameDurationSecs" => {
+                            settings.min_game_duration_secs =
+                                map.next_value().unwrap_or(DEFAULT_MIN_GAME_DURATION_SECS);
+                        }
+                        "keepDiscardedRecordings" => {
+                            settings.keep_discarded_recordings =
+                                map.next_value().unwrap_or(DEFAULT_KEEP_DISCARDED_RECORDINGS);
+                        }
+                        "segmentation" => {
+                            settings.segmentation = map.next_value().ok();
+                        }
+                        "stopGracePeriodSecs" => {
+                            settings.stop_grace_period_secs = map
+                                .next_value()
+                                .unwrap_or(DEFAULT_STOP_GRACE_PERIOD_SECS)
+                                .min(MAX_GRACE_PERIOD_SECS);
+                        }
+                        "reconnectDebounceSecs" => {
+                            settings.reconnect_debounce_secs = map
+                                .next_value()
+                                .unwrap_or(DEFAULT_RECONNECT_DEBOUNCE_SECS)
+                                .min(MAX_GRACE_PERIOD_SECS);
+                        }
+                        "recordWithoutClient" => {
+                            settings.record_without_client = map.next_value().unwrap_or(DEFAULT_RECORD_WITHOUT_CLIENT);
+                        }
+                        "prewarmRecorder" => {
+                            settings.prewarm_recorder = map.next_value().unwrap_or(DEFAULT_PREWARM_RECORDER);
+                        }
+                        "livePollJitterMs" => {
+                            settings.live_poll_jitter_ms = map.next_value().unwrap_or(DEFAULT_LIVE_POLL_JITTER_MS);
+                        }
                         "autoplayVideo" => {
                             settings.autoplay_video = map.next_value().unwrap_or(DEFAULT_AUTOPLAY_VIDEO);
                         }
@@ -477,12 +1209,51 @@ impl<'de> Deserialize<'de> for Settings {
                         "ffmpegPath" => {
                             settings.ffmpeg_path = map.next_value().ok();
                         }
+                        "editorPath" => {
+                            settings.editor_path = map.next_value().ok();
+                        }
                         "developerMode" => {
                             settings.developer_mode = map.next_value().unwrap_or(false);
                         }
+                        "dryRun" => {
+                            settings.dry_run = map.next_value().unwrap_or(DEFAULT_DRY_RUN);
+                        }
+                        "autoDownloadReplay" => {
+                            settings.auto_download_replay = map.next_value().unwrap_or(DEFAULT_AUTO_DOWNLOAD_REPLAY);
+                        }
+                        "postgameScreenshot" => {
+                            settings.postgame_screenshot = map.next_value().unwrap_or(DEFAULT_POSTGAME_SCREENSHOT);
+                        }
+                        "discordWebhook" => {
+                            settings.discord_webhook = map.next_value().ok();
+                        }
+                        "youtubeClientId" => {
+                            settings.youtube_client_id = map.next_value().ok();
+                        }
+                        "youtubeClientSecret" => {
+                            settings.youtube_client_secret = map.next_value().ok();
+                        }
+                        "backup" => {
+                            settings.backup = map.next_value().ok();
+                        }
+                        "telemetry" => {
+                            settings.telemetry = map.next_value().unwrap_or(DEFAULT_TELEMETRY);
+                        }
+                        "overlay" => {
+                            settings.overlay = map.next_value().ok();
+                        }
+                        "notifications" => {
+                            settings.notifications = map.next_value().unwrap_or_default();
+                        }
+                        "exportPresets" => {
+                            settings.export_presets = map.next_value().unwrap_or_default();
+                        }
                         "matchHistoryBaseUrl" => {
                             settings.match_history_base_url = map.next_value().ok();
                         }
+                        "riotApiKey" => {
+                            settings.riot_api_key = map.next_value().ok();
+                        }
                         "scrollFrameStepModifier" => {
                             settings.scroll_frame_step_modifier = map.next_value().ok();
                         }
@@ -499,6 +1270,10 @@ impl<'de> Deserialize<'de> for Settings {
                     }
                 }
 
+                if let Some(quality) = encoding_quality.filter(|_| !has_encoder) {
+                    settings.encoder = EncoderSettings { quality, ..Default::default() }.validated();
+                }
+
                 Ok(settings)
             }
         }
@@ -507,6 +1282,339 @@ impl<'de> Deserialize<'de> for Settings {
     }
 }
 
+/// Retention per category of recording. Favorites are always kept.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetentionRules {
+    /// overrides 'maxRecordingAgeDays' for ranked games
+    pub ranked_max_age_days: Option<u64>,
+    /// overrides 'maxRecordingAgeDays' for all other recordings (normals, ARAM, customs, ...)
+    pub other_max_age_days: Option<u64>,
+    pub clips_max_age_days: Option<u64>,
+    /// clips have their own budget and don't count towards 'maxRecordingsSizeGb'
+    pub clips_max_size_gb: Option<u64>,
+}
+
+/// Rate control, preset and keyframe interval of the video encoder
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EncoderSettings {
+    pub rate_control: RateControlMode,
+    /// only for 'cqp': 0 (best quality, big files) to 51 (heavily compressed, small files)
+    pub quality: u32,
+    /// only for 'cbr' and 'vbr': target bitrate in kbit/s
+    pub bitrate_kbps: u32,
+    pub keyframe_interval_secs: u32,
+    pub preset: EncoderPreset,
+}
+
+const MAX_QUALITY: u32 = 51;
+// typical bitrate of a recorded game with 'cqp' as bits per pixel of every frame at quality 23
+const CQP_REFERENCE_QUALITY: u32 = 23;
+const CQP_REFERENCE_BITS_PER_PIXEL: f64 = 0.1;
+const BITRATE_RANGE_KBPS: (u32, u32) = (500, 100_000);
+const KEYFRAME_INTERVAL_RANGE_SECS: (u32, u32) = (1, 10);
+
+impl Default for EncoderSettings {
+    fn default() -> Self {
+        Self {
+            rate_control: RateControlMode::default(),
+            quality: DEFAULT_ENCODING_QUALITY,
+            bitrate_kbps: DEFAULT_BITRATE_KBPS,
+            keyframe_interval_secs: DEFAULT_KEYFRAME_INTERVAL_SECS,
+            preset: EncoderPreset::default(),
+        }
+    }
+}
+
+impl EncoderSettings {
+    /// Clamp all values to the range every encoder supports
+    pub fn validated(self) -> Self {
+        let validated = Self {
+            quality: self.quality.min(MAX_QUALITY),
+            bitrate_kbps: self.bitrate_kbps.clamp(BITRATE_RANGE_KBPS.0, BITRATE_RANGE_KBPS.1),
+            keyframe_interval_secs: self
+                .keyframe_interval_secs
+                .clamp(KEYFRAME_INTERVAL_RANGE_SECS.0, KEYFRAME_INTERVAL_RANGE_SECS.1),
+            ..self
+        };
+        if validated.quality != self.quality
+            || validated.bitrate_kbps != self.bitrate_kbps
+            || validated.keyframe_interval_secs != self.keyframe_interval_secs
+        {
+            log::warn!("encoder settings out of range, using {validated:?}");
+        }
+        validated
+    }
+
+    /// Rough estimate of the video bitrate in kbit/s. With 'cqp' the real bitrate depends heavily on the content.
+    pub fn estimated_bitrate_kbps(&self, width: u32, height: u32, fps: f64) -> f64 {
+        match self.rate_control {
+            RateControlMode::Cbr | RateControlMode::Vbr => self.bitrate_kbps as f64,
+            RateControlMode::Cqp => {
+                // the bitrate roughly halves with every 6 steps of quality
+                let bits_per_pixel = CQP_REFERENCE_BITS_PER_PIXEL
+                    * 2f64.powf((CQP_REFERENCE_QUALITY as f64 - self.quality as f64) / 6.0);
+                width as f64 * height as f64 * fps * bits_per_pixel / 1000.0
+            }
+        }
+    }
+
+    pub fn rate_control(&self) -> RateControl {
+        match self.rate_control {
+            RateControlMode::Cqp => RateControl::CQP(self.quality),
+            RateControlMode::Cbr => RateControl::CBR(self.bitrate_kbps),
+            RateControlMode::Vbr => RateControl::VBR(self.bitrate_kbps),
+        }
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RateControlMode {
+    /// constant quality, the file size depends on the content
+    #[default]
+    Cqp,
+    /// constant bitrate, predictable file size
+    Cbr,
+    /// variable bitrate around the target bitrate
+    Vbr,
+}
+
+/// Toast notifications that can be enabled individually
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Notifications {
+    pub recording_started: bool,
+    pub recording_finished: bool,
+    pub highlight_saved: bool,
+}
+
+/// Button combos of an XInput controller (e.g. "Back+A") that do the same as the keyboard hotkeys
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GamepadHotkeys {
+    pub highlight: Option<String>,
+    pub bookmark: Option<String>,
+    pub stop_recording: Option<String>,
+}
+
+/// Split long recordings into multiple files so a corrupt container doesn't lose the whole game.
+/// A new file is started as soon as one of the limits is reached.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segmentation {
+    #[serde(default)]
+    pub max_duration_mins: Option<u64>,
+    #[serde(default)]
+    pub max_size_mb: Option<u64>,
+}
+
+/// Small always-on-top window that shows the recording status ingame
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySettings {
+    #[serde(default)]
+    pub position: OverlayPosition,
+    /// 0.0 (invisible) to 1.0
+    #[serde(default = "default_overlay_opacity")]
+    pub opacity: f64,
+}
+
+#[inline]
+fn default_overlay_opacity() -> f64 {
+    0.8
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// How recordings of a game on a display with HDR enabled are handled
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HdrMode {
+    /// tonemap to SDR so the recording looks right on every display
+    #[default]
+    Tonemap,
+    /// record 10-bit HDR if HDR is enabled on the display and the encoder supports it (AV1), otherwise tonemap
+    Hdr,
+}
+
+/// Which releases on GitHub are offered as updates
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// pre-releases too, whichever release is newer
+    Beta,
+}
+
+/// Subfolder of the recordings folder new recordings are saved in
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrganizeBy {
+    #[default]
+    None,
+    /// e.g. '2024-05'
+    Month,
+    /// e.g. 'RANKED'
+    Queue,
+    /// e.g. '14.10'
+    Patch,
+}
+
+/// Discord webhook that gets a message for every finished game
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscordWebhook {
+    pub url: String,
+    #[serde(default)]
+    pub only_ranked: bool,
+    #[serde(default)]
+    pub only_wins: bool,
+    /// attach a short clip of the last highlight if it is small enough for Discord (8MB)
+    #[serde(default)]
+    pub upload_highlight_clip: bool,
+}
+
+// the url contains the token of the webhook
+impl fmt::Debug for DiscordWebhook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiscordWebhook")
+            .field("url", &REDACTED)
+            .field("only_ranked", &self.only_ranked)
+            .field("only_wins", &self.only_wins)
+            .field("upload_highlight_clip", &self.upload_highlight_clip)
+            .finish()
+    }
+}
+
+/// Named set of export parameters so exporting e.g. a 720p copy to a network share is a single action
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+    pub name: String,
+    /// container of the exported file (e.g. "mp4", "mkv" or "webm")
+    #[serde(default = "default_export_format")]
+    pub format: String,
+    /// height of the exported video in pixels (keeps the aspect ratio) or the original resolution if not set
+    #[serde(default)]
+    pub resolution: Option<u32>,
+    /// video bitrate in kbit/s or FFmpeg's default for the format if not set
+    #[serde(default)]
+    pub bitrate: Option<u32>,
+    pub destination: PathBuf,
+}
+
+#[inline]
+fn default_export_format() -> String {
+    String::from("mp4")
+}
+
+/// Mirror finished recordings to remote storage
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSettings {
+    pub target: BackupTarget,
+    #[serde(default)]
+    pub only_favorites: bool,
+    /// delete backups this many days after they were uploaded (keep forever if not set)
+    #[serde(default)]
+    pub retention_days: Option<u64>,
+    /// number of recordings uploaded at the same time
+    #[serde(default = "default_backup_concurrency")]
+    pub concurrency: usize,
+    /// upload limit in KB/s shared by all concurrent uploads
+    #[serde(default)]
+    pub bandwidth_limit_kbps: Option<u64>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BackupTarget {
+    /// any S3-compatible storage (path-style requests)
+    #[serde(rename_all = "camelCase")]
+    S3 {
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key: String,
+        secret_key: String,
+        #[serde(default)]
+        prefix: String,
+    },
+    /// URL of an existing WebDAV folder
+    #[serde(rename_all = "camelCase")]
+    WebDav {
+        url: String,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+impl fmt::Debug for BackupTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::S3 {
+                endpoint,
+                region,
+                bucket,
+                prefix,
+                ..
+            } => f
+                .debug_struct("S3")
+                .field("endpoint", endpoint)
+                .field("region", region)
+                .field("bucket", bucket)
+                .field("access_key", &REDACTED)
+                .field("secret_key", &REDACTED)
+                .field("prefix", prefix)
+                .finish(),
+            Self::WebDav { url, username, password } => f
+                .debug_struct("WebDav")
+                .field("url", url)
+                .field("username", &redact(username))
+                .field("password", &redact(password))
+                .finish(),
+        }
+    }
+}
+
+/// Debug output of an optional credential that only shows whether it is set
+fn redact(secret: &Option<String>) -> Option<&'static str> {
+    secret.as_ref().map(|_| REDACTED)
+}
+
+#[inline]
+fn default_backup_concurrency() -> usize {
+    1
+}
+
 #[cfg_attr(test, derive(specta::Type))]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MarkerFlags {
@@ -518,6 +1626,8 @@ pub struct MarkerFlags {
     voidgrub: bool,
     herald: bool,
     baron: bool,
+    highlight: bool,
+    bookmark: bool,
 }
 
 // Infallible
@@ -568,6 +1678,12 @@ impl<'de> Deserialize<'de> for MarkerFlags {
                         "baron" => {
                             marker_flags.baron = map.next_value().unwrap_or(true);
                         }
+                        "highlight" => {
+                            marker_flags.highlight = map.next_value().unwrap_or(true);
+                        }
+                        "bookmark" => {
+                            marker_flags.bookmark = map.next_value().unwrap_or(true);
+                        }
                         _ => { /* ignored */ }
                     }
                 }
@@ -592,6 +1708,9 @@ impl Default for MarkerFlags {
             voidgrub: true,
             herald: true,
             baron: true,
+
+            highlight: true,
+            bookmark: true,
         }
     }
 }
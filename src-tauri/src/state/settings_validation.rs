@@ -0,0 +1,211 @@
+//! Reports everything in a settings.json that the (forgiving) deserializer of `Settings` silently replaces with a
+//! default or can't use, so the settings editor and the notepad edit flow can tell the user about it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::state::{GamepadCombo, Hotkey, Settings};
+
+// keys that aren't part of `Settings` but are still read (or documented and ignored) for old settings files
+const LEGACY_KEYS: [&str; 2] = ["encodingQuality", "onlyRecordRanked"];
+const HOTKEY_KEYS: [&str; 7] = [
+    "hightlightHotkey",
+    "bookmarkHotkey",
+    "removeHighlightHotkey",
+    "startRecordingHotkey",
+    "stopRecordingHotkey",
+    "webcamHotkey",
+    "pushToTalkHotkey",
+];
+const GAMEPAD_KEYS: [&str; 3] = ["highlight", "bookmark", "stopRecording"];
+const WRITE_TEST_FILE: &str = ".league_record_write_test";
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingsWarningKind {
+    /// the key doesn't exist and is ignored
+    UnknownKey,
+    /// the value has the wrong type or is out of range, the default is used instead
+    InvalidValue,
+    /// the hotkey (or gamepad combo) can't be parsed and never triggers
+    InvalidHotkey,
+    /// recordings or clips can't be saved to the folder
+    FolderNotWritable,
+    /// an external folder that doesn't exist
+    FolderNotFound,
+    /// settings.json isn't valid JSON, the previous settings are kept until it is fixed
+    InvalidFile,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsWarning {
+    /// name of the setting, nested settings are separated by a dot (e.g. 'gamepadHotkeys.highlight')
+    pub key: String,
+    pub kind: SettingsWarningKind,
+    pub message: String,
+}
+
+impl SettingsWarning {
+    fn new(key: impl Into<String>, kind: SettingsWarningKind, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// settings.json as a whole can't be used (e.g. a syntax error), `key` is empty
+    pub fn invalid_file(error: impl std::fmt::Display) -> Self {
+        Self::new("", SettingsWarningKind::InvalidFile, error.to_string())
+    }
+}
+
+/// Check the content of a settings.json. Only fails if it isn't a JSON object at all.
+pub fn validate_settings(app_handle: &AppHandle, json: &str) -> Result<Vec<SettingsWarning>> {
+    let Value::Object(input) = serde_json::from_str::<Value>(json)? else {
+        bail!("settings have to be a JSON object");
+    };
+    let Value::Object(defaults) = serde_json::to_value(Settings::default())? else {
+        bail!("failed to serialize the default settings");
+    };
+
+    let mut warnings = Vec::new();
+    for (key, value) in &input {
+        let Some(default) = defaults.get(key) else {
+            if !LEGACY_KEYS.contains(&key.as_str()) {
+                warnings.push(SettingsWarning::new(
+                    key,
+                    SettingsWarningKind::UnknownKey,
+                    "unknown setting, ignored",
+                ));
+            }
+            continue;
+        };
+
+        if is_defaulted(key, value, default)? {
+            warnings.push(SettingsWarning::new(
+                key,
+                SettingsWarningKind::InvalidValue,
+                format!("invalid value {value}, using the default {default}"),
+            ));
+        }
+    }
+
+    check_hotkeys(&input, &mut warnings);
+
+    let settings = serde_json::from_value::<Settings>(Value::Object(input))?;
+    if !settings.framerate.is_valid() {
+        warnings.push(SettingsWarning::new(
+            "framerate",
+            SettingsWarningKind::InvalidValue,
+            "numerator and denominator have to be at least 1",
+        ));
+    }
+    check_folders(app_handle, &settings, &mut warnings);
+
+    Ok(warnings)
+}
+
+/// Whether the deserializer replaced a value that isn't the default with the default
+fn is_defaulted(key: &str, value: &Value, default: &Value) -> Result<bool> {
+    let mut single = Map::new();
+    single.insert(key.to_owned(), value.clone());
+    let parsed = serde_json::to_value(serde_json::from_value::<Settings>(Value::Object(single))?)?;
+
+    // a whole number of fps is stored as fraction
+    let value = match value.as_u64() {
+        Some(fps) if key == "framerate" => json!([fps, 1]),
+        _ => value.clone(),
+    };
+    Ok(parsed.get(key) == Some(default) && !is_subset(&value, default))
+}
+
+/// Like `==` but numbers only have to have the same value (1 == 1.0) and fields that are missing in `value` are filled
+/// in with their defaults by the deserializer anyway
+fn is_subset(value: &Value, other: &Value) -> bool {
+    match (value, other) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| is_subset(a, b)),
+        (Value::Object(a), Value::Object(b)) => a.iter().all(|(key, a)| b.get(key).is_some_and(|b| is_subset(a, b))),
+        _ => value == other,
+    }
+}
+
+fn check_hotkeys(input: &Map<String, Value>, warnings: &mut Vec<SettingsWarning>) {
+    for key in HOTKEY_KEYS {
+        if let Some(Err(e)) = input.get(key).and_then(Value::as_str).map(str::parse::<Hotkey>) {
+            warnings.push(SettingsWarning::new(key, SettingsWarningKind::InvalidHotkey, e));
+        }
+    }
+
+    let Some(gamepad_hotkeys) = input.get("gamepadHotkeys").and_then(Value::as_object) else { return };
+    for key in GAMEPAD_KEYS {
+        if let Some(Err(e)) = gamepad_hotkeys
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::parse::<GamepadCombo>)
+        {
+            warnings.push(SettingsWarning::new(
+                format!("gamepadHotkeys.{key}"),
+                SettingsWarningKind::InvalidHotkey,
+                e,
+            ));
+        }
+    }
+}
+
+fn check_folders(app_handle: &AppHandle, settings: &Settings, warnings: &mut Vec<SettingsWarning>) {
+    // relative folders are resolved the same way as in `SettingsWrapper::load_from_file()`
+    let video_dir = app_handle.path().video_dir().ok();
+    let resolve = |folder: &Path| match &video_dir {
+        Some(video_dir) if folder.is_relative() => video_dir.join(folder),
+        _ => folder.to_path_buf(),
+    };
+
+    for (key, folder) in [
+        ("recordingsFolder", &settings.recordings_folder),
+        ("clipsFolder", &settings.clips_folder),
+    ] {
+        let folder = resolve(folder);
+        if let Err(e) = check_writable(&folder) {
+            warnings.push(SettingsWarning::new(
+                key,
+                SettingsWarningKind::FolderNotWritable,
+                format!("can't write to {}: {e}", folder.display()),
+            ));
+        }
+    }
+
+    for folder in &settings.external_folders {
+        if !folder.is_dir() {
+            warnings.push(SettingsWarning::new(
+                "externalFolders",
+                SettingsWarningKind::FolderNotFound,
+                format!("{} doesn't exist", folder.display()),
+            ));
+        }
+    }
+}
+
+/// Folders that don't exist yet get created when the settings are loaded => check the closest existing parent instead
+fn check_writable(folder: &Path) -> Result<()> {
+    let Some(existing) = folder.ancestors().find(|path| path.exists()).map(PathBuf::from) else {
+        bail!("invalid path");
+    };
+    if !existing.is_dir() {
+        bail!("{} is a file", existing.display());
+    }
+
+    let test_file = existing.join(WRITE_TEST_FILE);
+    fs::write(&test_file, [])?;
+    _ = fs::remove_file(test_file);
+    Ok(())
+}
@@ -1,9 +1,23 @@
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// What the recorder is doing, shown as tray icon
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TrayStatus {
+    #[default]
+    Idle,
+    WaitingForGame,
+    Recording,
+    ProcessingMetadata,
+}
 
 #[derive(Debug, Default)]
 pub struct TrayState {
     update_available: AtomicBool,
     recording: AtomicBool,
+    status: Mutex<TrayStatus>,
+    recording_since: Mutex<Option<Instant>>,
 }
 
 impl TrayState {
@@ -17,9 +31,30 @@ impl TrayState {
 
     pub fn set_recording(&self, recording: bool) {
         self.recording.store(recording, Ordering::Release);
+        *self.recording_since.lock().unwrap() = recording.then(Instant::now);
     }
 
     pub fn recording(&self) -> bool {
         self.recording.load(Ordering::Acquire)
     }
+
+    pub fn set_status(&self, status: TrayStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+
+    /// The status of the listener, except that an actually running recording is always `Recording`
+    pub fn status(&self) -> TrayStatus {
+        if self.recording() {
+            TrayStatus::Recording
+        } else {
+            *self.status.lock().unwrap()
+        }
+    }
+
+    pub fn recording_minutes(&self) -> u64 {
+        self.recording_since
+            .lock()
+            .unwrap()
+            .map_or(0, |since| since.elapsed().as_secs() / 60)
+    }
 }
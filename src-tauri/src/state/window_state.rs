@@ -3,6 +3,8 @@ use std::sync::Mutex;
 pub struct WindowState {
     size: Mutex<(f64, f64)>,
     position: Mutex<Option<(f64, f64)>>,
+    // recording that should get selected once the window has loaded
+    pending_recording: Mutex<Option<String>>,
 }
 
 impl WindowState {
@@ -29,6 +31,14 @@ impl WindowState {
         };
         log::info!("saved window position: {}x {}y", position.0, position.1);
     }
+
+    pub fn set_pending_recording(&self, video_id: String) {
+        *self.pending_recording.lock().unwrap() = Some(video_id);
+    }
+
+    pub fn take_pending_recording(&self) -> Option<String> {
+        self.pending_recording.lock().unwrap().take()
+    }
 }
 
 impl Default for WindowState {
@@ -36,6 +46,7 @@ impl Default for WindowState {
         Self {
             size: Mutex::from((1200.0, 650.0)),
             position: Mutex::from(None),
+            pending_recording: Mutex::from(None),
         }
     }
 }
@@ -0,0 +1,132 @@
+//! Names of champions, items and runes per patch from CommunityDragon.
+//!
+//! The League client doesn't know every champion (e.g. the Swarm versions) and looking them up one by one costs a
+//! request per participant. The catalogs are downloaded once per patch, kept in memory and cached on disk.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{bail, Result};
+use riot_datatypes::{Champion, ChampionId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::recorder;
+
+pub const CACHE_DIR: &str = "static_data_cache";
+const CDRAGON_URL: &str = "https://raw.communitydragon.org";
+const GAME_DATA_PATH: &str = "plugins/rcp-be-lol-game-data/global/default/v1";
+// CommunityDragon takes a while to publish a new patch
+const LATEST: &str = "latest";
+
+static CACHE_PATH: OnceLock<PathBuf> = OnceLock::new();
+// catalogs that were already loaded, the lock is held while downloading so each patch is only downloaded once
+static CATALOGS: Mutex<Vec<(String, Arc<Catalog>)>> = Mutex::const_new(Vec::new());
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Catalog {
+    pub champions: HashMap<ChampionId, Champion>,
+    /// item id => name
+    pub items: HashMap<i64, String>,
+    /// rune (perk) id => name
+    pub runes: HashMap<i64, String>,
+}
+
+impl Catalog {
+    pub fn champion(&self, champion_id: ChampionId) -> Option<&Champion> {
+        self.champions.get(&champion_id)
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedEntry {
+    id: i64,
+    name: String,
+}
+
+/// Set the folder the catalogs get cached in
+pub fn init(app_handle: &AppHandle) {
+    if let Ok(app_dir) = app_handle.path().app_local_data_dir() {
+        _ = CACHE_PATH.set(app_dir.join(CACHE_DIR));
+    }
+}
+
+/// The catalog of the patch of `game_version` (e.g. '14.10.584.1234')
+pub async fn catalog(game_version: &str) -> Result<Arc<Catalog>> {
+    let patch = recorder::patch(game_version).unwrap_or_else(|| LATEST.to_owned());
+
+    let mut catalogs = CATALOGS.lock().await;
+    if let Some((_, catalog)) = catalogs.iter().find(|(cached, _)| *cached == patch) {
+        return Ok(catalog.clone());
+    }
+
+    let catalog = match read_cache(&patch) {
+        Some(catalog) => catalog,
+        None => match download(&patch).await {
+            Ok(catalog) => {
+                write_cache(&patch, &catalog);
+                catalog
+            }
+            // not cached on disk so the data of the patch gets downloaded once it's available
+            Err(e) => {
+                log::warn!("failed to download static data of patch {patch}, using the latest: {e}");
+                download(LATEST).await?
+            }
+        },
+    };
+
+    let catalog = Arc::new(catalog);
+    catalogs.push((patch, catalog.clone()));
+    Ok(catalog)
+}
+
+async fn download(patch: &str) -> Result<Catalog> {
+    let champions = get::<Vec<Champion>>(patch, "champion-summary.json").await?;
+    let items = get::<Vec<NamedEntry>>(patch, "items.json").await?;
+    let runes = get::<Vec<NamedEntry>>(patch, "perks.json").await?;
+
+    Ok(Catalog {
+        champions: champions.into_iter().map(|champion| (champion.id, champion)).collect(),
+        items: items.into_iter().map(|item| (item.id, item.name)).collect(),
+        runes: runes.into_iter().map(|rune| (rune.id, rune.name)).collect(),
+    })
+}
+
+async fn get<T: DeserializeOwned>(patch: &str, file: &str) -> Result<T> {
+    let response = reqwest::get(format!("{CDRAGON_URL}/{patch}/{GAME_DATA_PATH}/{file}")).await?;
+    if !response.status().is_success() {
+        bail!("request for {file} failed: {}", response.status());
+    }
+    Ok(response.json().await?)
+}
+
+fn cache_file(patch: &str) -> Option<PathBuf> {
+    Some(CACHE_PATH.get()?.join(format!("{patch}.json")))
+}
+
+fn read_cache(patch: &str) -> Option<Catalog> {
+    // the latest data changes with every patch
+    if patch == LATEST {
+        return None;
+    }
+
+    let data = fs::read(cache_file(patch)?).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+fn write_cache(patch: &str, catalog: &Catalog) {
+    let Some(cache_file) = cache_file(patch).filter(|_| patch != LATEST) else { return };
+    let result = cache_file
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&cache_file, serde_json::to_vec(catalog)?));
+    if let Err(e) = result {
+        log::warn!("failed to cache static data of patch {patch}: {e}");
+    }
+}
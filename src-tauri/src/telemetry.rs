@@ -0,0 +1,181 @@
+//! Anonymized usage statistics.
+//!
+//! Only compiled in with the `telemetry` feature. Feature usage is counted locally and can be previewed with
+//! `get_pending_telemetry`. The counters only get transmitted if the user enabled 'telemetry' in the settings and the
+//! build has an endpoint configured (`LEAGUE_RECORD_TELEMETRY_URL` at compile time).
+//! Nothing that identifies the user (summoner names, paths, setting values, ...) is ever counted.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::state::Settings;
+
+/// Everything that would be sent with the next transmission
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryReport {
+    pub app_version: String,
+    pub os: String,
+    /// how often each feature has been used since the last transmission
+    pub counters: BTreeMap<String, u64>,
+}
+
+#[cfg_attr(not(feature = "telemetry"), allow(dead_code))]
+pub enum TelemetryEvent {
+    RecordingMade,
+    ClipCreated,
+    /// name of a setting that got changed (not its value)
+    SettingChanged(String),
+}
+
+impl TelemetryEvent {
+    #[cfg_attr(not(feature = "telemetry"), allow(dead_code))]
+    fn key(&self) -> String {
+        match self {
+            TelemetryEvent::RecordingMade => "recordingsMade".into(),
+            TelemetryEvent::ClipCreated => "clipsCreated".into(),
+            TelemetryEvent::SettingChanged(setting) => format!("settingChanged.{setting}"),
+        }
+    }
+}
+
+#[cfg(feature = "telemetry")]
+mod collector {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use anyhow::{bail, Result};
+    use tauri::{AppHandle, Manager};
+
+    use super::{TelemetryEvent, TelemetryReport};
+    use crate::constants::CURRENT_VERSION;
+    use crate::state::SettingsWrapper;
+
+    const COUNTERS_FILE: &str = "telemetry.json";
+    const TELEMETRY_URL: Option<&str> = option_env!("LEAGUE_RECORD_TELEMETRY_URL");
+
+    pub struct Telemetry {
+        counters: Mutex<BTreeMap<String, u64>>,
+        counters_file: PathBuf,
+    }
+
+    impl Telemetry {
+        pub fn new(app_handle: &AppHandle) -> Result<Self> {
+            let counters_file = app_handle.path().app_config_dir()?.join(COUNTERS_FILE);
+            let counters = fs::read(&counters_file)
+                .ok()
+                .and_then(|counters| serde_json::from_slice(&counters).ok())
+                .unwrap_or_default();
+
+            Ok(Self {
+                counters: Mutex::new(counters),
+                counters_file,
+            })
+        }
+
+        pub fn record(&self, event: TelemetryEvent) {
+            let mut counters = self.counters.lock().unwrap();
+            *counters.entry(event.key()).or_default() += 1;
+            self.save(&counters);
+        }
+
+        pub fn report(&self) -> TelemetryReport {
+            TelemetryReport {
+                app_version: CURRENT_VERSION.to_owned(),
+                os: std::env::consts::OS.to_owned(),
+                counters: self.counters.lock().unwrap().clone(),
+            }
+        }
+
+        fn save(&self, counters: &BTreeMap<String, u64>) {
+            let result = serde_json::to_vec_pretty(counters)
+                .map_err(std::io::Error::from)
+                .and_then(|json| fs::write(&self.counters_file, json));
+            if let Err(e) = result {
+                log::warn!("failed to save telemetry counters: {e}");
+            }
+        }
+    }
+
+    /// Transmit the counters and reset them. Does nothing without the user's consent.
+    pub async fn transmit(app_handle: &AppHandle) -> Result<()> {
+        if !app_handle.state::<SettingsWrapper>().telemetry() {
+            return Ok(());
+        }
+        let Some(url) = TELEMETRY_URL else { return Ok(()) };
+
+        let telemetry = app_handle.state::<Telemetry>();
+        let report = telemetry.report();
+        if report.counters.is_empty() {
+            return Ok(());
+        }
+
+        let response = reqwest::Client::new().post(url).json(&report).send().await?;
+        if !response.status().is_success() {
+            bail!("sending telemetry failed: {}", response.status());
+        }
+
+        // only subtract what was sent in case something got counted in the meantime
+        let mut counters = telemetry.counters.lock().unwrap();
+        for (key, sent) in report.counters {
+            if let Some(count) = counters.get_mut(&key) {
+                *count = count.saturating_sub(sent);
+            }
+        }
+        counters.retain(|_, count| *count > 0);
+        telemetry.save(&counters);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "telemetry")]
+pub use collector::{transmit, Telemetry};
+
+/// Count the usage of a feature. Does nothing if the app was built without the `telemetry` feature.
+pub fn record(app_handle: &AppHandle, event: TelemetryEvent) {
+    #[cfg(feature = "telemetry")]
+    {
+        use tauri::Manager;
+        app_handle.state::<Telemetry>().record(event);
+    }
+    #[cfg(not(feature = "telemetry"))]
+    let _ = (app_handle, event);
+}
+
+/// Count every top level setting that differs between `old` and `new`.
+pub fn record_settings_changes(app_handle: &AppHandle, old: &Settings, new: &Settings) {
+    if !cfg!(feature = "telemetry") {
+        return;
+    }
+
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return;
+    };
+
+    for (setting, value) in new {
+        if old.get(&setting) != Some(&value) {
+            record(app_handle, TelemetryEvent::SettingChanged(setting));
+        }
+    }
+}
+
+/// The counters that would be sent with the next transmission or `None` if the app was built without telemetry.
+pub fn pending_report(app_handle: &AppHandle) -> Option<TelemetryReport> {
+    #[cfg(feature = "telemetry")]
+    {
+        use tauri::Manager;
+        Some(app_handle.state::<Telemetry>().report())
+    }
+    #[cfg(not(feature = "telemetry"))]
+    {
+        let _ = app_handle;
+        None
+    }
+}
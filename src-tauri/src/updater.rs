@@ -0,0 +1,214 @@
+//! Checks the GitHub releases for a new version and installs it once the user confirms.
+//!
+//! A found update only adds the 'Update' entry to the tray menu, nothing is downloaded before the user clicks it. The
+//! updater plugin refuses to install anything that isn't signed with the key in tauri.conf.json.
+//!
+//! GitHub only redirects 'releases/latest' to the newest release that isn't a pre-release, so the releases are listed
+//! through the GitHub API and the update manifest of the newest release of the 'updateChannel' gets checked.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::app::SystemTrayManager;
+use crate::constants::{APP_NAME, CURRENT_VERSION};
+use crate::recorder::LeagueRecorder;
+use crate::state::{CurrentlyRecording, SettingsWrapper, Shutdown, UpdateChannel};
+
+// don't compete with the start of the app and the first check for a running game
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(30);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const RELEASES_API_URL: &str = "https://api.github.com/repos/arasan95/league_record_custom/releases";
+const RELEASE_DOWNLOAD_URL: &str = "https://github.com/arasan95/league_record_custom/releases/download";
+// created by `tauri build` with 'createUpdaterArtifacts' and attached to every release
+const UPDATE_MANIFEST: &str = "latest.json";
+
+/// The update found by the last check
+#[derive(Default)]
+pub struct PendingUpdate(Mutex<Option<Update>>);
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateReport {
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    /// newest release that isn't a pre-release
+    pub stable: Option<String>,
+    /// newest release including pre-releases
+    pub beta: Option<String>,
+    /// version the update of the selected channel installs, None if LeagueRecord is up to date
+    pub update: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    prerelease: bool,
+    draft: bool,
+}
+
+impl Release {
+    fn version(&self) -> String {
+        self.tag_name.trim_start_matches('v').to_owned()
+    }
+}
+
+/// Check for updates after the start and then periodically while 'checkForUpdates' is enabled
+pub fn watch(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    async_runtime::spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if !app_handle.state::<SettingsWrapper>().check_for_updates() {
+                continue;
+            }
+            if let Err(e) = check(&app_handle).await {
+                log::warn!("failed to check for updates: {e}");
+            }
+        }
+    });
+}
+
+/// Look up the newest release of each channel and check for an update in the channel of the settings
+///
+/// A found update gets offered in the tray menu.
+pub async fn check(app_handle: &AppHandle) -> Result<UpdateReport> {
+    let channel = app_handle.state::<SettingsWrapper>().update_channel();
+    // sorted newest first
+    let releases = releases().await?;
+    let stable = releases.iter().find(|release| !release.prerelease);
+    let beta = releases.first();
+
+    let release = match channel {
+        UpdateChannel::Stable => stable,
+        UpdateChannel::Beta => beta,
+    };
+    let update = match release {
+        // only newer versions count as update, switching back to 'stable' waits for the next stable release
+        Some(release) => {
+            let manifest = format!("{RELEASE_DOWNLOAD_URL}/{}/{UPDATE_MANIFEST}", release.tag_name);
+            app_handle
+                .updater_builder()
+                .endpoints(vec![manifest.parse()?])?
+                .build()?
+                .check()
+                .await?
+        }
+        None => None,
+    };
+    match &update {
+        Some(update) => log::info!(
+            "update available ({channel:?}): v{} -> v{}",
+            update.current_version,
+            update.version
+        ),
+        None => log::info!("no update available ({channel:?})"),
+    }
+
+    let report = UpdateReport {
+        current_version: CURRENT_VERSION.to_owned(),
+        channel,
+        stable: stable.map(Release::version),
+        beta: beta.map(Release::version),
+        update: update.as_ref().map(|update| update.version.clone()),
+    };
+
+    let update_available = update.is_some();
+    *app_handle.state::<PendingUpdate>().0.lock().unwrap() = update;
+    app_handle.set_tray_menu_update_available(update_available);
+    Ok(report)
+}
+
+async fn releases() -> Result<Vec<Release>> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/vnd.github+json"));
+    // the GitHub API rejects requests without a user agent
+    let client = reqwest::Client::builder()
+        .user_agent(format!("{APP_NAME}/{CURRENT_VERSION}"))
+        .default_headers(headers)
+        .build()?;
+
+    let releases = client
+        .get(RELEASES_API_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<Release>>()
+        .await?;
+    Ok(releases.into_iter().filter(|release| !release.draft).collect())
+}
+
+/// Ask the user to install the update found by the last check ('Update' in the tray menu)
+pub fn confirm_install(app_handle: &AppHandle) {
+    let Some(update) = app_handle.state::<PendingUpdate>().0.lock().unwrap().clone() else { return };
+
+    // the installer closes the app, which would cut the recording short
+    if app_handle.state::<CurrentlyRecording>().get().is_some() {
+        app_handle
+            .dialog()
+            .message("A game is being recorded, install the update after the game.")
+            .title("Update")
+            .show(|_| {});
+        return;
+    }
+
+    let mut message = format!(
+        "LeagueRecord v{} is available (installed: v{}).",
+        update.version, update.current_version
+    );
+    if let Some(notes) = update.body.as_deref().filter(|notes| !notes.trim().is_empty()) {
+        message.push_str("\n\n");
+        message.push_str(notes.trim());
+    }
+    message.push_str("\n\nLeagueRecord restarts once the update is installed.");
+
+    let handle = app_handle.clone();
+    app_handle
+        .dialog()
+        .message(message)
+        .title(format!("Update to v{}", update.version))
+        .buttons(MessageDialogButtons::OkCancelCustom("Install".into(), "Later".into()))
+        .show(move |confirmed| {
+            if confirmed {
+                async_runtime::spawn(install(handle, update));
+            }
+        });
+}
+
+async fn install(app_handle: AppHandle, update: Update) {
+    log::info!("downloading update v{}", update.version);
+    let bytes = match update.download(|_, _| {}, || {}).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("failed to download update v{}: {e}", update.version);
+            app_handle
+                .dialog()
+                .message(format!("Downloading the update failed: {e}"))
+                .title("Update failed")
+                .kind(MessageDialogKind::Error)
+                .show(|_| {});
+            return;
+        }
+    };
+
+    // same as quitting: the recorder has to be stopped before the installer replaces it
+    app_handle.state::<Shutdown>().set();
+    app_handle.state::<LeagueRecorder>().stop().await;
+
+    log::info!("installing update v{}", update.version);
+    if let Err(e) = update.install(bytes) {
+        log::error!("failed to install update v{}: {e}", update.version);
+    }
+    // starts the new version, or the old one again if the installation failed since the recorder is already stopped
+    app_handle.restart();
+}
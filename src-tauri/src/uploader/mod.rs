@@ -0,0 +1,47 @@
+mod youtube;
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::app::{action, AppEvent, EventManager};
+
+pub use youtube::{Privacy, YoutubeClient, YoutubeLogin};
+
+/// Progress of an upload that is sent to the frontend after every uploaded chunk
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadProgress {
+    pub clip_id: String,
+    pub uploaded_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Upload the clip to YouTube and remember the URL of the video in the clip metadata. Returns the URL.
+pub async fn upload_clip(app_handle: &AppHandle, clip_path: &Path, title: &str, privacy: Privacy) -> Result<String> {
+    let clip_id = clip_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let client = YoutubeClient::new(app_handle)?;
+
+    let on_progress = |uploaded_bytes, total_bytes| {
+        let payload = UploadProgress {
+            clip_id: clip_id.clone(),
+            uploaded_bytes,
+            total_bytes,
+        };
+        if let Err(e) = app_handle.send_event(AppEvent::UploadProgress { payload }) {
+            log::warn!("failed to send upload progress: {e}");
+        }
+    };
+    let url = client.upload(clip_path, title, privacy, on_progress).await?;
+
+    let mut clip_metadata = action::get_clip_metadata(clip_path).unwrap_or_default();
+    clip_metadata.youtube_url = Some(url.clone());
+    if let Err(e) = action::save_clip_metadata(clip_path, &clip_metadata) {
+        log::error!("failed to save clip metadata: {e}");
+    }
+
+    Ok(url)
+}
@@ -0,0 +1,311 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::header::{HeaderMap, CONTENT_RANGE, LOCATION, RANGE};
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
+
+use crate::state::SettingsWrapper;
+
+const DEVICE_CODE_URL: &str = "https://oauth2.googleapis.com/device/code";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const UPLOAD_URL: &str = "https://www.googleapis.com/upload/youtube/v3/videos?uploadType=resumable&part=snippet,status";
+const UPLOAD_SCOPE: &str = "https://www.googleapis.com/auth/youtube.upload";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const TOKEN_FILE: &str = "youtube_token.json";
+// chunks of a resumable upload have to be a multiple of 256KiB
+const CHUNK_SIZE: usize = 32 * 256 * 1024;
+// refresh the access token a bit before it actually expires so it doesn't run out during a request
+const TOKEN_EXPIRY_MARGIN: i64 = 60;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Privacy {
+    Public,
+    Unlisted,
+    Private,
+}
+
+impl Privacy {
+    fn as_str(self) -> &'static str {
+        match self {
+            Privacy::Public => "public",
+            Privacy::Unlisted => "unlisted",
+            Privacy::Private => "private",
+        }
+    }
+}
+
+/// Pending OAuth device-flow login. The user has to enter `user_code` at `verification_url` to allow the upload.
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeLogin {
+    pub user_code: String,
+    pub verification_url: String,
+    device_code: String,
+    interval: u64,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    verification_url: String,
+    expires_in: u64,
+    interval: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    // only part of the response to the initial login, not when refreshing the access token
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredToken {
+    access_token: String,
+    refresh_token: String,
+    // unix timestamp in seconds
+    expires_at: i64,
+}
+
+#[derive(Deserialize)]
+struct UploadedVideo {
+    id: String,
+}
+
+/// YouTube Data API client for uploading videos with a user supplied OAuth client (type "TVs and Limited Input
+/// devices"). The tokens of the logged in account are stored in the app config folder.
+pub struct YoutubeClient {
+    client: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+    token_file: PathBuf,
+}
+
+impl YoutubeClient {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let settings = app_handle.state::<SettingsWrapper>();
+        let (Some(client_id), Some(client_secret)) = (settings.youtube_client_id(), settings.youtube_client_secret())
+        else {
+            bail!("no YouTube OAuth client configured");
+        };
+        let token_file = app_handle.path().app_config_dir()?.join(TOKEN_FILE);
+
+        // the 308 response to an uploaded chunk means 'continue' and must not be treated as redirect
+        let client = reqwest::Client::builder().redirect(Policy::none()).build()?;
+
+        Ok(Self {
+            client,
+            client_id,
+            client_secret,
+            token_file,
+        })
+    }
+
+    pub async fn request_login(&self) -> Result<YoutubeLogin> {
+        let response = self
+            .client
+            .post(DEVICE_CODE_URL)
+            .form(&[("client_id", self.client_id.as_str()), ("scope", UPLOAD_SCOPE)])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("device code request failed: {}", response.status());
+        }
+
+        let DeviceCodeResponse {
+            device_code,
+            user_code,
+            verification_url,
+            expires_in,
+            interval,
+        } = response.json().await?;
+        Ok(YoutubeLogin {
+            user_code,
+            verification_url,
+            device_code,
+            interval,
+            expires_in,
+        })
+    }
+
+    /// Wait until the user allowed (or denied) the login and store the tokens.
+    pub async fn finish_login(&self, login: &YoutubeLogin) -> Result<()> {
+        let mut interval = Duration::from_secs(login.interval.max(1));
+        let attempts = login.expires_in / login.interval.max(1);
+
+        for _ in 0..attempts {
+            sleep(interval).await;
+
+            let response = self
+                .client
+                .post(TOKEN_URL)
+                .form(&[
+                    ("client_id", self.client_id.as_str()),
+                    ("client_secret", self.client_secret.as_str()),
+                    ("device_code", login.device_code.as_str()),
+                    ("grant_type", DEVICE_CODE_GRANT),
+                ])
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let token = response.json::<TokenResponse>().await?;
+                let refresh_token = token.refresh_token.context("login response without refresh token")?;
+                return self.store_token(token.access_token, refresh_token, token.expires_in);
+            }
+
+            match response.json::<TokenError>().await?.error.as_str() {
+                "authorization_pending" => {}
+                "slow_down" => interval += Duration::from_secs(5),
+                error => bail!("YouTube login failed: {error}"),
+            }
+        }
+
+        bail!("YouTube login expired")
+    }
+
+    fn store_token(&self, access_token: String, refresh_token: String, expires_in: i64) -> Result<()> {
+        let token = StoredToken {
+            access_token,
+            refresh_token,
+            expires_at: chrono::Utc::now().timestamp() + expires_in,
+        };
+        fs::write(&self.token_file, serde_json::to_vec(&token)?)?;
+        Ok(())
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        let token = fs::read(&self.token_file).context("not logged in to YouTube")?;
+        let token = serde_json::from_slice::<StoredToken>(&token)?;
+        if token.expires_at - TOKEN_EXPIRY_MARGIN > chrono::Utc::now().timestamp() {
+            return Ok(token.access_token);
+        }
+
+        let response = self
+            .client
+            .post(TOKEN_URL)
+            .form(&[
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("refresh_token", token.refresh_token.as_str()),
+                ("grant_type", "refresh_token"),
+            ])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("refreshing the YouTube access token failed: {}", response.status());
+        }
+
+        let refreshed = response.json::<TokenResponse>().await?;
+        let refresh_token = refreshed.refresh_token.unwrap_or(token.refresh_token);
+        self.store_token(refreshed.access_token.clone(), refresh_token, refreshed.expires_in)?;
+        Ok(refreshed.access_token)
+    }
+
+    /// Upload a video with a resumable upload and return its URL. `on_progress` gets called with the number of uploaded
+    /// bytes after every chunk.
+    pub async fn upload(
+        &self,
+        video_path: &Path,
+        title: &str,
+        privacy: Privacy,
+        on_progress: impl Fn(u64, u64),
+    ) -> Result<String> {
+        let access_token = self.access_token().await?;
+        let total = fs::metadata(video_path)?.len();
+
+        let response = self
+            .client
+            .post(UPLOAD_URL)
+            .bearer_auth(&access_token)
+            .header("X-Upload-Content-Type", "video/mp4")
+            .header("X-Upload-Content-Length", total)
+            .json(&json!({
+                "snippet": { "title": title },
+                "status": { "privacyStatus": privacy.as_str() },
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("starting the upload failed: {}", response.status());
+        }
+        let upload_url = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .context("no upload URL in response")?
+            .to_owned();
+
+        let mut file = File::open(video_path)?;
+        let mut uploaded = 0;
+        loop {
+            // a single read can return less than a whole chunk, only the last chunk is allowed to be smaller
+            let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+            file.seek(SeekFrom::Start(uploaded))?;
+            (&mut file).take(CHUNK_SIZE as u64).read_to_end(&mut chunk)?;
+            if chunk.is_empty() {
+                bail!("upload incomplete after the whole file was sent");
+            }
+
+            let response = self
+                .client
+                .put(&upload_url)
+                .bearer_auth(&access_token)
+                .header(
+                    CONTENT_RANGE,
+                    format!("bytes {uploaded}-{}/{total}", uploaded + chunk.len() as u64 - 1),
+                )
+                .body(chunk)
+                .send()
+                .await?;
+
+            match response.status() {
+                // 308 = chunk received, continue after the last byte the server actually stored
+                StatusCode::PERMANENT_REDIRECT => {
+                    let received = received_bytes(response.headers());
+                    if received <= uploaded {
+                        bail!("upload stalled at {uploaded} of {total} bytes");
+                    }
+                    uploaded = received;
+                    on_progress(uploaded, total);
+                }
+                status if status.is_success() => {
+                    on_progress(total, total);
+                    let video = response.json::<UploadedVideo>().await?;
+                    return Ok(format!("https://youtu.be/{}", video.id));
+                }
+                status => bail!("upload failed: {status}"),
+            }
+        }
+    }
+}
+
+/// Number of bytes of a resumable upload the server has stored, from the `Range` header of a 308 response
+/// (e.g. 'bytes=0-999'). The header is missing if nothing has been stored yet.
+fn received_bytes(headers: &HeaderMap) -> u64 {
+    headers
+        .get(RANGE)
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| range.rsplit('-').next())
+        .and_then(|last_byte| last_byte.trim().parse::<u64>().ok())
+        .map_or(0, |last_byte| last_byte + 1)
+}
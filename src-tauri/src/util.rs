@@ -1,5 +1,8 @@
 use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::process::Command;
 
 use anyhow::Result;
 
@@ -25,8 +28,255 @@ macro_rules! cancellable {
     };
 }
 
+/// working set size of this process in bytes
+pub fn memory_usage() -> Option<u64> {
+    use windows_sys::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+    (ok != 0).then_some(counters.WorkingSetSize as u64)
+}
+
+/// free space in bytes on the volume that contains `path`
+pub fn free_disk_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut free_bytes = 0u64;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide_path.as_ptr(),
+            &mut free_bytes,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(free_bytes)
+}
+
+/// (idle, total) time all CPU cores spent since the system started, in 100ns units
+pub fn system_cpu_times() -> Option<(u64, u64)> {
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::GetSystemTimes;
+
+    let mut idle: FILETIME = unsafe { std::mem::zeroed() };
+    let mut kernel: FILETIME = unsafe { std::mem::zeroed() };
+    let mut user: FILETIME = unsafe { std::mem::zeroed() };
+    let ok = unsafe { GetSystemTimes(&mut idle, &mut kernel, &mut user) };
+    let ticks = |time: FILETIME| (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+    // kernel time includes the idle time
+    (ok != 0).then(|| (ticks(idle), ticks(kernel) + ticks(user)))
+}
+
+/// Open a file with the program the OS uses for its file type
+pub fn open_with_default_app(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = Command::new("explorer");
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    command.arg(path).spawn().map(drop)
+}
+
+/// Open a http(s) URL in the default browser
+pub fn open_url(url: &reqwest::Url) -> io::Result<()> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a web URL: {url}"),
+        ));
+    }
+
+    // unlike 'explorer' and 'start' this doesn't try to interpret the URL as a path or a command line
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    command.arg(url.as_str()).spawn().map(drop)
+}
+
+/// Show a file in the file manager (Explorer, Finder, ...)
+pub fn reveal_in_file_manager(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("explorer");
+        command.arg("/select,").arg(path);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    };
+    // there is no common way to select a file => only open the folder
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(path));
+        command
+    };
+
+    command.spawn().map(drop)
+}
+
+/// Whether the MP4 file got closed properly. A file whose writer crashed has no index (moov box) or, if it was written
+/// fragmented, no fragment index (mfra box) at the end.
+pub fn is_mp4_finalized(path: &Path) -> Result<bool> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let (mut moov, mut moof, mut mfra) = (false, false, false);
+    let mut position = 0;
+    while position + 8 <= file_len {
+        let mut header = [0; 8];
+        file.read_exact(&mut header)?;
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let size = match size {
+            // box extends to the end of the file (the size never got written)
+            0 => file_len - position,
+            // 64 bit size follows the box type
+            1 => {
+                let mut large_size = [0; 8];
+                file.read_exact(&mut large_size)?;
+                u64::from_be_bytes(large_size)
+            }
+            size => size,
+        };
+        match &header[4..8] {
+            b"moov" => moov = true,
+            b"moof" => moof = true,
+            b"mfra" => mfra = true,
+            _ => {}
+        }
+
+        if size < 8 || position + size > file_len {
+            // truncated box
+            return Ok(false);
+        }
+        position += size;
+        file.seek(SeekFrom::Start(position))?;
+    }
+
+    Ok(moov && (!moof || mfra))
+}
+
+/// `f` applied to every item, spread over all CPU cores (e.g. to read a lot of files). Keeps the order of `items`.
+pub fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = items.len().div_ceil(threads).max(1);
+
+    let f = &f;
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    })
+}
+
 pub fn compare_time(a: &Path, b: &Path) -> Result<Ordering> {
     let a_time = a.metadata()?.created()?;
     let b_time = b.metadata()?.created()?;
     Ok(a_time.cmp(&b_time).reverse())
 }
+
+/// Time a video was recorded at (unix timestamp in seconds).
+/// Uses the timestamp in the filename if there is one (e.g. videos of other capture tools that got copied around and
+/// lost their original creation time), otherwise the creation time of the file.
+pub fn recorded_at(path: &Path) -> Option<i64> {
+    let from_filename = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(timestamp_from_filename);
+    from_filename.or_else(|| created_at(path))
+}
+
+/// Creation time of the file (unix timestamp in seconds)
+pub fn created_at(path: &Path) -> Option<i64> {
+    let created = path.metadata().ok()?.created().ok()?;
+    Some(chrono::DateTime::<chrono::Utc>::from(created).timestamp())
+}
+
+/// Best-effort search for a date (and time) in a filename.
+/// Supports `yyyy-mm-dd`, `dd-mm-yyyy`, `mm-dd-yyyy` (with any separators) and `yyyymmdd(_hhmmss)`, each optionally
+/// followed by the time of day.
+pub fn timestamp_from_filename(filename: &str) -> Option<i64> {
+    use chrono::{Local, NaiveDate, NaiveTime};
+
+    // the filename only gets split at non-digits so every number is plain ASCII
+    fn two_digit_parts(number: &str) -> Vec<&str> {
+        (0..number.len())
+            .step_by(2)
+            .map(|i| &number[i..(i + 2).min(number.len())])
+            .collect()
+    }
+    let parse = |number: &str| number.parse::<u32>().ok();
+
+    let numbers: Vec<&str> = filename
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|number| !number.is_empty())
+        .collect();
+
+    for (i, &number) in numbers.iter().enumerate() {
+        let (date, time): (Option<NaiveDate>, Vec<&str>) = match (number.len(), &numbers[i + 1..]) {
+            (14, _) => (
+                NaiveDate::parse_from_str(&number[..8], "%Y%m%d").ok(),
+                two_digit_parts(&number[8..]),
+            ),
+            (8, [time, ..]) if time.len() == 6 => {
+                (NaiveDate::parse_from_str(number, "%Y%m%d").ok(), two_digit_parts(time))
+            }
+            (8, _) => (NaiveDate::parse_from_str(number, "%Y%m%d").ok(), vec![]),
+            (4, [month, day, time @ ..]) if month.len() <= 2 && day.len() <= 2 => {
+                let date =
+                    parse(number).and_then(|year| NaiveDate::from_ymd_opt(year as i32, parse(month)?, parse(day)?));
+                (date, time.to_vec())
+            }
+            (1..=2, [second, year, time @ ..]) if second.len() <= 2 && year.len() == 4 => {
+                let (Some(first), Some(second), Some(year)) = (parse(number), parse(second), parse(year)) else {
+                    continue;
+                };
+                // day first if that's a valid date, otherwise month first
+                let date = NaiveDate::from_ymd_opt(year as i32, second, first)
+                    .or_else(|| NaiveDate::from_ymd_opt(year as i32, first, second));
+                (date, time.to_vec())
+            }
+            _ => continue,
+        };
+        let Some(date) = date else { continue };
+
+        let time: Vec<u32> = time
+            .iter()
+            .take(3)
+            .map_while(|n| parse(n).filter(|_| n.len() <= 2))
+            .collect();
+        let time = match time.as_slice() {
+            [hour, minute, second] => NaiveTime::from_hms_opt(*hour, *minute, *second),
+            [hour, minute] => NaiveTime::from_hms_opt(*hour, *minute, 0),
+            _ => None,
+        }
+        .unwrap_or_default();
+
+        if let Some(datetime) = date.and_time(time).and_local_timezone(Local).earliest() {
+            return Some(datetime.timestamp());
+        }
+    }
+    None
+}